@@ -2,7 +2,10 @@ mod buffer;
 mod error;
 mod tests;
 
-use crate::{graph::operation::Activation, shape::Shape};
+use crate::{
+    graph::operation::{Activation, ConvSettings, PoolSettings},
+    shape::Shape,
+};
 
 pub use buffer::DeviceBuffer;
 pub use error::OperationError;
@@ -37,6 +40,211 @@ pub trait Device: Sized + 'static {
         activation: Activation,
     ) -> OperationResult<Self::DeviceError>;
 
+    /// Adds `bias`, which has `bias_size` elements and is repeated across the `size` elements
+    /// of `output`, then applies `activation`, all in place.
+    fn bias_activate(
+        size: usize,
+        bias_size: usize,
+        bias: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+        activation: Activation,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Writes the gradient of the input of `activation` to `input_grad`, computing the
+    /// derivative from its `output`, so is unsupported for `Square`, `GELU` and `SiLU`.
+    fn backprop_activate_from_output(
+        size: usize,
+        output: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+        activation: Activation,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn abs(size: usize, input: &Self::BufferF32, output: &mut Self::BufferF32) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_abs(
+        size: usize,
+        input: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+        output_grad: &Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Elementwise maximum of `input_a` and `input_b` if `max` is set, otherwise the minimum.
+    fn elementwise_min_max(
+        size: usize,
+        max: bool,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Gradients are passed to whichever input was selected, with ties going to `input_a`.
+    fn backprop_elementwise_min_max(
+        size: usize,
+        max: bool,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: Option<&mut Self::BufferF32>,
+        input_b_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn elementwise_mul(
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_elementwise_mul(
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: Option<&mut Self::BufferF32>,
+        input_b_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Elementwise `input_a / input_b`, where elements of `input_b` smaller in magnitude
+    /// than `epsilon` are replaced by `epsilon` with the same sign.
+    fn elementwise_div(
+        size: usize,
+        epsilon: f32,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_elementwise_div(
+        size: usize,
+        epsilon: f32,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: Option<&mut Self::BufferF32>,
+        input_b_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn avg_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_avg_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Scaled dot-product attention over `seq_len` tokens of size `dim`, stored token by token,
+    /// with an optional additive `seq_len x seq_len` mask (`[query][key]`) that may be shared
+    /// across the batch. The attention probabilities are written to `probs`.
+    fn attention(
+        batch_size: usize,
+        seq_len: usize,
+        dim: usize,
+        q: &Self::BufferF32,
+        k: &Self::BufferF32,
+        v: &Self::BufferF32,
+        mask: Option<&Self::BufferF32>,
+        mask_batched: bool,
+        probs: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Uses `scores_grad` as scratch space of the same size as `probs`.
+    fn backprop_attention(
+        batch_size: usize,
+        seq_len: usize,
+        dim: usize,
+        q: &Self::BufferF32,
+        k: &Self::BufferF32,
+        v: &Self::BufferF32,
+        probs: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        scores_grad: &mut Self::BufferF32,
+        q_grad: Option<&mut Self::BufferF32>,
+        k_grad: Option<&mut Self::BufferF32>,
+        v_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn conv2d(
+        batch_size: usize,
+        settings: ConvSettings,
+        filters: &Self::BufferF32,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Gradients are accumulated into `filters_grad` and `input_grad`, when present.
+    fn backprop_conv2d(
+        batch_size: usize,
+        settings: ConvSettings,
+        filters: &Self::BufferF32,
+        input: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        filters_grad: Option<&mut Self::BufferF32>,
+        input_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Also writes the position of the maximum within each window to `argmax`.
+    fn max_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        input: &Self::BufferF32,
+        argmax: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_max_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        argmax: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Clamps each element of `input` to `[min, max]`.
+    fn clamp(
+        size: usize,
+        min: f32,
+        max: f32,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_clamp(
+        size: usize,
+        min: f32,
+        max: f32,
+        input: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+        output_grad: &Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Leaky ReLU where the negative slope of each of the `size` elements is given by `slope`,
+    /// which is shared across the batch.
+    fn prelu(
+        size: usize,
+        batch_size: usize,
+        input: &Self::BufferF32,
+        slope: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_prelu(
+        size: usize,
+        batch_size: usize,
+        input: &Self::BufferF32,
+        slope: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: Option<&mut Self::BufferF32>,
+        slope_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn sgemm(
         input_a: &Self::BufferF32,
         shape_a: Shape,
@@ -69,12 +277,16 @@ pub trait Device: Sized + 'static {
         output: &mut Self::BufferF32,
     ) -> OperationResult<Self::DeviceError>;
 
+    /// Sums the `batch_size` columns of `input`, scaled by `alpha`, into `output`,
+    /// overwriting it unless `increment` is set.
     fn reduce_add(
         ones: &Self::BufferF32,
         size: usize,
         batch_size: usize,
+        alpha: f32,
         input: &Self::BufferF32,
         output: &mut Self::BufferF32,
+        increment: bool,
     ) -> OperationResult<Self::DeviceError>;
 
     /// If `input_a = None`, then take `input_a = output`, i.e. perform the
@@ -156,6 +368,17 @@ pub trait Device: Sized + 'static {
         add: bool,
     ) -> OperationResult<Self::DeviceError>;
 
+    /// Transposes each `rows x cols` matrix in `input`, either writing or adding the result
+    /// into `output`.
+    fn transpose(
+        rows: usize,
+        cols: usize,
+        batch_size: usize,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+        add: bool,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn mask(
         batch_size: usize,
         single_size: usize,
@@ -227,6 +450,30 @@ pub trait Device: Sized + 'static {
         input_grads: &mut Self::BufferF32,
     ) -> OperationResult<Self::DeviceError>;
 
+    /// Looks up the column of the `dim x vocab` `table` for each of the `nnz` indices,
+    /// writing zeroes for padding indices of `-1`.
+    fn embedding(
+        batch_size: usize,
+        dim: usize,
+        vocab: usize,
+        nnz: usize,
+        table: &Self::BufferF32,
+        indices: &Self::BufferI32,
+        outputs: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Scatters `output_grads` into the looked up columns of `table_grads`, leaving the
+    /// rest of the table untouched.
+    fn backprop_embedding(
+        batch_size: usize,
+        dim: usize,
+        vocab: usize,
+        nnz: usize,
+        output_grads: &Self::BufferF32,
+        indices: &Self::BufferI32,
+        table_grads: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn abs_power_error(
         power: f32,
         size: usize,
@@ -244,6 +491,63 @@ pub trait Device: Sized + 'static {
         input_a_grad: &mut Self::BufferF32,
     ) -> OperationResult<Self::DeviceError>;
 
+    /// Elementwise Huber loss of `input_a - input_b`, which is quadratic for
+    /// differences up to `delta` in magnitude and linear beyond that.
+    fn huber_error(
+        delta: f32,
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_huber_error_single(
+        delta: f32,
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Hinge loss on the ordering of each pair of batch elements `(2i, 2i + 1)`,
+    /// `max(0, margin - sign(t_a - t_b) * (p_a - p_b))`, split evenly between the pair.
+    /// Pairs with equal targets, and an unpaired final element, have zero loss.
+    fn pairwise_ranking(
+        batch_size: usize,
+        margin: f32,
+        preds: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_pairwise_ranking(
+        batch_size: usize,
+        margin: f32,
+        preds: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        preds_grad: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Elementwise binary cross-entropy between `sigmoid(logits)` and `targets`,
+    /// computed directly from the logits for numerical stability.
+    fn sigmoid_bce(
+        size: usize,
+        logits: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_sigmoid_bce(
+        size: usize,
+        logits: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        logits_grad: Option<&mut Self::BufferF32>,
+        targets_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn softmax_across_batch(
         batch_size: usize,
         single_size: usize,
@@ -251,6 +555,14 @@ pub trait Device: Sized + 'static {
         output: &mut Self::BufferF32,
     ) -> OperationResult<Self::DeviceError>;
 
+    fn backprop_softmax_across_batch(
+        batch_size: usize,
+        single_size: usize,
+        softmaxed: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn crossentropy(
         size: usize,
         pred: &Self::BufferF32,
@@ -297,6 +609,92 @@ pub trait Device: Sized + 'static {
         input_grad: &mut Self::BufferF32,
     ) -> OperationResult<Self::DeviceError>;
 
+    /// Writes `(1 - smoothing) * target + smoothing / n` for each of the `n` active entries of
+    /// `masks` to `output`, spreading `smoothing` of each target uniformly over its masked entries.
+    fn smooth_targets_masked(
+        batch_size: usize,
+        nnz: usize,
+        masks: &Self::BufferI32,
+        smoothing: f32,
+        target: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Normalises each column of `input` to zero mean and unit variance, then applies
+    /// `scale` and `shift`, writing the mean and reciprocal standard deviation of
+    /// each column to `stats`, which must hold `2 * batch_size` values.
+    fn layer_norm(
+        batch_size: usize,
+        single_size: usize,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        shift: &Self::BufferF32,
+        stats: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_layer_norm(
+        batch_size: usize,
+        single_size: usize,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        stats: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: Option<&mut Self::BufferF32>,
+        scale_grad: Option<&mut Self::BufferF32>,
+        shift_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Normalises each row of `input` across the batch, then applies `scale` and `shift`.
+    ///
+    /// If `training`, the batch statistics are used and the running statistics are
+    /// updated with `momentum`, otherwise the running statistics are used. Either way
+    /// the mean and reciprocal standard deviation of each row are written to `stats`,
+    /// which must hold `2 * single_size` values.
+    fn batch_norm(
+        batch_size: usize,
+        single_size: usize,
+        training: bool,
+        momentum: f32,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        shift: &Self::BufferF32,
+        running_mean: &mut Self::BufferF32,
+        running_var: &mut Self::BufferF32,
+        stats: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_batch_norm(
+        batch_size: usize,
+        single_size: usize,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        stats: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: Option<&mut Self::BufferF32>,
+        scale_grad: Option<&mut Self::BufferF32>,
+        shift_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Zeroes each element of `input` with probability `rate`, scaling the rest
+    /// by `1 / (1 - rate)`, and writes the resulting multipliers to `mask`.
+    fn dropout(
+        size: usize,
+        rate: f32,
+        seed: u32,
+        input: &Self::BufferF32,
+        mask: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
+    fn backprop_dropout(
+        size: usize,
+        mask: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn adam(
         size: usize,
         params: &mut Self::BufferF32,
@@ -310,8 +708,51 @@ pub trait Device: Sized + 'static {
         denom: bool,
     ) -> OperationResult<Self::DeviceError>;
 
+    /// As `adam`, but `belief` tracks the variance of the gradient around `momentum`,
+    /// rather than its raw second moment.
+    fn adabelief(
+        size: usize,
+        params: &mut Self::BufferF32,
+        gradient: &Self::BufferF32,
+        momentum: &mut Self::BufferF32,
+        belief: &mut Self::BufferF32,
+        beta1: f32,
+        beta2: f32,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn clip(size: usize, params: &mut Self::BufferF32, min: f32, max: f32) -> OperationResult<Self::DeviceError>;
 
+    /// As `adam`, but only updates the `num_columns` columns of the `dim`-row `params` listed in
+    /// `columns`, with `gradient` holding the gradients of just those columns, one after another.
+    fn sparse_adam(
+        dim: usize,
+        num_columns: usize,
+        columns: &Self::BufferI32,
+        params: &mut Self::BufferF32,
+        gradient: &Self::BufferF32,
+        momentum: &mut Self::BufferF32,
+        velocity: &mut Self::BufferF32,
+        beta1: f32,
+        beta2: f32,
+        gradient_factor: f32,
+        learning_rate: f32,
+        denom: bool,
+    ) -> OperationResult<Self::DeviceError>;
+
+    /// Scales the `num_columns` columns of the `dim`-row `params` listed in `columns`
+    /// by `scale`, then clips them to `[min, max]`.
+    fn sparse_scale_clip(
+        dim: usize,
+        num_columns: usize,
+        columns: &Self::BufferI32,
+        params: &mut Self::BufferF32,
+        scale: f32,
+        min: f32,
+        max: f32,
+    ) -> OperationResult<Self::DeviceError>;
+
     fn sparse_to_dense(
         batch_size: usize,
         size: usize,
@@ -319,4 +760,17 @@ pub trait Device: Sized + 'static {
         sparse: &Self::BufferI32,
         dense: &mut Self::BufferF32,
     ) -> OperationResult<Self::DeviceError>;
+
+    /// Writes the index `a * size_b + b` of each pair of active indices `a` of `input_a` and `b`
+    /// of `input_b` to `output`, which has `nnz_a * nnz_b` indices per batch element, padded with `-1`.
+    #[allow(clippy::too_many_arguments)]
+    fn sparse_outer(
+        batch_size: usize,
+        nnz_a: usize,
+        input_a: &Self::BufferI32,
+        size_b: usize,
+        nnz_b: usize,
+        input_b: &Self::BufferI32,
+        output: &mut Self::BufferI32,
+    ) -> OperationResult<Self::DeviceError>;
 }
@@ -7,6 +7,8 @@ pub enum OperationError<T: Debug> {
     UnsupportedOperation(String),
     MismatchedBatchSizes,
     DeviceError(Box<T>),
+    /// Error raised while executing the node with the given label.
+    InNode(String, Box<Self>),
 }
 
 impl<T: Debug> From<T> for OperationError<T> {
@@ -1,22 +1,40 @@
 pub mod builder;
 pub mod error;
+pub mod gradcheck;
+mod memory;
 pub mod operation;
+pub mod stats;
 pub mod tests;
 
 use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
 use builder::Node;
+use memory::MemoryPlan;
+use operation::Operation;
+use stats::ActivationStats;
 
 use crate::{
     device::{Device, OperationError},
-    tensor::Tensor,
+    tensor::{Matrix, Tensor},
 };
 
 pub struct Graph<D: Device> {
     nodes: Vec<RefCell<Tensor<D>>>,
     root: usize,
+    in_backward: Vec<bool>,
     inputs: HashMap<String, usize>,
     weights: HashMap<String, usize>,
+    is_weight: Vec<bool>,
+    labels: Vec<String>,
+    named: HashMap<String, usize>,
+    loss_components: Vec<(String, usize)>,
+    aliases: HashMap<usize, usize>,
+    memory: MemoryPlan<D>,
+    activation_stats: Option<Vec<ActivationStats>>,
+    stats_interval: usize,
+    forward_passes: usize,
+    description: String,
+    training: bool,
     device: Arc<D>,
 }
 
@@ -24,23 +42,126 @@ impl<D: Device> Graph<D> {
     pub fn forward(&mut self) -> Result<f32, OperationError<D::DeviceError>> {
         for node in 0..self.nodes.len() {
             let node = { self.nodes[node].borrow().own };
-            self.forward_node(node)?;
+            self.forward_node(node).map_err(|e| self.labelled(node, e))?;
+        }
+
+        if let Some(stats) = self.activation_stats.as_mut() {
+            if self.forward_passes % self.stats_interval == 0 {
+                for (node, stats) in self.nodes.iter().zip(stats.iter_mut()) {
+                    let node = node.borrow();
+                    if let (Some(_), Matrix::Dense(values)) = (&node.operation, &node.values) {
+                        let mut buf = vec![0.0; values.size()];
+                        values.write_to_slice(&mut buf)?;
+                        stats.push(&buf);
+                    }
+                }
+            }
+
+            self.forward_passes += 1;
         }
 
         Ok(self.nodes[self.root].borrow().get_scalar().unwrap())
     }
 
+    /// Gradients of intermediate nodes share device buffers where their lifetimes
+    /// don't overlap, so they are only available during the backward pass.
     pub fn backward(&mut self) -> Result<(), OperationError<D::DeviceError>> {
         self.nodes[self.root].get_mut().set_grad_to_unit()?;
 
-        for node in (0..self.nodes.len()).rev() {
-            let node = { self.nodes[node].borrow().own };
-            self.backward_node(node)?;
+        for idx in (0..self.nodes.len()).rev() {
+            if !self.in_backward[idx] {
+                continue;
+            }
+
+            let node = { self.nodes[idx].borrow().own };
+            self.memory.acquire(&self.nodes, node).map_err(|e| self.labelled(node, e.into()))?;
+            self.backward_node(node).map_err(|e| self.labelled(node, e))?;
+            self.memory.release(&self.nodes, node);
         }
 
         Ok(())
     }
 
+    fn labelled(&self, node: Node, error: OperationError<D::DeviceError>) -> OperationError<D::DeviceError> {
+        OperationError::InNode(self.labels[node.idx].clone(), Box::new(error))
+    }
+
+    /// The label attached to a node with `GraphBuilder::set_label`, otherwise its id
+    /// if it is an input or weight, otherwise its index.
+    pub fn node_label(&self, node: Node) -> &str {
+        &self.labels[self.resolve(node)]
+    }
+
+    /// The index of the node that computes the values of `node`, which differs if it was
+    /// merged into an identical earlier node when the graph was built.
+    fn resolve(&self, node: Node) -> usize {
+        self.aliases.get(&node.idx).copied().unwrap_or(node.idx)
+    }
+
+    /// The total value across the batch of each node recorded with
+    /// `GraphBuilder::add_loss_component`, as of the last forward pass.
+    pub fn loss_components(&self) -> Result<Vec<(String, f32)>, OperationError<D::DeviceError>> {
+        let mut components = Vec::with_capacity(self.loss_components.len());
+
+        for (id, idx) in &self.loss_components {
+            let vals = self.nodes[*idx].borrow().get_dense_vals()?;
+            components.push((id.clone(), vals.iter().sum()));
+        }
+
+        Ok(components)
+    }
+
+    /// Whether the graph is in training mode, which is the default. Some operations,
+    /// such as `BatchNorm`, behave differently when evaluating the network.
+    pub fn is_training(&self) -> bool {
+        self.training
+    }
+
+    pub fn set_training(&mut self, training: bool) {
+        self.training = training;
+    }
+
+    /// Sets the temperature of every softmax cross-entropy loss in the graph, overriding the
+    /// temperature it was built with, e.g. to anneal the temperature over training.
+    pub fn set_softmax_temperature(&mut self, temperature: f32) {
+        assert!(temperature > 0.0, "Softmax temperature must be positive!");
+
+        for node in &mut self.nodes {
+            match node.get_mut().operation.as_mut() {
+                Some(Operation::SoftmaxCrossEntropyLoss(_, _, t, _))
+                | Some(Operation::MaskedSoftmaxCrossEntropyLoss(_, _, _, t, _)) => *t = temperature,
+                _ => {}
+            }
+        }
+    }
+
+    /// Records the minimum, maximum, mean and standard deviation of the values of every
+    /// dense node computed by an operation, over every `interval`-th forward pass (starting
+    /// with the next) until they are taken with `take_activation_stats`, or stops recording
+    /// if `interval` is `None`. This copies every activation to the host, so is slow.
+    pub fn record_activation_stats(&mut self, interval: Option<usize>) {
+        if let Some(interval) = interval {
+            assert!(interval > 0, "Activation stats must be recorded at a positive interval!");
+            self.stats_interval = interval;
+        }
+
+        self.forward_passes = 0;
+        self.activation_stats = interval.map(|_| vec![ActivationStats::default(); self.nodes.len()]);
+    }
+
+    /// The statistics recorded for each node since they were last taken, labelled as in
+    /// `node_label`, if recording is enabled.
+    pub fn take_activation_stats(&mut self) -> Vec<(String, ActivationStats)> {
+        let Some(stats) = self.activation_stats.as_mut() else { return Vec::new() };
+
+        stats
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, stats)| stats.count > 0)
+            .map(|(idx, stats)| (self.labels[idx].clone(), std::mem::take(stats)))
+            .collect()
+    }
+
     pub fn zero_grads(&mut self) -> Result<(), D::DeviceError> {
         for node in &mut self.nodes {
             node.get_mut().zero_grad()?;
@@ -49,6 +170,18 @@ impl<D: Device> Graph<D> {
         Ok(())
     }
 
+    /// Zeroes the gradients of every node except the weights, so that the
+    /// next backward pass adds to the existing gradients of the weights.
+    pub fn zero_grads_except_weights(&mut self) -> Result<(), D::DeviceError> {
+        for (node, &is_weight) in self.nodes.iter_mut().zip(&self.is_weight) {
+            if !is_weight {
+                node.get_mut().zero_grad()?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn input_ids(&self) -> Vec<String> {
         self.inputs.keys().cloned().collect()
     }
@@ -74,7 +207,14 @@ impl<D: Device> Graph<D> {
     }
 
     pub fn get_node(&self, node: Node) -> std::cell::Ref<'_, Tensor<D>> {
-        self.nodes[node.idx].borrow()
+        self.nodes[self.resolve(node)].borrow()
+    }
+
+    /// The node given `label` with `GraphBuilder::set_label`, e.g. to read the values
+    /// of a hidden layer after a forward pass. If several nodes share a label, the last
+    /// of them is returned.
+    pub fn get_named(&self, label: &str) -> Option<std::cell::Ref<'_, Tensor<D>>> {
+        self.named.get(label).map(|&idx| self.nodes[idx].borrow())
     }
 
     pub fn get_num_params(&self) -> usize {
@@ -98,4 +238,51 @@ impl<D: Device> Graph<D> {
     pub fn device(&self) -> Arc<D> {
         self.device.clone()
     }
+
+    /// Describes the operations, shapes and weights of the graph in JSON, which is saved
+    /// alongside checkpoints and can be rebuilt with `GraphBuilder::from_description`.
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Describes the graph in the Graphviz DOT language, with each node labelled by its
+    /// label, shape and operation. Inputs are drawn as boxes, weights as ellipses and the
+    /// loss with a double border, e.g. render with `dot -Tsvg graph.dot -o graph.svg`.
+    pub fn to_dot(&self) -> String {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut dot = String::from("digraph {\n");
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let node = node.borrow();
+            let shape = node.own.shape;
+            let label = escape(&self.labels[idx]);
+            let layout = if node.own.sparse.is_some() { " (sparse)" } else { "" };
+
+            let (desc, style) = if let Some(op) = &node.operation {
+                // the variant name, without its arguments
+                let op = format!("{op:?}");
+                let name = op.split('(').next().unwrap();
+                (format!("{label}\\n{name}\\n{shape}"), "shape=box, style=rounded")
+            } else if self.inputs.values().any(|&i| i == idx) {
+                (format!("{label}\\ninput{layout}\\n{shape}"), "shape=box")
+            } else if self.weights.values().any(|&i| i == idx) {
+                (format!("{label}\\nweights\\n{shape}"), "shape=ellipse")
+            } else {
+                // removed by an optimisation pass
+                continue;
+            };
+
+            let root = if idx == self.root { ", peripheries=2" } else { "" };
+            dot.push_str(&format!("    n{idx} [label=\"{desc}\", {style}{root}];\n"));
+
+            if let Some(op) = &node.operation {
+                for parent in op.nodes() {
+                    dot.push_str(&format!("    n{} -> n{idx};\n", parent.idx));
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
 }
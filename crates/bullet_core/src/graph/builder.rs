@@ -1,3 +1,7 @@
+mod cse;
+mod description;
+mod fusion;
+
 use std::{
     cell::RefCell,
     collections::{HashMap, HashSet},
@@ -8,13 +12,14 @@ use std::{
 
 use super::{
     error::GraphError,
+    memory::MemoryPlan,
     operation::{GraphBuilderError, GraphBuilderErrorType, Operation},
     Graph,
 };
 use crate::{
     device::{Device, OperationError},
     shape::Shape,
-    tensor::Tensor,
+    tensor::{SparseGradients, Tensor},
 };
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -30,12 +35,12 @@ impl Node {
         self.shape
     }
 
-    pub fn reshape(mut self, shape: Shape) -> Result<Self, GraphBuilderErrorType> {
+    pub fn reshape(mut self, shape: Shape) -> Result<Self, GraphBuilderError> {
         if self.shape.size() == shape.size() {
             self.shape = shape;
             Ok(self)
         } else {
-            Err(GraphBuilderErrorType::MismatchedInputShapes(vec![self.shape, shape]))
+            Err(GraphBuilderError::reshape(self, shape))
         }
     }
 
@@ -46,6 +51,7 @@ impl Node {
 
 pub(crate) struct NodeData {
     id: Option<String>,
+    label: Option<String>,
     size: usize,
     requires_grad: bool,
     parent_operation: Option<Operation>,
@@ -53,6 +59,10 @@ pub(crate) struct NodeData {
 }
 
 impl NodeData {
+    fn display_name(&self, idx: usize) -> String {
+        self.label.clone().or_else(|| self.id.clone()).unwrap_or_else(|| format!("node {idx}"))
+    }
+
     fn new(
         id: Option<String>,
         parent_operation: Option<Operation>,
@@ -62,7 +72,7 @@ impl NodeData {
         sparse: Option<NonZeroUsize>,
     ) -> Self {
         let own = Node { idx: usize::MAX, shape: Shape::new(usize::MAX, usize::MAX), can_be_batched, sparse };
-        Self { id, size, requires_grad, parent_operation, own }
+        Self { id, label: None, size, requires_grad, parent_operation, own }
     }
 }
 
@@ -70,9 +80,12 @@ impl NodeData {
 pub struct GraphBuilder {
     nodes: Vec<NodeData>,
     roots: HashSet<usize>,
+    loss: Option<usize>,
     inputs: HashSet<usize>,
     weights: HashSet<usize>,
+    sparse_gradients: HashSet<usize>,
     ids: HashSet<String>,
+    loss_components: Vec<(String, usize)>,
 }
 
 impl GraphBuilder {
@@ -151,6 +164,27 @@ impl GraphBuilder {
         Ok(node)
     }
 
+    /// Creates weights that are not updated by the optimiser, but are otherwise
+    /// treated like any other weights, e.g. they are saved in checkpoints.
+    pub fn create_non_trainable_weights(&mut self, id: &str, shape: Shape) -> Result<Node, GraphBuilderErrorType> {
+        let data = NodeData::new(Some(id.to_string()), None, shape.size(), false, false, None);
+        let node = self.create_node(data, shape, None)?;
+
+        self.weights.insert(node.idx);
+
+        Ok(node)
+    }
+
+    /// Stores the gradients of `weights` as just the columns that were looked up in the
+    /// backward pass, rather than as a dense matrix, so that large embedding tables only
+    /// have the rows (columns of the table) used in each batch updated by the optimiser.
+    /// The weights may only be used as the table of `Operation::Embedding`.
+    pub fn use_sparse_gradients(&mut self, weights: Node) {
+        assert!(self.weights.contains(&weights.idx), "Only weights can have sparse gradients!");
+        assert!(self.get(weights.idx).requires_grad, "Weights must be trainable to have sparse gradients!");
+        self.sparse_gradients.insert(weights.idx);
+    }
+
     pub fn create_result_of_operation(
         &mut self,
         operation: Operation,
@@ -158,41 +192,131 @@ impl GraphBuilder {
     ) -> Result<Node, GraphBuilderError> {
         match operation.output_shape() {
             Ok(shape) => {
-                let data = NodeData::new(None, Some(operation), shape.size(), true, requires_grad, None);
-                self.create_node(data, shape, None).map_err(|e| GraphBuilderError::new(&operation, e))
+                let sparse = operation.output_nnz();
+                let data = NodeData::new(None, Some(operation), shape.size(), true, requires_grad, sparse);
+                self.create_node(data, shape, sparse).map_err(|e| GraphBuilderError::new(&operation, e))
             }
             Err(s) => Err(s),
         }
     }
 
+    /// Attaches a human-readable label to a node, used in error messages.
+    pub fn set_label(&mut self, node: Node, label: &str) {
+        self.nodes[node.idx].label = Some(label.to_string());
+    }
+
+    /// The label of a node if it has one, otherwise its id if it is an input or weight.
+    pub fn label(&self, node: Node) -> String {
+        self.get(node.idx).display_name(node.idx)
+    }
+
+    /// Describes an error from `create_result_of_operation` or `Node::reshape` in terms of the labels of the inputs.
+    pub fn describe_error(&self, error: &GraphBuilderError) -> String {
+        let inputs =
+            error.inputs.iter().map(|&node| format!("\n    {} ({})", self.label(node), node.shape)).collect::<String>();
+
+        match &error.op {
+            Some(op) => format!("{op:?} failed with {:?}, inputs:{inputs}", error.ty),
+            None => format!("Reshape failed with {:?}, inputs:{inputs}", error.ty),
+        }
+    }
+
+    /// Records a node, such as one term of a weighted sum of losses, whose total
+    /// value across the batch is reported by `Graph::loss_components`.
+    pub fn add_loss_component(&mut self, id: &str, node: Node) {
+        assert!(!node.is_sparse(), "Loss components must be dense!");
+        self.loss_components.push((id.to_string(), node.idx));
+    }
+
+    /// Designates the node that the backward pass is run from, which allows the graph to
+    /// have other outputs, e.g. auxiliary heads or activations to inspect.
+    pub fn set_loss(&mut self, node: Node) {
+        self.loss = Some(node.idx);
+    }
+
+    /// The node set with `set_loss`, otherwise the single output of the graph.
     pub fn root(&self) -> Node {
-        assert_eq!(self.roots.len(), 1, "Graph must have a single output!");
-        self.nodes[*self.roots.iter().next().unwrap()].own
+        self.nodes[self.root_idx()].own
     }
 
-    pub fn build<D: Device>(self, device: D) -> Result<Graph<D>, GraphError<D::DeviceError>> {
-        assert_eq!(self.roots.len(), 1, "Graph must have a single output!");
+    fn root_idx(&self) -> usize {
+        if let Some(loss) = self.loss {
+            return loss;
+        }
+
+        assert_eq!(self.roots.len(), 1, "Graph must have a single output, or a loss set with `set_loss`!");
+        *self.roots.iter().next().unwrap()
+    }
 
-        let root = *self.roots.iter().next().unwrap();
+    /// Identical operations on identical inputs are computed once, with `Graph::get_node`
+    /// returning the shared values for each of them.
+    pub fn build<D: Device>(mut self, device: D) -> Result<Graph<D>, GraphError<D::DeviceError>> {
+        let root = self.root_idx();
+        let aliases = self.eliminate_common_subexpressions();
+        let root = aliases.get(&root).copied().unwrap_or(root);
         assert!(self.get(root).requires_grad, "Output cannot be an input!");
         assert!(!self.weights.contains(&root), "Can't output trainable weights!");
         assert_eq!(self.nodes[root].own.shape, Shape::new(1, 1), "Graph output must be scalar!");
         assert_eq!(self.get(root).size, 1);
 
+        let mut in_backward = vec![false; self.nodes.len()];
+        in_backward[root] = true;
+
+        for idx in (0..=root).rev() {
+            if let (true, Some(op)) = (in_backward[idx], &self.nodes[idx].parent_operation) {
+                for parent in op.nodes() {
+                    in_backward[parent.idx] = true;
+                }
+            }
+        }
+
+        for data in &self.nodes {
+            if let Some(op) = &data.parent_operation {
+                let embedding_table = match *op {
+                    Operation::Embedding(table, indices) if table.idx != indices.idx => Some(table.idx),
+                    _ => None,
+                };
+
+                for node in op.nodes() {
+                    assert!(
+                        !self.sparse_gradients.contains(&node.idx) || embedding_table == Some(node.idx),
+                        "Weights with sparse gradients can only be used as embedding tables!",
+                    );
+                }
+            }
+        }
+
         let device = Arc::new(device);
 
+        let memory = MemoryPlan::new(
+            device.clone(),
+            &self.nodes.iter().map(|data| data.parent_operation.as_ref()).collect::<Vec<_>>(),
+            &self.nodes.iter().map(|data| data.size).collect::<Vec<_>>(),
+            &self.nodes.iter().map(|data| data.requires_grad).collect::<Vec<_>>(),
+            &in_backward,
+            root,
+        )
+        .map_err(OperationError::from)?;
+
         let mut nodes = Vec::new();
-        for node_data in &self.nodes {
+        for (idx, node_data) in self.nodes.iter().enumerate() {
             let tensor = Tensor::new(
                 device.clone(),
                 node_data.size,
-                node_data.requires_grad,
+                node_data.requires_grad && !memory.is_planned(idx),
                 node_data.parent_operation,
                 node_data.own,
             );
-            let tensor = tensor.map_err(OperationError::from);
+            let mut tensor = tensor.map_err(OperationError::from)?;
+
+            if self.sparse_gradients.contains(&idx) {
+                let shape = node_data.own.shape;
+                let grads = SparseGradients::new(device.clone(), shape.rows(), shape.cols());
+                tensor.gradients = None;
+                tensor.sparse_gradients = Some(grads.map_err(OperationError::from)?);
+            }
 
-            nodes.push(RefCell::new(tensor?));
+            nodes.push(RefCell::new(tensor));
         }
 
         let inputs =
@@ -201,6 +325,46 @@ impl GraphBuilder {
         let weights =
             self.weights.iter().map(|&node| (self.get(node).id.clone().unwrap(), node)).collect::<HashMap<_, _>>();
 
-        Ok(Graph { nodes, root, inputs, weights, device })
+        let mut is_weight = vec![false; self.nodes.len()];
+        for &idx in &self.weights {
+            is_weight[idx] = true;
+        }
+
+        let labels = self.nodes.iter().enumerate().map(|(idx, data)| data.display_name(idx)).collect();
+
+        let named = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, data)| data.label.clone().map(|label| (label, idx)))
+            .collect::<HashMap<_, _>>();
+
+        let loss_components = self
+            .loss_components
+            .iter()
+            .map(|(id, idx)| (id.clone(), aliases.get(idx).copied().unwrap_or(*idx)))
+            .collect::<Vec<_>>();
+
+        let description = self.describe(root, &loss_components);
+
+        Ok(Graph {
+            nodes,
+            root,
+            in_backward,
+            inputs,
+            weights,
+            is_weight,
+            labels,
+            named,
+            loss_components,
+            aliases,
+            memory,
+            activation_stats: None,
+            stats_interval: 1,
+            forward_passes: 0,
+            description,
+            training: true,
+            device,
+        })
     }
 }
@@ -0,0 +1,62 @@
+use std::collections::{HashMap, HashSet};
+
+use super::GraphBuilder;
+use crate::graph::operation::Operation;
+
+impl GraphBuilder {
+    /// Merges each operation that is identical to an earlier one, with identical inputs,
+    /// into the earlier one, returning the node that replaces each removed node.
+    ///
+    /// Operations with random or stateful behaviour, such as `Dropout`, are never merged,
+    /// nor are labelled nodes, so that each label still refers to its own node.
+    pub(super) fn eliminate_common_subexpressions(&mut self) -> HashMap<usize, usize> {
+        let mut replaced = HashMap::new();
+        let mut needed = HashSet::new();
+        let mut seen: Vec<usize> = Vec::new();
+
+        for idx in 0..self.nodes.len() {
+            let Some(op) = self.nodes[idx].parent_operation.as_mut() else { continue };
+
+            // backprop borrows each input separately, so an operation can't be
+            // left with the same node as two of its inputs
+            let mut used = op.nodes().iter().map(|node| node.idx).collect::<HashSet<_>>();
+
+            for node in op.nodes_mut() {
+                if let Some(&new) = replaced.get(&node.idx) {
+                    if used.insert(new) {
+                        node.idx = new;
+                    } else {
+                        needed.insert(node.idx);
+                    }
+                }
+            }
+
+            if matches!(op, Operation::Dropout(_, _) | Operation::BatchNorm(_, _, _, _, _, _)) {
+                continue;
+            }
+
+            let earlier = seen.iter().copied().find(|&other| {
+                let other = &self.nodes[other];
+                other.requires_grad == self.nodes[idx].requires_grad
+                    && other.parent_operation == self.nodes[idx].parent_operation
+            });
+
+            match earlier {
+                Some(earlier) if self.nodes[idx].label.is_none() => {
+                    replaced.insert(idx, earlier);
+                }
+                _ => seen.push(idx),
+            }
+        }
+
+        replaced.retain(|idx, _| !needed.contains(idx));
+
+        for &idx in replaced.keys() {
+            let removed = &mut self.nodes[idx];
+            removed.parent_operation = None;
+            removed.requires_grad = false;
+        }
+
+        replaced
+    }
+}
@@ -0,0 +1,573 @@
+use super::{GraphBuilder, Node, NodeData};
+use crate::{
+    graph::{
+        error::GraphDescriptionError,
+        operation::{Activation, ConvSettings, NodeList, Operation, PoolSettings},
+    },
+    shape::Shape,
+};
+
+impl GraphBuilder {
+    /// As `Graph::description`, for the graph as built so far.
+    pub fn description(&self) -> String {
+        self.describe(self.root_idx(), &self.loss_components)
+    }
+
+    /// Describes the nodes, operations and weights of the graph in JSON, from which
+    /// an identical graph can be rebuilt with `from_description`.
+    pub(super) fn describe(&self, root: usize, loss_components: &[(String, usize)]) -> String {
+        let nodes = self.nodes.iter().enumerate().map(|(idx, data)| self.describe_node(idx, data)).collect();
+
+        let components =
+            loss_components.iter().map(|(id, idx)| Json::Arr(vec![Json::Str(id.clone()), idx.encode()])).collect();
+
+        let desc = Json::Obj(vec![
+            ("nodes".to_string(), Json::Arr(nodes)),
+            ("loss".to_string(), root.encode()),
+            ("loss_components".to_string(), Json::Arr(components)),
+        ]);
+
+        let mut out = String::new();
+        desc.write(&mut out);
+        out
+    }
+
+    fn describe_node(&self, idx: usize, data: &NodeData) -> Json {
+        let own = data.own;
+        let mut fields = Vec::new();
+        let mut field = |name: &str, value: Json| fields.push((name.to_string(), value));
+
+        let kind = if self.inputs.contains(&idx) {
+            "input"
+        } else if self.weights.contains(&idx) {
+            "weights"
+        } else if data.parent_operation.is_some() {
+            "operation"
+        } else {
+            "removed"
+        };
+
+        field("kind", Json::Str(kind.to_string()));
+        field("shape", Json::Arr(vec![own.shape.rows().encode(), own.shape.cols().encode()]));
+
+        if let Some(id) = &data.id {
+            field("id", Json::Str(id.clone()));
+        }
+
+        if let Some(label) = &data.label {
+            field("label", Json::Str(label.clone()));
+        }
+
+        match kind {
+            "input" => {
+                field("batched", own.can_be_batched.encode());
+                field("sparse", own.sparse.map_or(Json::Null, |nnz| nnz.get().encode()));
+            }
+            "weights" => {
+                field("trainable", data.requires_grad.encode());
+
+                if self.sparse_gradients.contains(&idx) {
+                    field("sparse_gradients", true.encode());
+                }
+            }
+            "operation" => {
+                field("op", encode_operation(data.parent_operation.as_ref().unwrap()));
+                field("requires_grad", data.requires_grad.encode());
+            }
+            _ => {}
+        }
+
+        Json::Obj(fields)
+    }
+
+    /// Rebuilds a graph from the output of `Graph::description`, e.g. to load a checkpoint
+    /// without the code that originally built the network.
+    pub fn from_description(desc: &str) -> Result<Self, GraphDescriptionError> {
+        let desc = Json::parse(desc).ok_or(GraphDescriptionError::InvalidJson)?;
+        let nodes = desc.get("nodes").and_then(Json::arr).ok_or(GraphDescriptionError::InvalidJson)?;
+
+        let mut builder = Self::default();
+
+        for (idx, node) in nodes.iter().enumerate() {
+            builder.push_described_node(node).ok_or(GraphDescriptionError::InvalidNode(idx))??;
+        }
+
+        let num = builder.nodes.len();
+        let loss = desc.get("loss").and_then(Json::usize).filter(|&idx| idx < num);
+        builder.loss = Some(loss.ok_or(GraphDescriptionError::InvalidJson)?);
+
+        let components = desc.get("loss_components").and_then(Json::arr).ok_or(GraphDescriptionError::InvalidJson)?;
+
+        for component in components {
+            let component = component.arr().ok_or(GraphDescriptionError::InvalidJson)?;
+            let id = component.first().and_then(Json::str).ok_or(GraphDescriptionError::InvalidJson)?;
+            let idx = component.get(1).and_then(Json::usize).filter(|&idx| idx < num);
+            builder.loss_components.push((id.to_string(), idx.ok_or(GraphDescriptionError::InvalidJson)?));
+        }
+
+        Ok(builder)
+    }
+
+    /// Returns `None` if the description of the node is malformed.
+    fn push_described_node(&mut self, node: &Json) -> Option<Result<(), GraphDescriptionError>> {
+        let idx = self.nodes.len();
+        let invalid = GraphDescriptionError::InvalidNode(idx);
+
+        let shape = node.get("shape")?.arr()?;
+        let (rows, cols) = (shape.first()?.usize()?, shape.get(1)?.usize()?);
+        let shape = (rows > 0 && cols > 0).then(|| Shape::new(rows, cols))?;
+        let id = node.get("id").and_then(Json::str);
+
+        let created = match node.get("kind")?.str()? {
+            "input" => {
+                let id = id?;
+                let sparse = match node.get("sparse")? {
+                    Json::Null => None,
+                    nnz => Some(nnz.usize().filter(|&nnz| nnz > 0)?),
+                };
+
+                if node.get("batched")?.bool()? {
+                    match sparse {
+                        Some(nnz) => self.create_sparse_input(id, shape, nnz),
+                        None => self.create_dense_input(id, shape),
+                    }
+                } else {
+                    self.create_unbatched_input(id, shape, sparse)
+                }
+            }
+            "weights" => {
+                if node.get("trainable")?.bool()? {
+                    let sparse = node.get("sparse_gradients").and_then(Json::bool) == Some(true);
+                    let weights = self.create_weights(id?, shape);
+
+                    if let (true, Ok(weights)) = (sparse, &weights) {
+                        self.use_sparse_gradients(*weights);
+                    }
+
+                    weights
+                } else {
+                    self.create_non_trainable_weights(id?, shape)
+                }
+            }
+            "operation" => {
+                let op = decode_operation(node.get("op")?, &self.nodes)?;
+                let requires_grad = node.get("requires_grad")?.bool()?;
+
+                match self.create_result_of_operation(op, requires_grad) {
+                    Ok(created) if created.shape == shape => Ok(created),
+                    Ok(_) => return Some(Err(invalid)),
+                    Err(e) => return Some(Err(GraphDescriptionError::Builder(e))),
+                }
+            }
+            "removed" => {
+                let data = NodeData::new(None, None, shape.size(), true, false, None);
+                self.create_node(data, shape, None)
+            }
+            _ => return None,
+        };
+
+        let Ok(created) = created else { return Some(Err(invalid)) };
+
+        if let Some(label) = node.get("label") {
+            self.set_label(created, label.str()?);
+        }
+
+        Some(Ok(()))
+    }
+}
+
+/// A JSON value, with numbers kept as written so that they can be read back exactly.
+#[derive(Clone, Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Num(String),
+    Str(String),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+impl Json {
+    fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Obj(fields) => fields.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn arr(&self) -> Option<&[Json]> {
+        match self {
+            Json::Arr(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    fn str(&self) -> Option<&str> {
+        match self {
+            Json::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn bool(&self) -> Option<bool> {
+        match self {
+            Json::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    fn usize(&self) -> Option<usize> {
+        match self {
+            Json::Num(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn write(&self, out: &mut String) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(b) => out.push_str(&b.to_string()),
+            Json::Num(n) => out.push_str(n),
+            Json::Str(s) => write_str(s, out),
+            Json::Arr(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    item.write(out);
+                }
+                out.push(']');
+            }
+            Json::Obj(fields) => {
+                out.push('{');
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_str(name, out);
+                    out.push_str(": ");
+                    value.write(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+
+    fn parse(s: &str) -> Option<Json> {
+        let mut chars = s.chars().peekable();
+        let value = parse_value(&mut chars)?;
+        skip_whitespace(&mut chars);
+        chars.next().is_none().then_some(value)
+    }
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+fn write_str(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn skip_whitespace(chars: &mut Chars) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_value(chars: &mut Chars) -> Option<Json> {
+    skip_whitespace(chars);
+
+    match *chars.peek()? {
+        'n' => parse_literal(chars, "null", Json::Null),
+        't' => parse_literal(chars, "true", Json::Bool(true)),
+        'f' => parse_literal(chars, "false", Json::Bool(false)),
+        '"' => parse_str(chars).map(Json::Str),
+        '[' => {
+            chars.next();
+            let items = parse_sequence(chars, ']', parse_value)?;
+            Some(Json::Arr(items))
+        }
+        '{' => {
+            chars.next();
+            let fields = parse_sequence(chars, '}', |chars| {
+                skip_whitespace(chars);
+                let name = parse_str(chars)?;
+                skip_whitespace(chars);
+                chars.next_if_eq(&':')?;
+                Some((name, parse_value(chars)?))
+            })?;
+            Some(Json::Obj(fields))
+        }
+        _ => {
+            let mut num = String::new();
+            while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+                num.push(c);
+            }
+            num.parse::<f64>().ok().map(|_| Json::Num(num))
+        }
+    }
+}
+
+fn parse_literal(chars: &mut Chars, literal: &str, value: Json) -> Option<Json> {
+    for expected in literal.chars() {
+        chars.next_if_eq(&expected)?;
+    }
+
+    Some(value)
+}
+
+fn parse_sequence<T>(chars: &mut Chars, end: char, mut item: impl FnMut(&mut Chars) -> Option<T>) -> Option<Vec<T>> {
+    let mut items = Vec::new();
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&end).is_some() {
+        return Some(items);
+    }
+
+    loop {
+        items.push(item(chars)?);
+        skip_whitespace(chars);
+
+        match chars.next()? {
+            ',' => {}
+            c if c == end => return Some(items),
+            _ => return None,
+        }
+    }
+}
+
+fn parse_str(chars: &mut Chars) -> Option<String> {
+    chars.next_if_eq(&'"')?;
+    let mut s = String::new();
+
+    loop {
+        match chars.next()? {
+            '"' => return Some(s),
+            '\\' => match chars.next()? {
+                'n' => s.push('\n'),
+                't' => s.push('\t'),
+                'u' => {
+                    let hex = (0..4).map(|_| chars.next()).collect::<Option<String>>()?;
+                    s.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+                }
+                c => s.push(c),
+            },
+            c => s.push(c),
+        }
+    }
+}
+
+/// An argument of an operation, which refers to earlier nodes by index.
+trait Field: Sized {
+    fn encode(&self) -> Json;
+
+    fn decode(json: &Json, nodes: &[NodeData]) -> Option<Self>;
+}
+
+impl Field for usize {
+    fn encode(&self) -> Json {
+        Json::Num(self.to_string())
+    }
+
+    fn decode(json: &Json, _: &[NodeData]) -> Option<Self> {
+        json.usize()
+    }
+}
+
+impl Field for f32 {
+    fn encode(&self) -> Json {
+        // shortest representation that reads back as the same value
+        Json::Num(self.to_string())
+    }
+
+    fn decode(json: &Json, _: &[NodeData]) -> Option<Self> {
+        match json {
+            Json::Num(n) => n.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl Field for bool {
+    fn encode(&self) -> Json {
+        Json::Bool(*self)
+    }
+
+    fn decode(json: &Json, _: &[NodeData]) -> Option<Self> {
+        json.bool()
+    }
+}
+
+impl Field for Node {
+    fn encode(&self) -> Json {
+        Json::Arr(vec![self.idx.encode(), self.shape.rows().encode(), self.shape.cols().encode()])
+    }
+
+    fn decode(json: &Json, nodes: &[NodeData]) -> Option<Self> {
+        let [idx, rows, cols] = json.arr()? else { return None };
+        let node = nodes.get(idx.usize()?)?.own;
+        let (rows, cols) = (rows.usize()?, cols.usize()?);
+        (rows > 0 && cols > 0).then(|| node.reshape(Shape::new(rows, cols)).ok())?
+    }
+}
+
+impl Field for Option<Node> {
+    fn encode(&self) -> Json {
+        self.as_ref().map_or(Json::Null, Node::encode)
+    }
+
+    fn decode(json: &Json, nodes: &[NodeData]) -> Option<Self> {
+        match json {
+            Json::Null => Some(None),
+            json => Node::decode(json, nodes).map(Some),
+        }
+    }
+}
+
+impl Field for NodeList {
+    fn encode(&self) -> Json {
+        Json::Arr(self.iter().map(Node::encode).collect())
+    }
+
+    fn decode(json: &Json, nodes: &[NodeData]) -> Option<Self> {
+        let list = json.arr()?.iter().map(|node| Node::decode(node, nodes)).collect::<Option<Vec<_>>>()?;
+        NodeList::new(&list)
+    }
+}
+
+impl Field for Activation {
+    fn encode(&self) -> Json {
+        Json::Str(format!("{self:?}"))
+    }
+
+    fn decode(json: &Json, _: &[NodeData]) -> Option<Self> {
+        use Activation::*;
+        let name = json.str()?;
+        [Identity, ReLU, CReLU, SCReLU, SqrReLU, Sigmoid, Square, GELU, SiLU]
+            .into_iter()
+            .find(|act| act.encode().str() == Some(name))
+    }
+}
+
+impl Field for PoolSettings {
+    fn encode(&self) -> Json {
+        let fields = [self.channels, self.height, self.width, self.pool_height, self.pool_width];
+        Json::Arr(fields.iter().map(usize::encode).collect())
+    }
+
+    fn decode(json: &Json, nodes: &[NodeData]) -> Option<Self> {
+        let [channels, height, width, pool_height, pool_width] = json.arr()? else { return None };
+        let field = |json| usize::decode(json, nodes);
+
+        Some(Self {
+            channels: field(channels)?,
+            height: field(height)?,
+            width: field(width)?,
+            pool_height: field(pool_height)?,
+            pool_width: field(pool_width)?,
+        })
+    }
+}
+
+impl Field for ConvSettings {
+    fn encode(&self) -> Json {
+        let fields =
+            [self.in_channels, self.out_channels, self.height, self.width, self.kernel_height, self.kernel_width];
+        Json::Arr(fields.iter().map(usize::encode).collect())
+    }
+
+    fn decode(json: &Json, nodes: &[NodeData]) -> Option<Self> {
+        let [in_channels, out_channels, height, width, kernel_height, kernel_width] = json.arr()? else { return None };
+        let field = |json| usize::decode(json, nodes);
+
+        Some(Self {
+            in_channels: field(in_channels)?,
+            out_channels: field(out_channels)?,
+            height: field(height)?,
+            width: field(width)?,
+            kernel_height: field(kernel_height)?,
+            kernel_width: field(kernel_width)?,
+        })
+    }
+}
+
+/// Lists the arguments of each operation, which are written in order after its name.
+macro_rules! operations {
+    ($($variant:ident($($field:ident: $ty:ty),+)),+ $(,)?) => {
+        fn encode_operation(op: &Operation) -> Json {
+            match op {
+                $(Operation::$variant($($field),+) => {
+                    Json::Arr(vec![Json::Str(stringify!($variant).to_string()) $(, $field.encode())+])
+                })+
+            }
+        }
+
+        fn decode_operation(json: &Json, nodes: &[NodeData]) -> Option<Operation> {
+            let (name, mut args) = json.arr()?.split_first().map(|(name, args)| (name, args.iter()))?;
+
+            let op = match name.str()? {
+                $(stringify!($variant) => Operation::$variant($(<$ty>::decode(args.next()?, nodes)?),+),)+
+                _ => return None,
+            };
+
+            args.next().is_none().then_some(op)
+        }
+    };
+}
+
+operations! {
+    Abs(a: Node),
+    Activate(a: Node, act: Activation),
+    Affine(w: Node, a: Node, b: Node),
+    AffineActivate(w: Node, a: Node, b: Node, act: Activation),
+    Attention(q: Node, k: Node, v: Node, mask: Option<Node>, seq_len: usize),
+    AvgPool(a: Node, settings: PoolSettings),
+    BatchNorm(a: Node, w: Node, b: Node, mean: Node, var: Node, momentum: f32),
+    Clamp(a: Node, min: f32, max: f32),
+    SparseAffine(w: Node, a: Node, b: Option<Node>),
+    SparseAffineDualActivate(w: Node, stm: Node, ntm: Node, b: Node, act: Activation),
+    SparseOuter(a: Node, b: Node),
+    Concat(a: Node, b: Node),
+    Conv2d(w: Node, a: Node, settings: ConvSettings),
+    ConcatMany(nodes: NodeList),
+    Dropout(a: Node, rate: f32),
+    ElementwiseDiv(a: Node, b: Node, epsilon: f32),
+    Embedding(w: Node, a: Node),
+    ElementwiseMul(a: Node, b: Node),
+    Gather(a: Node, b: Node),
+    HuberError(a: Node, b: Node, delta: f32),
+    LayerNorm(a: Node, w: Node, b: Node),
+    LinearCombination(alpha: f32, a: Node, beta: f32, b: Node),
+    Mask(a: Node, b: Node),
+    Matmul(a: Node, trans_a: bool, b: Node, trans_b: bool),
+    MaxPool(a: Node, settings: PoolSettings),
+    Max(a: Node, b: Node),
+    Min(a: Node, b: Node),
+    PairwiseMul(a: Node, post_concat: bool),
+    PairwiseRankingLoss(a: Node, b: Node, margin: f32),
+    PowerError(a: Node, b: Node, power: f32),
+    PReLU(a: Node, slope: Node),
+    ReduceAcrossBatch(a: Node),
+    ReduceMean(a: Node),
+    ReduceSum(a: Node),
+    Repeat(a: Node, times: usize),
+    RepeatAcrossBatch(a: Node, batched: Node),
+    Select(a: Node, b: Node),
+    Slice(a: Node, start: usize, end: usize),
+    SliceColumns(a: Node, start: usize, end: usize),
+    Softmax(a: Node),
+    ToDense(a: Node),
+    Transpose(a: Node),
+    MaskedSoftmaxCrossEntropyLoss(mask: Node, a: Node, b: Node, temperature: f32, smoothing: f32),
+    SoftmaxCrossEntropyLoss(a: Node, b: Node, temperature: f32, smoothing: f32),
+    KLDivergenceLoss(a: Node, b: Node),
+    SigmoidCrossEntropyLoss(a: Node, b: Node),
+}
@@ -0,0 +1,51 @@
+use super::GraphBuilder;
+use crate::graph::operation::Operation;
+
+impl GraphBuilder {
+    /// Fuses each `Affine` whose only use is an `Activate` into a single `AffineActivate`,
+    /// which adds the bias and applies the activation in one kernel, returning the number
+    /// of operations fused.
+    ///
+    /// The fused `Affine` nodes no longer hold values, so nodes with a label, loss components
+    /// and outputs of the graph are left as they are.
+    pub fn fuse_affine_activate(&mut self) -> usize {
+        let mut uses = vec![0; self.nodes.len()];
+
+        for data in &self.nodes {
+            if let Some(op) = &data.parent_operation {
+                for node in op.nodes() {
+                    uses[node.idx] += 1;
+                }
+            }
+        }
+
+        let protected = |builder: &Self, idx: usize| {
+            builder.nodes[idx].label.is_some()
+                || builder.roots.contains(&idx)
+                || builder.loss == Some(idx)
+                || builder.loss_components.iter().any(|(_, node)| *node == idx)
+        };
+
+        let mut fused = 0;
+
+        for idx in 0..self.nodes.len() {
+            let Some(Operation::Activate(input, act)) = self.nodes[idx].parent_operation else { continue };
+            let Some(Operation::Affine(w, i, b)) = self.nodes[input.idx].parent_operation else { continue };
+
+            // a reshaped `Affine` would change the shape of the output
+            let same_shape = input.shape == self.nodes[input.idx].own.shape;
+
+            if act.can_be_fused() && same_shape && uses[input.idx] == 1 && !protected(self, input.idx) {
+                self.nodes[idx].parent_operation = Some(Operation::AffineActivate(w, i, b, act));
+
+                let removed = &mut self.nodes[input.idx];
+                removed.parent_operation = None;
+                removed.requires_grad = false;
+
+                fused += 1;
+            }
+        }
+
+        fused
+    }
+}
@@ -22,3 +22,11 @@ impl<T: Debug> From<OperationError<T>> for GraphError<T> {
         Self::Operation(value)
     }
 }
+
+/// Reasons that a graph could not be rebuilt with `GraphBuilder::from_description`.
+#[derive(Debug)]
+pub enum GraphDescriptionError {
+    InvalidJson,
+    InvalidNode(usize),
+    Builder(GraphBuilderError),
+}
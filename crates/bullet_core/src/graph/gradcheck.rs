@@ -0,0 +1,93 @@
+use crate::{
+    device::{Device, OperationError},
+    tensor::Matrix,
+};
+
+use super::Graph;
+
+/// A weight whose analytic gradient disagrees with the central difference estimate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradCheckFailure {
+    pub weights: String,
+    pub index: usize,
+    pub analytic: f32,
+    pub numerical: f32,
+}
+
+/// Fills every weight and dense input of the graph with normally distributed values,
+/// keeping the current batch size of each.
+pub fn randomise<D: Device>(graph: &mut Graph<D>, stdev: f32) -> Result<(), D::DeviceError> {
+    for id in graph.weight_ids() {
+        graph.get_weights_mut(&id).seed_random(0.0, stdev, true)?;
+    }
+
+    for id in graph.input_ids() {
+        let input = graph.get_input_mut(&id);
+        if let Matrix::Dense(_) = input.values {
+            input.seed_random(0.0, stdev, true)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Compares the gradient of the loss with respect to every element of every weight, as
+/// computed by `Graph::backward`, with the central difference `(f(x + e) - f(x - e)) / 2e`,
+/// returning every element for which they differ by more than `tolerance` (relative to the
+/// larger of the two, or absolute if both are below `1`).
+///
+/// The graph should be small, as it is run forward twice per element, and deterministic,
+/// so e.g. `Dropout` should not be present. Kinks in non-smooth operations (such as `ReLU`
+/// at `0`) can cause spurious failures if a value lies within `epsilon` of them.
+pub fn gradcheck<D: Device>(
+    graph: &mut Graph<D>,
+    epsilon: f32,
+    tolerance: f32,
+) -> Result<Vec<GradCheckFailure>, OperationError<D::DeviceError>> {
+    graph.zero_grads()?;
+    graph.forward()?;
+    graph.backward()?;
+
+    let mut ids = graph.weight_ids();
+    ids.sort();
+
+    let mut failures = Vec::new();
+
+    for id in ids {
+        let (batch_size, vals, grads) = {
+            let weights = graph.get_weights(&id);
+            let Some(grad) = weights.gradients.as_ref() else { continue };
+
+            let vals = weights.get_dense_vals()?;
+            let mut grads = vec![0.0; vals.len()];
+            grad.write_to_slice(&mut grads)?;
+
+            (weights.values.batch_size(), vals, grads)
+        };
+
+        let mut perturbed = vals.clone();
+
+        for (index, (&val, &analytic)) in vals.iter().zip(grads.iter()).enumerate() {
+            perturbed[index] = val + epsilon;
+            graph.get_weights_mut(&id).load_dense_from_slice(batch_size, &perturbed)?;
+            let plus = graph.forward()?;
+
+            perturbed[index] = val - epsilon;
+            graph.get_weights_mut(&id).load_dense_from_slice(batch_size, &perturbed)?;
+            let minus = graph.forward()?;
+
+            perturbed[index] = val;
+
+            let numerical = (plus - minus) / (2.0 * epsilon);
+            let scale = numerical.abs().max(analytic.abs()).max(1.0);
+
+            if (numerical - analytic).abs() > tolerance * scale {
+                failures.push(GradCheckFailure { weights: id.clone(), index, analytic, numerical });
+            }
+        }
+
+        graph.get_weights_mut(&id).load_dense_from_slice(batch_size, &vals)?;
+    }
+
+    Ok(failures)
+}
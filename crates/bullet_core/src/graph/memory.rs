@@ -0,0 +1,121 @@
+use std::{cell::RefCell, num::NonZeroUsize, sync::Arc};
+
+use super::{builder::Node, operation::Operation};
+use crate::{
+    device::{Device, DeviceBuffer},
+    tensor::{DenseMatrix, Tensor},
+};
+
+/// Assigns the gradients of intermediate nodes to a set of shared device buffers.
+///
+/// The gradient of an intermediate node is only live during the backward pass,
+/// from the first time one of its consumers backprops into it until its own backward
+/// has run, so nodes whose gradients are never live at the same time can share a buffer.
+/// Values are not planned, as most operations need their forward values in backward.
+pub(crate) struct MemoryPlan<D: Device> {
+    /// The buffer holding the gradient of each node, if it is planned.
+    slots: Vec<Option<usize>>,
+    /// The planned nodes whose gradients are first written by the backward of each node.
+    acquire: Vec<Vec<usize>>,
+    /// The batch size of the gradient of each node when it was last released.
+    batch_sizes: Vec<Option<NonZeroUsize>>,
+    buffers: Vec<Option<D::BufferF32>>,
+}
+
+impl<D: Device> MemoryPlan<D> {
+    pub fn new(
+        device: Arc<D>,
+        operations: &[Option<&Operation>],
+        sizes: &[usize],
+        requires_grad: &[bool],
+        in_backward: &[bool],
+        root: usize,
+    ) -> Result<Self, D::DeviceError> {
+        let num = operations.len();
+
+        let mut first_write = vec![None; num];
+        for (idx, op) in operations.iter().enumerate() {
+            if let (true, Some(op)) = (in_backward[idx], op) {
+                for input in op.nodes() {
+                    first_write[input.idx] = Some(idx);
+                }
+            }
+        }
+
+        let planned = |idx: usize| {
+            idx != root
+                && in_backward[idx]
+                && requires_grad[idx]
+                && operations[idx].is_some()
+                && first_write[idx].is_some()
+        };
+
+        let mut acquire = vec![Vec::new(); num];
+        for idx in (0..num).filter(|&idx| planned(idx)) {
+            acquire[first_write[idx].unwrap()].push(idx);
+        }
+
+        let mut slots = vec![None; num];
+        let mut slot_sizes: Vec<usize> = Vec::new();
+        let mut free = Vec::new();
+
+        for idx in (0..num).rev().filter(|&idx| in_backward[idx]) {
+            for &node in &acquire[idx] {
+                let slot = free.pop().unwrap_or_else(|| {
+                    slot_sizes.push(0);
+                    slot_sizes.len() - 1
+                });
+
+                slot_sizes[slot] = slot_sizes[slot].max(sizes[node]);
+                slots[node] = Some(slot);
+            }
+
+            if let Some(slot) = slots[idx] {
+                free.push(slot);
+            }
+        }
+
+        let buffers = slot_sizes
+            .into_iter()
+            .map(|size| D::BufferF32::new(device.clone(), size).map(Some))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { slots, acquire, batch_sizes: vec![None; num], buffers })
+    }
+
+    /// Whether the gradient of a node lives in a shared buffer rather than its own.
+    pub fn is_planned(&self, idx: usize) -> bool {
+        self.slots[idx].is_some()
+    }
+
+    /// Number of shared buffers holding the gradients of planned nodes.
+    pub fn num_buffers(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Hands a zeroed gradient to each planned node first written by the backward of `node`.
+    pub fn acquire(&mut self, nodes: &[RefCell<Tensor<D>>], node: Node) -> Result<(), D::DeviceError> {
+        for &idx in &self.acquire[node.idx] {
+            let slot = self.slots[idx].unwrap();
+            let buf = self.buffers[slot].take().expect("Gradient buffer is still in use!");
+
+            let tensor = &mut *nodes[idx].borrow_mut();
+            let mut grad = DenseMatrix { buf, single_size: tensor.values.single_size(), batch_size: None };
+            grad.set_batch_size(self.batch_sizes[idx].map(NonZeroUsize::get))?;
+            grad.set_zero()?;
+            tensor.gradients = Some(grad);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the gradient of `node` to its shared buffer, once its backward has run.
+    pub fn release(&mut self, nodes: &[RefCell<Tensor<D>>], node: Node) {
+        if let Some(slot) = self.slots[node.idx] {
+            if let Some(grad) = nodes[node.idx].borrow_mut().gradients.take() {
+                self.batch_sizes[node.idx] = grad.batch_size;
+                self.buffers[slot] = Some(grad.buf);
+            }
+        }
+    }
+}
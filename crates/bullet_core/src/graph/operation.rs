@@ -1,10 +1,17 @@
 mod concat;
 mod linear_comb;
 mod matmul;
+mod repeat;
 mod slice;
 mod sparse;
 
-use std::{cell::RefCell, collections::HashMap, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    num::NonZeroUsize,
+    ops::{Deref, DerefMut},
+    sync::Arc,
+};
 
 use crate::{
     device::{Device, DeviceBuffer, OperationError},
@@ -26,38 +33,194 @@ pub enum Activation {
     SqrReLU = 4,
     Sigmoid = 5,
     Square = 6,
+    GELU = 7,
+    SiLU = 8,
+}
+
+impl Activation {
+    /// Whether the derivative can be computed from the output of the activation, which
+    /// is required for it to be fused into a preceding affine transformation.
+    pub fn can_be_fused(self) -> bool {
+        !matches!(self, Activation::Square | Activation::GELU | Activation::SiLU)
+    }
+}
+
+/// Non-overlapping pooling over `channels` planes, each of size `height x width`
+/// stored in row-major order, with windows of size `pool_height x pool_width`.
+/// One-dimensional pooling is given by `height = pool_height = 1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolSettings {
+    pub channels: usize,
+    pub height: usize,
+    pub width: usize,
+    pub pool_height: usize,
+    pub pool_width: usize,
+}
+
+impl PoolSettings {
+    pub fn input_size(&self) -> usize {
+        self.channels * self.height * self.width
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.channels * (self.height / self.pool_height) * (self.width / self.pool_width)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.pool_height > 0
+            && self.pool_width > 0
+            && self.height % self.pool_height == 0
+            && self.width % self.pool_width == 0
+    }
+}
+
+/// Stride-1 convolution over `in_channels` planes, each of size `height x width`
+/// stored in row-major order, producing `out_channels` planes of the same size.
+/// Inputs are zero-padded by `kernel_height / 2` and `kernel_width / 2`, so kernel
+/// dimensions must be odd. Filters are laid out as `[out][in][kernel_y][kernel_x]`,
+/// i.e. as a `(in_channels * kernel_height * kernel_width) x out_channels` matrix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConvSettings {
+    pub in_channels: usize,
+    pub out_channels: usize,
+    pub height: usize,
+    pub width: usize,
+    pub kernel_height: usize,
+    pub kernel_width: usize,
+}
+
+impl ConvSettings {
+    pub fn input_size(&self) -> usize {
+        self.in_channels * self.height * self.width
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.out_channels * self.height * self.width
+    }
+
+    pub fn filters_shape(&self) -> Shape {
+        Shape::new(self.in_channels * self.kernel_height * self.kernel_width, self.out_channels)
+    }
+
+    pub fn filters_size(&self) -> usize {
+        self.filters_shape().size()
+    }
+
+    fn is_valid(&self) -> bool {
+        self.in_channels > 0
+            && self.out_channels > 0
+            && self.kernel_height % 2 == 1
+            && self.kernel_width % 2 == 1
+            && self.height > 0
+            && self.width > 0
+            && self.kernel_height < 2 * self.height
+            && self.kernel_width < 2 * self.width
+    }
+}
+
+/// Inputs of `Operation::ConcatMany`, stored inline so that `Operation` remains `Copy`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NodeList {
+    len: usize,
+    nodes: [Node; NodeList::CAPACITY],
+}
+
+impl NodeList {
+    pub const CAPACITY: usize = 8;
+
+    /// Returns `None` if there are more than `CAPACITY` nodes.
+    pub fn new(nodes: &[Node]) -> Option<Self> {
+        if nodes.len() > Self::CAPACITY {
+            return None;
+        }
+
+        let unused = Node { idx: usize::MAX, shape: Shape::new(1, 1), sparse: None, can_be_batched: false };
+        let mut list = Self { len: nodes.len(), nodes: [unused; Self::CAPACITY] };
+        list.nodes[..nodes.len()].copy_from_slice(nodes);
+
+        Some(list)
+    }
+}
+
+impl Deref for NodeList {
+    type Target = [Node];
+
+    fn deref(&self) -> &[Node] {
+        &self.nodes[..self.len]
+    }
+}
+
+impl DerefMut for NodeList {
+    fn deref_mut(&mut self) -> &mut [Node] {
+        &mut self.nodes[..self.len]
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Operation {
+    Abs(Node),
     Activate(Node, Activation),
     Affine(Node, Node, Node),
+    AffineActivate(Node, Node, Node, Activation),
+    Attention(Node, Node, Node, Option<Node>, usize),
+    AvgPool(Node, PoolSettings),
+    BatchNorm(Node, Node, Node, Node, Node, f32),
+    Clamp(Node, f32, f32),
     SparseAffine(Node, Node, Option<Node>),
     SparseAffineDualActivate(Node, Node, Node, Node, Activation),
+    SparseOuter(Node, Node),
     Concat(Node, Node),
+    Conv2d(Node, Node, ConvSettings),
+    ConcatMany(NodeList),
+    Dropout(Node, f32),
+    ElementwiseDiv(Node, Node, f32),
+    Embedding(Node, Node),
+    ElementwiseMul(Node, Node),
     Gather(Node, Node),
+    HuberError(Node, Node, f32),
+    LayerNorm(Node, Node, Node),
     LinearCombination(f32, Node, f32, Node),
     Mask(Node, Node),
     Matmul(Node, bool, Node, bool),
+    MaxPool(Node, PoolSettings),
+    Max(Node, Node),
+    Min(Node, Node),
     PairwiseMul(Node, bool),
+    PairwiseRankingLoss(Node, Node, f32),
     PowerError(Node, Node, f32),
+    PReLU(Node, Node),
     ReduceAcrossBatch(Node),
+    ReduceMean(Node),
+    ReduceSum(Node),
+    Repeat(Node, usize),
+    RepeatAcrossBatch(Node, Node),
     Select(Node, Node),
     Slice(Node, usize, usize),
+    SliceColumns(Node, usize, usize),
+    Softmax(Node),
     ToDense(Node),
-    MaskedSoftmaxCrossEntropyLoss(Node, Node, Node),
-    SoftmaxCrossEntropyLoss(Node, Node),
+    Transpose(Node),
+    MaskedSoftmaxCrossEntropyLoss(Node, Node, Node, f32, f32),
+    SoftmaxCrossEntropyLoss(Node, Node, f32, f32),
+    KLDivergenceLoss(Node, Node),
+    SigmoidCrossEntropyLoss(Node, Node),
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct GraphBuilderError {
-    pub op: Box<Operation>,
+    /// The operation that could not be created, or `None` if a node could not be reshaped.
+    pub op: Option<Box<Operation>>,
+    pub inputs: Vec<Node>,
     pub ty: GraphBuilderErrorType,
 }
 
 impl GraphBuilderError {
     pub fn new(op: &Operation, ty: GraphBuilderErrorType) -> Self {
-        Self { op: Box::new(*op), ty }
+        Self { op: Some(Box::new(*op)), inputs: op.nodes(), ty }
+    }
+
+    pub fn reshape(node: Node, shape: Shape) -> Self {
+        Self { op: None, inputs: vec![node], ty: GraphBuilderErrorType::MismatchedInputShapes(vec![node.shape, shape]) }
     }
 }
 
@@ -71,6 +234,8 @@ pub enum GraphBuilderErrorType {
     InvalidMatmulDims,
     ActivationCannotBeFused,
     NodeWithIdAlreadyExists,
+    InvalidTemperature(f32),
+    InvalidSmoothing(f32),
 }
 
 impl Operation {
@@ -80,9 +245,8 @@ impl Operation {
 
         let ret = |cond, ok, err| if cond { Ok(ok) } else { Err(err) };
 
-        let mismatch = |nodes: &[&Node]| GraphBuilderError {
-            op: Box::new(*self),
-            ty: MismatchedInputShapes(nodes.iter().map(|&x| x.shape).collect::<Vec<_>>()),
+        let mismatch = |nodes: &[&Node]| {
+            GraphBuilderError::new(self, MismatchedInputShapes(nodes.iter().map(|&x| x.shape).collect::<Vec<_>>()))
         };
 
         let check_dense_eq = |node: &Node, dense: bool| {
@@ -101,6 +265,16 @@ impl Operation {
             }
         };
 
+        let check_softmax_loss = |temperature: f32, smoothing: f32| {
+            if temperature <= 0.0 {
+                Err(GraphBuilderError::new(self, GraphBuilderErrorType::InvalidTemperature(temperature)))
+            } else if !(0.0..1.0).contains(&smoothing) {
+                Err(GraphBuilderError::new(self, GraphBuilderErrorType::InvalidSmoothing(smoothing)))
+            } else {
+                Ok(())
+            }
+        };
+
         let check_matmul = |a: Shape, b: Shape| {
             if let Some(c) = a.matmul(b) {
                 Ok(c)
@@ -110,19 +284,83 @@ impl Operation {
         };
 
         match self {
-            Activate(node, _) => {
+            Abs(node) | Activate(node, _) | Clamp(node, _, _) => {
                 check_dense_eq(node, true)?;
                 Ok(node.shape)
             }
-            Affine(w, i, b) => {
+            PReLU(input, slope) => {
+                check_dense_eq(input, true)?;
+                check_dense_eq(slope, true)?;
+                check_not_batched(slope)?;
+                let valid = input.shape.cols() == 1 && input.shape == slope.shape;
+                ret(valid, input.shape, mismatch(&[input, slope]))
+            }
+            AvgPool(node, settings) | MaxPool(node, settings) => {
+                check_dense_eq(node, true)?;
+                let is = node.shape;
+                let valid = is.cols() == 1 && is.rows() == settings.input_size() && settings.is_valid();
+                ret(valid, Shape::new(settings.output_size(), 1), GraphBuilderError::new(self, InvalidInputShape(is)))
+            }
+            Attention(q, k, v, mask, seq_len) => {
+                for node in [q, k, v] {
+                    check_dense_eq(node, true)?;
+                }
+
+                let is = q.shape;
+                let valid = is.cols() == 1 && *seq_len > 0 && is.rows() % seq_len == 0;
+                if !valid || k.shape != is || v.shape != is {
+                    return Err(mismatch(&[q, k, v]));
+                }
+
+                if let Some(mask) = mask {
+                    check_dense_eq(mask, true)?;
+                    if mask.shape != Shape::new(seq_len * seq_len, 1) {
+                        return Err(GraphBuilderError::new(self, InvalidInputShape(mask.shape)));
+                    }
+                }
+
+                Ok(is)
+            }
+            Conv2d(filters, input, settings) => {
+                check_dense_eq(filters, true)?;
+                check_dense_eq(input, true)?;
+                check_not_batched(filters)?;
+
+                let is = input.shape;
+                let valid = is.cols() == 1
+                    && is.rows() == settings.input_size()
+                    && filters.shape == settings.filters_shape()
+                    && settings.is_valid();
+                ret(valid, Shape::new(settings.output_size(), 1), mismatch(&[filters, input]))
+            }
+            Affine(w, i, b) | AffineActivate(w, i, b, _) => {
                 check_dense_eq(w, true)?;
                 check_dense_eq(i, true)?;
                 check_dense_eq(b, true)?;
                 check_not_batched(w)?;
                 check_not_batched(b)?;
 
+                if let AffineActivate(_, _, _, act) = self {
+                    if !act.can_be_fused() {
+                        return Err(GraphBuilderError::new(self, GraphBuilderErrorType::ActivationCannotBeFused));
+                    }
+                }
+
                 let out = check_matmul(w.shape, i.shape)?;
-                ret(out == b.shape, out, mismatch(&[w, i]))
+                let valid = out == b.shape || b.shape == Shape::new(out.rows(), 1);
+                ret(valid, out, mismatch(&[w, i]))
+            }
+            BatchNorm(input, scale, shift, mean, var, _) => {
+                check_dense_eq(input, true)?;
+
+                for node in [scale, shift, mean, var] {
+                    check_dense_eq(node, true)?;
+                    check_not_batched(node)?;
+                }
+
+                let is = input.shape;
+                let valid = is.cols() == 1 && [scale, shift, mean, var].iter().all(|node| node.shape == is);
+                ret(valid, is, mismatch(&[input, scale, shift, mean, var]))
             }
             Concat(a, b) => {
                 check_dense_eq(a, true)?;
@@ -135,6 +373,31 @@ impl Operation {
                 let out = Shape::new(a.shape.rows() + b.shape.rows(), a.shape.cols());
                 ret(a.shape.cols() == b.shape.cols(), out, mismatch(&[a, b]))
             }
+            ConcatMany(nodes) => {
+                for node in nodes.iter() {
+                    check_dense_eq(node, true)?;
+
+                    if node.shape.cols() != 1 {
+                        return Err(GraphBuilderError::new(self, InvalidInputShape(node.shape)));
+                    }
+                }
+
+                let rows = nodes.iter().map(|node| node.shape.rows()).sum();
+                ret(!nodes.is_empty(), Shape::new(rows, 1), mismatch(&nodes.iter().collect::<Vec<_>>()))
+            }
+            Dropout(node, _) => {
+                check_dense_eq(node, true)?;
+                Ok(node.shape)
+            }
+            Embedding(table, indices) => {
+                check_dense_eq(table, true)?;
+                check_dense_eq(indices, false)?;
+                check_not_batched(table)?;
+
+                let nnz = indices.sparse.map(usize::from).unwrap_or(0);
+                let valid = indices.shape.cols() == 1 && indices.shape.rows() == table.shape.cols();
+                ret(valid, Shape::new(table.shape.rows() * nnz, 1), mismatch(&[table, indices]))
+            }
             Gather(input, mask) => {
                 check_dense_eq(input, true)?;
                 check_dense_eq(mask, false)?;
@@ -142,10 +405,34 @@ impl Operation {
                 let valid = input.shape.cols() == 1 && mask.shape.cols() == 1;
                 ret(valid, mask.shape, mismatch(&[input, mask]))
             }
+            LayerNorm(input, scale, shift) => {
+                check_dense_eq(input, true)?;
+                check_dense_eq(scale, true)?;
+                check_dense_eq(shift, true)?;
+                check_not_batched(scale)?;
+                check_not_batched(shift)?;
+
+                let is = input.shape;
+                let valid = is.cols() == 1 && scale.shape == is && shift.shape == is;
+                ret(valid, is, mismatch(&[input, scale, shift]))
+            }
             LinearCombination(_, a, _, b) => {
                 check_dense_eq(a, true)?;
                 check_dense_eq(b, true)?;
 
+                if a.shape == b.shape {
+                    return Ok(a.shape);
+                }
+
+                // a column vector is broadcast across the columns of the other input
+                let (full, vector) = if b.shape.cols() == 1 { (a, b) } else { (b, a) };
+                check_not_batched(vector)?;
+                ret(vector.shape == Shape::new(full.shape.rows(), 1), full.shape, mismatch(&[a, b]))
+            }
+            ElementwiseDiv(a, b, _) | ElementwiseMul(a, b) | Max(a, b) | Min(a, b) => {
+                check_dense_eq(a, true)?;
+                check_dense_eq(b, true)?;
+
                 ret(a.shape == b.shape, a.shape, mismatch(&[a, b]))
             }
             Mask(input, mask) => {
@@ -167,9 +454,16 @@ impl Operation {
                 let out = Shape::new(is.rows() / 2, is.cols());
                 ret(is.rows() % min == 0, out, GraphBuilderError::new(self, InvalidInputShape(is)))
             }
-            PowerError(a, b, _) => {
+            HuberError(a, b, _) | PowerError(a, b, _) => {
+                check_dense_eq(a, true)?;
+                check_dense_eq(b, true)?;
+                ret(a.shape == b.shape, a.shape, mismatch(&[a, b]))
+            }
+            PairwiseRankingLoss(a, b, _) => {
                 check_dense_eq(a, true)?;
                 check_dense_eq(b, true)?;
+                let is = a.shape;
+                ret(is == Shape::new(1, 1), is, GraphBuilderError::new(self, InvalidInputShape(is)))?;
                 ret(a.shape == b.shape, a.shape, mismatch(&[a, b]))
             }
             ReduceAcrossBatch(node) => {
@@ -177,6 +471,21 @@ impl Operation {
                 let is = node.shape;
                 ret(is == Shape::new(1, 1), is, GraphBuilderError::new(self, InvalidInputShape(is)))
             }
+            ReduceMean(node) | ReduceSum(node) => {
+                check_dense_eq(node, true)?;
+                Ok(Shape::new(1, node.shape.cols()))
+            }
+            Repeat(node, times) => {
+                check_dense_eq(node, true)?;
+                let is = node.shape;
+                let out = Shape::new(is.rows(), is.cols() * times);
+                ret(*times > 0, out, GraphBuilderError::new(self, InvalidInputShape(is)))
+            }
+            RepeatAcrossBatch(node, _) => {
+                check_dense_eq(node, true)?;
+                check_not_batched(node)?;
+                Ok(node.shape)
+            }
             Select(input, buckets) => {
                 check_dense_eq(input, true)?;
                 check_dense_eq(buckets, false)?;
@@ -193,6 +502,22 @@ impl Operation {
                 let out = Shape::new(end - start, 1);
                 ret(valid, out, GraphBuilderError::new(self, OutOfBounds(is, [*start, *end])))
             }
+            SliceColumns(input, start, end) => {
+                check_dense_eq(input, true)?;
+                let is = input.shape;
+                let valid = end > start && *end <= is.cols();
+                let out = Shape::new(is.rows(), end - start);
+                ret(valid, out, GraphBuilderError::new(self, OutOfBounds(is, [*start, *end])))
+            }
+            Transpose(node) => {
+                check_dense_eq(node, true)?;
+                Ok(node.shape.transpose())
+            }
+            Softmax(node) => {
+                check_dense_eq(node, true)?;
+                let is = node.shape;
+                ret(is.cols() == 1, is, GraphBuilderError::new(self, InvalidInputShape(is)))
+            }
             SparseAffine(w, i, b) => {
                 check_dense_eq(w, true)?;
                 check_dense_eq(i, false)?;
@@ -215,7 +540,7 @@ impl Operation {
                 check_not_batched(b)?;
                 let shb = b.shape;
 
-                if *act == Activation::Square {
+                if !act.can_be_fused() {
                     return Err(GraphBuilderError::new(self, GraphBuilderErrorType::ActivationCannotBeFused));
                 }
 
@@ -223,11 +548,17 @@ impl Operation {
                 let valid = s.shape == n.shape && out == shb;
                 ret(valid, Shape::new(2 * shb.rows(), shb.cols()), mismatch(&[w, s, n, b]))
             }
+            SparseOuter(a, b) => {
+                check_dense_eq(a, false)?;
+                check_dense_eq(b, false)?;
+                let valid = a.shape.cols() == 1 && b.shape.cols() == 1;
+                ret(valid, Shape::new(a.shape.rows() * b.shape.rows(), 1), mismatch(&[a, b]))
+            }
             ToDense(node) => {
                 check_dense_eq(node, false)?;
                 Ok(node.shape)
             }
-            MaskedSoftmaxCrossEntropyLoss(mask, input, target) => {
+            MaskedSoftmaxCrossEntropyLoss(mask, input, target, temperature, smoothing) => {
                 check_dense_eq(input, true)?;
                 check_dense_eq(target, true)?;
                 let is = input.shape;
@@ -235,13 +566,33 @@ impl Operation {
                     && mask.shape == is
                     && is.cols() == 1
                     && target.shape.cols() == 1;
+                check_softmax_loss(*temperature, *smoothing)?;
                 ret(valid, Shape::new(1, 1), mismatch(&[mask, input, target]))
             }
-            SoftmaxCrossEntropyLoss(a, b) => {
+            SoftmaxCrossEntropyLoss(a, b, temperature, smoothing) => {
                 check_dense_eq(a, true)?;
                 check_dense_eq(b, true)?;
+                check_softmax_loss(*temperature, *smoothing)?;
                 ret(a.shape == b.shape, Shape::new(1, 1), mismatch(&[a, b]))
             }
+            KLDivergenceLoss(a, b) => {
+                check_dense_eq(a, true)?;
+                check_dense_eq(b, true)?;
+                ret(a.shape == b.shape && a.shape.cols() == 1, Shape::new(1, 1), mismatch(&[a, b]))
+            }
+            SigmoidCrossEntropyLoss(a, b) => {
+                check_dense_eq(a, true)?;
+                check_dense_eq(b, true)?;
+                ret(a.shape == b.shape, a.shape, mismatch(&[a, b]))
+            }
+        }
+    }
+
+    /// The number of nonzero indices per batch element of the output, if it is sparse.
+    pub(crate) fn output_nnz(&self) -> Option<NonZeroUsize> {
+        match self {
+            Operation::SparseOuter(a, b) => a.sparse.zip(b.sparse).and_then(|(a, b)| a.checked_mul(b)),
+            _ => None,
         }
     }
 
@@ -249,18 +600,115 @@ impl Operation {
         use Operation::*;
 
         match *self {
+            Abs(node) => vec![node],
+            Activate(node, _) => vec![node],
+            Clamp(node, _, _) => vec![node],
+            PReLU(input, slope) => vec![input, slope],
+            Affine(a, b, c) => vec![a, b, c],
+            AffineActivate(a, b, c, _) => vec![a, b, c],
+            AvgPool(node, _) => vec![node],
+            MaxPool(node, _) => vec![node],
+            BatchNorm(input, scale, shift, mean, var, _) => vec![input, scale, shift, mean, var],
+            Attention(q, k, v, mask, _) => {
+                if let Some(mask) = mask {
+                    vec![q, k, v, mask]
+                } else {
+                    vec![q, k, v]
+                }
+            }
+            Concat(a, b) => vec![a, b],
+            Conv2d(filters, input, _) => vec![filters, input],
+            ConcatMany(nodes) => nodes.to_vec(),
+            Dropout(node, _) => vec![node],
+            ElementwiseDiv(a, b, _) => vec![a, b],
+            ElementwiseMul(a, b) => vec![a, b],
+            Embedding(table, indices) => vec![table, indices],
+            Gather(input, mask) => vec![input, mask],
+            LayerNorm(input, scale, shift) => vec![input, scale, shift],
+            LinearCombination(_, a, _, b) => vec![a, b],
+            Mask(input, mask) => vec![input, mask],
+            Matmul(a, _, b, _) => vec![a, b],
+            Max(a, b) => vec![a, b],
+            Min(a, b) => vec![a, b],
+            PairwiseMul(input, _) => vec![input],
+            HuberError(a, b, _) => vec![a, b],
+            PairwiseRankingLoss(a, b, _) => vec![a, b],
+            PowerError(a, b, _) => vec![a, b],
+            ReduceAcrossBatch(node) => vec![node],
+            ReduceMean(node) => vec![node],
+            ReduceSum(node) => vec![node],
+            Repeat(node, _) => vec![node],
+            RepeatAcrossBatch(node, batched) => vec![node, batched],
+            Select(input, buckets) => vec![input, buckets],
+            Slice(input, _, _) => vec![input],
+            SliceColumns(input, _, _) => vec![input],
+            Softmax(node) => vec![node],
+            SparseAffine(w, i, b) => {
+                if let Some(b) = b {
+                    vec![w, i, b]
+                } else {
+                    vec![w, i]
+                }
+            }
+            ToDense(node) => vec![node],
+            Transpose(node) => vec![node],
+            SparseAffineDualActivate(w, s, n, b, _) => vec![w, s, n, b],
+            SparseOuter(a, b) => vec![a, b],
+            MaskedSoftmaxCrossEntropyLoss(mask, input, target, _, _) => vec![mask, input, target],
+            SoftmaxCrossEntropyLoss(a, b, _, _) => vec![a, b],
+            KLDivergenceLoss(a, b) => vec![a, b],
+            SigmoidCrossEntropyLoss(a, b) => vec![a, b],
+        }
+    }
+
+    /// The inputs of the operation, for rewriting the graph.
+    pub(crate) fn nodes_mut(&mut self) -> Vec<&mut Node> {
+        use Operation::*;
+
+        match self {
+            Abs(node) => vec![node],
             Activate(node, _) => vec![node],
+            Clamp(node, _, _) => vec![node],
+            PReLU(input, slope) => vec![input, slope],
             Affine(a, b, c) => vec![a, b, c],
+            AffineActivate(a, b, c, _) => vec![a, b, c],
+            AvgPool(node, _) => vec![node],
+            MaxPool(node, _) => vec![node],
+            BatchNorm(input, scale, shift, mean, var, _) => vec![input, scale, shift, mean, var],
+            Attention(q, k, v, mask, _) => {
+                if let Some(mask) = mask {
+                    vec![q, k, v, mask]
+                } else {
+                    vec![q, k, v]
+                }
+            }
             Concat(a, b) => vec![a, b],
+            Conv2d(filters, input, _) => vec![filters, input],
+            ConcatMany(nodes) => nodes.iter_mut().collect(),
+            Dropout(node, _) => vec![node],
+            ElementwiseDiv(a, b, _) => vec![a, b],
+            ElementwiseMul(a, b) => vec![a, b],
+            Embedding(table, indices) => vec![table, indices],
             Gather(input, mask) => vec![input, mask],
+            LayerNorm(input, scale, shift) => vec![input, scale, shift],
             LinearCombination(_, a, _, b) => vec![a, b],
             Mask(input, mask) => vec![input, mask],
             Matmul(a, _, b, _) => vec![a, b],
+            Max(a, b) => vec![a, b],
+            Min(a, b) => vec![a, b],
             PairwiseMul(input, _) => vec![input],
+            HuberError(a, b, _) => vec![a, b],
+            PairwiseRankingLoss(a, b, _) => vec![a, b],
             PowerError(a, b, _) => vec![a, b],
             ReduceAcrossBatch(node) => vec![node],
+            ReduceMean(node) => vec![node],
+            ReduceSum(node) => vec![node],
+            Repeat(node, _) => vec![node],
+            RepeatAcrossBatch(node, batched) => vec![node, batched],
             Select(input, buckets) => vec![input, buckets],
             Slice(input, _, _) => vec![input],
+            SliceColumns(input, _, _) => vec![input],
+            Softmax(node) => vec![node],
             SparseAffine(w, i, b) => {
                 if let Some(b) = b {
                     vec![w, i, b]
@@ -269,9 +717,13 @@ impl Operation {
                 }
             }
             ToDense(node) => vec![node],
+            Transpose(node) => vec![node],
             SparseAffineDualActivate(w, s, n, b, _) => vec![w, s, n, b],
-            MaskedSoftmaxCrossEntropyLoss(mask, input, target) => vec![mask, input, target],
-            SoftmaxCrossEntropyLoss(a, b) => vec![a, b],
+            SparseOuter(a, b) => vec![a, b],
+            MaskedSoftmaxCrossEntropyLoss(mask, input, target, _, _) => vec![mask, input, target],
+            SoftmaxCrossEntropyLoss(a, b, _, _) => vec![a, b],
+            KLDivergenceLoss(a, b) => vec![a, b],
+            SigmoidCrossEntropyLoss(a, b) => vec![a, b],
         }
     }
 }
@@ -285,6 +737,12 @@ impl<D: Device> Graph<D> {
         let output_tensor = &mut *self.nodes[output_node.idx].borrow_mut();
         let op = if let Some(op) = &output_tensor.operation { op } else { return Ok(()) };
         let internal = &mut output_tensor.internal;
+
+        if let SparseOuter(a, b) = op {
+            let output = output_tensor.values.sparse_mut()?;
+            return sparse::sparse_outer(get(*a).values.sparse()?, get(*b).values.sparse()?, output);
+        }
+
         let output = output_tensor.values.dense_mut()?;
         let outn = output_tensor.own;
 
@@ -296,90 +754,84 @@ impl<D: Device> Graph<D> {
                 output.set_batch_size(input.batch_size())?;
                 D::activate(input.size(), &input.buf, &mut output.buf, *act)
             }
-            Affine(wn, inp, bn) => {
-                let w = get(*wn);
-                let i = get(*inp);
-                let b = get(*bn);
-                let w = w.values.dense()?;
-                let i = i.values.dense()?;
-                let b = b.values.dense()?;
-
-                let bs = i.batch_size().unwrap_or(1);
-                setup_ones(w.buf.device(), internal, bs)?;
-                let ones = &internal.get("ones").unwrap().borrow().buf;
-                matmul::dense_affine(w, wn.shape, i, inp.shape, b, bn.shape, ones, output)
-            }
-            LinearCombination(alpha, an, beta, bn) => {
-                let a = get(*an);
-                let a = a.values.dense()?;
-                let bs = a.batch_size().unwrap_or(1);
-                setup_ones(a.buf.device(), internal, bs)?;
-                let ones = &internal.get("ones").unwrap().borrow().buf;
-                linear_comb::linear_comb(ones, *alpha, a, an.shape, *beta, get(*bn).values.dense()?, bn.shape, output)
-            }
-            Gather(input, indices) => {
-                let input = get(*input);
+            AvgPool(node, settings) => {
+                let input = get(*node);
                 let input = input.values.dense()?;
-                let indices = get(*indices);
-                let indices = indices.values.sparse()?;
+                assert_eq!(input.single_size(), settings.input_size());
+                assert_eq!(outn.shape.size(), settings.output_size());
+                output.set_batch_size(input.batch_size())?;
+                D::avg_pool(input.batch_size().unwrap_or(1), *settings, &input.buf, &mut output.buf)
+            }
+            Attention(q, k, v, mask, seq_len) => {
+                let dim = q.shape.size() / seq_len;
+                let q = get(*q);
+                let q = q.values.dense()?;
+                let k = get(*k);
+                let k = k.values.dense()?;
+                let v = get(*v);
+                let v = v.values.dense()?;
+                let mask = mask.map(get);
+                let mask = if let Some(mask) = &mask { Some(mask.values.dense()?) } else { None };
+
+                let batch_size = q.batch_size();
+                assert_eq!(batch_size, k.batch_size());
+                assert_eq!(batch_size, v.batch_size());
+
+                let bs = batch_size.unwrap_or(1);
+                setup_zeroed(q.buf.device(), internal, "probs", bs * seq_len * seq_len)?;
+                let mut probs = internal.get("probs").unwrap().borrow_mut();
+
+                let mask_batched = mask.is_some_and(|mask| mask.batch_size().is_some());
+                if mask_batched {
+                    assert_eq!(batch_size, mask.unwrap().batch_size());
+                }
 
-                let batch_size = indices.batch_size();
-                assert_eq!(input.batch_size(), batch_size);
-                assert_eq!(indices.nnz, indices.single_size());
                 output.set_batch_size(batch_size)?;
-
-                D::gather(
-                    batch_size.unwrap_or(1),
-                    input.single_size(),
-                    output.single_size(),
-                    &input.buf,
-                    &indices.buf,
+                D::attention(
+                    bs,
+                    *seq_len,
+                    dim,
+                    &q.buf,
+                    &k.buf,
+                    &v.buf,
+                    mask.map(|mask| &mask.buf),
+                    mask_batched,
+                    &mut probs.buf,
                     &mut output.buf,
                 )
             }
-            Concat(a, b) => concat::concat(get(*a).values.dense()?, a.shape, get(*b).values.dense()?, b.shape, output),
-            Mask(input, mask) => {
+            Conv2d(filters, input, settings) => {
+                let filters = get(*filters);
+                let filters = filters.values.dense()?;
                 let input = get(*input);
                 let input = input.values.dense()?;
-                let mask = get(*mask);
-                let mask = mask.values.sparse()?;
+                assert_eq!(filters.batch_size(), None);
+                assert_eq!(input.single_size(), settings.input_size());
+                assert_eq!(outn.shape.size(), settings.output_size());
+                output.set_batch_size(input.batch_size())?;
+                D::conv2d(input.batch_size().unwrap_or(1), *settings, &filters.buf, &input.buf, &mut output.buf)
+            }
+            MaxPool(node, settings) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+                assert_eq!(input.single_size(), settings.input_size());
+                assert_eq!(outn.shape.size(), settings.output_size());
 
-                let batch_size = mask.batch_size();
-                let single_size = mask.single_size();
-                assert_eq!(input.batch_size(), batch_size);
-                assert_eq!(input.single_size(), single_size);
-                assert!(mask.nnz <= single_size);
-                assert_eq!(output.single_size(), single_size);
-                output.set_batch_size(batch_size)?;
+                let batch_size = input.batch_size().unwrap_or(1);
+                setup_zeroed(input.buf.device(), internal, "argmax", batch_size * settings.output_size())?;
+                let mut argmax = internal.get("argmax").unwrap().borrow_mut();
 
-                D::mask(batch_size.unwrap_or(1), single_size, mask.nnz, &input.buf, &mask.buf, &mut output.buf)
+                output.set_batch_size(input.batch_size())?;
+                D::max_pool(batch_size, *settings, &input.buf, &mut argmax.buf, &mut output.buf)
             }
-            Matmul(a, trans_a, b, trans_b) => matmul::matmul(
-                get(*a).values.dense()?,
-                a.shape,
-                *trans_a,
-                get(*b).values.dense()?,
-                b.shape,
-                *trans_b,
-                output,
-            ),
-            PairwiseMul(node, post_concat) => {
+            Abs(node) => {
                 let input = get(*node);
-                let input = &input.values;
-                assert_eq!(node.shape.cols(), 1);
-                assert_eq!(node.shape.size(), input.single_size());
-                assert_eq!(node.shape.size() % 2, 0);
-                assert_eq!(node.shape.size() / 2, output.single_size());
+                let input = input.values.dense()?;
+                assert_eq!(outn.shape, node.shape);
                 output.set_batch_size(input.batch_size())?;
-                D::pairwise(
-                    input.single_size(),
-                    input.batch_size().unwrap_or(1),
-                    &input.dense()?.buf,
-                    &mut output.buf,
-                    *post_concat,
-                )
+                D::abs(input.size(), &input.buf, &mut output.buf)
             }
-            PowerError(a, b, p) => {
+            ElementwiseDiv(a, b, epsilon) => {
                 let size = a.shape.size();
                 assert_eq!(a.shape, b.shape);
 
@@ -388,35 +840,369 @@ impl<D: Device> Graph<D> {
                 let b = get(*b);
                 let b = b.values.dense()?;
 
-                assert_eq!(size, a.single_size());
-                assert_eq!(size, b.single_size());
-                assert_eq!(size, output.single_size());
-
                 let batch_size = a.batch_size();
                 assert_eq!(batch_size, b.batch_size());
                 output.set_batch_size(batch_size)?;
 
-                D::abs_power_error(*p, size * batch_size.unwrap_or(1), &a.buf, &b.buf, &mut output.buf)
-            }
-            ReduceAcrossBatch(node) => {
-                let input = get(*node);
-                let input = input.values.dense()?;
-                setup_ones(input.buf.device(), internal, input.batch_size().unwrap_or(1))?;
-                let ones = internal.get("ones").unwrap().borrow();
-                assert_eq!(input.single_size(), node.shape.size());
-                D::reduce_add(
-                    &ones.buf,
-                    input.single_size(),
-                    input.batch_size().unwrap_or(1),
-                    &input.buf,
-                    &mut output.buf,
-                )
+                D::elementwise_div(size * batch_size.unwrap_or(1), *epsilon, &a.buf, &b.buf, &mut output.buf)
             }
-            Select(input, buckets) => {
-                let rows = input.shape.rows();
-                let num_buckets = buckets.shape.rows();
+            ElementwiseMul(a, b) => {
+                let size = a.shape.size();
+                assert_eq!(a.shape, b.shape);
 
-                assert_eq!(input.shape.cols(), 1);
+                let a = get(*a);
+                let a = a.values.dense()?;
+                let b = get(*b);
+                let b = b.values.dense()?;
+
+                let batch_size = a.batch_size();
+                assert_eq!(batch_size, b.batch_size());
+                output.set_batch_size(batch_size)?;
+
+                D::elementwise_mul(size * batch_size.unwrap_or(1), &a.buf, &b.buf, &mut output.buf)
+            }
+            Max(a, b) | Min(a, b) => {
+                let size = a.shape.size();
+                assert_eq!(a.shape, b.shape);
+
+                let a = get(*a);
+                let a = a.values.dense()?;
+                let b = get(*b);
+                let b = b.values.dense()?;
+
+                let batch_size = a.batch_size();
+                assert_eq!(batch_size, b.batch_size());
+                output.set_batch_size(batch_size)?;
+
+                let max = matches!(op, Max(_, _));
+                D::elementwise_min_max(size * batch_size.unwrap_or(1), max, &a.buf, &b.buf, &mut output.buf)
+            }
+            Clamp(node, min, max) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+                assert_eq!(outn.shape, node.shape);
+                output.set_batch_size(input.batch_size())?;
+                D::clamp(input.size(), *min, *max, &input.buf, &mut output.buf)
+            }
+            PReLU(input, slope) => {
+                let input = get(*input);
+                let input = input.values.dense()?;
+                let slope = get(*slope);
+                let slope = slope.values.dense()?;
+                assert_eq!(slope.batch_size(), None);
+                assert_eq!(input.single_size(), slope.single_size());
+                output.set_batch_size(input.batch_size())?;
+                D::prelu(input.single_size(), input.batch_size().unwrap_or(1), &input.buf, &slope.buf, &mut output.buf)
+            }
+            Affine(wn, inp, bn) => {
+                let w = get(*wn);
+                let i = get(*inp);
+                let b = get(*bn);
+                let w = w.values.dense()?;
+                let i = i.values.dense()?;
+                let b = b.values.dense()?;
+
+                let bs = i.batch_size().unwrap_or(1);
+                setup_ones(w.buf.device(), internal, bs * outn.shape.cols())?;
+                let ones = &internal.get("ones").unwrap().borrow().buf;
+                matmul::dense_affine(w, wn.shape, i, inp.shape, b, bn.shape, ones, output)
+            }
+            AffineActivate(wn, inp, bn, act) => {
+                let w = get(*wn);
+                let i = get(*inp);
+                let b = get(*bn);
+                let w = w.values.dense()?;
+                let i = i.values.dense()?;
+                let b = b.values.dense()?;
+                matmul::dense_affine_activate(w, wn.shape, i, inp.shape, b, bn.shape, *act, output)
+            }
+            LayerNorm(input, scale, shift) => {
+                let input = get(*input);
+                let input = input.values.dense()?;
+                let scale = get(*scale);
+                let shift = get(*shift);
+
+                let batch_size = input.batch_size();
+                let single_size = input.single_size();
+                assert_eq!(outn.shape.size(), single_size);
+
+                setup_zeroed(input.buf.device(), internal, "stats", 2 * batch_size.unwrap_or(1))?;
+                let mut stats = internal.get("stats").unwrap().borrow_mut();
+
+                output.set_batch_size(batch_size)?;
+                D::layer_norm(
+                    batch_size.unwrap_or(1),
+                    single_size,
+                    &input.buf,
+                    &scale.values.dense()?.buf,
+                    &shift.values.dense()?.buf,
+                    &mut stats.buf,
+                    &mut output.buf,
+                )
+            }
+            LinearCombination(alpha, an, beta, bn) => {
+                let a = get(*an);
+                let a = a.values.dense()?;
+                let b = get(*bn);
+                let b = b.values.dense()?;
+                let bs = a.batch_size().or(b.batch_size()).unwrap_or(1);
+                setup_ones(a.buf.device(), internal, bs * outn.shape.cols())?;
+                let ones = &internal.get("ones").unwrap().borrow().buf;
+                linear_comb::linear_comb(ones, *alpha, a, an.shape, *beta, b, bn.shape, output)
+            }
+            BatchNorm(input, scale, shift, mean, var, momentum) => {
+                let input = get(*input);
+                let input = input.values.dense()?;
+                let scale = get(*scale);
+                let shift = get(*shift);
+                let mut mean = self.nodes[mean.idx].borrow_mut();
+                let mut var = self.nodes[var.idx].borrow_mut();
+
+                let batch_size = input.batch_size();
+                let single_size = input.single_size();
+                assert_eq!(outn.shape.size(), single_size);
+
+                setup_zeroed(input.buf.device(), internal, "stats", 2 * single_size)?;
+                let mut stats = internal.get("stats").unwrap().borrow_mut();
+
+                output.set_batch_size(batch_size)?;
+                D::batch_norm(
+                    batch_size.unwrap_or(1),
+                    single_size,
+                    self.training,
+                    *momentum,
+                    &input.buf,
+                    &scale.values.dense()?.buf,
+                    &shift.values.dense()?.buf,
+                    &mut mean.values.dense_mut()?.buf,
+                    &mut var.values.dense_mut()?.buf,
+                    &mut stats.buf,
+                    &mut output.buf,
+                )
+            }
+            Dropout(node, rate) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+                assert_eq!(outn.shape, node.shape);
+                output.set_batch_size(input.batch_size())?;
+
+                if self.training {
+                    setup_zeroed(input.buf.device(), internal, "mask", input.size())?;
+                    let mut mask = internal.get("mask").unwrap().borrow_mut();
+                    D::dropout(input.size(), *rate, rand::random(), &input.buf, &mut mask.buf, &mut output.buf)
+                } else {
+                    D::linear_comb_single(input.size(), 1.0, Some(&input.buf), 0.0, None, &mut output.buf)
+                }
+            }
+            Embedding(table, indices) => {
+                let (dim, vocab) = (table.shape.rows(), table.shape.cols());
+                let table = get(*table);
+                let table = table.values.dense()?;
+                let indices = get(*indices);
+                let indices = indices.values.sparse()?;
+
+                let batch_size = indices.batch_size();
+                assert_eq!(table.batch_size(), None);
+                assert_eq!(outn.shape.size(), dim * indices.nnz);
+                output.set_batch_size(batch_size)?;
+
+                D::embedding(
+                    batch_size.unwrap_or(1),
+                    dim,
+                    vocab,
+                    indices.nnz,
+                    &table.buf,
+                    &indices.buf,
+                    &mut output.buf,
+                )
+            }
+            Gather(input, indices) => {
+                let input = get(*input);
+                let input = input.values.dense()?;
+                let indices = get(*indices);
+                let indices = indices.values.sparse()?;
+
+                let batch_size = indices.batch_size();
+                assert_eq!(input.batch_size(), batch_size);
+                assert_eq!(indices.nnz, indices.single_size());
+                output.set_batch_size(batch_size)?;
+
+                D::gather(
+                    batch_size.unwrap_or(1),
+                    input.single_size(),
+                    output.single_size(),
+                    &input.buf,
+                    &indices.buf,
+                    &mut output.buf,
+                )
+            }
+            Concat(a, b) => concat::concat(get(*a).values.dense()?, a.shape, get(*b).values.dense()?, b.shape, output),
+            ConcatMany(nodes) => {
+                let inputs = nodes.iter().map(|&node| get(node)).collect::<Vec<_>>();
+                let inputs = inputs.iter().map(|input| input.values.dense()).collect::<Result<Vec<_>, _>>()?;
+                let shapes = nodes.iter().map(|node| node.shape).collect::<Vec<_>>();
+                concat::concat_many(&inputs, &shapes, output)
+            }
+            Mask(input, mask) => {
+                let input = get(*input);
+                let input = input.values.dense()?;
+                let mask = get(*mask);
+                let mask = mask.values.sparse()?;
+
+                let batch_size = mask.batch_size();
+                let single_size = mask.single_size();
+                assert_eq!(input.batch_size(), batch_size);
+                assert_eq!(input.single_size(), single_size);
+                assert!(mask.nnz <= single_size);
+                assert_eq!(output.single_size(), single_size);
+                output.set_batch_size(batch_size)?;
+
+                D::mask(batch_size.unwrap_or(1), single_size, mask.nnz, &input.buf, &mask.buf, &mut output.buf)
+            }
+            Matmul(a, trans_a, b, trans_b) => {
+                let (a_tensor, b_tensor) = (get(*a), get(*b));
+                let (a_vals, b_vals) = (a_tensor.values.dense()?, b_tensor.values.dense()?);
+
+                if let (Some(bs), None) = (a_vals.batch_size(), b_vals.batch_size()) {
+                    // each matrix of `a` is multiplied by the same `b`, which is copied for each
+                    setup_ones(output.buf.device(), internal, bs)?;
+                    setup_zeroed(output.buf.device(), internal, "tiled", b_vals.single_size())?;
+                    let ones = internal.get("ones").unwrap().borrow();
+                    let mut tiled = internal.get("tiled").unwrap().borrow_mut();
+                    repeat::repeat_across_batch(&ones.buf, b_vals, bs, &mut tiled)?;
+                    matmul::matmul(a_vals, a.shape, *trans_a, &tiled, b.shape, *trans_b, output)
+                } else {
+                    matmul::matmul(a_vals, a.shape, *trans_a, b_vals, b.shape, *trans_b, output)
+                }
+            }
+            PairwiseMul(node, post_concat) => {
+                let input = get(*node);
+                let input = &input.values;
+                assert_eq!(node.shape.cols(), 1);
+                assert_eq!(node.shape.size(), input.single_size());
+                assert_eq!(node.shape.size() % 2, 0);
+                assert_eq!(node.shape.size() / 2, output.single_size());
+                output.set_batch_size(input.batch_size())?;
+                D::pairwise(
+                    input.single_size(),
+                    input.batch_size().unwrap_or(1),
+                    &input.dense()?.buf,
+                    &mut output.buf,
+                    *post_concat,
+                )
+            }
+            HuberError(a, b, delta) => {
+                let size = a.shape.size();
+                assert_eq!(a.shape, b.shape);
+
+                let a = get(*a);
+                let a = a.values.dense()?;
+                let b = get(*b);
+                let b = b.values.dense()?;
+
+                assert_eq!(size, a.single_size());
+                assert_eq!(size, b.single_size());
+                assert_eq!(size, output.single_size());
+
+                let batch_size = a.batch_size();
+                assert_eq!(batch_size, b.batch_size());
+                output.set_batch_size(batch_size)?;
+
+                D::huber_error(*delta, size * batch_size.unwrap_or(1), &a.buf, &b.buf, &mut output.buf)
+            }
+            PairwiseRankingLoss(a, b, margin) => {
+                let a = get(*a);
+                let a = a.values.dense()?;
+                let b = get(*b);
+                let b = b.values.dense()?;
+
+                assert_eq!(a.single_size(), 1);
+                assert_eq!(b.single_size(), 1);
+                assert_eq!(output.single_size(), 1);
+
+                let batch_size = a.batch_size();
+                assert_eq!(batch_size, b.batch_size());
+                output.set_batch_size(batch_size)?;
+
+                D::pairwise_ranking(batch_size.unwrap_or(1), *margin, &a.buf, &b.buf, &mut output.buf)
+            }
+            PowerError(a, b, p) => {
+                let size = a.shape.size();
+                assert_eq!(a.shape, b.shape);
+
+                let a = get(*a);
+                let a = a.values.dense()?;
+                let b = get(*b);
+                let b = b.values.dense()?;
+
+                assert_eq!(size, a.single_size());
+                assert_eq!(size, b.single_size());
+                assert_eq!(size, output.single_size());
+
+                let batch_size = a.batch_size();
+                assert_eq!(batch_size, b.batch_size());
+                output.set_batch_size(batch_size)?;
+
+                D::abs_power_error(*p, size * batch_size.unwrap_or(1), &a.buf, &b.buf, &mut output.buf)
+            }
+            ReduceAcrossBatch(node) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+                setup_ones(input.buf.device(), internal, input.batch_size().unwrap_or(1))?;
+                let ones = internal.get("ones").unwrap().borrow();
+                assert_eq!(input.single_size(), node.shape.size());
+                D::reduce_add(
+                    &ones.buf,
+                    input.single_size(),
+                    input.batch_size().unwrap_or(1),
+                    1.0,
+                    &input.buf,
+                    &mut output.buf,
+                    false,
+                )
+            }
+            Repeat(node, times) => repeat::repeat(get(*node).values.dense()?, *times, output),
+            RepeatAcrossBatch(node, batched) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+
+                match get(*batched).values.batch_size() {
+                    Some(bs) => {
+                        setup_ones(output.buf.device(), internal, bs)?;
+                        let ones = internal.get("ones").unwrap().borrow();
+                        repeat::repeat_across_batch(&ones.buf, input, bs, output)
+                    }
+                    None => repeat::repeat(input, 1, output),
+                }
+            }
+            ReduceMean(node) | ReduceSum(node) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+                let rows = node.shape.rows();
+                let cols = node.shape.cols() * input.batch_size().unwrap_or(1);
+                assert_eq!(input.single_size(), node.shape.size());
+
+                let scale = if matches!(op, ReduceMean(_)) { 1.0 / rows as f32 } else { 1.0 };
+                setup_reduce_weights(input.buf.device(), internal, rows, scale)?;
+                let weights = internal.get("reduce_weights").unwrap().borrow();
+
+                output.set_batch_size(input.batch_size())?;
+                D::sgemm(
+                    &weights.buf,
+                    Shape::new(1, rows),
+                    false,
+                    &input.buf,
+                    Shape::new(rows, cols),
+                    false,
+                    &mut output.buf,
+                    false,
+                )
+            }
+            Select(input, buckets) => {
+                let rows = input.shape.rows();
+                let num_buckets = buckets.shape.rows();
+
+                assert_eq!(input.shape.cols(), 1);
                 assert_eq!(buckets.shape.cols(), 1);
                 assert_eq!(rows % num_buckets, 0, "Cannot divide vector evenly among buckets!");
 
@@ -440,6 +1226,36 @@ impl<D: Device> Graph<D> {
             Slice(input, start, end) => {
                 slice::slice_vector_batched(input.shape, get(*input).values.dense()?, *start, *end, output)
             }
+            SliceColumns(input, start, end) => {
+                slice::slice_columns_batched(input.shape, get(*input).values.dense()?, *start, *end, output)
+            }
+            Transpose(node) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+                assert_eq!(node.shape.size(), input.single_size());
+                output.set_batch_size(input.batch_size())?;
+                D::transpose(
+                    node.shape.rows(),
+                    node.shape.cols(),
+                    input.batch_size().unwrap_or(1),
+                    &input.buf,
+                    &mut output.buf,
+                    false,
+                )
+            }
+            Softmax(node) => {
+                let input = get(*node);
+                let input = input.values.dense()?;
+                assert_eq!(outn.shape, node.shape);
+                assert_eq!(node.shape.size(), input.single_size());
+                output.set_batch_size(input.batch_size())?;
+                D::softmax_across_batch(
+                    input.batch_size().unwrap_or(1),
+                    input.single_size(),
+                    &input.buf,
+                    &mut output.buf,
+                )
+            }
             SparseAffine(wn, inp, bn) => {
                 let i = get(*inp);
                 let w = get(*wn);
@@ -468,7 +1284,8 @@ impl<D: Device> Graph<D> {
                 )
             }
             ToDense(node) => get(*node).values.sparse()?.copy_into_dense(output),
-            MaskedSoftmaxCrossEntropyLoss(mask, input, target) => {
+            SparseOuter(_, _) => unreachable!("Sparse outputs are computed above!"),
+            MaskedSoftmaxCrossEntropyLoss(mask, input, target, temperature, smoothing) => {
                 let masks = get(*mask);
                 let inputs = get(*input);
                 let targets = get(*target);
@@ -491,24 +1308,35 @@ impl<D: Device> Graph<D> {
                 let nnz = masks.nnz;
 
                 setup_softmax(masks.buf.device(), internal, nnz * batch_size)?;
+                setup_scaled_logits(internal, inputs, *temperature)?;
 
+                if *smoothing > 0.0 {
+                    setup_zeroed(masks.buf.device(), internal, "smoothed_targets", nnz * batch_size)?;
+                    let mut smoothed = internal.get("smoothed_targets").unwrap().borrow_mut();
+                    D::smooth_targets_masked(batch_size, nnz, &masks.buf, *smoothing, &targets.buf, &mut smoothed.buf)?;
+                }
+
+                let scaled = (*temperature != 1.0).then(|| internal.get("scaled_logits").unwrap().borrow());
+                let logits = scaled.as_ref().map_or(&inputs.buf, |scaled| &scaled.buf);
+                let smoothed = (*smoothing > 0.0).then(|| internal.get("smoothed_targets").unwrap().borrow());
+                let targets = smoothed.as_ref().map_or(&targets.buf, |smoothed| &smoothed.buf);
                 let mut smax = internal.get("softmaxed").unwrap().borrow_mut();
                 let mut indv = internal.get("individual_losses").unwrap().borrow_mut();
 
                 output.set_batch_size(masks.batch_size())?;
-                D::softmax_across_batch_masked(batch_size, single_size, nnz, &masks.buf, &inputs.buf, &mut smax.buf)?;
+                D::softmax_across_batch_masked(batch_size, single_size, nnz, &masks.buf, logits, &mut smax.buf)?;
                 D::crossentropy_masked(
                     batch_size,
                     single_size,
                     nnz,
                     &masks.buf,
                     &smax.buf,
-                    &targets.buf,
+                    targets,
                     &mut indv.buf,
                     &mut output.buf,
                 )
             }
-            SoftmaxCrossEntropyLoss(an, bn) => {
+            SoftmaxCrossEntropyLoss(an, bn, temperature, smoothing) => {
                 let a = get(*an);
                 let b = get(*bn);
                 let a = a.values.dense()?;
@@ -526,13 +1354,63 @@ impl<D: Device> Graph<D> {
 
                 setup_softmax(a.buf.device(), internal, single_size * batch_size)?;
                 setup_ones(a.buf.device(), internal, single_size)?;
+                setup_scaled_logits(internal, a, *temperature)?;
+                setup_smoothed_targets(internal, b, *smoothing)?;
+
+                let scaled = (*temperature != 1.0).then(|| internal.get("scaled_logits").unwrap().borrow());
+                let logits = scaled.as_ref().map_or(&a.buf, |scaled| &scaled.buf);
+                let smoothed = (*smoothing > 0.0).then(|| internal.get("smoothed_targets").unwrap().borrow());
+                let targets = smoothed.as_ref().map_or(&b.buf, |smoothed| &smoothed.buf);
+                let ones = internal.get("ones").unwrap().borrow();
+                let mut smax = internal.get("softmaxed").unwrap().borrow_mut();
+                let mut indv = internal.get("individual_losses").unwrap().borrow_mut();
+
+                D::softmax_across_batch(batch_size, single_size, logits, &mut smax.buf)?;
+                D::crossentropy(batch_size * single_size, &smax.buf, targets, &mut indv.buf)?;
+
+                output.set_batch_size(a.batch_size())?;
+                D::sgemm(
+                    &ones.buf,
+                    Shape::new(1, single_size),
+                    false,
+                    &indv.buf,
+                    Shape::new(single_size, batch_size),
+                    false,
+                    &mut output.buf,
+                    false,
+                )
+            }
+            KLDivergenceLoss(an, bn) => {
+                let a = get(*an);
+                let b = get(*bn);
+                let a = a.values.dense()?;
+                let b = b.values.dense()?;
+
+                assert_eq!(an.shape, bn.shape);
+                assert_eq!(an.shape.cols(), 1);
+                assert_eq!(an.shape.size(), a.single_size());
+                assert_eq!(bn.shape.size(), b.single_size());
+                assert_eq!(a.batch_size(), b.batch_size());
+                assert_eq!(output.single_size(), 1);
+
+                let batch_size = a.batch_size().unwrap_or(1);
+                let single_size = a.single_size();
+                let size = single_size * batch_size;
+
+                setup_softmax(a.buf.device(), internal, size)?;
+                setup_ones(a.buf.device(), internal, single_size)?;
+                setup_zeroed(a.buf.device(), internal, "target_entropy", size)?;
 
                 let ones = internal.get("ones").unwrap().borrow();
                 let mut smax = internal.get("softmaxed").unwrap().borrow_mut();
                 let mut indv = internal.get("individual_losses").unwrap().borrow_mut();
+                let mut entropy = internal.get("target_entropy").unwrap().borrow_mut();
 
+                // the divergence is the cross-entropy less the entropy of the targets
                 D::softmax_across_batch(batch_size, single_size, &a.buf, &mut smax.buf)?;
-                D::crossentropy(batch_size * single_size, &smax.buf, &b.buf, &mut indv.buf)?;
+                D::crossentropy(size, &smax.buf, &b.buf, &mut indv.buf)?;
+                D::crossentropy(size, &b.buf, &b.buf, &mut entropy.buf)?;
+                D::linear_comb_single(size, 1.0, None, -1.0, Some(&entropy.buf), &mut indv.buf)?;
 
                 output.set_batch_size(a.batch_size())?;
                 D::sgemm(
@@ -546,6 +1424,25 @@ impl<D: Device> Graph<D> {
                     false,
                 )
             }
+            SigmoidCrossEntropyLoss(an, bn) => {
+                let size = an.shape.size();
+                assert_eq!(an.shape, bn.shape);
+
+                let a = get(*an);
+                let a = a.values.dense()?;
+                let b = get(*bn);
+                let b = b.values.dense()?;
+
+                assert_eq!(size, a.single_size());
+                assert_eq!(size, b.single_size());
+                assert_eq!(size, output.single_size());
+
+                let batch_size = a.batch_size();
+                assert_eq!(batch_size, b.batch_size());
+                output.set_batch_size(batch_size)?;
+
+                D::sigmoid_bce(size * batch_size.unwrap_or(1), &a.buf, &b.buf, &mut output.buf)
+            }
         }
     }
 
@@ -576,14 +1473,292 @@ impl<D: Device> Graph<D> {
                     D::backprop_activate(input.size(), &input.buf, &mut grad.buf, &output_grad.buf, *act)?;
                 }
             }
+            AvgPool(node, settings) => {
+                let input = &mut *get(*node);
+                if let Some(grad) = input.gradients.as_mut() {
+                    let batch_size = input.values.batch_size();
+                    assert_eq!(output_grad.batch_size(), batch_size);
+                    assert_eq!(output_grad.single_size(), settings.output_size());
+                    grad.set_batch_size(batch_size)?;
+                    D::backprop_avg_pool(batch_size.unwrap_or(1), *settings, &output_grad.buf, &mut grad.buf)?;
+                }
+            }
+            Attention(qn, kn, vn, _, seq_len) => {
+                let dim = qn.shape.size() / seq_len;
+                let q = &mut *get(*qn);
+                let k = &mut *get(*kn);
+                let v = &mut *get(*vn);
+
+                let batch_size = q.values.batch_size();
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(qn.shape.size(), output_grad.single_size());
+
+                let bs = batch_size.unwrap_or(1);
+                setup_zeroed(output_grad.buf.device(), internal, "scores_grad", bs * seq_len * seq_len)?;
+                let probs = internal.get("probs").unwrap().borrow();
+                let mut scores_grad = internal.get("scores_grad").unwrap().borrow_mut();
+
+                for node in [&mut *q, &mut *k, &mut *v] {
+                    if let Some(grd) = node.gradients.as_mut() {
+                        grd.set_batch_size(batch_size)?;
+                    }
+                }
+
+                D::backprop_attention(
+                    bs,
+                    *seq_len,
+                    dim,
+                    &q.values.dense()?.buf,
+                    &k.values.dense()?.buf,
+                    &v.values.dense()?.buf,
+                    &probs.buf,
+                    &output_grad.buf,
+                    &mut scores_grad.buf,
+                    q.gradients.as_mut().map(|grd| &mut grd.buf),
+                    k.gradients.as_mut().map(|grd| &mut grd.buf),
+                    v.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
+            Conv2d(wn, inp, settings) => {
+                let filters = &mut *get(*wn);
+                let input = &mut *get(*inp);
+
+                let batch_size = input.values.batch_size();
+                assert_eq!(filters.values.batch_size(), None);
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(output_grad.single_size(), settings.output_size());
+
+                if let Some(grd) = input.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                D::backprop_conv2d(
+                    batch_size.unwrap_or(1),
+                    *settings,
+                    &filters.values.dense()?.buf,
+                    &input.values.dense()?.buf,
+                    &output_grad.buf,
+                    filters.gradients.as_mut().map(|grd| &mut grd.buf),
+                    input.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
+            MaxPool(node, settings) => {
+                let input = &mut *get(*node);
+                if let Some(grad) = input.gradients.as_mut() {
+                    let batch_size = input.values.batch_size();
+                    assert_eq!(output_grad.batch_size(), batch_size);
+                    assert_eq!(output_grad.single_size(), settings.output_size());
+
+                    let argmax = internal.get("argmax").unwrap().borrow();
+
+                    grad.set_batch_size(batch_size)?;
+                    D::backprop_max_pool(
+                        batch_size.unwrap_or(1),
+                        *settings,
+                        &argmax.buf,
+                        &output_grad.buf,
+                        &mut grad.buf,
+                    )?;
+                }
+            }
+            Abs(node) => {
+                let input = &mut *get(*node);
+                if let Some(grad) = input.gradients.as_mut() {
+                    let input = input.values.dense()?;
+                    assert_eq!(outn.shape, node.shape);
+                    assert_eq!(output_grad.size(), input.size());
+                    assert_eq!(output_grad.batch_size(), input.batch_size());
+                    grad.set_batch_size(output_grad.batch_size())?;
+                    D::backprop_abs(input.size(), &input.buf, &mut grad.buf, &output_grad.buf)?;
+                }
+            }
+            ElementwiseDiv(an, bn, epsilon) => {
+                let size = an.shape.size();
+                assert_eq!(an.shape, bn.shape);
+
+                let a = &mut *get(*an);
+                let b = &mut *get(*bn);
+
+                let batch_size = a.values.batch_size();
+                assert_eq!(batch_size, b.values.batch_size());
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(size, output_grad.single_size());
+
+                if let Some(grd) = a.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                if let Some(grd) = b.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                D::backprop_elementwise_div(
+                    size * batch_size.unwrap_or(1),
+                    *epsilon,
+                    &a.values.dense()?.buf,
+                    &b.values.dense()?.buf,
+                    &output_grad.buf,
+                    a.gradients.as_mut().map(|grd| &mut grd.buf),
+                    b.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
+            ElementwiseMul(an, bn) => {
+                let size = an.shape.size();
+                assert_eq!(an.shape, bn.shape);
+
+                let a = &mut *get(*an);
+                let b = &mut *get(*bn);
+
+                let batch_size = a.values.batch_size();
+                assert_eq!(batch_size, b.values.batch_size());
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(size, output_grad.single_size());
+
+                if let Some(grd) = a.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                if let Some(grd) = b.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                D::backprop_elementwise_mul(
+                    size * batch_size.unwrap_or(1),
+                    &a.values.dense()?.buf,
+                    &b.values.dense()?.buf,
+                    &output_grad.buf,
+                    a.gradients.as_mut().map(|grd| &mut grd.buf),
+                    b.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
+            Max(an, bn) | Min(an, bn) => {
+                let size = an.shape.size();
+                assert_eq!(an.shape, bn.shape);
+
+                let a = &mut *get(*an);
+                let b = &mut *get(*bn);
+
+                let batch_size = a.values.batch_size();
+                assert_eq!(batch_size, b.values.batch_size());
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(size, output_grad.single_size());
+
+                if let Some(grd) = a.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                if let Some(grd) = b.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                D::backprop_elementwise_min_max(
+                    size * batch_size.unwrap_or(1),
+                    matches!(op, Max(_, _)),
+                    &a.values.dense()?.buf,
+                    &b.values.dense()?.buf,
+                    &output_grad.buf,
+                    a.gradients.as_mut().map(|grd| &mut grd.buf),
+                    b.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
+            Clamp(node, min, max) => {
+                let input = &mut *get(*node);
+                if let Some(grad) = input.gradients.as_mut() {
+                    let input = input.values.dense()?;
+                    assert_eq!(outn.shape, node.shape);
+                    assert_eq!(output_grad.size(), input.size());
+                    assert_eq!(output_grad.batch_size(), input.batch_size());
+                    grad.set_batch_size(output_grad.batch_size())?;
+                    D::backprop_clamp(input.size(), *min, *max, &input.buf, &mut grad.buf, &output_grad.buf)?;
+                }
+            }
+            PReLU(inp, sn) => {
+                let input = &mut *get(*inp);
+                let slope = &mut *get(*sn);
+
+                let batch_size = input.values.batch_size();
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(inp.shape.size(), output_grad.single_size());
+
+                if let Some(grd) = input.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                D::backprop_prelu(
+                    inp.shape.size(),
+                    batch_size.unwrap_or(1),
+                    &input.values.dense()?.buf,
+                    &slope.values.dense()?.buf,
+                    &output_grad.buf,
+                    input.gradients.as_mut().map(|grd| &mut grd.buf),
+                    slope.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
             Affine(wn, inp, bn) => {
                 let i = &mut *get(*inp);
                 let w = &mut *get(*wn);
                 let bs = i.values.batch_size().unwrap_or(1);
-                setup_ones(w.values.dense()?.buf.device(), internal, bs)?;
+                setup_ones(w.values.dense()?.buf.device(), internal, bs * outn.shape.cols())?;
                 let ones = &internal.get("ones").unwrap().borrow().buf;
                 matmul::backprop_dense_affine(w, wn.shape, i, inp.shape, &mut *get(*bn), ones, output_grad)?;
             }
+            AffineActivate(wn, inp, bn, act) => {
+                let i = &mut *get(*inp);
+                let w = &mut *get(*wn);
+                let output = output_tensor.values.dense()?;
+                let device = output.buf.device();
+
+                let bs = i.values.batch_size().unwrap_or(1);
+                setup_ones(device.clone(), internal, bs * outn.shape.cols())?;
+
+                if !internal.contains_key("preact_grad") {
+                    let grad = DenseMatrix::zeroed(device, output.single_size())?;
+                    internal.insert("preact_grad".to_string(), RefCell::new(grad));
+                }
+
+                let ones = &internal.get("ones").unwrap().borrow().buf;
+                let mut preact_grad = internal.get("preact_grad").unwrap().borrow_mut();
+
+                assert_eq!(output.batch_size(), output_grad.batch_size());
+                preact_grad.set_batch_size(output_grad.batch_size())?;
+                D::backprop_activate_from_output(
+                    output_grad.size(),
+                    &output.buf,
+                    &output_grad.buf,
+                    &mut preact_grad.buf,
+                    *act,
+                )?;
+
+                matmul::backprop_dense_affine(w, wn.shape, i, inp.shape, &mut *get(*bn), ones, &preact_grad)?;
+            }
+            LayerNorm(input, scale, shift) => {
+                let input = &mut *get(*input);
+                let scale = &mut *get(*scale);
+                let shift = &mut *get(*shift);
+
+                let vals = input.values.dense()?;
+                let batch_size = vals.batch_size();
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(vals.single_size(), output_grad.single_size());
+
+                if let Some(grd) = input.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                let stats = internal.get("stats").unwrap().borrow();
+
+                D::backprop_layer_norm(
+                    batch_size.unwrap_or(1),
+                    vals.single_size(),
+                    &vals.buf,
+                    &scale.values.dense()?.buf,
+                    &stats.buf,
+                    &output_grad.buf,
+                    input.gradients.as_mut().map(|grd| &mut grd.buf),
+                    scale.gradients.as_mut().map(|grd| &mut grd.buf),
+                    shift.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
             LinearCombination(alpha, an, beta, bn) => {
                 let a = &mut *get(*an);
                 let b = &mut *get(*bn);
@@ -591,7 +1766,7 @@ impl<D: Device> Graph<D> {
                 let abs = a.values.batch_size().unwrap_or(1);
                 let bbs = b.values.batch_size().unwrap_or(1);
                 let bs = abs.max(bbs);
-                setup_ones(a.values.dense()?.buf.device(), internal, bs)?;
+                setup_ones(a.values.dense()?.buf.device(), internal, bs * outn.shape.cols())?;
                 let ones = &internal.get("ones").unwrap().borrow().buf;
 
                 linear_comb::linear_comb_backward(
@@ -605,6 +1780,77 @@ impl<D: Device> Graph<D> {
                     output_grad,
                 )?;
             }
+            BatchNorm(input, scale, shift, _, _, _) => {
+                if !self.training {
+                    return Err(OperationError::UnsupportedOperation("batch_norm outside of training".to_string()));
+                }
+
+                let input = &mut *get(*input);
+                let scale = &mut *get(*scale);
+                let shift = &mut *get(*shift);
+
+                let vals = input.values.dense()?;
+                let batch_size = vals.batch_size();
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(vals.single_size(), output_grad.single_size());
+
+                if let Some(grd) = input.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                }
+
+                let stats = internal.get("stats").unwrap().borrow();
+
+                D::backprop_batch_norm(
+                    batch_size.unwrap_or(1),
+                    vals.single_size(),
+                    &vals.buf,
+                    &scale.values.dense()?.buf,
+                    &stats.buf,
+                    &output_grad.buf,
+                    input.gradients.as_mut().map(|grd| &mut grd.buf),
+                    scale.gradients.as_mut().map(|grd| &mut grd.buf),
+                    shift.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
+            }
+            Dropout(node, _) => {
+                let input = &mut *get(*node);
+                if let Some(grad) = input.gradients.as_mut() {
+                    let size = input.values.size();
+                    assert_eq!(output_grad.size(), size);
+                    grad.set_batch_size(output_grad.batch_size())?;
+
+                    if self.training {
+                        let mask = internal.get("mask").unwrap().borrow();
+                        D::backprop_dropout(size, &mask.buf, &output_grad.buf, &mut grad.buf)?;
+                    } else {
+                        D::linear_comb_single(size, 1.0, None, 1.0, Some(&output_grad.buf), &mut grad.buf)?;
+                    }
+                }
+            }
+            Embedding(table, indices) => {
+                let (dim, vocab) = (table.shape.rows(), table.shape.cols());
+                let table = &mut *get(*table);
+                let indices = get(*indices);
+                let indices = indices.values.sparse()?;
+
+                let batch_size = indices.batch_size();
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(dim * indices.nnz, output_grad.single_size());
+
+                if let Some(grd) = table.gradients.as_mut() {
+                    D::backprop_embedding(
+                        batch_size.unwrap_or(1),
+                        dim,
+                        vocab,
+                        indices.nnz,
+                        &output_grad.buf,
+                        &indices.buf,
+                        &mut grd.buf,
+                    )?;
+                } else if let Some(grd) = table.sparse_gradients.as_mut() {
+                    grd.accumulate(batch_size.unwrap_or(1) * indices.nnz, &indices.buf, &output_grad.buf)?;
+                }
+            }
             Gather(input, indices) => {
                 let input = &mut *get(*input);
                 let indices = get(*indices);
@@ -642,6 +1888,21 @@ impl<D: Device> Graph<D> {
                     output_grad,
                 )?;
             }
+            ConcatMany(nodes) => {
+                let mut offset = 0;
+
+                for node in nodes.iter() {
+                    let input = &mut *get(*node);
+                    concat::backprop_concat_many(
+                        input.values.dense()?,
+                        input.gradients.as_mut(),
+                        node.shape,
+                        offset,
+                        output_grad,
+                    )?;
+                    offset += node.shape.rows();
+                }
+            }
             Mask(input, mask) => {
                 if let Some(grd) = get(*input).gradients.as_mut() {
                     let mask = get(*mask);
@@ -664,17 +1925,42 @@ impl<D: Device> Graph<D> {
                 let a = &mut *get(*an);
                 let b = &mut *get(*bn);
 
-                matmul::backprop_matmul(
-                    a.values.dense()?,
-                    a.gradients.as_mut(),
-                    an.shape,
-                    *trans_a,
-                    b.values.dense()?,
-                    b.gradients.as_mut(),
-                    bn.shape,
-                    *trans_b,
-                    output_grad,
-                )?;
+                if let (Some(bs), None) = (a.values.batch_size(), b.values.batch_size()) {
+                    setup_zeroed(output_grad.buf.device(), internal, "tiled_grad", bn.shape.size())?;
+                    let ones = internal.get("ones").unwrap().borrow();
+                    let tiled = internal.get("tiled").unwrap().borrow();
+                    let mut tiled_grad = internal.get("tiled_grad").unwrap().borrow_mut();
+                    tiled_grad.set_batch_size(Some(bs))?;
+                    tiled_grad.set_zero()?;
+
+                    matmul::backprop_matmul(
+                        a.values.dense()?,
+                        a.gradients.as_mut(),
+                        an.shape,
+                        *trans_a,
+                        &tiled,
+                        b.gradients.is_some().then_some(&mut *tiled_grad),
+                        bn.shape,
+                        *trans_b,
+                        output_grad,
+                    )?;
+
+                    if let Some(grad) = b.gradients.as_mut() {
+                        linear_comb::backprop_add_single_scaled(&ones.buf, 1.0, b.values.dense()?, grad, &tiled_grad)?;
+                    }
+                } else {
+                    matmul::backprop_matmul(
+                        a.values.dense()?,
+                        a.gradients.as_mut(),
+                        an.shape,
+                        *trans_a,
+                        b.values.dense()?,
+                        b.gradients.as_mut(),
+                        bn.shape,
+                        *trans_b,
+                        output_grad,
+                    )?;
+                }
             }
             PairwiseMul(node, post_concat) => {
                 let input = &mut *get(*node);
@@ -696,6 +1982,67 @@ impl<D: Device> Graph<D> {
                     )?;
                 }
             }
+            HuberError(a, b, delta) => {
+                let size = a.shape.size();
+                assert_eq!(a.shape, b.shape);
+
+                let a = &mut *get(*a);
+                let b = &mut *get(*b);
+
+                assert_eq!(size, a.values.single_size());
+                assert_eq!(size, b.values.single_size());
+                assert_eq!(size, output_grad.single_size());
+
+                let batch_size = a.values.batch_size();
+                assert_eq!(batch_size, b.values.batch_size());
+                assert_eq!(batch_size, output_grad.batch_size());
+
+                if let Some(grd) = a.gradients.as_mut() {
+                    assert_eq!(size, grd.single_size());
+                    grd.set_batch_size(batch_size)?;
+                    D::backprop_huber_error_single(
+                        *delta,
+                        size * batch_size.unwrap_or(1),
+                        &a.values.dense()?.buf,
+                        &b.values.dense()?.buf,
+                        &output_grad.buf,
+                        &mut grd.buf,
+                    )?;
+                }
+
+                if let Some(grd) = b.gradients.as_mut() {
+                    assert_eq!(size, grd.single_size());
+                    grd.set_batch_size(batch_size)?;
+                    D::backprop_huber_error_single(
+                        *delta,
+                        size * batch_size.unwrap_or(1),
+                        &b.values.dense()?.buf,
+                        &a.values.dense()?.buf,
+                        &output_grad.buf,
+                        &mut grd.buf,
+                    )?;
+                }
+            }
+            PairwiseRankingLoss(a, b, margin) => {
+                let a = &mut *get(*a);
+                let b = &*get(*b);
+
+                let batch_size = a.values.batch_size();
+                assert_eq!(batch_size, b.values.batch_size());
+                assert_eq!(batch_size, output_grad.batch_size());
+
+                if let Some(grd) = a.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                    D::backprop_pairwise_ranking(
+                        batch_size.unwrap_or(1),
+                        *margin,
+                        &a.values.dense()?.buf,
+                        &b.values.dense()?.buf,
+                        &output_grad.buf,
+                        &mut grd.buf,
+                    )?;
+                }
+            }
             PowerError(a, b, p) => {
                 let size = a.shape.size();
                 assert_eq!(a.shape, b.shape);
@@ -737,6 +2084,45 @@ impl<D: Device> Graph<D> {
                     )?;
                 }
             }
+            Repeat(node, times) => {
+                let input = &mut *get(*node);
+                repeat::backprop_repeat(input.values.dense()?, input.gradients.as_mut(), *times, output_grad)?;
+            }
+            RepeatAcrossBatch(node, _) => {
+                let input = &mut *get(*node);
+                if let Some(grd) = input.gradients.as_mut() {
+                    setup_ones(output_grad.buf.device(), internal, output_grad.batch_size().unwrap_or(1))?;
+                    let ones = internal.get("ones").unwrap().borrow();
+                    linear_comb::backprop_add_single_scaled(&ones.buf, 1.0, input.values.dense()?, grd, output_grad)?;
+                }
+            }
+            ReduceMean(node) | ReduceSum(node) => {
+                let input = &mut *get(*node);
+                if let Some(grd) = input.gradients.as_mut() {
+                    let vals = input.values.dense()?;
+                    let rows = node.shape.rows();
+                    let cols = node.shape.cols() * vals.batch_size().unwrap_or(1);
+
+                    assert_eq!(output_grad.batch_size(), vals.batch_size());
+                    assert_eq!(vals.single_size(), grd.single_size());
+
+                    let scale = if matches!(op, ReduceMean(_)) { 1.0 / rows as f32 } else { 1.0 };
+                    setup_reduce_weights(vals.buf.device(), internal, rows, scale)?;
+                    let weights = internal.get("reduce_weights").unwrap().borrow();
+
+                    grd.set_batch_size(vals.batch_size())?;
+                    D::sgemm(
+                        &weights.buf,
+                        Shape::new(1, rows),
+                        true,
+                        &output_grad.buf,
+                        Shape::new(1, cols),
+                        false,
+                        &mut grd.buf,
+                        true,
+                    )?;
+                }
+            }
             ReduceAcrossBatch(input) => {
                 let input = &mut *get(*input);
                 if let Some(grd) = input.gradients.as_mut() {
@@ -811,6 +2197,55 @@ impl<D: Device> Graph<D> {
                     )?;
                 }
             }
+            SliceColumns(node, start, end) => {
+                let input = &mut *get(*node);
+                if let Some(grd) = input.gradients.as_mut() {
+                    slice::backprop_slice_columns_batched(
+                        node.shape,
+                        input.values.dense()?,
+                        grd,
+                        *start,
+                        *end,
+                        output_grad,
+                    )?;
+                }
+            }
+            Transpose(node) => {
+                let input = &mut *get(*node);
+                if let Some(grd) = input.gradients.as_mut() {
+                    let batch_size = output_grad.batch_size();
+                    assert_eq!(batch_size, input.values.batch_size());
+                    assert_eq!(node.shape.size(), output_grad.single_size());
+
+                    grd.set_batch_size(batch_size)?;
+                    D::transpose(
+                        node.shape.cols(),
+                        node.shape.rows(),
+                        batch_size.unwrap_or(1),
+                        &output_grad.buf,
+                        &mut grd.buf,
+                        true,
+                    )?;
+                }
+            }
+            Softmax(node) => {
+                let input = &mut *get(*node);
+                if let Some(grd) = input.gradients.as_mut() {
+                    let softmaxed = output_tensor.values.dense()?;
+                    let batch_size = softmaxed.batch_size();
+                    assert_eq!(batch_size, output_grad.batch_size());
+                    assert_eq!(softmaxed.single_size(), grd.single_size());
+
+                    grd.set_batch_size(batch_size)?;
+                    D::backprop_softmax_across_batch(
+                        batch_size.unwrap_or(1),
+                        softmaxed.single_size(),
+                        &softmaxed.buf,
+                        &output_grad.buf,
+                        &mut grd.buf,
+                    )?;
+                }
+            }
             SparseAffine(wn, inp, bn) => {
                 let i = &mut *get(*inp);
                 let w = &mut *get(*wn);
@@ -855,14 +2290,14 @@ impl<D: Device> Graph<D> {
                 )?;
             }
             ToDense(_) => return Err(OperationError::UnsupportedOperation("to_dense".to_string())),
-            MaskedSoftmaxCrossEntropyLoss(mask, input, target) => {
+            SparseOuter(_, _) => return Err(OperationError::UnsupportedOperation("sparse_outer".to_string())),
+            MaskedSoftmaxCrossEntropyLoss(mask, input, target, temperature, smoothing) => {
                 let masks = &*get(*mask);
                 let masks = masks.values.sparse()?;
                 let inputs = &mut *get(*input);
                 let targets = &mut *get(*target);
                 let targets = targets.values.dense()?;
 
-                let smax = internal.get("softmaxed").unwrap().borrow();
                 let batch_size = masks.batch_size();
                 let single_size = masks.single_size();
                 let nnz = masks.nnz;
@@ -872,6 +2307,15 @@ impl<D: Device> Graph<D> {
                 assert_eq!(batch_size, output_grad.batch_size());
 
                 if let Some(grd) = inputs.gradients.as_mut() {
+                    // the logits were divided by the temperature before the softmax
+                    let size = batch_size.unwrap_or(1);
+                    setup_zeroed(output_grad.buf.device(), internal, "scaled_grad", size)?;
+                    let mut scaled = internal.get("scaled_grad").unwrap().borrow_mut();
+                    D::linear_comb_single(size, 1.0 / temperature, Some(&output_grad.buf), 0.0, None, &mut scaled.buf)?;
+
+                    let smax = internal.get("softmaxed").unwrap().borrow();
+                    let smoothed = (*smoothing > 0.0).then(|| internal.get("smoothed_targets").unwrap().borrow());
+                    let targets = smoothed.as_ref().map_or(&targets.buf, |smoothed| &smoothed.buf);
                     grd.set_batch_size(batch_size)?;
                     D::backprop_softmax_crossentropy_masked(
                         batch_size.unwrap_or(1),
@@ -879,13 +2323,13 @@ impl<D: Device> Graph<D> {
                         nnz,
                         &masks.buf,
                         &smax.buf,
-                        &targets.buf,
-                        &output_grad.buf,
+                        targets,
+                        &scaled.buf,
                         &mut grd.buf,
                     )?;
                 }
             }
-            SoftmaxCrossEntropyLoss(an, bn) => {
+            SoftmaxCrossEntropyLoss(an, bn, temperature, smoothing) => {
                 let a = &mut *get(*an);
                 let b = &mut *get(*bn);
 
@@ -917,17 +2361,85 @@ impl<D: Device> Graph<D> {
                 )?;
 
                 let smax = &smax.buf;
-                let indv = &indv.buf;
+
+                if let Some(grd) = b.gradients.as_mut() {
+                    grd.set_batch_size(batch_size)?;
+                    D::backprop_softmax_crossentropy(size, smax, &a.values.dense()?.buf, &indv.buf, &mut grd.buf)?;
+                }
+
+                if let Some(grd) = a.gradients.as_mut() {
+                    // the logits were divided by the temperature before the softmax
+                    if *temperature != 1.0 {
+                        D::linear_comb_single(size, 1.0 / temperature, None, 0.0, None, &mut indv.buf)?;
+                    }
+
+                    let smoothed = (*smoothing > 0.0).then(|| internal.get("smoothed_targets").unwrap().borrow());
+                    let targets = smoothed.as_ref().map_or(&b.values.dense()?.buf, |smoothed| &smoothed.buf);
+                    grd.set_batch_size(batch_size)?;
+                    D::backprop_softmax_crossentropy(size, smax, targets, &indv.buf, &mut grd.buf)?;
+                }
+            }
+            KLDivergenceLoss(an, bn) => {
+                let a = &mut *get(*an);
+                let b = &*get(*bn);
+
+                assert_eq!(a.values.batch_size(), b.values.batch_size());
+                assert_eq!(a.values.batch_size(), output_grad.batch_size());
+                assert_eq!(output_grad.single_size(), 1);
+
+                // the targets are treated as fixed, e.g. the outputs of a teacher network
+                if let Some(grd) = a.gradients.as_mut() {
+                    let ones = internal.get("ones").unwrap().borrow();
+                    let smax = internal.get("softmaxed").unwrap().borrow();
+                    let mut indv = internal.get("individual_losses").unwrap().borrow_mut();
+
+                    let batch_size = a.values.batch_size();
+                    let single_size = a.values.single_size();
+                    let size = single_size * batch_size.unwrap_or(1);
+
+                    D::sgemm(
+                        &ones.buf,
+                        Shape::new(single_size, 1),
+                        false,
+                        &output_grad.buf,
+                        Shape::new(1, batch_size.unwrap_or(1)),
+                        false,
+                        &mut indv.buf,
+                        false,
+                    )?;
+
+                    grd.set_batch_size(batch_size)?;
+                    D::backprop_softmax_crossentropy(size, &smax.buf, &b.values.dense()?.buf, &indv.buf, &mut grd.buf)?;
+                }
+            }
+            SigmoidCrossEntropyLoss(an, bn) => {
+                let size = an.shape.size();
+                assert_eq!(an.shape, bn.shape);
+
+                let a = &mut *get(*an);
+                let b = &mut *get(*bn);
+
+                let batch_size = a.values.batch_size();
+                assert_eq!(batch_size, b.values.batch_size());
+                assert_eq!(batch_size, output_grad.batch_size());
+                assert_eq!(size, output_grad.single_size());
 
                 if let Some(grd) = a.gradients.as_mut() {
                     grd.set_batch_size(batch_size)?;
-                    D::backprop_softmax_crossentropy(size, smax, &b.values.dense()?.buf, indv, &mut grd.buf)?;
                 }
 
                 if let Some(grd) = b.gradients.as_mut() {
                     grd.set_batch_size(batch_size)?;
-                    D::backprop_softmax_crossentropy(size, smax, &a.values.dense()?.buf, indv, &mut grd.buf)?;
                 }
+
+                D::backprop_sigmoid_bce(
+                    size * batch_size.unwrap_or(1),
+                    &a.values.dense()?.buf,
+                    &b.values.dense()?.buf,
+                    &output_grad.buf,
+                    a.gradients.as_mut().map(|grd| &mut grd.buf),
+                    b.gradients.as_mut().map(|grd| &mut grd.buf),
+                )?;
             }
         }
 
@@ -952,6 +2464,87 @@ fn setup_ones<D: Device>(
     Ok(())
 }
 
+/// A row vector of `size` elements all equal to `scale`.
+fn setup_reduce_weights<D: Device>(
+    device: Arc<D>,
+    internal: &mut HashMap<String, RefCell<DenseMatrix<D>>>,
+    size: usize,
+    scale: f32,
+) -> Result<(), OperationError<D::DeviceError>> {
+    if !internal.contains_key("reduce_weights") {
+        let mut weights = DenseMatrix::ones(device, size)?;
+
+        if scale != 1.0 {
+            D::linear_comb_single(size, scale, None, 0.0, None, &mut weights.buf)?;
+        }
+
+        internal.insert("reduce_weights".to_string(), RefCell::new(weights));
+    }
+
+    Ok(())
+}
+
+fn setup_zeroed<D: Device>(
+    device: Arc<D>,
+    internal: &mut HashMap<String, RefCell<DenseMatrix<D>>>,
+    id: &str,
+    size: usize,
+) -> Result<(), D::DeviceError> {
+    if let Some(buf) = internal.get_mut(id) {
+        if buf.borrow().size() < size {
+            *buf = RefCell::new(DenseMatrix::zeroed(device, size)?);
+        }
+    } else {
+        let zeros = RefCell::new(DenseMatrix::zeroed(device, size)?);
+        internal.insert(id.to_string(), zeros);
+    }
+
+    Ok(())
+}
+
+/// Divides `input` by `temperature` into the internal buffer "scaled_logits", unless the
+/// temperature is 1, in which case the softmax is taken of `input` directly.
+fn setup_scaled_logits<D: Device>(
+    internal: &mut HashMap<String, RefCell<DenseMatrix<D>>>,
+    input: &DenseMatrix<D>,
+    temperature: f32,
+) -> Result<(), OperationError<D::DeviceError>> {
+    if temperature == 1.0 {
+        return Ok(());
+    }
+
+    setup_zeroed(input.buf.device(), internal, "scaled_logits", input.size())?;
+    let mut scaled = internal.get("scaled_logits").unwrap().borrow_mut();
+    D::linear_comb_single(input.size(), 1.0 / temperature, Some(&input.buf), 0.0, None, &mut scaled.buf)
+}
+
+/// Mixes `targets` with the uniform distribution over their rows, weighted by `smoothing`, into
+/// the internal buffer "smoothed_targets", unless the smoothing is 0.
+fn setup_smoothed_targets<D: Device>(
+    internal: &mut HashMap<String, RefCell<DenseMatrix<D>>>,
+    targets: &DenseMatrix<D>,
+    smoothing: f32,
+) -> Result<(), OperationError<D::DeviceError>> {
+    if smoothing == 0.0 {
+        return Ok(());
+    }
+
+    let device = targets.buf.device();
+    setup_ones(device.clone(), internal, targets.size())?;
+    setup_zeroed(device, internal, "smoothed_targets", targets.size())?;
+    let ones = internal.get("ones").unwrap().borrow();
+    let mut smoothed = internal.get("smoothed_targets").unwrap().borrow_mut();
+    let uniform = smoothing / targets.single_size() as f32;
+    D::linear_comb_single(
+        targets.size(),
+        uniform,
+        Some(&ones.buf),
+        1.0 - smoothing,
+        Some(&targets.buf),
+        &mut smoothed.buf,
+    )
+}
+
 fn setup_softmax<D: Device>(
     device: Arc<D>,
     internal: &mut HashMap<String, RefCell<DenseMatrix<D>>>,
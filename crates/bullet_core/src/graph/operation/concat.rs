@@ -104,3 +104,75 @@ pub fn backprop_concat<D: Device>(
 
     Ok(())
 }
+
+pub fn concat_many<D: Device>(
+    inputs: &[&DenseMatrix<D>],
+    shapes: &[Shape],
+    output: &mut DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    assert_eq!(inputs.len(), shapes.len());
+
+    let batch_size = inputs[0].batch_size();
+    let shape_o = Shape::new(shapes.iter().map(Shape::rows).sum(), 1);
+    assert_eq!(shape_o.size(), output.single_size());
+
+    output.set_batch_size(batch_size)?;
+
+    let mut offset = 0;
+
+    for (input, shape) in inputs.iter().zip(shapes) {
+        assert_eq!(shape.cols(), 1);
+        assert_eq!(shape.size(), input.single_size());
+        assert_eq!(input.batch_size(), batch_size);
+
+        D::copy_or_add_strided(
+            shape.rows(),
+            batch_size.unwrap_or(1),
+            &input.buf,
+            0,
+            shape.rows(),
+            &mut output.buf,
+            offset,
+            shape_o.rows(),
+            false,
+        )?;
+
+        offset += shape.rows();
+    }
+
+    Ok(())
+}
+
+/// Backpropagates into a single input of `concat_many`, which starts at
+/// row `offset` of the output.
+pub fn backprop_concat_many<D: Device>(
+    input: &DenseMatrix<D>,
+    input_grad: Option<&mut DenseMatrix<D>>,
+    shape: Shape,
+    offset: usize,
+    output_grad: &DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    assert_eq!(shape.cols(), 1);
+    assert_eq!(shape.size(), input.single_size());
+    assert_eq!(input.batch_size(), output_grad.batch_size());
+    assert!(offset + shape.rows() <= output_grad.single_size());
+
+    if let Some(grad) = input_grad {
+        assert_eq!(grad.single_size(), input.single_size());
+        grad.set_batch_size(input.batch_size())?;
+
+        D::copy_or_add_strided(
+            shape.rows(),
+            grad.batch_size().unwrap_or(1),
+            &output_grad.buf,
+            offset,
+            output_grad.single_size(),
+            &mut grad.buf,
+            0,
+            shape.rows(),
+            true,
+        )?;
+    }
+
+    Ok(())
+}
@@ -15,7 +15,10 @@ pub fn linear_comb<D: Device>(
     shape_b: Shape,
     output: &mut DenseMatrix<D>,
 ) -> Result<(), OperationError<D::DeviceError>> {
-    assert_eq!(shape_a, shape_b);
+    if shape_a != shape_b {
+        return linear_comb_broadcast(ones, alpha, input_a, shape_a, beta, input_b, shape_b, output);
+    }
+
     let size = shape_a.size();
 
     match (input_a.batch_size(), input_b.batch_size()) {
@@ -38,6 +41,32 @@ pub fn linear_comb<D: Device>(
     }
 }
 
+/// One of the inputs is an unbatched column vector, which is added to every column of the other.
+#[allow(clippy::too_many_arguments)]
+fn linear_comb_broadcast<D: Device>(
+    ones: &D::BufferF32,
+    alpha: f32,
+    input_a: &DenseMatrix<D>,
+    shape_a: Shape,
+    beta: f32,
+    input_b: &DenseMatrix<D>,
+    shape_b: Shape,
+    output: &mut DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    let ((full, full_scale, full_shape), (vector, vector_scale, vector_shape)) = if shape_b.cols() == 1 {
+        ((input_a, alpha, shape_a), (input_b, beta, shape_b))
+    } else {
+        ((input_b, beta, shape_b), (input_a, alpha, shape_a))
+    };
+
+    assert_eq!(vector_shape, Shape::new(full_shape.rows(), 1));
+    assert!(vector.batch_size().is_none(), "Cannot broadcast a batched column vector!");
+
+    let reps = full.batch_size().unwrap_or(1) * full_shape.cols();
+    copy_into_scaled(full_scale, full, output)?;
+    D::add_assign_single_to_batched_scaled(vector_shape.rows(), reps, ones, vector_scale, &vector.buf, &mut output.buf)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn linear_comb_backward<D: Device>(
     ones: &D::BufferF32,
@@ -88,16 +117,20 @@ pub fn backprop_add_single_scaled<D: Device>(
     input_grad: &mut DenseMatrix<D>,
     output_grad: &DenseMatrix<D>,
 ) -> Result<(), OperationError<D::DeviceError>> {
-    assert_eq!(input.single_size(), output_grad.single_size());
     assert_eq!(input.single_size(), input_grad.single_size());
+    assert_eq!(output_grad.single_size() % input.single_size(), 0);
     input_grad.set_batch_size(input.batch_size())?;
 
+    // the input was broadcast across the columns of the output
+    let broadcast = input.single_size() != output_grad.single_size();
+
     match (input.batch_size(), output_grad.batch_size()) {
-        (Some(_), Some(_)) | (None, None) => add_assign_scaled(alpha, output_grad, input_grad),
-        (None, Some(x)) => {
-            assert!(output_grad.batch_size().unwrap_or(1) <= ones.size());
-            D::reduce_add(ones, input.single_size(), x, &output_grad.buf, &mut input_grad.buf)
+        (Some(_), Some(_)) | (None, None) if !broadcast => add_assign_scaled(alpha, output_grad, input_grad),
+        (None, _) => {
+            let cols = output_grad.size() / input.single_size();
+            assert!(cols <= ones.size());
+            D::reduce_add(ones, input.single_size(), cols, alpha, &output_grad.buf, &mut input_grad.buf, true)
         }
-        (Some(_), None) => Err(OperationError::UnsupportedOperation("backprop add".to_string())),
+        (Some(_), _) => Err(OperationError::UnsupportedOperation("backprop add".to_string())),
     }
 }
@@ -4,7 +4,7 @@ use crate::{
     tensor::{DenseMatrix, SparseMatrix, Tensor},
 };
 
-use super::linear_comb::backprop_add_single_scaled;
+use super::{linear_comb::backprop_add_single_scaled, Activation};
 
 #[allow(clippy::too_many_arguments)]
 pub fn dense_affine<D: Device>(
@@ -18,14 +18,38 @@ pub fn dense_affine<D: Device>(
     out: &mut DenseMatrix<D>,
 ) -> Result<(), OperationError<D::DeviceError>> {
     let output_shape = shape_a * shape_b;
-    assert_eq!(output_shape.size(), c.single_size());
-    assert_eq!(output_shape, shape_c);
+    assert_eq!(output_shape.rows(), shape_c.rows());
+    assert!(shape_c == output_shape || shape_c.cols() == 1);
+    assert_eq!(shape_c.size(), c.single_size());
     assert!(c.batch_size().is_none());
 
     matmul(a, shape_a, false, b, shape_b, false, out)?;
 
-    let bs = out.batch_size().unwrap_or(1);
-    D::add_assign_single_to_batched_scaled(c.single_size(), bs, ones, 1.0, &c.buf, &mut out.buf)
+    let reps = out.batch_size().unwrap_or(1) * output_shape.size() / c.single_size();
+    D::add_assign_single_to_batched_scaled(c.single_size(), reps, ones, 1.0, &c.buf, &mut out.buf)
+}
+
+/// As `dense_affine`, with the bias added and `activation` applied in a single kernel.
+#[allow(clippy::too_many_arguments)]
+pub fn dense_affine_activate<D: Device>(
+    a: &DenseMatrix<D>,
+    shape_a: Shape,
+    b: &DenseMatrix<D>,
+    shape_b: Shape,
+    c: &DenseMatrix<D>,
+    shape_c: Shape,
+    activation: Activation,
+    out: &mut DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    let output_shape = shape_a * shape_b;
+    assert_eq!(output_shape.rows(), shape_c.rows());
+    assert!(shape_c == output_shape || shape_c.cols() == 1);
+    assert_eq!(shape_c.size(), c.single_size());
+    assert!(c.batch_size().is_none());
+
+    matmul(a, shape_a, false, b, shape_b, false, out)?;
+
+    D::bias_activate(out.size(), c.single_size(), &c.buf, &mut out.buf, activation)
 }
 
 pub fn backprop_dense_affine<D: Device>(
@@ -38,7 +62,7 @@ pub fn backprop_dense_affine<D: Device>(
     output_grad: &DenseMatrix<D>,
 ) -> Result<(), OperationError<D::DeviceError>> {
     let output_shape = shape_a * shape_b;
-    assert_eq!(output_shape.size(), c.values.single_size());
+    assert_eq!(output_shape.size() % c.values.single_size(), 0);
     assert!(c.values.batch_size().is_none());
 
     backprop_matmul(
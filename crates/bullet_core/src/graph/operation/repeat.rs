@@ -0,0 +1,80 @@
+use crate::{
+    device::{Device, OperationError},
+    tensor::DenseMatrix,
+};
+
+/// Copies `input` `times` times along its columns, for each batch element.
+pub fn repeat<D: Device>(
+    input: &DenseMatrix<D>,
+    times: usize,
+    output: &mut DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    let size = input.single_size();
+    assert_eq!(size * times, output.single_size());
+
+    output.set_batch_size(input.batch_size())?;
+    let out_size = output.single_size();
+
+    for i in 0..times {
+        D::copy_or_add_strided(
+            size,
+            input.batch_size().unwrap_or(1),
+            &input.buf,
+            0,
+            size,
+            &mut output.buf,
+            i * size,
+            out_size,
+            false,
+        )?;
+    }
+
+    Ok(())
+}
+
+pub fn backprop_repeat<D: Device>(
+    input: &DenseMatrix<D>,
+    input_grad: Option<&mut DenseMatrix<D>>,
+    times: usize,
+    output_grad: &DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    let size = input.single_size();
+    assert_eq!(size * times, output_grad.single_size());
+    assert_eq!(input.batch_size(), output_grad.batch_size());
+
+    if let Some(grad) = input_grad {
+        assert_eq!(grad.single_size(), size);
+        grad.set_batch_size(input.batch_size())?;
+
+        for i in 0..times {
+            D::copy_or_add_strided(
+                size,
+                grad.batch_size().unwrap_or(1),
+                &output_grad.buf,
+                i * size,
+                output_grad.single_size(),
+                &mut grad.buf,
+                0,
+                size,
+                true,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Copies the single `input` once for each of `batch_size` batch elements.
+pub fn repeat_across_batch<D: Device>(
+    ones: &D::BufferF32,
+    input: &DenseMatrix<D>,
+    batch_size: usize,
+    output: &mut DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    assert!(input.batch_size().is_none());
+    assert_eq!(input.single_size(), output.single_size());
+
+    output.set_batch_size(Some(batch_size))?;
+    output.set_zero()?;
+    D::add_assign_single_to_batched_scaled(input.single_size(), batch_size, ones, 1.0, &input.buf, &mut output.buf)
+}
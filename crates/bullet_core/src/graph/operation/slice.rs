@@ -72,3 +72,70 @@ pub fn backprop_slice_vector_batched<D: Device>(
         true,
     )
 }
+
+pub fn slice_columns_batched<D: Device>(
+    shape: Shape,
+    input: &DenseMatrix<D>,
+    start: usize,
+    end: usize,
+    output: &mut DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    assert!(end > start, "Invalid slice indices! end = {end} > start = {start}");
+    assert!(
+        end <= shape.cols(),
+        "Slice index out of bounds! Number of cols is {} but slice endpoint is {end}!",
+        shape.cols()
+    );
+
+    let output_shape = Shape::new(shape.rows(), end - start);
+    output.set_batch_size(input.batch_size())?;
+
+    D::copy_or_add_strided(
+        output_shape.size(),
+        input.batch_size().unwrap_or(1),
+        &input.buf,
+        start * shape.rows(),
+        shape.size(),
+        &mut output.buf,
+        0,
+        output_shape.size(),
+        false,
+    )
+}
+
+pub fn backprop_slice_columns_batched<D: Device>(
+    shape: Shape,
+    input: &DenseMatrix<D>,
+    input_grad: &mut DenseMatrix<D>,
+    start: usize,
+    end: usize,
+    output_grad: &DenseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    assert!(end > start, "Invalid slice indices! end = {end} > start = {start}");
+    assert!(
+        end <= shape.cols(),
+        "Slice index out of bounds! Number of cols is {} but slice endpoint is {end}!",
+        shape.cols()
+    );
+
+    let output_shape = Shape::new(shape.rows(), end - start);
+
+    assert_eq!(input.single_size, shape.size());
+    assert_eq!(input.single_size, input_grad.single_size);
+    assert_eq!(input.batch_size, output_grad.batch_size);
+    assert_eq!(output_grad.single_size, output_shape.size());
+
+    input_grad.set_batch_size(input.batch_size())?;
+
+    D::copy_or_add_strided(
+        output_shape.size(),
+        input.batch_size().unwrap_or(1),
+        &output_grad.buf,
+        0,
+        output_shape.size(),
+        &mut input_grad.buf,
+        start * shape.rows(),
+        shape.size(),
+        true,
+    )
+}
@@ -90,3 +90,18 @@ pub fn backprop_sparse_affine_dual_activate<D: Device>(
 
     Ok(())
 }
+
+/// Pairs each active index of `a` with each active index of `b`, see `Operation::SparseOuter`.
+pub fn sparse_outer<D: Device>(
+    a: &SparseMatrix<D>,
+    b: &SparseMatrix<D>,
+    output: &mut SparseMatrix<D>,
+) -> Result<(), OperationError<D::DeviceError>> {
+    assert_eq!(a.batch_size(), b.batch_size());
+    assert_eq!(output.nnz, a.nnz * b.nnz);
+    assert_eq!(output.single_size(), a.single_size() * b.single_size());
+
+    output.set_batch_size(a.batch_size())?;
+
+    D::sparse_outer(a.batch_size().unwrap_or(1), a.nnz, &a.buf, b.single_size(), b.nnz, &b.buf, &mut output.buf)
+}
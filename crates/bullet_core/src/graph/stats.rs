@@ -0,0 +1,38 @@
+/// Summary of the values taken by a node over a number of forward passes.
+#[derive(Clone, Copy, Debug)]
+pub struct ActivationStats {
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Default for ActivationStats {
+    fn default() -> Self {
+        Self { count: 0, min: f32::INFINITY, max: f32::NEG_INFINITY, sum: 0.0, sum_sq: 0.0 }
+    }
+}
+
+impl ActivationStats {
+    pub fn push(&mut self, values: &[f32]) {
+        for &x in values {
+            self.min = self.min.min(x);
+            self.max = self.max.max(x);
+            self.sum += f64::from(x);
+            self.sum_sq += f64::from(x) * f64::from(x);
+        }
+
+        self.count += values.len();
+    }
+
+    pub fn mean(&self) -> f32 {
+        (self.sum / self.count.max(1) as f64) as f32
+    }
+
+    pub fn std(&self) -> f32 {
+        let mean = self.sum / self.count.max(1) as f64;
+        let var = self.sum_sq / self.count.max(1) as f64 - mean * mean;
+        var.max(0.0).sqrt() as f32
+    }
+}
@@ -1,11 +1,51 @@
 mod activate;
+mod attention;
+mod checkpoint;
 mod concat;
+mod conv;
+mod cse;
+mod description;
+mod dropout;
+mod elementwise;
+mod embedding;
+mod fusion;
+mod gradcheck;
+mod loss;
 mod matmul;
+mod memory;
+mod norm;
+mod optimiser;
+mod outputs;
+mod pool;
+mod reduce;
+mod repeat;
+mod slice;
+mod softmax;
 mod sparse_affine;
 
 pub use activate::*;
+pub use attention::*;
+pub use checkpoint::*;
 pub use concat::*;
+pub use conv::*;
+pub use cse::*;
+pub use description::*;
+pub use dropout::*;
+pub use elementwise::*;
+pub use embedding::*;
+pub use fusion::*;
+pub use gradcheck::*;
+pub use loss::*;
 pub use matmul::*;
+pub use memory::*;
+pub use norm::*;
+pub use optimiser::*;
+pub use outputs::*;
+pub use pool::*;
+pub use reduce::*;
+pub use repeat::*;
+pub use slice::*;
+pub use softmax::*;
 pub use sparse_affine::*;
 
 #[macro_export]
@@ -21,3 +61,10 @@ macro_rules! make_tests {
 }
 
 pub use make_tests;
+
+fn assert_approx_eq(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len());
+    for (x, y) in a.iter().zip(b.iter()) {
+        assert!((x - y).abs() < 0.001, "{a:?} != {b:?}");
+    }
+}
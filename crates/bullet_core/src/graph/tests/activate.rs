@@ -8,6 +8,8 @@ use crate::{
     shape::Shape,
 };
 
+use super::assert_approx_eq;
+
 pub fn relu<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
     activate(device, Activation::ReLU, [0.0, 0.5, 2.0, 0.0], [0.0, 1.0, 1.0, 0.0])
 }
@@ -24,6 +26,72 @@ pub fn sqrrelu<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
     activate(device, Activation::SqrReLU, [0.0, 0.25, 4.0, 0.0], [0.0, 1.0, 4.0, 0.0])
 }
 
+pub fn gelu<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    activate_approx(
+        device,
+        Activation::GELU,
+        [-0.15866, 0.34573, 1.9545, -0.0455],
+        [-0.08332, 0.8675, 1.08523, -0.08523],
+    )
+}
+
+pub fn silu<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    activate_approx(
+        device,
+        Activation::SiLU,
+        [-0.26894, 0.31123, 1.76159, -0.23841],
+        [0.07233, 0.73996, 1.09078, -0.09078],
+    )
+}
+
+pub fn abs<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Abs(w), true).unwrap();
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true).unwrap();
+    let mut graph = builder.build(device).unwrap();
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(5), &[-1.0, 0.5, 2.0, -2.0, 0.0]).unwrap();
+
+    let err = graph.forward().unwrap();
+    assert_eq!(err, 5.5);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[1.0, 0.5, 2.0, 2.0, 0.0]);
+
+    graph.backward().unwrap();
+
+    let mut buf = [0.0; 5];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [-1.0, 1.0, 1.0, -1.0, 0.0]);
+
+    Ok(())
+}
+
+pub fn clamp<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Clamp(w, -1.5, 1.0), true).unwrap();
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true).unwrap();
+    let mut graph = builder.build(device).unwrap();
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(4), &[-1.0, 0.5, 2.0, -2.0]).unwrap();
+
+    let err = graph.forward().unwrap();
+    assert_eq!(err, 0.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[-1.0, 0.5, 1.0, -1.5]);
+
+    graph.backward().unwrap();
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [1.0, 1.0, 0.0, 0.0]);
+
+    Ok(())
+}
+
 fn activate<D: Device>(
     device: D,
     activation: Activation,
@@ -52,3 +120,65 @@ fn activate<D: Device>(
 
     Ok(())
 }
+
+fn activate_approx<D: Device>(
+    device: D,
+    activation: Activation,
+    fwd: [f32; 4],
+    bwd: [f32; 4],
+) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Activate(w, activation), true).unwrap();
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true).unwrap();
+    let mut graph = builder.build(device).unwrap();
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(4), &[-1.0, 0.5, 2.0, -2.0]).unwrap();
+
+    let err = graph.forward().unwrap();
+    assert!((err - fwd.iter().sum::<f32>()).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &fwd);
+
+    graph.backward().unwrap();
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &bwd);
+
+    Ok(())
+}
+
+pub fn prelu<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 1)).unwrap();
+    let slope = builder.create_weights("slope", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::PReLU(w, slope), true).unwrap();
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true).unwrap();
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true).unwrap();
+    let mut graph = builder.build(device).unwrap();
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(2), &[-1.0, 0.5, 2.0, -2.0]).unwrap();
+    graph.get_weights_mut("slope").load_dense_from_slice(None, &[0.25, 0.5]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+
+    let err = graph.forward().unwrap();
+    assert_eq!(err, 0.75);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[-0.25, 0.5, 2.0, -1.0]);
+
+    graph.backward().unwrap();
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [0.25, 2.0, 1.0, 1.0]);
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("slope").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [-1.0, -4.0]);
+
+    Ok(())
+}
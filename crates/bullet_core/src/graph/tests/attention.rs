@@ -0,0 +1,46 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn attention<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let q = builder.create_weights("q", Shape::new(2, 1)).unwrap();
+    let k = builder.create_weights("k", Shape::new(2, 1)).unwrap();
+    let v = builder.create_weights("v", Shape::new(2, 1)).unwrap();
+    let mask = builder.create_dense_input("mask", Shape::new(4, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Attention(q, k, v, Some(mask), 2), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("q").load_dense_from_slice(None, &[0.0, 0.0]).unwrap();
+    graph.get_weights_mut("k").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+    graph.get_weights_mut("v").load_dense_from_slice(None, &[1.0, 3.0]).unwrap();
+    graph.get_input_mut("mask").load_dense_from_slice(None, &[0.0, -1e9, 0.0, 0.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 1.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 3.0).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[1.0, 2.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("q").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[0.0, 0.5]);
+
+    graph.get_weights("k").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[0.0, 0.0]);
+
+    graph.get_weights("v").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[1.5, 0.5]);
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{
+    device::Device,
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    optimiser::utils::read_from_byte_buffer,
+    shape::Shape,
+};
+
+pub fn checkpoint_portable<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(4, 1)).unwrap();
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Matmul(dot, false, w, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    let values = [1.0, -0.0, f32::MIN_POSITIVE / 2.0, f32::from_bits(0x7fc0_1234)];
+    graph.get_weights_mut("w").load_dense_from_slice(None, &values).unwrap();
+
+    let bytes = graph.get_weights("w").values.dense().unwrap().write_to_byte_buffer("w").unwrap();
+
+    // the on-disk format must not depend on the device or host that wrote it
+    let mut expected = vec![b'w', b'\n', 4, 0, 0, 0, 0, 0, 0, 0];
+    for val in values {
+        expected.extend_from_slice(&val.to_bits().to_le_bytes());
+    }
+
+    assert_eq!(bytes, expected);
+
+    let (read, id, bytes_read) = read_from_byte_buffer(&bytes, false);
+
+    assert_eq!(id, "w");
+    assert_eq!(bytes_read, bytes.len());
+    assert_eq!(read.iter().map(|x| x.to_bits()).collect::<Vec<_>>(), values.map(f32::to_bits));
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &read).unwrap();
+    let reloaded = graph.get_weights("w").values.dense().unwrap().write_to_byte_buffer("w").unwrap();
+
+    assert_eq!(reloaded, bytes);
+
+    Ok(())
+}
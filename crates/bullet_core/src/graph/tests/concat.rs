@@ -1,6 +1,10 @@
 use crate::{
     device::{Device, OperationError},
-    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    graph::{
+        builder::GraphBuilder,
+        error::GraphError,
+        operation::{NodeList, Operation},
+    },
     shape::Shape,
 };
 
@@ -37,3 +41,40 @@ pub fn concat<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
 
     Ok(())
 }
+
+pub fn concat_many<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w1 = builder.create_weights("w1", Shape::new(2, 1)).unwrap();
+    let w2 = builder.create_weights("w2", Shape::new(1, 1)).unwrap();
+    let w3 = builder.create_weights("w3", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::ConcatMany(NodeList::new(&[w1, w2, w3]).unwrap()), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 5)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w1").load_dense_from_slice(Some(2), &[1.0, 2.0, 3.0, 4.0]).unwrap();
+    graph.get_weights_mut("w2").load_dense_from_slice(Some(2), &[5.0, 6.0]).unwrap();
+    graph.get_weights_mut("w3").load_dense_from_slice(Some(2), &[7.0, 8.0, 9.0, 10.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+
+    graph.forward()?;
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[1.0, 2.0, 5.0, 7.0, 8.0, 3.0, 4.0, 6.0, 9.0, 10.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w1").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [1.0, 2.0, 1.0, 2.0]);
+
+    graph.get_weights("w3").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [4.0, 5.0, 4.0, 5.0]);
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("w2").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [3.0, 3.0]);
+
+    Ok(())
+}
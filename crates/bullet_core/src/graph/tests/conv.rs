@@ -0,0 +1,53 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{
+        builder::GraphBuilder,
+        error::GraphError,
+        operation::{ConvSettings, Operation},
+    },
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn conv2d<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let settings =
+        ConvSettings { in_channels: 1, out_channels: 2, height: 2, width: 2, kernel_height: 3, kernel_width: 3 };
+
+    let mut builder = GraphBuilder::default();
+    let filters = builder.create_weights("f", settings.filters_shape()).unwrap();
+    let w = builder.create_weights("w", Shape::new(4, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Conv2d(filters, w, settings), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 8)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    let mut filter_vals = [0.0; 18];
+    filter_vals[4] = 1.0;
+    filter_vals[9..].fill(1.0);
+
+    graph.get_weights_mut("f").load_dense_from_slice(None, &filter_vals).unwrap();
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0; 8]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 50.0).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[1.0, 2.0, 3.0, 4.0, 10.0, 10.0, 10.0, 10.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[5.0; 4]);
+
+    let mut buf = [0.0; 18];
+    graph.get_weights("f").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    let expected = [1.0, 3.0, 2.0, 4.0, 10.0, 6.0, 3.0, 7.0, 4.0];
+    assert_approx_eq(&buf[..9], &expected);
+    assert_approx_eq(&buf[9..], &expected);
+
+    Ok(())
+}
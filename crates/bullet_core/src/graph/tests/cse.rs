@@ -0,0 +1,73 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn common_subexpressions<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let a = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    let b = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    let c = builder.create_result_of_operation(Operation::Dropout(a, 0.5), true)?;
+    let d = builder.create_result_of_operation(Operation::Dropout(b, 0.5), true)?;
+    let abs_a = builder.create_result_of_operation(Operation::Abs(a), true)?;
+    let abs_b = builder.create_result_of_operation(Operation::Abs(b), true)?;
+    // merging `abs_b` would leave this with the same node as both inputs
+    let sum = builder.create_result_of_operation(Operation::LinearCombination(1.0, abs_a, 2.0, abs_b), true)?;
+    let drop = builder.create_result_of_operation(Operation::LinearCombination(0.0, c, 0.0, d), true)?;
+    let out = builder.create_result_of_operation(Operation::LinearCombination(1.0, sum, 1.0, drop), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    let dot = graph.to_dot();
+    assert_eq!(dot.matches("\\nMatmul\\n").count(), 1);
+    assert_eq!(dot.matches("\\nAbs\\n").count(), 2);
+    assert_eq!(dot.matches("\\nDropout\\n").count(), 2);
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[1.0, 2.0, 4.0, 3.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 6.0).abs() < 0.001);
+
+    let output = graph.get_node(b).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[-1.0, 1.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[9.0, 3.0]);
+
+    Ok(())
+}
+
+pub fn common_subexpressions_labelled<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let a = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    let b = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    builder.set_label(a, "first");
+    builder.set_label(b, "second");
+    let out = builder.create_result_of_operation(Operation::LinearCombination(1.0, a, 2.0, b), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[1.0, 2.0, 4.0, 3.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!(err.abs() < 0.001);
+
+    for label in ["first", "second"] {
+        let output = graph.get_named(label).unwrap().get_dense_vals().unwrap();
+        assert_approx_eq(&output, &[-1.0, 1.0]);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use crate::{
+    device::Device,
+    graph::{
+        builder::GraphBuilder,
+        error::GraphError,
+        operation::{Activation, Operation},
+    },
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn description_round_trip<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let hidden = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    builder.set_label(hidden, "hidden \"h\"");
+    let relu = builder.create_result_of_operation(Operation::Activate(hidden, Activation::ReLU), true)?;
+    let out = builder.create_result_of_operation(Operation::LinearCombination(1.5, relu, -0.25, hidden), true)?;
+    builder.add_loss_component("out", out);
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+
+    let desc = builder.description();
+    let restored = GraphBuilder::from_description(&desc).unwrap();
+    assert_eq!(restored.description(), desc);
+
+    let mut graph = restored.build(device)?;
+    assert_eq!(GraphBuilder::from_description(graph.description()).unwrap().description(), desc);
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[1.0, 2.0, 4.0, 3.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 1.5).abs() < 0.001);
+
+    let hidden = graph.get_named("hidden \"h\"").unwrap().get_dense_vals().unwrap();
+    assert_approx_eq(&hidden, &[-1.0, 1.0]);
+    assert_eq!(graph.loss_components()?, vec![("out".to_string(), 1.5)]);
+
+    assert!(GraphBuilder::from_description(&desc[1..]).is_err());
+    assert!(GraphBuilder::from_description(&desc.replace("Matmul", "Matmil")).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+pub fn dropout<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Dropout(w, 0.5), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    let inputs = (1..=1000).map(|x| x as f32).collect::<Vec<_>>();
+    graph.get_weights_mut("w").load_dense_from_slice(Some(1000), &inputs).unwrap();
+
+    graph.forward()?;
+    graph.backward()?;
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    let mut grads = [0.0; 1000];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut grads).map_err(OperationError::from)?;
+
+    let mut dropped = 0;
+    for ((&x, &y), &grad) in inputs.iter().zip(output.iter()).zip(grads.iter()) {
+        if y == 0.0 {
+            dropped += 1;
+            assert_eq!(grad, 0.0);
+        } else {
+            assert_eq!(y, 2.0 * x);
+            assert_eq!(grad, 2.0);
+        }
+    }
+
+    assert!((400..600).contains(&dropped), "Dropped {dropped} of 1000 values with rate 0.5!");
+
+    graph.set_training(false);
+    graph.forward()?;
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(output, inputs);
+
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+pub fn elementwise_min<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    min_max(device, false)
+}
+
+pub fn elementwise_max<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    min_max(device, true)
+}
+
+fn min_max<D: Device>(device: D, max: bool) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let a = builder.create_weights("a", Shape::new(1, 1)).unwrap();
+    let b = builder.create_weights("b", Shape::new(1, 1)).unwrap();
+    let op = if max { Operation::Max(a, b) } else { Operation::Min(a, b) };
+    let out = builder.create_result_of_operation(op, true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("a").load_dense_from_slice(Some(3), &[1.0, -2.0, 3.0]).unwrap();
+    graph.get_weights_mut("b").load_dense_from_slice(Some(3), &[2.0, -3.0, 3.0]).unwrap();
+
+    graph.forward()?;
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    let expected = if max { [2.0, -2.0, 3.0] } else { [1.0, -3.0, 3.0] };
+    assert_eq!(&output, &expected);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 3];
+    graph.get_weights("a").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, if max { [0.0, 1.0, 1.0] } else { [1.0, 0.0, 1.0] });
+
+    graph.get_weights("b").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, if max { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] });
+
+    Ok(())
+}
+
+pub fn elementwise_mul<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let a = builder.create_weights("a", Shape::new(2, 1)).unwrap();
+    let b = builder.create_dense_input("b", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::ElementwiseMul(a, b), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("a").load_dense_from_slice(Some(2), &[1.0, -2.0, 2.0, 1.0]).unwrap();
+    graph.get_input_mut("b").load_dense_from_slice(Some(2), &[3.0, 0.5, -1.0, 2.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert_eq!(err, 3.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[3.0, -1.0, -2.0, 2.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("a").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [3.0, 1.0, -1.0, 4.0]);
+
+    Ok(())
+}
+
+pub fn elementwise_div<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let a = builder.create_weights("a", Shape::new(1, 1)).unwrap();
+    let b = builder.create_weights("b", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::ElementwiseDiv(a, b, 0.5), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("a").load_dense_from_slice(Some(3), &[3.0, 1.0, 2.0]).unwrap();
+    graph.get_weights_mut("b").load_dense_from_slice(Some(3), &[2.0, 0.0, -0.25]).unwrap();
+
+    graph.forward()?;
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[1.5, 2.0, -4.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 3];
+    graph.get_weights("a").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [0.5, 2.0, -2.0]);
+
+    graph.get_weights("b").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [-0.75, 0.0, 0.0]);
+
+    Ok(())
+}
+
+pub fn linear_comb_broadcast<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let a = builder.create_weights("a", Shape::new(2, 2)).unwrap();
+    let b = builder.create_weights("b", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::LinearCombination(1.0, a, 2.0, b), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    let out3 = builder.create_result_of_operation(Operation::Transpose(out2), true)?;
+    let out4 = builder.create_result_of_operation(Operation::Matmul(dot, false, out3, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out4), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("a").load_dense_from_slice(Some(2), &[1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 0.0, 0.0]).unwrap();
+    graph.get_weights_mut("b").load_dense_from_slice(None, &[10.0, 20.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 1.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert_eq!(err, 250.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[21.0, 42.0, 23.0, 44.0, 20.0, 40.0, 20.0, 40.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 8];
+    graph.get_weights("a").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [1.0; 8]);
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("b").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [8.0, 8.0]);
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    optimiser::{
+        adam::{AdamW, AdamWParams},
+        Optimiser,
+    },
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn embedding<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let table = builder.create_weights("table", Shape::new(2, 4)).unwrap();
+    let i = builder.create_sparse_input("i", Shape::new(4, 1), 2).unwrap();
+    let out = builder.create_result_of_operation(Operation::Embedding(table, i), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("table").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0]).unwrap();
+
+    unsafe {
+        graph.get_input_mut("i").load_sparse_from_slice(2, Some(2), &[1, -1, 3, 1]).unwrap();
+    }
+
+    let err = graph.forward()?;
+    assert_eq!(err, 59.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[3.0, 4.0, 0.0, 0.0, 7.0, 8.0, 3.0, 4.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 8];
+    graph.get_weights("table").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [0.0, 0.0, 4.0, 6.0, 0.0, 0.0, 1.0, 2.0]);
+
+    Ok(())
+}
+
+pub fn embedding_sparse_gradients<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let table = builder.create_weights("table", Shape::new(2, 4)).unwrap();
+    builder.use_sparse_gradients(table);
+    let i = builder.create_sparse_input("i", Shape::new(4, 1), 2).unwrap();
+    let out = builder.create_result_of_operation(Operation::Embedding(table, i), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("table").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0]).unwrap();
+
+    unsafe {
+        graph.get_input_mut("i").load_sparse_from_slice(2, Some(2), &[1, -1, 3, 1]).unwrap();
+    }
+
+    graph.forward()?;
+    graph.backward()?;
+
+    {
+        let table = graph.get_weights("table");
+        assert!(table.gradients.is_none());
+
+        let grads = table.sparse_gradients.as_ref().unwrap();
+        assert_eq!(grads.num_columns(), 2);
+
+        let mut buf = [0.0; 8];
+        grads.to_dense()?.write_to_slice(&mut buf).map_err(OperationError::from)?;
+        assert_eq!(buf, [0.0, 0.0, 4.0, 6.0, 0.0, 0.0, 1.0, 2.0]);
+    }
+
+    let params = AdamWParams { min_weight: -7.0, max_weight: 7.0, ..Default::default() };
+    let mut optimiser = Optimiser::<D, AdamW<D>>::new(graph, params).map_err(GraphError::DeviceError)?;
+    optimiser.update(1.0, 0.1)?;
+
+    // columns 0 and 2 were not looked up, so are neither decayed, updated nor clipped
+    let table = optimiser.graph.get_weights("table").get_dense_vals()?;
+    assert_approx_eq(&table, &[1.0, 2.0, 2.6808, 3.6798, 5.0, 6.0, 6.6768, 7.0]);
+
+    Ok(())
+}
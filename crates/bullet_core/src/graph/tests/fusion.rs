@@ -0,0 +1,75 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{
+        builder::GraphBuilder,
+        error::GraphError,
+        operation::{Activation, GraphBuilderError, NodeList, Operation},
+    },
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn fuse_affine_activate<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let b = builder.create_weights("b", Shape::new(1, 1)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let h = builder.create_result_of_operation(Operation::Affine(w, x, b), true)?;
+    let out = builder.create_result_of_operation(Operation::Activate(h, Activation::CReLU), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+
+    assert_eq!(builder.fuse_affine_activate(), 1);
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[0.25, -0.5]).unwrap();
+    graph.get_weights_mut("b").load_dense_from_slice(None, &[0.5]).unwrap();
+    graph.get_input_mut("x").load_dense_from_slice(Some(3), &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 1.0).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[0.75, 0.0, 0.25]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[2.0, 1.0]);
+
+    let mut buf = [0.0];
+    graph.get_weights("b").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[2.0]);
+
+    Ok(())
+}
+
+pub fn fuse_affine_activate_skipped<D: Device>(_device: D) -> Result<(), GraphBuilderError> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let b = builder.create_weights("b", Shape::new(1, 1)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+
+    // used twice
+    let h = builder.create_result_of_operation(Operation::Affine(w, x, b), true)?;
+    let a = builder.create_result_of_operation(Operation::Activate(h, Activation::ReLU), true)?;
+    let a = builder.create_result_of_operation(Operation::LinearCombination(1.0, a, 1.0, h), true)?;
+
+    // labelled
+    let h = builder.create_result_of_operation(Operation::Affine(w, x, b), true)?;
+    builder.set_label(h, "hidden");
+    let c = builder.create_result_of_operation(Operation::Activate(h, Activation::ReLU), true)?;
+
+    // cannot be computed from the output
+    let h = builder.create_result_of_operation(Operation::Affine(w, x, b), true)?;
+    let d = builder.create_result_of_operation(Operation::Activate(h, Activation::GELU), true)?;
+
+    let out = builder.create_result_of_operation(Operation::ConcatMany(NodeList::new(&[a, c, d]).unwrap()), true)?;
+    let out = builder.create_result_of_operation(Operation::ReduceSum(out), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+
+    assert_eq!(builder.fuse_affine_activate(), 0);
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{
+    device::Device,
+    graph::{
+        builder::GraphBuilder,
+        error::GraphError,
+        gradcheck::{gradcheck, randomise},
+        operation::{Activation, ConvSettings, Operation},
+    },
+    shape::Shape,
+};
+
+pub fn gradcheck_ops<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let settings =
+        ConvSettings { in_channels: 1, out_channels: 2, height: 2, width: 2, kernel_height: 3, kernel_width: 3 };
+
+    let mut builder = GraphBuilder::default();
+    let x = builder.create_dense_input("x", Shape::new(4, 1)).unwrap();
+    let f = builder.create_weights("f", settings.filters_shape()).unwrap();
+    let w = builder.create_weights("w", Shape::new(4, 8)).unwrap();
+    let b = builder.create_weights("b", Shape::new(4, 1)).unwrap();
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+
+    let conv = builder.create_result_of_operation(Operation::Conv2d(f, x, settings), true)?;
+    let conv = builder.create_result_of_operation(Operation::Activate(conv, Activation::SiLU), true)?;
+    let h = builder.create_result_of_operation(Operation::Affine(w, conv, b), true)?;
+    let q = builder.create_result_of_operation(Operation::Activate(h, Activation::GELU), true)?;
+    let k = builder.create_result_of_operation(Operation::Activate(h, Activation::Sigmoid), true)?;
+    let v = builder.create_result_of_operation(Operation::ElementwiseMul(q, k), true)?;
+    let att = builder.create_result_of_operation(Operation::Attention(q, k, v, None, 2), true)?;
+    let out = builder.create_result_of_operation(Operation::Matmul(dot, false, att, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[0.0; 8]).unwrap();
+    randomise(&mut graph, 0.5).unwrap();
+
+    let failures = gradcheck(&mut graph, 0.01, 0.05)?;
+    assert!(failures.is_empty(), "{failures:?}");
+
+    Ok(())
+}
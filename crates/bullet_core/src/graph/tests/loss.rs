@@ -0,0 +1,117 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn sigmoid_bce<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let t = builder.create_dense_input("t", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::SigmoidCrossEntropyLoss(w, t), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    let ln3 = 3f32.ln();
+    graph.get_weights_mut("w").load_dense_from_slice(Some(3), &[0.0, ln3, -ln3]).unwrap();
+    graph.get_input_mut("t").load_dense_from_slice(Some(3), &[1.0, 1.0, 0.5]).unwrap();
+
+    let err = graph.forward()?;
+    let expected = [2f32.ln(), (4.0f32 / 3.0).ln(), 0.5 * ln3 + (4.0f32 / 3.0).ln()];
+    assert!((err - expected.iter().sum::<f32>()).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &expected);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 3];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[-0.5, -0.25, -0.25]);
+
+    Ok(())
+}
+
+pub fn huber<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let t = builder.create_dense_input("t", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::HuberError(w, t, 1.0), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(3), &[0.5, 3.0, -2.0]).unwrap();
+    graph.get_input_mut("t").load_dense_from_slice(Some(3), &[0.0, 1.0, 0.5]).unwrap();
+
+    let err = graph.forward()?;
+    let expected = [0.125, 1.5, 2.0];
+    assert!((err - expected.iter().sum::<f32>()).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &expected);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 3];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[0.5, 1.0, -1.0]);
+
+    Ok(())
+}
+
+pub fn pairwise_ranking<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let t = builder.create_dense_input("t", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::PairwiseRankingLoss(w, t, 1.0), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(7), &[0.0, 0.5, 3.0, 1.0, 0.2, 0.0, 4.0]).unwrap();
+    graph.get_input_mut("t").load_dense_from_slice(Some(7), &[0.3, 0.1, 0.9, 0.2, 0.5, 0.5, 1.0]).unwrap();
+
+    let err = graph.forward()?;
+    let expected = [0.75, 0.75, 0.0, 0.0, 0.0, 0.0, 0.0];
+    assert!((err - expected.iter().sum::<f32>()).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &expected);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 7];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[-1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+    Ok(())
+}
+
+pub fn loss_components<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let t = builder.create_dense_input("t", Shape::new(1, 1)).unwrap();
+    let mse = builder.create_result_of_operation(Operation::PowerError(w, t, 2.0), true)?;
+    let huber = builder.create_result_of_operation(Operation::HuberError(w, t, 1.0), true)?;
+    let out = builder.create_result_of_operation(Operation::LinearCombination(0.5, mse, 2.0, huber), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    builder.add_loss_component("mse", mse);
+    builder.add_loss_component("huber", huber);
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(2), &[0.5, 3.0]).unwrap();
+    graph.get_input_mut("t").load_dense_from_slice(Some(2), &[0.0, 1.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - (0.5 * 4.25 + 2.0 * 1.625)).abs() < 0.001);
+
+    let components = graph.loss_components()?;
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0].0, "mse");
+    assert!((components[0].1 - 4.25).abs() < 0.001);
+    assert_eq!(components[1].0, "huber");
+    assert!((components[1].1 - 1.625).abs() < 0.001);
+
+    Ok(())
+}
@@ -67,3 +67,36 @@ pub fn matmul2<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
 
     Ok(())
 }
+
+pub fn matmul_batched_single<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w1 = builder.create_weights("w1", Shape::new(2, 2)).unwrap();
+    let w2 = builder.create_weights("w2", Shape::new(2, 1)).unwrap();
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Matmul(w1, false, w2, false), true)?;
+    let err = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(err), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w1").load_dense_from_slice(Some(2), &[1.0, 2.0, 3.0, 4.0, 0.0, 1.0, -1.0, 2.0]).unwrap();
+    graph.get_weights_mut("w2").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+    graph.get_input_mut("dot").load_from_slice(None, &[1.0; 2]).unwrap();
+
+    let err = graph.forward()?;
+    assert_eq!(err, 20.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[7.0, 10.0, -2.0, 5.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 8];
+    graph.get_weights("w1").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [1.0, 1.0, 2.0, 2.0, 1.0, 1.0, 2.0, 2.0]);
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("w2").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [4.0, 8.0]);
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn shared_gradient_buffers<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 1)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(1, 1)).unwrap();
+    let mut node = builder.create_result_of_operation(Operation::LinearCombination(1.0, w, 1.0, x), true)?;
+    let mut intermediate = Vec::new();
+
+    for _ in 0..6 {
+        node = builder.create_result_of_operation(Operation::Abs(node), true)?;
+        intermediate.push(node);
+    }
+
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(node), true)?;
+    let mut graph = builder.build(device)?;
+
+    // a chain only ever needs the gradients of two consecutive nodes at once
+    assert_eq!(graph.memory.num_buffers(), 2);
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[0.5]).unwrap();
+
+    for (inputs, loss, grad) in [(vec![1.0, 2.0], 4.0, 2.0), (vec![-4.0, 1.0, 1.0], 6.5, 1.0)] {
+        graph.get_input_mut("x").load_dense_from_slice(Some(inputs.len()), &inputs).unwrap();
+
+        let err = graph.forward()?;
+        assert!((err - loss).abs() < 0.001);
+
+        graph.zero_grads().map_err(OperationError::from)?;
+        graph.backward()?;
+
+        for &node in &intermediate {
+            assert!(graph.get_node(node).gradients.is_none());
+        }
+
+        let mut buf = [0.0];
+        graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+        assert_approx_eq(&buf, &[grad]);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn layer_norm<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let x = builder.create_weights("x", Shape::new(3, 1)).unwrap();
+    let scale = builder.create_weights("scale", Shape::new(3, 1)).unwrap();
+    let shift = builder.create_weights("shift", Shape::new(3, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::LayerNorm(x, scale, shift), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 3)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("x").load_dense_from_slice(Some(2), &[1.0, 2.0, 3.0, 0.0, 0.0, 3.0]).unwrap();
+    graph.get_weights_mut("scale").load_dense_from_slice(None, &[1.0, 2.0, 3.0]).unwrap();
+    graph.get_weights_mut("shift").load_dense_from_slice(None, &[0.0, 1.0, 0.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0; 3]).unwrap();
+
+    graph.forward()?;
+
+    let a = 1.5f32.sqrt();
+    let b = 0.5f32.sqrt();
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[-a, 1.0, 3.0 * a, -b, 1.0 - 2.0 * b, 6.0 * b]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 6];
+    graph.get_weights("x").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[0.0, 0.0, 0.0, -0.5 * b, 0.5 * b, 0.0]);
+
+    let mut buf = [0.0; 3];
+    graph.get_weights("scale").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[-a - b, -b, a + 2.0 * b]);
+
+    let mut buf = [0.0; 3];
+    graph.get_weights("shift").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[2.0; 3]);
+
+    Ok(())
+}
+
+pub fn batch_norm<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let x = builder.create_weights("x", Shape::new(2, 1)).unwrap();
+    let scale = builder.create_weights("scale", Shape::new(2, 1)).unwrap();
+    let shift = builder.create_weights("shift", Shape::new(2, 1)).unwrap();
+    let mean = builder.create_non_trainable_weights("mean", Shape::new(2, 1)).unwrap();
+    let var = builder.create_non_trainable_weights("var", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::BatchNorm(x, scale, shift, mean, var, 0.5), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("x").load_dense_from_slice(Some(2), &[1.0, 0.0, 3.0, 4.0]).unwrap();
+    graph.get_weights_mut("scale").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+    graph.get_weights_mut("shift").load_dense_from_slice(None, &[0.0, 1.0]).unwrap();
+    graph.get_weights_mut("var").load_dense_from_slice(None, &[1.0, 1.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+
+    graph.forward()?;
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[-1.0, -1.0, 1.0, 3.0]);
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("mean").values.dense()?.write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[1.0, 1.0]);
+
+    graph.get_weights("var").values.dense()?.write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[1.0, 2.5]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("x").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[0.0; 4]);
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("scale").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[0.0, 0.0]);
+
+    graph.get_weights("shift").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[2.0, 4.0]);
+
+    assert!(graph.get_weights("mean").gradients.is_none());
+
+    graph.set_training(false);
+    graph.forward()?;
+
+    let rstd = 1.0 / 2.5f32.sqrt();
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[0.0, 1.0 - 2.0 * rstd, 2.0, 1.0 + 6.0 * rstd]);
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("mean").values.dense()?.write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[1.0, 1.0]);
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{
+        builder::GraphBuilder,
+        error::GraphError,
+        operation::{Activation, Operation},
+    },
+    optimiser::{
+        bucketed::BucketedLr,
+        clip::{WeightClipping, WeightClippingParams},
+        sgd::{Sgd, SgdParams},
+        utils::Placement,
+        Optimiser,
+    },
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn bucketed_lr<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(4, 1)).unwrap();
+    let b = builder.create_weights("b", Shape::new(1, 1)).unwrap();
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Matmul(dot, false, w, false), true)?;
+    let out = builder.create_result_of_operation(Operation::LinearCombination(1.0, out, 1.0, b), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[0.5, 0.5, -0.85, -0.85]).unwrap();
+    graph.get_weights_mut("b").load_dense_from_slice(None, &[0.0]).unwrap();
+    graph.get_input_mut("dot").load_from_slice(None, &[1.0; 4]).unwrap();
+
+    let params = WeightClippingParams {
+        inner: SgdParams { momentum: 0.0, nesterov: false },
+        placement: Placement::After,
+        min: -1.0,
+        max: 1.0,
+    };
+
+    let mut optimiser =
+        Optimiser::<D, BucketedLr<D, WeightClipping<Sgd<D>>>>::new(graph, params).map_err(GraphError::DeviceError)?;
+
+    // the bias has a single element, so would not split into two buckets
+    optimiser.state.get_mut("w").unwrap().set_multipliers(vec![1.0, 3.0]);
+
+    optimiser.graph.forward()?;
+    optimiser.graph.backward()?;
+    optimiser.update(1.0, 0.1)?;
+
+    // the second bucket moves three times as far, and is then clipped
+    let w = optimiser.graph.get_weights("w").get_dense_vals()?;
+    assert_approx_eq(&w, &[0.4, 0.4, -1.0, -1.0]);
+
+    let b = optimiser.graph.get_weights("b").get_dense_vals()?;
+    assert_approx_eq(&b, &[-0.1]);
+
+    Ok(())
+}
+
+pub fn gradient_accumulation<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(4, 1)).unwrap();
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Matmul(dot, false, w, false), true)?;
+    let out = builder.create_result_of_operation(Operation::Activate(out, Activation::Square), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, -1.0, 0.5, 2.0]).unwrap();
+
+    let first = [1.0, 2.0, 3.0, 4.0];
+    let second = [-1.0, 0.0, 2.0, 1.0];
+
+    graph.get_input_mut("dot").load_from_slice(Some(2), &[first, second].concat()).unwrap();
+    graph.zero_grads().map_err(GraphError::DeviceError)?;
+    graph.forward()?;
+    graph.backward()?;
+    let mut expected = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut expected).map_err(OperationError::from)?;
+
+    // the gradients of the weights are summed over the micro-batches,
+    // while those of every other node are cleared between them
+    graph.get_input_mut("dot").load_from_slice(Some(1), &first).unwrap();
+    graph.zero_grads().map_err(GraphError::DeviceError)?;
+    graph.forward()?;
+    graph.backward()?;
+
+    graph.get_input_mut("dot").load_from_slice(Some(1), &second).unwrap();
+    graph.zero_grads_except_weights().map_err(GraphError::DeviceError)?;
+    graph.forward()?;
+    graph.backward()?;
+
+    let mut accumulated = [0.0; 4];
+    graph
+        .get_weights("w")
+        .gradients
+        .as_ref()
+        .unwrap()
+        .write_to_slice(&mut accumulated)
+        .map_err(OperationError::from)?;
+    assert_approx_eq(&accumulated, &expected);
+
+    Ok(())
+}
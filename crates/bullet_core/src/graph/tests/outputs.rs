@@ -0,0 +1,128 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn multiple_outputs<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    let aux = builder.create_result_of_operation(Operation::LinearCombination(2.0, out, 1.0, out), true)?;
+    let loss = builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    builder.set_loss(loss);
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[1.0, 2.0, 4.0, 3.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 0.0).abs() < 0.001);
+
+    let output = graph.get_node(aux).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[-3.0, 3.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[5.0, 5.0]);
+
+    Ok(())
+}
+
+pub fn dot_export<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    builder.set_label(out, "l0");
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let graph = builder.build(device)?;
+
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph {\n"));
+    assert!(dot.contains("n0 [label=\"w\\nweights\\n1 x 2\", shape=ellipse];"));
+    assert!(dot.contains("n1 [label=\"x\\ninput\\n2 x 1\", shape=box];"));
+    assert!(dot.contains("n2 [label=\"l0\\nMatmul\\n1 x 1\", shape=box, style=rounded];"));
+    assert!(dot.contains("n3 [label=\"node 3\\nReduceAcrossBatch\\n1 x 1\", shape=box, style=rounded, peripheries=2];"));
+    assert!(dot.contains("n0 -> n2;\n    n1 -> n2;"));
+    assert!(dot.contains("n2 -> n3;"));
+
+    Ok(())
+}
+
+pub fn named_nodes<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let hidden = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    builder.set_label(hidden, "hidden");
+    let out = builder.create_result_of_operation(Operation::Abs(hidden), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    assert!(graph.get_named("w").is_none());
+    assert!(graph.get_named("output").is_none());
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[1.0, 2.0, 4.0, 3.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 2.0).abs() < 0.001);
+
+    let hidden = graph.get_named("hidden").unwrap().get_dense_vals().unwrap();
+    assert_approx_eq(&hidden, &[-1.0, 1.0]);
+
+    Ok(())
+}
+
+pub fn activation_stats<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 2)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(2, 1)).unwrap();
+    let hidden = builder.create_result_of_operation(Operation::Matmul(w, false, x, false), true)?;
+    builder.set_label(hidden, "hidden");
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(hidden), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+    graph.forward()?;
+    assert!(graph.take_activation_stats().is_empty());
+
+    graph.record_activation_stats(Some(1));
+
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[1.0, 2.0, 4.0, 3.0]).unwrap();
+    graph.forward()?;
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[5.0, 2.0, 3.0, 0.0]).unwrap();
+    graph.forward()?;
+
+    let stats = graph.take_activation_stats();
+    assert_eq!(stats.len(), 2);
+
+    let (label, hidden) = &stats[0];
+    assert_eq!(label, "hidden");
+    assert_eq!(hidden.count, 4);
+    assert_approx_eq(&[hidden.min, hidden.max, hidden.mean(), hidden.std()], &[-1.0, 3.0, 1.5, 1.6583]);
+
+    assert!(graph.take_activation_stats().is_empty());
+
+    // only the first and third forward passes are sampled
+    graph.record_activation_stats(Some(2));
+
+    for _ in 0..3 {
+        graph.forward()?;
+    }
+
+    let stats = graph.take_activation_stats();
+    assert_eq!(stats[0].1.count, 4);
+
+    graph.record_activation_stats(None);
+    graph.forward()?;
+    assert!(graph.take_activation_stats().is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{
+        builder::GraphBuilder,
+        error::GraphError,
+        operation::{Operation, PoolSettings},
+    },
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn avg_pool<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let settings = PoolSettings { channels: 2, height: 2, width: 4, pool_height: 2, pool_width: 2 };
+
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(16, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::AvgPool(w, settings), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph
+        .get_weights_mut("w")
+        .load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 0.0, 0.0, 4.0, 4.0, 0.0, 0.0, 4.0, 4.0])
+        .unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 30.5).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[3.5, 5.5, 0.0, 4.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 16];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    let (a, b, c, d) = (0.25, 0.5, 0.75, 1.0);
+    assert_approx_eq(&buf, &[a, a, b, b, a, a, b, b, c, c, d, d, c, c, d, d]);
+
+    Ok(())
+}
+
+pub fn max_pool<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let settings = PoolSettings { channels: 2, height: 2, width: 4, pool_height: 2, pool_width: 2 };
+
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(16, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::MaxPool(w, settings), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 4)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph
+        .get_weights_mut("w")
+        .load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 0.0, 0.0, 4.0, 4.0, 0.0, 0.0, 4.0, 4.0])
+        .unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 38.0).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[6.0, 8.0, 0.0, 4.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 16];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    let mut expected = [0.0; 16];
+    expected[5] = 1.0;
+    expected[7] = 2.0;
+    expected[8] = 3.0;
+    expected[10] = 4.0;
+    assert_approx_eq(&buf, &expected);
+
+    Ok(())
+}
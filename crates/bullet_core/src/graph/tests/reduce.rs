@@ -0,0 +1,48 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn reduce_sum<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    reduce(device, false)
+}
+
+pub fn reduce_mean<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    reduce(device, true)
+}
+
+fn reduce<D: Device>(device: D, mean: bool) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(3, 2)).unwrap();
+    let op = if mean { Operation::ReduceMean(w) } else { Operation::ReduceSum(w) };
+    let out = builder.create_result_of_operation(op, true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let reshaped = out.reshape(Shape::new(2, 1)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, reshaped, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph
+        .get_weights_mut("w")
+        .load_dense_from_slice(Some(2), &[1.0, 2.0, 3.0, -1.0, 0.0, 4.0, 0.0, 0.0, 3.0, 1.0, 1.0, 1.0])
+        .unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+
+    graph.forward()?;
+
+    let scale = if mean { 1.0 / 3.0 } else { 1.0 };
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[6.0 * scale, 3.0 * scale, 3.0 * scale, 3.0 * scale]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 12];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    let (a, b) = (scale, 2.0 * scale);
+    assert_approx_eq(&buf, &[a, a, a, b, b, b, a, a, a, b, b, b]);
+
+    Ok(())
+}
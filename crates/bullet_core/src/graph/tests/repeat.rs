@@ -0,0 +1,62 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+pub fn repeat<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Repeat(w, 3), true)?;
+    let sum = builder.create_result_of_operation(Operation::ReduceSum(out), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(3, 1)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(sum, false, dot, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(2), &[1.0, 2.0, 3.0, 4.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert_eq!(err, 60.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[1.0, 2.0, 1.0, 2.0, 1.0, 2.0, 3.0, 4.0, 3.0, 4.0, 3.0, 4.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [6.0; 4]);
+
+    Ok(())
+}
+
+pub fn repeat_across_batch<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(3, 1)).unwrap();
+    let x = builder.create_dense_input("x", Shape::new(1, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::RepeatAcrossBatch(w, x), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 3)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, 2.0, 3.0]).unwrap();
+    graph.get_input_mut("x").load_dense_from_slice(Some(2), &[0.0, 0.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert_eq!(err, 28.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 3];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [2.0, 4.0, 6.0]);
+
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+pub fn slice_columns<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 3)).unwrap();
+    let out = builder.create_result_of_operation(Operation::SliceColumns(w, 1, 3), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let dot2 = builder.create_dense_input("dot2", Shape::new(2, 1)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    let out3 = builder.create_result_of_operation(Operation::Matmul(out2, false, dot2, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out3), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0]).unwrap();
+    graph.get_input_mut("dot2").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert_eq!(err, -6.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[3.0, 4.0, 5.0, 6.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 6];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [0.0, 0.0, 1.0, 2.0, -1.0, -2.0]);
+
+    Ok(())
+}
+
+pub fn transpose<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 3)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Transpose(w), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 3)).unwrap();
+    let dot2 = builder.create_dense_input("dot2", Shape::new(2, 1)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    let out3 = builder.create_result_of_operation(Operation::Matmul(out2, false, dot2, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out3), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 2.0, 3.0]).unwrap();
+    graph.get_input_mut("dot2").load_dense_from_slice(None, &[1.0, -1.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert_eq!(err, -6.0);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[1.0, 3.0, 5.0, 2.0, 4.0, 6.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 6];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [1.0, -1.0, 2.0, -2.0, 3.0, -3.0]);
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use crate::{
+    device::{Device, OperationError},
+    graph::{builder::GraphBuilder, error::GraphError, operation::Operation},
+    shape::Shape,
+};
+
+use super::assert_approx_eq;
+
+pub fn softmax<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::Softmax(w), true)?;
+    let dot = builder.create_dense_input("dot", Shape::new(1, 2)).unwrap();
+    let out2 = builder.create_result_of_operation(Operation::Matmul(dot, false, out, false), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out2), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(2), &[0.0, 3f32.ln(), 1.0, 1.0]).unwrap();
+    graph.get_input_mut("dot").load_dense_from_slice(None, &[1.0, 0.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 1.25).abs() < 0.001);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_approx_eq(&output, &[0.25, 0.75, 0.5, 0.5]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[0.1875, -0.1875, 0.25, -0.25]);
+
+    Ok(())
+}
+
+pub fn softmax_crossentropy_temperature<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 1)).unwrap();
+    let t = builder.create_dense_input("t", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::SoftmaxCrossEntropyLoss(w, t, 2.0, 0.0), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[0.0, 2.0 * 3f32.ln()]).unwrap();
+    graph.get_input_mut("t").load_dense_from_slice(None, &[1.0, 0.0]).unwrap();
+
+    let err = graph.forward()?;
+    assert!((err - 4f32.ln()).abs() < 0.001);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[-0.375, 0.375]);
+
+    graph.set_softmax_temperature(1.0);
+
+    let err = graph.forward()?;
+    assert!((err - 10f32.ln()).abs() < 0.001);
+
+    Ok(())
+}
+
+pub fn softmax_crossentropy_smoothing<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 1)).unwrap();
+    let t = builder.create_dense_input("t", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::SoftmaxCrossEntropyLoss(w, t, 1.0, 0.2), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[0.0, 3f32.ln()]).unwrap();
+    graph.get_input_mut("t").load_dense_from_slice(None, &[1.0, 0.0]).unwrap();
+
+    // the targets are smoothed to [0.9, 0.1]
+    let err = graph.forward()?;
+    assert!((err - 1.2764).abs() < 0.001);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 2];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[-0.65, 0.65]);
+
+    Ok(())
+}
+
+pub fn kl_divergence<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(2, 1)).unwrap();
+    let t = builder.create_dense_input("t", Shape::new(2, 1)).unwrap();
+    let out = builder.create_result_of_operation(Operation::KLDivergenceLoss(w, t), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(Some(2), &[0.0, 3f32.ln(), 1.0, 1.0]).unwrap();
+    graph.get_input_mut("t").load_dense_from_slice(Some(2), &[0.5, 0.5, 0.5, 0.5]).unwrap();
+
+    // the second prediction matches its target exactly
+    let err = graph.forward()?;
+    assert!((err - 0.1438).abs() < 0.001);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 4];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_approx_eq(&buf, &[-0.25, 0.25, 0.0, 0.0]);
+
+    Ok(())
+}
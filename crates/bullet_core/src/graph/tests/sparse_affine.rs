@@ -98,3 +98,38 @@ pub fn check_not_batched<D: Device>(_device: D) -> Result<(), GraphBuilderError>
 
     Ok(())
 }
+
+pub fn sparse_affine_outer<D: Device>(device: D) -> Result<(), GraphError<D::DeviceError>> {
+    let mut builder = GraphBuilder::default();
+    let w = builder.create_weights("w", Shape::new(1, 6)).unwrap();
+    let a = builder.create_sparse_input("a", Shape::new(2, 1), 2).unwrap();
+    let b = builder.create_sparse_input("b", Shape::new(3, 1), 2).unwrap();
+    let outer = builder.create_result_of_operation(Operation::SparseOuter(a, b), false)?;
+    let out = builder.create_result_of_operation(Operation::SparseAffine(w, outer, None), true)?;
+    builder.create_result_of_operation(Operation::ReduceAcrossBatch(out), true)?;
+    let mut graph = builder.build(device)?;
+
+    graph.get_weights_mut("w").load_dense_from_slice(None, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0]).unwrap();
+
+    unsafe {
+        graph.get_input_mut("a").load_sparse_from_slice(2, Some(2), &[0, 1, 1, -1]).unwrap();
+        graph.get_input_mut("b").load_sparse_from_slice(2, Some(2), &[2, -1, 0, -1]).unwrap();
+    }
+
+    let err = graph.forward()?;
+    assert_eq!(err, 13.0);
+
+    let indices = graph.get_node(outer).get_sparse_vals().unwrap();
+    assert_eq!(&indices, &[2, 5, -1, -1, 3, -1, -1, -1]);
+
+    let output = graph.get_node(out).get_dense_vals().unwrap();
+    assert_eq!(&output, &[9.0, 4.0]);
+
+    graph.backward()?;
+
+    let mut buf = [0.0; 6];
+    graph.get_weights("w").gradients.as_ref().unwrap().write_to_slice(&mut buf).map_err(OperationError::from)?;
+    assert_eq!(buf, [0.0, 0.0, 1.0, 1.0, 0.0, 1.0]);
+
+    Ok(())
+}
@@ -1,8 +1,14 @@
+pub mod adabelief;
 pub mod adam;
+pub mod bucketed;
 pub mod clip;
 pub mod decay;
+pub mod ema;
+pub mod lamb;
 pub mod radam;
 pub mod ranger;
+pub mod sgd;
+pub mod swa;
 pub mod utils;
 
 use std::{collections::HashMap, fmt::Debug, marker::PhantomData, sync::Arc};
@@ -10,9 +16,11 @@ use std::{collections::HashMap, fmt::Debug, marker::PhantomData, sync::Arc};
 use crate::{
     device::{Device, OperationError},
     graph::Graph,
-    tensor::DenseMatrix,
+    tensor::{DenseMatrix, SparseGradients},
 };
 
+use utils::CheckpointCompression;
+
 pub trait OptimiserState<D: Device>: Sized {
     type Params: Clone + Debug + Default;
 
@@ -26,6 +34,25 @@ pub trait OptimiserState<D: Device>: Sized {
         learning_rate: f32,
     ) -> Result<(), OperationError<D::DeviceError>>;
 
+    /// As `update`, for weights built with `GraphBuilder::use_sparse_gradients`. Optimisers that
+    /// support it only update the columns that have gradients, otherwise they are made dense first.
+    fn update_sparse(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &SparseGradients<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        let mut grads = grads.to_dense()?;
+        self.update(weights, &mut grads, gradient_factor, learning_rate)
+    }
+
+    /// Reapplies any clipping this optimiser does to the weights after an update,
+    /// for wrappers that modify the weights after the inner optimiser has run.
+    fn clip_weights(&self, _weights: &mut DenseMatrix<D>) -> Result<(), OperationError<D::DeviceError>> {
+        Ok(())
+    }
+
     fn reset(&mut self) -> Result<(), D::DeviceError>;
 
     fn load_from_checkpoint(
@@ -34,7 +61,11 @@ pub trait OptimiserState<D: Device>: Sized {
         old_format: bool,
     ) -> Result<(), D::DeviceError>;
 
-    fn write_to_checkpoint(map: &HashMap<String, &Self>, path: &str) -> Result<(), D::DeviceError>;
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError>;
 
     fn set_params(&mut self, params: Self::Params);
 }
@@ -42,6 +73,12 @@ pub trait OptimiserState<D: Device>: Sized {
 pub struct Optimiser<D: Device, S: OptimiserState<D>> {
     pub graph: Graph<D>,
     pub state: HashMap<String, S>,
+    lr_multipliers: HashMap<String, f32>,
+    max_grad_norm: Option<f32>,
+    ema: Option<ema::WeightEma<D>>,
+    swa: Option<swa::WeightAverage<D>>,
+    checkpoint_compression: CheckpointCompression,
+    norm: DenseMatrix<D>,
 }
 
 impl<D: Device, S: OptimiserState<D>> Optimiser<D, S> {
@@ -61,19 +98,114 @@ impl<D: Device, S: OptimiserState<D>> Optimiser<D, S> {
             assert!(old.is_none());
         }
 
-        Ok(Self { graph, state })
+        let norm = DenseMatrix::zeroed(graph.device(), 1)?;
+
+        Ok(Self {
+            graph,
+            state,
+            lr_multipliers: HashMap::new(),
+            max_grad_norm: None,
+            ema: None,
+            swa: None,
+            checkpoint_compression: CheckpointCompression::None,
+            norm,
+        })
+    }
+
+    /// Scales the learning rate applied to the weights with the given id by `multiplier`,
+    /// on top of any learning rate schedule, e.g. to train some weights more slowly.
+    pub fn set_lr_multiplier(&mut self, id: &str, multiplier: f32) {
+        assert!(self.state.contains_key(id), "Weights with id '{id}' do not exist!");
+        self.lr_multipliers.insert(id.to_string(), multiplier);
+    }
+
+    /// Maintains an exponential moving average of the weights, starting from their current
+    /// values, which is updated after every step and saved in checkpoints.
+    pub fn enable_ema(&mut self, decay: f32) -> Result<(), D::DeviceError> {
+        self.ema = Some(ema::WeightEma::new(&self.graph, decay)?);
+        Ok(())
+    }
+
+    pub fn ema(&self) -> Option<&ema::WeightEma<D>> {
+        self.ema.as_ref()
+    }
+
+    /// Adds the current weights to the stochastic weight average, which is
+    /// started on the first call and saved in checkpoints from then on.
+    pub fn add_to_swa(&mut self) -> Result<(), OperationError<D::DeviceError>> {
+        if self.swa.is_none() {
+            self.swa = Some(swa::WeightAverage::new(&self.graph)?);
+        }
+
+        self.swa.as_mut().unwrap().add(&self.graph)
+    }
+
+    pub fn swa(&self) -> Option<&swa::WeightAverage<D>> {
+        self.swa.as_ref()
+    }
+
+    /// Compression applied to the weight and optimiser state files written by `write_to_checkpoint`.
+    pub fn set_checkpoint_compression(&mut self, compression: CheckpointCompression) {
+        self.checkpoint_compression = compression;
+    }
+
+    /// If set, the gradients of all weights are scaled down together before each
+    /// update so that their combined L2 norm is at most `max_norm`.
+    pub fn set_gradient_clipping(&mut self, max_norm: Option<f32>) {
+        if let Some(max_norm) = max_norm {
+            assert!(max_norm > 0.0, "Maximum gradient norm must be positive!");
+        }
+
+        self.max_grad_norm = max_norm;
+    }
+
+    /// The combined L2 norm of the gradients of all weights, after scaling by `gradient_factor`.
+    pub fn gradient_norm(&mut self, gradient_factor: f32) -> Result<f32, OperationError<D::DeviceError>> {
+        let mut sum_sq = 0.0;
+
+        for id in &self.graph.weight_ids() {
+            let weights = self.graph.get_weights(id);
+
+            if let Some(grads) = weights.gradients.as_ref() {
+                sum_sq += utils::l2_norm(grads, &mut self.norm)?.powi(2);
+            }
+
+            if let Some(grads) = weights.sparse_gradients.as_ref() {
+                sum_sq += utils::l2_norm(grads.values(), &mut self.norm)?.powi(2);
+            }
+        }
+
+        Ok(gradient_factor.abs() * sum_sq.sqrt())
     }
 
     pub fn update(&mut self, gradient_factor: f32, learning_rate: f32) -> Result<(), OperationError<D::DeviceError>> {
+        let mut gradient_factor = gradient_factor;
+
+        if let Some(max_norm) = self.max_grad_norm {
+            let norm = self.gradient_norm(gradient_factor)?;
+
+            if norm > max_norm {
+                gradient_factor *= max_norm / norm;
+            }
+        }
+
         for id in &self.graph.weight_ids() {
             let weights = self.graph.get_weights_mut(id);
             let single = self.state.get_mut(id).unwrap();
 
+            let learning_rate = learning_rate * self.lr_multipliers.get(id).copied().unwrap_or(1.0);
+
             if let Some(grads) = weights.gradients.as_mut() {
                 single.update(weights.values.dense_mut()?, grads, gradient_factor, learning_rate)?;
+            } else if let Some(grads) = weights.sparse_gradients.as_ref() {
+                single.update_sparse(weights.values.dense_mut()?, grads, gradient_factor, learning_rate)?;
             }
         }
 
+        if let Some(ema) = self.ema.as_mut() {
+            ema.update(&self.graph)?;
+        }
+
         Ok(())
     }
 
@@ -96,9 +228,21 @@ impl<D: Device, S: OptimiserState<D>> Optimiser<D, S> {
     }
 
     pub fn write_to_checkpoint(&self, path: &str) -> Result<(), D::DeviceError> {
-        utils::write_graph_weights_to_file(&self.graph, &format!("{path}/weights.bin"));
+        let compression = self.checkpoint_compression;
+
+        utils::write_graph_weights_to_file(&self.graph, &format!("{path}/weights.bin"), compression);
+        std::fs::write(format!("{path}/graph.json"), self.graph.description()).unwrap();
+
+        if let Some(ema) = &self.ema {
+            ema.write_to_file(&format!("{path}/ema_weights.bin"), compression)?;
+        }
+
+        if let Some(swa) = &self.swa {
+            swa.write_to_checkpoint(path, compression)?;
+        }
+
         let map = self.state.iter().map(|(id, single)| (id.clone(), single)).collect();
-        S::write_to_checkpoint(&map, path)
+        S::write_to_checkpoint(&map, path, compression)
     }
 
     pub fn load_from_checkpoint(&mut self, path: &str) -> Result<(), D::DeviceError> {
@@ -123,6 +267,23 @@ impl<D: Device, S: OptimiserState<D>> Optimiser<D, S> {
 
     fn load_from_checkpoint_(&mut self, path: &str, old_format: bool) -> Result<(), D::DeviceError> {
         self.load_weights_from_file_(&format!("{path}/weights.bin"), old_format)?;
+
+        if let Some(ema) = self.ema.as_mut() {
+            let ema_path = format!("{path}/ema_weights.bin");
+
+            if std::path::Path::new(&ema_path).exists() {
+                ema.load_from_file(&ema_path, old_format)?;
+            } else {
+                *ema = ema::WeightEma::new(&self.graph, ema.decay())?;
+            }
+        }
+
+        if std::path::Path::new(&format!("{path}/swa_weights.bin")).exists() {
+            let mut swa = swa::WeightAverage::new(&self.graph)?;
+            swa.load_from_checkpoint(path, old_format)?;
+            self.swa = Some(swa);
+        }
+
         let mut map = self.state.iter_mut().map(|(id, single)| (id.clone(), single)).collect();
         S::load_from_checkpoint(&mut map, path, old_format)
     }
@@ -155,6 +316,20 @@ where
         self.optimiser.update(weights, grads, gradient_factor, learning_rate)
     }
 
+    fn update_sparse(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &SparseGradients<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        self.optimiser.update_sparse(weights, grads, gradient_factor, learning_rate)
+    }
+
+    fn clip_weights(&self, weights: &mut DenseMatrix<D>) -> Result<(), OperationError<D::DeviceError>> {
+        self.optimiser.clip_weights(weights)
+    }
+
     fn reset(&mut self) -> Result<(), D::DeviceError> {
         self.optimiser.reset()
     }
@@ -172,8 +347,12 @@ where
         O::load_from_checkpoint(&mut map, path, old_format)
     }
 
-    fn write_to_checkpoint(map: &HashMap<String, &Self>, path: &str) -> Result<(), D::DeviceError> {
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
         let map = map.iter().map(|(id, single)| (id.clone(), &single.optimiser)).collect();
-        O::write_to_checkpoint(&map, path)
+        O::write_to_checkpoint(&map, path, compression)
     }
 }
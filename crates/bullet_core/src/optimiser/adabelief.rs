@@ -0,0 +1,147 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    device::{Device, OperationError},
+    tensor::DenseMatrix,
+};
+
+use super::{
+    clip::{WeightClipping, WeightClippingParams},
+    decay::{WeightDecay, WeightDecayParams},
+    utils::{self, CheckpointCompression, Placement},
+    OptimiserState, WrapOptimiser,
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct AdaBeliefParams {
+    pub beta1: f32,
+    pub beta2: f32,
+}
+
+impl Default for AdaBeliefParams {
+    fn default() -> Self {
+        Self { beta1: 0.9, beta2: 0.999 }
+    }
+}
+
+/// Adam with the step scaled by the "belief" in the current gradient, i.e. how far it
+/// is from the momentum, rather than by its magnitude.
+pub struct AdaBelief<D: Device> {
+    momentum: DenseMatrix<D>,
+    belief: DenseMatrix<D>,
+    params: AdaBeliefParams,
+}
+
+impl<D: Device> OptimiserState<D> for AdaBelief<D> {
+    type Params = AdaBeliefParams;
+
+    fn new(device: Arc<D>, size: usize, default_params: Self::Params) -> Result<Self, D::DeviceError> {
+        Ok(Self {
+            momentum: DenseMatrix::zeroed(device.clone(), size)?,
+            belief: DenseMatrix::zeroed(device, size)?,
+            params: default_params,
+        })
+    }
+
+    fn update(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &mut DenseMatrix<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        assert!(weights.batch_size().is_none());
+        assert!(self.momentum.batch_size().is_none());
+        assert!(self.belief.batch_size().is_none());
+        assert_eq!(weights.size(), self.momentum.size());
+        assert_eq!(weights.size(), self.belief.size());
+
+        D::adabelief(
+            weights.size(),
+            &mut weights.buf,
+            &grads.buf,
+            &mut self.momentum.buf,
+            &mut self.belief.buf,
+            self.params.beta1,
+            self.params.beta2,
+            gradient_factor,
+            learning_rate,
+        )
+    }
+
+    fn reset(&mut self) -> Result<(), D::DeviceError> {
+        self.momentum.set_zero()?;
+        self.belief.set_zero()
+    }
+
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
+        let momentum: Vec<_> = map.iter().map(|(id, single)| (id, &single.momentum)).collect();
+        let belief: Vec<_> = map.iter().map(|(id, single)| (id, &single.belief)).collect();
+        utils::write_weights_to_file(&momentum, &format!("{path}/momentum.bin"), compression)?;
+        utils::write_weights_to_file(&belief, &format!("{path}/belief.bin"), compression)
+    }
+
+    fn load_from_checkpoint(
+        map: &mut HashMap<String, &mut Self>,
+        path: &str,
+        old_format: bool,
+    ) -> Result<(), D::DeviceError> {
+        let paths = [format!("{path}/momentum.bin"), format!("{path}/belief.bin")];
+        let mut momentum = utils::load_weights_from_file(&paths[0], old_format);
+        let mut belief = utils::load_weights_from_file(&paths[1], old_format);
+
+        momentum.sort_by_key(|(id, _)| id.clone());
+        belief.sort_by_key(|(id, _)| id.clone());
+
+        for ((id1, mom), (id2, bel)) in momentum.iter().zip(belief.iter()) {
+            assert_eq!(id1, id2);
+
+            let single = map.get_mut(id1).unwrap();
+            single.momentum.load_from_slice(None, mom)?;
+            single.belief.load_from_slice(None, bel)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_params(&mut self, params: Self::Params) {
+        self.params = params;
+    }
+}
+
+type AdaBeliefWClip<D> = WeightClipping<WeightDecay<AdaBelief<D>>>;
+pub type AdaBeliefW<D> = WrapOptimiser<AdaBeliefWClip<D>, AdaBeliefWParams>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct AdaBeliefWParams {
+    pub decay: f32,
+    pub beta1: f32,
+    pub beta2: f32,
+    pub min_weight: f32,
+    pub max_weight: f32,
+}
+
+impl Default for AdaBeliefWParams {
+    fn default() -> Self {
+        Self { decay: 0.01, beta1: 0.9, beta2: 0.999, min_weight: -1.98, max_weight: 1.98 }
+    }
+}
+
+impl From<AdaBeliefWParams> for WeightClippingParams<WeightDecayParams<AdaBeliefParams>> {
+    fn from(value: AdaBeliefWParams) -> Self {
+        WeightClippingParams {
+            inner: WeightDecayParams {
+                inner: AdaBeliefParams { beta1: value.beta1, beta2: value.beta2 },
+                decay: value.decay,
+                placement: Placement::Before,
+            },
+            min: value.min_weight,
+            max: value.max_weight,
+            placement: Placement::After,
+        }
+    }
+}
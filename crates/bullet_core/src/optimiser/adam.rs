@@ -2,13 +2,13 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     device::{Device, OperationError},
-    tensor::DenseMatrix,
+    tensor::{DenseMatrix, SparseGradients},
 };
 
 use super::{
     clip::{WeightClipping, WeightClippingParams},
     decay::{WeightDecay, WeightDecayParams},
-    utils::{self, Placement},
+    utils::{self, CheckpointCompression, Placement},
     OptimiserState, WrapOptimiser,
 };
 
@@ -68,16 +68,50 @@ impl<D: Device> OptimiserState<D> for Adam<D> {
         )
     }
 
+    /// Only the columns that have gradients are updated, so the moments of the other
+    /// columns are left as they are rather than decayed, as in lazy Adam.
+    fn update_sparse(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &SparseGradients<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        assert!(weights.batch_size().is_none());
+        assert_eq!(weights.size(), grads.dim() * grads.vocab());
+        assert_eq!(weights.size(), self.momentum.size());
+        assert_eq!(weights.size(), self.velocity.size());
+
+        D::sparse_adam(
+            grads.dim(),
+            grads.num_columns(),
+            grads.columns(),
+            &mut weights.buf,
+            &grads.values().buf,
+            &mut self.momentum.buf,
+            &mut self.velocity.buf,
+            self.params.beta1,
+            self.params.beta2,
+            gradient_factor,
+            learning_rate,
+            true,
+        )
+    }
+
     fn reset(&mut self) -> Result<(), D::DeviceError> {
         self.momentum.set_zero()?;
         self.velocity.set_zero()
     }
 
-    fn write_to_checkpoint(map: &HashMap<String, &Self>, path: &str) -> Result<(), D::DeviceError> {
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
         let momentum: Vec<_> = map.iter().map(|(id, single)| (id, &single.momentum)).collect();
         let velocity: Vec<_> = map.iter().map(|(id, single)| (id, &single.velocity)).collect();
-        utils::write_weights_to_file(&momentum, &format!("{path}/momentum.bin"))?;
-        utils::write_weights_to_file(&velocity, &format!("{path}/velocity.bin"))
+        utils::write_weights_to_file(&momentum, &format!("{path}/momentum.bin"), compression)?;
+        utils::write_weights_to_file(&velocity, &format!("{path}/velocity.bin"), compression)
     }
 
     fn load_from_checkpoint(
@@ -126,6 +160,26 @@ impl Default for AdamWParams {
     }
 }
 
+/// Per-weight overrides of `AdamWParams`, where fields left as `None` keep the value
+/// they are applied to, e.g. to clip output layer weights to a tighter range for quantisation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AdamWOverrides {
+    pub decay: Option<f32>,
+    pub min_weight: Option<f32>,
+    pub max_weight: Option<f32>,
+}
+
+impl AdamWParams {
+    pub fn with_overrides(self, overrides: AdamWOverrides) -> Self {
+        Self {
+            decay: overrides.decay.unwrap_or(self.decay),
+            min_weight: overrides.min_weight.unwrap_or(self.min_weight),
+            max_weight: overrides.max_weight.unwrap_or(self.max_weight),
+            ..self
+        }
+    }
+}
+
 impl From<AdamWParams> for WeightClippingParams<WeightDecayParams<AdamParams>> {
     fn from(value: AdamWParams) -> Self {
         WeightClippingParams {
@@ -0,0 +1,152 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    device::{Device, OperationError},
+    shape::Shape,
+    tensor::DenseMatrix,
+};
+
+use super::{utils::CheckpointCompression, OptimiserState};
+
+/// Splits the columns of a set of weights into `multipliers.len()` equally sized
+/// contiguous buckets, and scales the update applied to each bucket by its multiplier.
+///
+/// For the feature transformer this gives a learning rate multiplier per input bucket,
+/// e.g. to boost rarely active king buckets. Multipliers are only applied to the weights
+/// they are set for with `set_multipliers`, and any weight clipping done by the inner
+/// optimiser is reapplied after scaling.
+pub struct BucketedLr<D: Device, S> {
+    inner: S,
+    device: Arc<D>,
+    size: usize,
+    multipliers: Vec<f32>,
+    prev: Option<DenseMatrix<D>>,
+    scales: Option<DenseMatrix<D>>,
+}
+
+impl<D: Device, S> BucketedLr<D, S> {
+    /// Sets the multiplier of each bucket, the number of which must evenly divide the
+    /// size of the weights. No scaling is applied if `multipliers` is empty.
+    pub fn set_multipliers(&mut self, multipliers: Vec<f32>) {
+        let size = self.size;
+        let buckets = multipliers.len();
+
+        assert!(buckets == 0 || size % buckets == 0, "Weights of size {size} cannot be split into {buckets} buckets!");
+
+        if self.multipliers != multipliers {
+            self.multipliers = multipliers;
+            self.scales = None;
+        }
+    }
+
+    pub fn multipliers(&self) -> &[f32] {
+        &self.multipliers
+    }
+
+    fn setup_buffers(&mut self) -> Result<(), D::DeviceError> {
+        let buckets = self.multipliers.len();
+
+        if self.prev.is_none() {
+            self.prev = Some(DenseMatrix::zeroed(self.device.clone(), self.size)?);
+        }
+
+        if self.scales.is_none() {
+            let mut diag = vec![0.0; buckets * buckets];
+
+            for (i, &mult) in self.multipliers.iter().enumerate() {
+                diag[buckets * i + i] = mult - 1.0;
+            }
+
+            let mut scales = DenseMatrix::zeroed(self.device.clone(), buckets * buckets)?;
+            scales.load_from_slice(None, &diag)?;
+            self.scales = Some(scales);
+        }
+
+        Ok(())
+    }
+}
+
+impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for BucketedLr<D, S> {
+    type Params = S::Params;
+
+    fn new(device: Arc<D>, size: usize, params: Self::Params) -> Result<Self, D::DeviceError> {
+        Ok(Self {
+            inner: S::new(device.clone(), size, params)?,
+            device,
+            size,
+            multipliers: Vec::new(),
+            prev: None,
+            scales: None,
+        })
+    }
+
+    fn update(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &mut DenseMatrix<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        if self.multipliers.is_empty() {
+            return self.inner.update(weights, grads, gradient_factor, learning_rate);
+        }
+
+        let size = weights.size();
+        let buckets = self.multipliers.len();
+        assert_eq!(size, self.size);
+        self.setup_buffers()?;
+
+        let prev = self.prev.as_mut().unwrap();
+        prev.copy_from(weights)?;
+
+        self.inner.update(weights, grads, gradient_factor, learning_rate)?;
+
+        // prev = update applied by the inner optimiser
+        D::linear_comb_single(size, -1.0, None, 1.0, Some(&weights.buf), &mut prev.buf)?;
+
+        // each bucket is a contiguous chunk of the weights, so treating them
+        // as columns we can scale each by `multiplier - 1` and add to the weights
+        D::sgemm(
+            &prev.buf,
+            Shape::new(size / buckets, buckets),
+            false,
+            &self.scales.as_ref().unwrap().buf,
+            Shape::new(buckets, buckets),
+            false,
+            &mut weights.buf,
+            true,
+        )?;
+
+        self.inner.clip_weights(weights)
+    }
+
+    fn clip_weights(&self, weights: &mut DenseMatrix<D>) -> Result<(), OperationError<D::DeviceError>> {
+        self.inner.clip_weights(weights)
+    }
+
+    fn reset(&mut self) -> Result<(), D::DeviceError> {
+        self.inner.reset()
+    }
+
+    fn set_params(&mut self, params: Self::Params) {
+        self.inner.set_params(params);
+    }
+
+    fn load_from_checkpoint(
+        map: &mut HashMap<String, &mut Self>,
+        path: &str,
+        old_format: bool,
+    ) -> Result<(), D::DeviceError> {
+        let mut map = map.iter_mut().map(|(id, single)| (id.clone(), &mut single.inner)).collect();
+        S::load_from_checkpoint(&mut map, path, old_format)
+    }
+
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
+        let map = map.iter().map(|(id, single)| (id.clone(), &single.inner)).collect();
+        S::write_to_checkpoint(&map, path, compression)
+    }
+}
@@ -2,10 +2,13 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     device::{Device, OperationError},
-    tensor::DenseMatrix,
+    tensor::{DenseMatrix, SparseGradients},
 };
 
-use super::{utils::Placement, OptimiserState};
+use super::{
+    utils::{CheckpointCompression, Placement},
+    OptimiserState,
+};
 
 #[derive(Clone, Debug)]
 pub struct WeightClippingParams<T> {
@@ -60,6 +63,42 @@ impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for WeightClipping<S> {
         Ok(())
     }
 
+    /// Only the columns that have gradients are clipped.
+    fn update_sparse(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &SparseGradients<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        let clip = |weights: &mut DenseMatrix<D>| {
+            let (dim, num) = (grads.dim(), grads.num_columns());
+            D::sparse_scale_clip(dim, num, grads.columns(), &mut weights.buf, 1.0, self.min, self.max)
+        };
+
+        if self.placement == Placement::Before {
+            clip(weights)?;
+        }
+
+        self.inner.update_sparse(weights, grads, gradient_factor, learning_rate)?;
+
+        if self.placement == Placement::After {
+            clip(weights)?;
+        }
+
+        Ok(())
+    }
+
+    fn clip_weights(&self, weights: &mut DenseMatrix<D>) -> Result<(), OperationError<D::DeviceError>> {
+        self.inner.clip_weights(weights)?;
+
+        if self.placement == Placement::After {
+            D::clip(weights.size(), &mut weights.buf, self.min, self.max)?;
+        }
+
+        Ok(())
+    }
+
     fn reset(&mut self) -> Result<(), D::DeviceError> {
         self.inner.reset()
     }
@@ -79,8 +118,12 @@ impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for WeightClipping<S> {
         S::load_from_checkpoint(&mut map, path, old_format)
     }
 
-    fn write_to_checkpoint(map: &HashMap<String, &Self>, path: &str) -> Result<(), D::DeviceError> {
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
         let map = map.iter().map(|(id, single)| (id.clone(), &single.inner)).collect();
-        S::write_to_checkpoint(&map, path)
+        S::write_to_checkpoint(&map, path, compression)
     }
 }
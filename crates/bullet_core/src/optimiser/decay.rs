@@ -2,10 +2,13 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::{
     device::{Device, OperationError},
-    tensor::DenseMatrix,
+    tensor::{DenseMatrix, SparseGradients},
 };
 
-use super::{utils::Placement, OptimiserState};
+use super::{
+    utils::{CheckpointCompression, Placement},
+    OptimiserState,
+};
 
 #[derive(Clone, Debug)]
 pub struct WeightDecayParams<T> {
@@ -59,6 +62,37 @@ impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for WeightDecay<S> {
         Ok(())
     }
 
+    /// Only the columns that have gradients are decayed.
+    fn update_sparse(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &SparseGradients<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        let factor = 1.0 - self.decay * learning_rate;
+        let decay = |weights: &mut DenseMatrix<D>| {
+            let (dim, num) = (grads.dim(), grads.num_columns());
+            D::sparse_scale_clip(dim, num, grads.columns(), &mut weights.buf, factor, f32::MIN, f32::MAX)
+        };
+
+        if self.placement == Placement::Before {
+            decay(weights)?;
+        }
+
+        self.inner.update_sparse(weights, grads, gradient_factor, learning_rate)?;
+
+        if self.placement == Placement::After {
+            decay(weights)?;
+        }
+
+        Ok(())
+    }
+
+    fn clip_weights(&self, weights: &mut DenseMatrix<D>) -> Result<(), OperationError<D::DeviceError>> {
+        self.inner.clip_weights(weights)
+    }
+
     fn reset(&mut self) -> Result<(), D::DeviceError> {
         self.inner.reset()
     }
@@ -77,8 +111,12 @@ impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for WeightDecay<S> {
         S::load_from_checkpoint(&mut map, path, old_format)
     }
 
-    fn write_to_checkpoint(map: &HashMap<String, &Self>, path: &str) -> Result<(), D::DeviceError> {
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
         let map = map.iter().map(|(id, single)| (id.clone(), &single.inner)).collect();
-        S::write_to_checkpoint(&map, path)
+        S::write_to_checkpoint(&map, path, compression)
     }
 }
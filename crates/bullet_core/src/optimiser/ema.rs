@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::{
+    device::{Device, OperationError},
+    graph::Graph,
+    tensor::DenseMatrix,
+};
+
+use super::utils::{self, CheckpointCompression};
+
+/// An exponential moving average of the weights of a graph, updated after each
+/// optimiser step with `ema = decay * ema + (1 - decay) * weights`.
+pub struct WeightEma<D: Device> {
+    decay: f32,
+    weights: HashMap<String, DenseMatrix<D>>,
+}
+
+impl<D: Device> WeightEma<D> {
+    /// Starts the average from the current weights of `graph`.
+    pub fn new(graph: &Graph<D>, decay: f32) -> Result<Self, D::DeviceError> {
+        assert!((0.0..1.0).contains(&decay), "EMA decay must be in [0, 1)!");
+
+        let mut weights = HashMap::new();
+
+        for id in graph.weight_ids() {
+            let current = graph.get_weights(&id);
+            let current = current.values.dense().unwrap();
+
+            let mut ema = DenseMatrix::zeroed(graph.device(), current.size())?;
+            ema.copy_from(current)?;
+
+            weights.insert(id, ema);
+        }
+
+        Ok(Self { decay, weights })
+    }
+
+    pub fn update(&mut self, graph: &Graph<D>) -> Result<(), OperationError<D::DeviceError>> {
+        for (id, ema) in &mut self.weights {
+            let current = graph.get_weights(id);
+            let current = current.values.dense()?;
+
+            D::linear_comb_single(
+                current.size(),
+                self.decay,
+                None,
+                1.0 - self.decay,
+                Some(&current.buf),
+                &mut ema.buf,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    pub fn decay(&self) -> f32 {
+        self.decay
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DenseMatrix<D>> {
+        self.weights.get(id)
+    }
+
+    pub fn write_to_file(&self, path: &str, compression: CheckpointCompression) -> Result<(), D::DeviceError> {
+        let weights = self.weights.iter().collect::<Vec<_>>();
+        utils::write_weights_to_file(&weights, path, compression)
+    }
+
+    pub fn load_from_file(&mut self, path: &str, old_format: bool) -> Result<(), D::DeviceError> {
+        for (id, values) in utils::load_weights_from_file(path, old_format) {
+            if let Some(ema) = self.weights.get_mut(&id) {
+                ema.load_from_slice(None, &values)?;
+            }
+        }
+
+        Ok(())
+    }
+}
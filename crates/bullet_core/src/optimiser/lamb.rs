@@ -0,0 +1,179 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    sync::Arc,
+};
+
+use crate::{
+    device::{Device, OperationError},
+    tensor::DenseMatrix,
+};
+
+use super::{
+    utils::{self, CheckpointCompression},
+    OptimiserState,
+};
+
+/// Layerwise adaptive moments, which computes the bias-corrected Adam update plus
+/// `decay * weights` for each set of weights, and rescales it by the trust ratio
+/// `||weights|| / ||update||`, capped at `max_trust_ratio`.
+///
+/// This keeps the step size of each layer proportional to its weights, so that very
+/// large batch sizes can be trained stably with a correspondingly large learning rate.
+#[derive(Clone, Copy, Debug)]
+pub struct LambParams {
+    pub beta1: f32,
+    pub beta2: f32,
+    pub decay: f32,
+    pub max_trust_ratio: f32,
+}
+
+impl Default for LambParams {
+    fn default() -> Self {
+        Self { beta1: 0.9, beta2: 0.999, decay: 0.01, max_trust_ratio: 10.0 }
+    }
+}
+
+pub struct Lamb<D: Device> {
+    momentum: DenseMatrix<D>,
+    velocity: DenseMatrix<D>,
+    update: DenseMatrix<D>,
+    norm: DenseMatrix<D>,
+    params: LambParams,
+    step: usize,
+}
+
+impl<D: Device> OptimiserState<D> for Lamb<D> {
+    type Params = LambParams;
+
+    fn new(device: Arc<D>, size: usize, default_params: Self::Params) -> Result<Self, D::DeviceError> {
+        Ok(Self {
+            momentum: DenseMatrix::zeroed(device.clone(), size)?,
+            velocity: DenseMatrix::zeroed(device.clone(), size)?,
+            update: DenseMatrix::zeroed(device.clone(), size)?,
+            norm: DenseMatrix::zeroed(device, 1)?,
+            params: default_params,
+            step: 0,
+        })
+    }
+
+    fn update(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &mut DenseMatrix<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        assert!(weights.batch_size().is_none());
+        assert!(self.momentum.batch_size().is_none());
+        assert!(self.velocity.batch_size().is_none());
+        assert_eq!(weights.size(), self.momentum.size());
+        assert_eq!(weights.size(), self.velocity.size());
+        assert_eq!(weights.size(), self.update.size());
+
+        self.step += 1;
+
+        let params = self.params;
+        let step = self.step as f32;
+        let size = weights.size();
+
+        // the adam kernel subtracts `rate * m / (sqrt(v) + eps)`, so applying it to
+        // zeroes with a negative rate gives the bias-corrected update direction
+        let bias_correction = (1.0 - params.beta2.powf(step)).sqrt() / (1.0 - params.beta1.powf(step));
+
+        self.update.set_zero()?;
+        D::adam(
+            size,
+            &mut self.update.buf,
+            &grads.buf,
+            &mut self.momentum.buf,
+            &mut self.velocity.buf,
+            params.beta1,
+            params.beta2,
+            gradient_factor,
+            -bias_correction,
+            true,
+        )?;
+
+        if params.decay != 0.0 {
+            D::linear_comb_single(size, 1.0, None, params.decay, Some(&weights.buf), &mut self.update.buf)?;
+        }
+
+        let weights_norm = utils::l2_norm(weights, &mut self.norm)?;
+        let update_norm = utils::l2_norm(&self.update, &mut self.norm)?;
+
+        let trust_ratio = if weights_norm > 0.0 && update_norm > 0.0 {
+            (weights_norm / update_norm).min(params.max_trust_ratio)
+        } else {
+            1.0
+        };
+
+        D::linear_comb_single(size, 1.0, None, -learning_rate * trust_ratio, Some(&self.update.buf), &mut weights.buf)
+    }
+
+    fn reset(&mut self) -> Result<(), D::DeviceError> {
+        self.step = 0;
+        self.momentum.set_zero()?;
+        self.velocity.set_zero()
+    }
+
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
+        let momentum: Vec<_> = map.iter().map(|(id, single)| (id, &single.momentum)).collect();
+        let velocity: Vec<_> = map.iter().map(|(id, single)| (id, &single.velocity)).collect();
+        utils::write_weights_to_file(&momentum, &format!("{path}/momentum.bin"), compression)?;
+        utils::write_weights_to_file(&velocity, &format!("{path}/velocity.bin"), compression)?;
+
+        let mut file = File::create(format!("{path}/step.txt")).unwrap();
+        for (id, single) in map.iter() {
+            writeln!(file, "{id},{}", single.step).unwrap();
+        }
+
+        Ok(())
+    }
+
+    fn load_from_checkpoint(
+        map: &mut HashMap<String, &mut Self>,
+        path: &str,
+        old_format: bool,
+    ) -> Result<(), D::DeviceError> {
+        let paths = [format!("{path}/momentum.bin"), format!("{path}/velocity.bin")];
+        let mut momentum = utils::load_weights_from_file(&paths[0], old_format);
+        let mut velocity = utils::load_weights_from_file(&paths[1], old_format);
+
+        let file = File::open(format!("{path}/step.txt")).unwrap();
+        let mut steps = BufReader::new(file)
+            .lines()
+            .map(|s| {
+                let s = s.unwrap();
+                let mut split = s.split(',');
+                let id = split.next().unwrap();
+                (id.to_string(), split.next().unwrap().parse().unwrap())
+            })
+            .collect::<Vec<(String, usize)>>();
+
+        momentum.sort_by_key(|(id, _)| id.clone());
+        velocity.sort_by_key(|(id, _)| id.clone());
+        steps.sort_by_key(|(id, _)| id.clone());
+
+        for (((id1, mom), (id2, vel)), (id3, step)) in momentum.iter().zip(velocity.iter()).zip(steps.iter()) {
+            assert_eq!(id1, id2);
+            assert_eq!(id1, id3);
+
+            let single = map.get_mut(id1).unwrap();
+            single.momentum.load_from_slice(None, mom)?;
+            single.velocity.load_from_slice(None, vel)?;
+            single.step = *step;
+        }
+
+        Ok(())
+    }
+
+    fn set_params(&mut self, params: Self::Params) {
+        self.params = params;
+    }
+}
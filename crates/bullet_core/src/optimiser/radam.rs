@@ -10,7 +10,10 @@ use crate::{
     tensor::DenseMatrix,
 };
 
-use super::{utils, OptimiserState};
+use super::{
+    utils::{self, CheckpointCompression},
+    OptimiserState,
+};
 
 #[derive(Clone, Copy, Debug)]
 pub struct RAdamParams {
@@ -96,11 +99,15 @@ impl<D: Device> OptimiserState<D> for RAdam<D> {
         self.velocity.set_zero()
     }
 
-    fn write_to_checkpoint(map: &HashMap<String, &Self>, path: &str) -> Result<(), D::DeviceError> {
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
         let momentum: Vec<_> = map.iter().map(|(id, single)| (id, &single.momentum)).collect();
         let velocity: Vec<_> = map.iter().map(|(id, single)| (id, &single.velocity)).collect();
-        utils::write_weights_to_file(&momentum, &format!("{path}/momentum.bin"))?;
-        utils::write_weights_to_file(&velocity, &format!("{path}/velocity.bin"))?;
+        utils::write_weights_to_file(&momentum, &format!("{path}/momentum.bin"), compression)?;
+        utils::write_weights_to_file(&velocity, &format!("{path}/velocity.bin"), compression)?;
 
         let mut file = File::create(format!("{path}/step.txt")).unwrap();
         for (id, single) in map.iter() {
@@ -1,4 +1,10 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::Arc,
+};
 
 use crate::{
     device::{Device, OperationError},
@@ -9,10 +15,13 @@ use super::{
     clip::{WeightClipping, WeightClippingParams},
     decay::{WeightDecay, WeightDecayParams},
     radam::{RAdam, RAdamParams},
-    utils::Placement,
+    utils::{self, CheckpointCompression, Placement},
     OptimiserState, WrapOptimiser,
 };
 
+/// Wraps any optimiser, which updates a set of "fast" weights as usual, and every
+/// `k` steps moves a copy of "slow" weights a fraction `alpha` of the way towards
+/// the fast weights, before resetting the fast weights to the slow weights.
 #[derive(Clone, Debug)]
 pub struct RangerLookaheadParams<T> {
     pub inner: T,
@@ -38,8 +47,10 @@ impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for RangerLookahead<D, S
     type Params = RangerLookaheadParams<S::Params>;
 
     fn new(device: Arc<D>, size: usize, params: Self::Params) -> Result<Self, D::DeviceError> {
+        assert!(params.k > 0, "Lookahead must sync at least every step!");
+
         Ok(Self {
-            inner: S::new(device.clone(), size, params.inner.clone())?,
+            inner: S::new(device.clone(), size, params.inner)?,
             slow_params: DenseMatrix::zeroed(device, size)?,
             alpha: params.alpha,
             k: params.k,
@@ -54,13 +65,17 @@ impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for RangerLookahead<D, S
         gradient_factor: f32,
         learning_rate: f32,
     ) -> Result<(), OperationError<D::DeviceError>> {
+        assert!(weights.batch_size().is_none());
+        assert_eq!(weights.size(), self.slow_params.size());
+
+        if self.step == 0 {
+            self.slow_params.copy_from(weights)?;
+        }
+
         self.step += 1;
         self.inner.update(weights, grads, gradient_factor, learning_rate)?;
 
         if self.step % self.k == 0 {
-            assert_eq!(weights.single_size, self.slow_params.single_size);
-            assert!(self.slow_params.batch_size().is_none());
-
             D::linear_comb_single(
                 weights.size(),
                 1.0 - self.alpha,
@@ -76,31 +91,75 @@ impl<D: Device, S: OptimiserState<D>> OptimiserState<D> for RangerLookahead<D, S
         Ok(())
     }
 
+    fn clip_weights(&self, weights: &mut DenseMatrix<D>) -> Result<(), OperationError<D::DeviceError>> {
+        self.inner.clip_weights(weights)
+    }
+
     fn reset(&mut self) -> Result<(), D::DeviceError> {
-        self.inner.reset()?;
         self.step = 0;
-        Ok(())
+        self.inner.reset()
     }
 
     fn set_params(&mut self, params: Self::Params) {
+        assert!(params.k > 0, "Lookahead must sync at least every step!");
+
         self.inner.set_params(params.inner);
         self.alpha = params.alpha;
         self.k = params.k;
-        self.step = 0;
     }
 
+    /// Checkpoints saved without slow weights leave the step at zero, so that
+    /// the slow weights are taken from the loaded weights on the next update.
     fn load_from_checkpoint(
         map: &mut HashMap<String, &mut Self>,
         path: &str,
         old_format: bool,
     ) -> Result<(), D::DeviceError> {
+        if Path::new(&format!("{path}/lookahead_step.txt")).exists() {
+            let mut slow_params = utils::load_weights_from_file(&format!("{path}/slow_weights.bin"), old_format);
+
+            let file = File::open(format!("{path}/lookahead_step.txt")).unwrap();
+            let mut steps = BufReader::new(file)
+                .lines()
+                .map(|s| {
+                    let s = s.unwrap();
+                    let mut split = s.split(',');
+                    let id = split.next().unwrap();
+                    (id.to_string(), split.next().unwrap().parse().unwrap())
+                })
+                .collect::<Vec<(String, usize)>>();
+
+            slow_params.sort_by_key(|(id, _)| id.clone());
+            steps.sort_by_key(|(id, _)| id.clone());
+
+            for ((id1, slow), (id2, step)) in slow_params.iter().zip(steps.iter()) {
+                assert_eq!(id1, id2);
+
+                let single = map.get_mut(id1).unwrap();
+                single.slow_params.load_from_slice(None, slow)?;
+                single.step = *step;
+            }
+        }
+
         let mut map = map.iter_mut().map(|(id, single)| (id.clone(), &mut single.inner)).collect();
         S::load_from_checkpoint(&mut map, path, old_format)
     }
 
-    fn write_to_checkpoint(map: &HashMap<String, &Self>, path: &str) -> Result<(), D::DeviceError> {
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
+        let slow_params: Vec<_> = map.iter().map(|(id, single)| (id, &single.slow_params)).collect();
+        utils::write_weights_to_file(&slow_params, &format!("{path}/slow_weights.bin"), compression)?;
+
+        let mut file = File::create(format!("{path}/lookahead_step.txt")).unwrap();
+        for (id, single) in map.iter() {
+            writeln!(file, "{id},{}", single.step).unwrap();
+        }
+
         let map = map.iter().map(|(id, single)| (id.clone(), &single.inner)).collect();
-        S::write_to_checkpoint(&map, path)
+        S::write_to_checkpoint(&map, path, compression)
     }
 }
 
@@ -0,0 +1,99 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    device::{Device, OperationError},
+    tensor::DenseMatrix,
+};
+
+use super::{
+    utils::{self, CheckpointCompression},
+    OptimiserState,
+};
+
+/// Stochastic gradient descent with heavy-ball momentum, `v = momentum * v + g`
+/// and `w -= lr * v`, or `w -= lr * (g + momentum * v)` if `nesterov` is set.
+///
+/// Setting `momentum` to zero gives plain SGD.
+#[derive(Clone, Copy, Debug)]
+pub struct SgdParams {
+    pub momentum: f32,
+    pub nesterov: bool,
+}
+
+impl Default for SgdParams {
+    fn default() -> Self {
+        Self { momentum: 0.9, nesterov: false }
+    }
+}
+
+pub struct Sgd<D: Device> {
+    velocity: DenseMatrix<D>,
+    params: SgdParams,
+}
+
+impl<D: Device> OptimiserState<D> for Sgd<D> {
+    type Params = SgdParams;
+
+    fn new(device: Arc<D>, size: usize, default_params: Self::Params) -> Result<Self, D::DeviceError> {
+        Ok(Self { velocity: DenseMatrix::zeroed(device, size)?, params: default_params })
+    }
+
+    fn update(
+        &mut self,
+        weights: &mut DenseMatrix<D>,
+        grads: &mut DenseMatrix<D>,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        assert!(weights.batch_size().is_none());
+        assert!(self.velocity.batch_size().is_none());
+        assert_eq!(weights.size(), self.velocity.size());
+        assert_eq!(weights.size(), grads.size());
+
+        let size = weights.size();
+        let momentum = self.params.momentum;
+
+        D::linear_comb_single(size, momentum, None, gradient_factor, Some(&grads.buf), &mut self.velocity.buf)?;
+
+        if self.params.nesterov {
+            let grad_rate = -learning_rate * gradient_factor;
+            let velocity_rate = -learning_rate * momentum;
+            D::linear_comb_single(size, 1.0, None, grad_rate, Some(&grads.buf), &mut weights.buf)?;
+            D::linear_comb_single(size, 1.0, None, velocity_rate, Some(&self.velocity.buf), &mut weights.buf)
+        } else {
+            D::linear_comb_single(size, 1.0, None, -learning_rate, Some(&self.velocity.buf), &mut weights.buf)
+        }
+    }
+
+    fn reset(&mut self) -> Result<(), D::DeviceError> {
+        self.velocity.set_zero()
+    }
+
+    fn write_to_checkpoint(
+        map: &HashMap<String, &Self>,
+        path: &str,
+        compression: CheckpointCompression,
+    ) -> Result<(), D::DeviceError> {
+        let velocity: Vec<_> = map.iter().map(|(id, single)| (id, &single.velocity)).collect();
+        utils::write_weights_to_file(&velocity, &format!("{path}/velocity.bin"), compression)
+    }
+
+    fn load_from_checkpoint(
+        map: &mut HashMap<String, &mut Self>,
+        path: &str,
+        old_format: bool,
+    ) -> Result<(), D::DeviceError> {
+        let velocity = utils::load_weights_from_file(&format!("{path}/velocity.bin"), old_format);
+
+        for (id, vel) in &velocity {
+            let single = map.get_mut(id).unwrap();
+            single.velocity.load_from_slice(None, vel)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_params(&mut self, params: Self::Params) {
+        self.params = params;
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::{
+    device::{Device, OperationError},
+    graph::Graph,
+    tensor::DenseMatrix,
+};
+
+use super::utils::{self, CheckpointCompression};
+
+/// An equally weighted average of snapshots of the weights of a graph,
+/// as used in stochastic weight averaging.
+pub struct WeightAverage<D: Device> {
+    count: usize,
+    weights: HashMap<String, DenseMatrix<D>>,
+}
+
+impl<D: Device> WeightAverage<D> {
+    pub fn new(graph: &Graph<D>) -> Result<Self, D::DeviceError> {
+        let mut weights = HashMap::new();
+
+        for id in graph.weight_ids() {
+            let size = graph.get_weights(&id).values.size();
+            weights.insert(id, DenseMatrix::zeroed(graph.device(), size)?);
+        }
+
+        Ok(Self { count: 0, weights })
+    }
+
+    /// Adds the current weights of `graph` to the average.
+    pub fn add(&mut self, graph: &Graph<D>) -> Result<(), OperationError<D::DeviceError>> {
+        self.count += 1;
+        let new = 1.0 / self.count as f32;
+
+        for (id, avg) in &mut self.weights {
+            let current = graph.get_weights(id);
+            let current = current.values.dense()?;
+
+            D::linear_comb_single(current.size(), 1.0 - new, None, new, Some(&current.buf), &mut avg.buf)?;
+        }
+
+        Ok(())
+    }
+
+    /// Number of snapshots in the average.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn get(&self, id: &str) -> Option<&DenseMatrix<D>> {
+        self.weights.get(id)
+    }
+
+    pub fn write_to_checkpoint(&self, path: &str, compression: CheckpointCompression) -> Result<(), D::DeviceError> {
+        let weights = self.weights.iter().collect::<Vec<_>>();
+        utils::write_weights_to_file(&weights, &format!("{path}/swa_weights.bin"), compression)?;
+        std::fs::write(format!("{path}/swa_count.txt"), self.count.to_string()).unwrap();
+        Ok(())
+    }
+
+    pub fn load_from_checkpoint(&mut self, path: &str, old_format: bool) -> Result<(), D::DeviceError> {
+        for (id, values) in utils::load_weights_from_file(&format!("{path}/swa_weights.bin"), old_format) {
+            if let Some(avg) = self.weights.get_mut(&id) {
+                avg.load_from_slice(None, &values)?;
+            }
+        }
+
+        self.count = std::fs::read_to_string(format!("{path}/swa_count.txt")).unwrap().trim().parse().unwrap();
+
+        Ok(())
+    }
+}
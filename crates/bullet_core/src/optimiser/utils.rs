@@ -1,4 +1,14 @@
-use crate::{device::Device, graph::Graph, tensor::DenseMatrix};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Write},
+};
+
+use crate::{
+    device::{Device, OperationError},
+    graph::Graph,
+    shape::Shape,
+    tensor::DenseMatrix,
+};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Placement {
@@ -6,11 +16,65 @@ pub enum Placement {
     After,
 }
 
+/// Compression applied to checkpoint weight and optimiser state files when
+/// they are written. Compressed files are detected automatically on load.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CheckpointCompression {
+    #[default]
+    None,
+    Zstd {
+        level: i32,
+    },
+}
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Writes a checkpoint file with the given compression.
+pub fn write_checkpoint_file(path: &str, buf: &[u8], compression: CheckpointCompression) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+
+    match compression {
+        CheckpointCompression::None => file.write_all(buf),
+        CheckpointCompression::Zstd { level } => {
+            let mut encoder = zstd::stream::write::Encoder::new(file, level)?;
+            encoder.write_all(buf)?;
+            encoder.finish()?;
+            Ok(())
+        }
+    }
+}
+
+/// Reads a checkpoint file, decompressing it on the fly if it is compressed.
+pub fn read_checkpoint_file(path: &str) -> std::io::Result<Vec<u8>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut buf = Vec::new();
+
+    if reader.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+        zstd::stream::read::Decoder::with_buffer(reader)?.read_to_end(&mut buf)?;
+    } else {
+        reader.read_to_end(&mut buf)?;
+    }
+
+    Ok(buf)
+}
+
+/// Computes the L2 norm of `vals` on the device, using `output` (of size at least 1) as scratch space.
+pub fn l2_norm<D: Device>(
+    vals: &DenseMatrix<D>,
+    output: &mut DenseMatrix<D>,
+) -> Result<f32, OperationError<D::DeviceError>> {
+    let shape = Shape::new(vals.size(), 1);
+    D::sgemm(&vals.buf, shape, true, &vals.buf, shape, false, &mut output.buf, false)?;
+
+    let mut buf = [0.0];
+    output.write_to_slice(&mut buf)?;
+
+    Ok(buf[0].sqrt())
+}
+
 /// Writes the weights of a graph to a file. If `gradients` is true,
 /// it will instead write the gradients of those weights.
-pub fn write_graph_weights_to_file<D: Device>(graph: &Graph<D>, path: &str) {
-    use std::{fs::File, io::Write};
-
+pub fn write_graph_weights_to_file<D: Device>(graph: &Graph<D>, path: &str, compression: CheckpointCompression) {
     let weight_ids = graph.weight_ids();
 
     let mut buf = Vec::new();
@@ -22,8 +86,7 @@ pub fn write_graph_weights_to_file<D: Device>(graph: &Graph<D>, path: &str) {
         buf.extend_from_slice(&this_buf);
     }
 
-    let mut file = File::create(path).unwrap();
-    file.write_all(&buf).unwrap();
+    write_checkpoint_file(path, &buf, compression).unwrap();
 }
 
 /// Loads the weights of a graph from a file. If `gradients` is true,
@@ -33,11 +96,7 @@ pub fn load_graph_weights_from_file<D: Device>(
     path: &str,
     old_format: bool,
 ) -> Result<(), D::DeviceError> {
-    use std::{fs::File, io::Read};
-
-    let mut buf = Vec::new();
-    let mut file = File::open(path).unwrap();
-    file.read_to_end(&mut buf).unwrap();
+    let buf = read_checkpoint_file(path).unwrap();
 
     let mut offset = 0;
 
@@ -55,9 +114,8 @@ pub fn load_graph_weights_from_file<D: Device>(
 pub fn write_weights_to_file<D: Device>(
     map: &[(impl AsRef<str>, &DenseMatrix<D>)],
     path: &str,
+    compression: CheckpointCompression,
 ) -> Result<(), D::DeviceError> {
-    use std::{fs::File, io::Write};
-
     let mut buf = Vec::new();
 
     for (id, weights) in map {
@@ -65,19 +123,14 @@ pub fn write_weights_to_file<D: Device>(
         buf.extend_from_slice(&this_buf);
     }
 
-    let mut file = File::create(path).unwrap();
-    file.write_all(&buf).unwrap();
+    write_checkpoint_file(path, &buf, compression).unwrap();
 
     Ok(())
 }
 
 /// Loads a set of labelled weights from a file into a `HashMap`.
 pub fn load_weights_from_file(path: &str, old_format: bool) -> Vec<(String, Vec<f32>)> {
-    use std::{fs::File, io::Read};
-
-    let mut buf = Vec::new();
-    let mut file = File::open(path).unwrap();
-    file.read_to_end(&mut buf).unwrap();
+    let buf = read_checkpoint_file(path).unwrap();
 
     let mut offset = 0;
 
@@ -94,8 +147,11 @@ pub fn load_weights_from_file(path: &str, old_format: bool) -> Vec<(String, Vec<
 
 /// Reads a matrix from a byte buffer, returning how many bytes were read
 /// and the matrix ID that was read.
+///
+/// See `DenseMatrix::write_to_byte_buffer` for the format, sizes are always
+/// read as 64-bit so checkpoints are portable between hosts.
 pub fn read_from_byte_buffer(bytes: &[u8], old_format: bool) -> (Vec<f32>, String, usize) {
-    const USIZE: usize = std::mem::size_of::<usize>();
+    const USIZE: usize = std::mem::size_of::<u64>();
 
     let mut offset = 0;
 
@@ -115,13 +171,13 @@ pub fn read_from_byte_buffer(bytes: &[u8], old_format: bool) -> (Vec<f32>, Strin
     single_size.copy_from_slice(&bytes[offset..offset + USIZE]);
     offset += USIZE;
 
-    let mut single_size = usize::from_le_bytes(single_size);
+    let mut single_size = u64::from_le_bytes(single_size) as usize;
 
     if old_format {
         let mut cols = [0u8; USIZE];
         cols.copy_from_slice(&bytes[offset..offset + USIZE]);
         offset += USIZE;
-        single_size *= usize::from_le_bytes(cols);
+        single_size *= u64::from_le_bytes(cols) as usize;
     }
 
     let total_read = offset + single_size * 4;
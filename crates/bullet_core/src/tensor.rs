@@ -2,12 +2,14 @@ mod dense;
 mod matrix;
 mod rng;
 mod sparse;
+mod sparse_grad;
 
 use std::{cell::RefCell, collections::HashMap, sync::Arc};
 
 pub use dense::DenseMatrix;
 pub use matrix::Matrix;
 pub use sparse::SparseMatrix;
+pub use sparse_grad::SparseGradients;
 
 use crate::{
     device::{Device, DeviceBuffer, OperationError},
@@ -17,6 +19,8 @@ use crate::{
 pub struct Tensor<D: Device> {
     pub values: Matrix<D>,
     pub gradients: Option<DenseMatrix<D>>,
+    /// Set instead of `gradients` for weights built with `GraphBuilder::use_sparse_gradients`.
+    pub sparse_gradients: Option<SparseGradients<D>>,
     pub(crate) internal: HashMap<String, RefCell<DenseMatrix<D>>>,
     pub(crate) operation: Option<Operation>,
     pub(crate) own: Node,
@@ -39,6 +43,7 @@ impl<D: Device> Tensor<D> {
         Ok(Self {
             values,
             gradients: if requires_grad { Some(DenseMatrix::zeroed(device, single_size)?) } else { None },
+            sparse_gradients: None,
             internal: HashMap::new(),
             operation,
             own,
@@ -50,6 +55,10 @@ impl<D: Device> Tensor<D> {
             grad.set_zero()?;
         }
 
+        if let Some(grad) = self.sparse_gradients.as_mut() {
+            grad.set_zero()?;
+        }
+
         Ok(())
     }
 
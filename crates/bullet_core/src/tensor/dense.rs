@@ -81,6 +81,10 @@ impl<D: Device> DenseMatrix<D> {
 
     /// Writes a complete description of the matrix into a buffer of bytes,
     /// along with an ID tag.
+    ///
+    /// The format is independent of the device and host: the ID followed by a newline,
+    /// the number of values as a little-endian `u64`, then the values in column-major
+    /// order as little-endian `f32`s.
     pub fn write_to_byte_buffer(&self, id: &str) -> std::io::Result<Vec<u8>> {
         use std::io::{Error, ErrorKind, Write};
 
@@ -103,7 +107,7 @@ impl<D: Device> DenseMatrix<D> {
         let mut buf = Vec::new();
 
         buf.write_all(&id_bytes)?;
-        buf.write_all(&usize::to_le_bytes(self.single_size))?;
+        buf.write_all(&u64::to_le_bytes(self.single_size as u64))?;
 
         let mut values = vec![0.0; self.single_size];
         self.write_to_slice(&mut values).unwrap();
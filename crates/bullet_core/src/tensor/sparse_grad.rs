@@ -0,0 +1,137 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::device::{Device, DeviceBuffer, OperationError};
+
+use super::DenseMatrix;
+
+/// Gradients of a `dim x vocab` matrix of which only a few columns are used in each batch,
+/// e.g. an embedding table, stored as the columns that were touched and the gradients of
+/// just those columns (one after another), rather than as a matrix the size of the weights.
+pub struct SparseGradients<D: Device> {
+    dim: usize,
+    vocab: usize,
+    positions: HashMap<i32, usize>,
+    columns: Vec<i32>,
+    columns_buf: D::BufferI32,
+    remapped: D::BufferI32,
+    values: DenseMatrix<D>,
+}
+
+impl<D: Device> SparseGradients<D> {
+    pub fn new(device: Arc<D>, dim: usize, vocab: usize) -> Result<Self, D::DeviceError> {
+        Ok(Self {
+            dim,
+            vocab,
+            positions: HashMap::new(),
+            columns: Vec::new(),
+            columns_buf: D::BufferI32::new(device.clone(), 1)?,
+            remapped: D::BufferI32::new(device.clone(), 1)?,
+            values: DenseMatrix::zeroed(device, dim)?,
+        })
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn vocab(&self) -> usize {
+        self.vocab
+    }
+
+    /// Number of distinct columns that have gradients.
+    pub fn num_columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// The columns that have gradients, in the order their gradients are stored.
+    pub fn columns(&self) -> &D::BufferI32 {
+        &self.columns_buf
+    }
+
+    /// The gradients of each column in `columns`, followed by zeroes up to the allocated size.
+    pub fn values(&self) -> &DenseMatrix<D> {
+        &self.values
+    }
+
+    /// Adds the `dim` gradients in `grads` of each of the `slots` columns listed in `indices`,
+    /// ignoring padding indices of `-1`.
+    pub fn accumulate(
+        &mut self,
+        slots: usize,
+        indices: &D::BufferI32,
+        grads: &D::BufferF32,
+    ) -> Result<(), OperationError<D::DeviceError>> {
+        if slots == 0 {
+            return Ok(());
+        }
+
+        let mut buf = vec![0; slots];
+        indices.write_into_slice(&mut buf, slots)?;
+
+        for idx in &mut buf {
+            if *idx == -1 {
+                continue;
+            }
+
+            if *idx < 0 || *idx as usize >= self.vocab {
+                return Err(OperationError::IndexOutOfBounds);
+            }
+
+            let columns = &mut self.columns;
+            let position = *self.positions.entry(*idx).or_insert_with(|| {
+                columns.push(*idx);
+                columns.len() - 1
+            });
+
+            *idx = position as i32;
+        }
+
+        let capacity = self.values.size() / self.dim;
+
+        if self.columns.len() > capacity {
+            let capacity = self.columns.len().max(2 * capacity);
+            let mut values = DenseMatrix::zeroed(self.values.buf.device(), capacity * self.dim)?;
+            values.buf.load_from_device(&self.values.buf, self.values.size())?;
+            self.values = values;
+        }
+
+        if self.columns.len() > self.columns_buf.size() {
+            self.columns_buf = D::BufferI32::new(self.columns_buf.device(), self.values.size() / self.dim)?;
+        }
+
+        if slots > self.remapped.size() {
+            self.remapped = D::BufferI32::new(self.remapped.device(), slots)?;
+        }
+
+        self.columns_buf.load_from_slice(&self.columns)?;
+        self.remapped.load_from_slice(&buf)?;
+
+        let capacity = self.values.size() / self.dim;
+        D::backprop_embedding(1, self.dim, capacity, slots, grads, &self.remapped, &mut self.values.buf)
+    }
+
+    /// Scatters the gradients into a dense `dim x vocab` matrix.
+    pub fn to_dense(&self) -> Result<DenseMatrix<D>, OperationError<D::DeviceError>> {
+        let mut dense = DenseMatrix::zeroed(self.values.buf.device(), self.dim * self.vocab)?;
+
+        if !self.columns.is_empty() {
+            D::backprop_embedding(
+                1,
+                self.dim,
+                self.vocab,
+                self.columns.len(),
+                &self.values.buf,
+                &self.columns_buf,
+                &mut dense.buf,
+            )?;
+        }
+
+        Ok(dense)
+    }
+
+    pub fn set_zero(&mut self) -> Result<(), D::DeviceError> {
+        self.positions.clear();
+        self.columns.clear();
+        self.values.set_zero()
+    }
+}
@@ -55,8 +55,8 @@ __global__ void buffer_backprop_kernel(const size_t size, const float* input, co
         for (size_t i = 0; i < size - 4 * tid; i++)
         {
             const size_t idx = 4 * tid + i;
-            const float this_in = input[i];
-            const float this_out_grad = output_grad[i];
+            const float this_in = input[idx];
+            const float this_out_grad = output_grad[idx];
             input_grad[idx] += op(this_in) * this_out_grad;
         }
     }
@@ -102,6 +102,21 @@ extern "C" {
         buffer_backprop<primeSquare>(size, input, output_grad, input_grad);
     }
 
+    void backpropAbs(const size_t size, const float* input, const float* output_grad, float* input_grad)
+    {
+        buffer_backprop<primeAbsolute>(size, input, output_grad, input_grad);
+    }
+
+    void backpropGELU(const size_t size, const float* input, const float* output_grad, float* input_grad)
+    {
+        buffer_backprop<primeGELU>(size, input, output_grad, input_grad);
+    }
+
+    void backpropSiLU(const size_t size, const float* input, const float* output_grad, float* input_grad)
+    {
+        buffer_backprop<primeSiLU>(size, input, output_grad, input_grad);
+    }
+
     void activateReLU(const size_t size, const float* in, float* out)
     {
         buffer_operation<ReLU>(size, in, out);
@@ -131,4 +146,228 @@ extern "C" {
     {
         buffer_operation<square>(size, in, out);
     }
+
+    void activateAbs(const size_t size, const float* in, float* out)
+    {
+        buffer_operation<absolute>(size, in, out);
+    }
+
+    void activateGELU(const size_t size, const float* in, float* out)
+    {
+        buffer_operation<GELU>(size, in, out);
+    }
+
+    void activateSiLU(const size_t size, const float* in, float* out)
+    {
+        buffer_operation<SiLU>(size, in, out);
+    }
+}
+
+__global__ void clampKernel(const size_t size, const float min, const float max, const float* in, float* out)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    out[i] = in[i] < min ? min : (in[i] > max ? max : in[i]);
+}
+
+__global__ void backpropClampKernel(
+    const size_t size,
+    const float min,
+    const float max,
+    const float* input,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    if (input[i] > min && input[i] < max)
+        input_grad[i] += output_grad[i];
+}
+
+extern "C" void activateClamp(const size_t size, const float min, const float max, const float* in, float* out)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    clampKernel<<<numBlocks, threadsPerBlock>>>(size, min, max, in, out);
+}
+
+extern "C" void backpropClamp(
+    const size_t size,
+    const float min,
+    const float max,
+    const float* input,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropClampKernel<<<numBlocks, threadsPerBlock>>>(size, min, max, input, output_grad, input_grad);
+}
+
+// `slope` has `size` elements and is shared across the batch
+__global__ void preluKernel(const size_t size, const size_t batchSize, const float* in, const float* slope, float* out)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size * batchSize)
+        return;
+
+    out[i] = in[i] > 0.0F ? in[i] : slope[i % size] * in[i];
+}
+
+__global__ void backpropPReLUInputKernel(
+    const size_t size,
+    const size_t batchSize,
+    const float* input,
+    const float* slope,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size * batchSize)
+        return;
+
+    input_grad[i] += output_grad[i] * (input[i] > 0.0F ? 1.0F : slope[i % size]);
+}
+
+// one thread per slope, summing over the batch
+__global__ void backpropPReLUSlopeKernel(
+    const size_t size,
+    const size_t batchSize,
+    const float* input,
+    const float* output_grad,
+    float* slope_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    float sum = 0.0F;
+
+    for (size_t b = 0; b < batchSize; b++) {
+        const size_t idx = b * size + i;
+
+        if (input[idx] <= 0.0F)
+            sum += output_grad[idx] * input[idx];
+    }
+
+    slope_grad[i] += sum;
+}
+
+extern "C" void activatePReLU(const size_t size, const size_t batchSize, const float* in, const float* slope, float* out)
+{
+    const size_t numBlocks = (size * batchSize + threadsPerBlock - 1) / threadsPerBlock;
+    preluKernel<<<numBlocks, threadsPerBlock>>>(size, batchSize, in, slope, out);
+}
+
+extern "C" void backpropPReLU(
+    const size_t size,
+    const size_t batchSize,
+    const float* input,
+    const float* slope,
+    const float* output_grad,
+    float* input_grad,
+    float* slope_grad)
+{
+    if (input_grad != nullptr) {
+        const size_t numBlocks = (size * batchSize + threadsPerBlock - 1) / threadsPerBlock;
+        backpropPReLUInputKernel<<<numBlocks, threadsPerBlock>>>(size, batchSize, input, slope, output_grad, input_grad);
+    }
+
+    if (slope_grad != nullptr) {
+        const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+        backpropPReLUSlopeKernel<<<numBlocks, threadsPerBlock>>>(size, batchSize, input, output_grad, slope_grad);
+    }
+}
+
+template<OpType op>
+__global__ void biasActivateKernel(const size_t size, const size_t biasSize, const float* bias, float* out)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    out[i] = op(out[i] + bias[i % biasSize]);
+}
+
+// derivatives are computed from the output of the activation
+template<OpType op>
+__global__ void backpropFromOutputKernel(const size_t size, const float* output, const float* output_grad, float* input_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    input_grad[i] = op(output[i]) * output_grad[i];
+}
+
+extern "C" void biasActivate(const size_t size, const size_t biasSize, const float* bias, float* out, const int32_t activation)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+
+    switch (activation)
+    {
+        case 0:
+            biasActivateKernel<Identity><<<numBlocks, threadsPerBlock>>>(size, biasSize, bias, out);
+            break;
+        case 1:
+            biasActivateKernel<ReLU><<<numBlocks, threadsPerBlock>>>(size, biasSize, bias, out);
+            break;
+        case 2:
+            biasActivateKernel<CReLU><<<numBlocks, threadsPerBlock>>>(size, biasSize, bias, out);
+            break;
+        case 3:
+            biasActivateKernel<SCReLU><<<numBlocks, threadsPerBlock>>>(size, biasSize, bias, out);
+            break;
+        case 4:
+            biasActivateKernel<SqrReLU><<<numBlocks, threadsPerBlock>>>(size, biasSize, bias, out);
+            break;
+        case 5:
+            biasActivateKernel<sigmoid><<<numBlocks, threadsPerBlock>>>(size, biasSize, bias, out);
+            break;
+        default:
+            std::abort();
+    }
+}
+
+extern "C" void backpropActivateFromOutput(
+    const size_t size,
+    const float* output,
+    const float* output_grad,
+    float* input_grad,
+    const int32_t activation)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+
+    switch (activation)
+    {
+        case 0:
+            backpropFromOutputKernel<primeInvIdentity><<<numBlocks, threadsPerBlock>>>(size, output, output_grad, input_grad);
+            break;
+        case 1:
+            backpropFromOutputKernel<primeInvReLU><<<numBlocks, threadsPerBlock>>>(size, output, output_grad, input_grad);
+            break;
+        case 2:
+            backpropFromOutputKernel<primeInvCReLU><<<numBlocks, threadsPerBlock>>>(size, output, output_grad, input_grad);
+            break;
+        case 3:
+            backpropFromOutputKernel<primeInvSCReLU><<<numBlocks, threadsPerBlock>>>(size, output, output_grad, input_grad);
+            break;
+        case 4:
+            backpropFromOutputKernel<primeInvSqrReLU><<<numBlocks, threadsPerBlock>>>(size, output, output_grad, input_grad);
+            break;
+        case 5:
+            backpropFromOutputKernel<primeInvSigmoid><<<numBlocks, threadsPerBlock>>>(size, output, output_grad, input_grad);
+            break;
+        default:
+            std::abort();
+    }
 }
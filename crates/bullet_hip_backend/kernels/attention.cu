@@ -0,0 +1,247 @@
+#include "util.cu"
+#ifdef __HIP_PLATFORM_AMD__
+#include <hip/hip_runtime.h>
+#endif
+
+// Queries, keys and values are `seqLen` tokens of size `dim`, stored token by token.
+// Attention probabilities are stored as `[batch][query][key]`.
+
+__global__ void attentionProbsKernel(
+    const size_t batchSize,
+    const size_t seqLen,
+    const size_t dim,
+    const float scale,
+    const float* q,
+    const float* k,
+    const float* mask,
+    const size_t maskStride,
+    float* probs)
+{
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * seqLen)
+        return;
+
+    const size_t b = tid / seqLen;
+    const size_t i = tid % seqLen;
+
+    const float* thisQ = q + tid * dim;
+    const float* thisK = k + b * seqLen * dim;
+    const float* thisMask = mask == nullptr ? nullptr : mask + b * maskStride + i * seqLen;
+    float* thisProbs = probs + tid * seqLen;
+
+    float maximum = -INFINITY;
+
+    for (size_t j = 0; j < seqLen; j++) {
+        float score = 0.0F;
+
+        for (size_t e = 0; e < dim; e++)
+            score += thisQ[e] * thisK[j * dim + e];
+
+        score *= scale;
+
+        if (thisMask != nullptr)
+            score += thisMask[j];
+
+        thisProbs[j] = score;
+        maximum = max(maximum, score);
+    }
+
+    float total = 0.0F;
+
+    for (size_t j = 0; j < seqLen; j++) {
+        const float p = expf(thisProbs[j] - maximum);
+        thisProbs[j] = p;
+        total += p;
+    }
+
+    for (size_t j = 0; j < seqLen; j++)
+        thisProbs[j] /= total;
+}
+
+__global__ void attentionOutputKernel(
+    const size_t batchSize,
+    const size_t seqLen,
+    const size_t dim,
+    const float* probs,
+    const float* v,
+    float* output)
+{
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * seqLen * dim)
+        return;
+
+    const size_t b = tid / (seqLen * dim);
+    const size_t i = (tid / dim) % seqLen;
+    const size_t e = tid % dim;
+
+    const float* thisProbs = probs + (b * seqLen + i) * seqLen;
+    const float* thisV = v + b * seqLen * dim;
+
+    float sum = 0.0F;
+
+    for (size_t j = 0; j < seqLen; j++)
+        sum += thisProbs[j] * thisV[j * dim + e];
+
+    output[tid] = sum;
+}
+
+// gradient of the loss with respect to the (scaled) attention scores
+__global__ void backpropAttentionScoresKernel(
+    const size_t batchSize,
+    const size_t seqLen,
+    const size_t dim,
+    const float scale,
+    const float* v,
+    const float* probs,
+    const float* output_grad,
+    float* scores_grad)
+{
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * seqLen)
+        return;
+
+    const size_t b = tid / seqLen;
+
+    const float* thisV = v + b * seqLen * dim;
+    const float* thisOutputGrad = output_grad + tid * dim;
+    const float* thisProbs = probs + tid * seqLen;
+    float* thisScoresGrad = scores_grad + tid * seqLen;
+
+    float weighted = 0.0F;
+
+    for (size_t j = 0; j < seqLen; j++) {
+        float grad = 0.0F;
+
+        for (size_t e = 0; e < dim; e++)
+            grad += thisOutputGrad[e] * thisV[j * dim + e];
+
+        thisScoresGrad[j] = grad;
+        weighted += thisProbs[j] * grad;
+    }
+
+    for (size_t j = 0; j < seqLen; j++)
+        thisScoresGrad[j] = scale * thisProbs[j] * (thisScoresGrad[j] - weighted);
+}
+
+__global__ void backpropAttentionQueryKernel(
+    const size_t batchSize,
+    const size_t seqLen,
+    const size_t dim,
+    const float* k,
+    const float* scores_grad,
+    float* q_grad)
+{
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * seqLen * dim)
+        return;
+
+    const size_t b = tid / (seqLen * dim);
+    const size_t i = (tid / dim) % seqLen;
+    const size_t e = tid % dim;
+
+    const float* thisK = k + b * seqLen * dim;
+    const float* thisScoresGrad = scores_grad + (b * seqLen + i) * seqLen;
+
+    float sum = 0.0F;
+
+    for (size_t j = 0; j < seqLen; j++)
+        sum += thisScoresGrad[j] * thisK[j * dim + e];
+
+    q_grad[tid] += sum;
+}
+
+__global__ void backpropAttentionKeyValueKernel(
+    const size_t batchSize,
+    const size_t seqLen,
+    const size_t dim,
+    const float* q,
+    const float* probs,
+    const float* output_grad,
+    const float* scores_grad,
+    float* k_grad,
+    float* v_grad)
+{
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * seqLen * dim)
+        return;
+
+    const size_t b = tid / (seqLen * dim);
+    const size_t j = (tid / dim) % seqLen;
+    const size_t e = tid % dim;
+
+    const size_t offset = b * seqLen * dim;
+    const float* thisProbs = probs + b * seqLen * seqLen;
+    const float* thisScoresGrad = scores_grad + b * seqLen * seqLen;
+
+    float kSum = 0.0F;
+    float vSum = 0.0F;
+
+    for (size_t i = 0; i < seqLen; i++) {
+        kSum += thisScoresGrad[i * seqLen + j] * q[offset + i * dim + e];
+        vSum += thisProbs[i * seqLen + j] * output_grad[offset + i * dim + e];
+    }
+
+    if (k_grad != nullptr)
+        k_grad[tid] += kSum;
+
+    if (v_grad != nullptr)
+        v_grad[tid] += vSum;
+}
+
+extern "C" void attention(
+    const size_t batchSize,
+    const size_t seqLen,
+    const size_t dim,
+    const float* q,
+    const float* k,
+    const float* v,
+    const float* mask,
+    const size_t maskStride,
+    float* probs,
+    float* output)
+{
+    const float scale = 1.0F / sqrtf(static_cast<float>(dim));
+
+    const size_t rows = batchSize * seqLen;
+    const size_t rowBlocks = (rows + threadsPerBlock - 1) / threadsPerBlock;
+    attentionProbsKernel<<<rowBlocks, threadsPerBlock>>>(batchSize, seqLen, dim, scale, q, k, mask, maskStride, probs);
+
+    const size_t size = rows * dim;
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    attentionOutputKernel<<<numBlocks, threadsPerBlock>>>(batchSize, seqLen, dim, probs, v, output);
+}
+
+extern "C" void backpropAttention(
+    const size_t batchSize,
+    const size_t seqLen,
+    const size_t dim,
+    const float* q,
+    const float* k,
+    const float* v,
+    const float* probs,
+    const float* output_grad,
+    float* scores_grad,
+    float* q_grad,
+    float* k_grad,
+    float* v_grad)
+{
+    const float scale = 1.0F / sqrtf(static_cast<float>(dim));
+
+    const size_t rows = batchSize * seqLen;
+    const size_t rowBlocks = (rows + threadsPerBlock - 1) / threadsPerBlock;
+    backpropAttentionScoresKernel<<<rowBlocks, threadsPerBlock>>>(batchSize, seqLen, dim, scale, v, probs, output_grad, scores_grad);
+
+    const size_t size = rows * dim;
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+
+    if (q_grad != nullptr)
+        backpropAttentionQueryKernel<<<numBlocks, threadsPerBlock>>>(batchSize, seqLen, dim, k, scores_grad, q_grad);
+
+    if (k_grad != nullptr || v_grad != nullptr)
+        backpropAttentionKeyValueKernel<<<numBlocks, threadsPerBlock>>>(batchSize, seqLen, dim, q, probs, output_grad, scores_grad, k_grad, v_grad);
+}
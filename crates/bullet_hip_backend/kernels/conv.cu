@@ -0,0 +1,215 @@
+#include "util.cu"
+#ifdef __HIP_PLATFORM_AMD__
+#include <hip/hip_runtime.h>
+#endif
+
+// Naive stride-1 convolutions with zero padding so that the output planes are the
+// same size as the input planes. Filters are laid out as [out][in][kernelY][kernelX].
+
+__global__ void conv2dKernel(
+    const size_t batchSize,
+    const size_t inChannels,
+    const size_t outChannels,
+    const size_t height,
+    const size_t width,
+    const size_t kernelHeight,
+    const size_t kernelWidth,
+    const float* filters,
+    const float* input,
+    float* output)
+{
+    const size_t planeSize = height * width;
+    const size_t outSize = outChannels * planeSize;
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * outSize)
+        return;
+
+    const size_t b = tid / outSize;
+    const size_t o = (tid % outSize) / planeSize;
+    const int y = static_cast<int>((tid % planeSize) / width);
+    const int x = static_cast<int>(tid % width);
+    const int padY = static_cast<int>(kernelHeight / 2);
+    const int padX = static_cast<int>(kernelWidth / 2);
+
+    const float* thisInput = input + b * inChannels * planeSize;
+
+    float sum = 0.0F;
+
+    for (size_t i = 0; i < inChannels; i++) {
+        for (size_t ky = 0; ky < kernelHeight; ky++) {
+            const int iy = y + static_cast<int>(ky) - padY;
+
+            if (iy < 0 || iy >= static_cast<int>(height))
+                continue;
+
+            for (size_t kx = 0; kx < kernelWidth; kx++) {
+                const int ix = x + static_cast<int>(kx) - padX;
+
+                if (ix < 0 || ix >= static_cast<int>(width))
+                    continue;
+
+                const float w = filters[((o * inChannels + i) * kernelHeight + ky) * kernelWidth + kx];
+                sum += w * thisInput[i * planeSize + iy * width + ix];
+            }
+        }
+    }
+
+    output[tid] = sum;
+}
+
+__global__ void backpropConv2dInputKernel(
+    const size_t batchSize,
+    const size_t inChannels,
+    const size_t outChannels,
+    const size_t height,
+    const size_t width,
+    const size_t kernelHeight,
+    const size_t kernelWidth,
+    const float* filters,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t planeSize = height * width;
+    const size_t inSize = inChannels * planeSize;
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * inSize)
+        return;
+
+    const size_t b = tid / inSize;
+    const size_t i = (tid % inSize) / planeSize;
+    const int y = static_cast<int>((tid % planeSize) / width);
+    const int x = static_cast<int>(tid % width);
+    const int padY = static_cast<int>(kernelHeight / 2);
+    const int padX = static_cast<int>(kernelWidth / 2);
+
+    const float* thisOutputGrad = output_grad + b * outChannels * planeSize;
+
+    float sum = 0.0F;
+
+    for (size_t o = 0; o < outChannels; o++) {
+        for (size_t ky = 0; ky < kernelHeight; ky++) {
+            const int oy = y - static_cast<int>(ky) + padY;
+
+            if (oy < 0 || oy >= static_cast<int>(height))
+                continue;
+
+            for (size_t kx = 0; kx < kernelWidth; kx++) {
+                const int ox = x - static_cast<int>(kx) + padX;
+
+                if (ox < 0 || ox >= static_cast<int>(width))
+                    continue;
+
+                const float w = filters[((o * inChannels + i) * kernelHeight + ky) * kernelWidth + kx];
+                sum += w * thisOutputGrad[o * planeSize + oy * width + ox];
+            }
+        }
+    }
+
+    input_grad[tid] += sum;
+}
+
+// one thread per filter weight, summing over the batch
+__global__ void backpropConv2dFiltersKernel(
+    const size_t batchSize,
+    const size_t inChannels,
+    const size_t outChannels,
+    const size_t height,
+    const size_t width,
+    const size_t kernelHeight,
+    const size_t kernelWidth,
+    const float* input,
+    const float* output_grad,
+    float* filters_grad)
+{
+    const size_t kernelSize = kernelHeight * kernelWidth;
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= outChannels * inChannels * kernelSize)
+        return;
+
+    const size_t o = tid / (inChannels * kernelSize);
+    const size_t i = (tid / kernelSize) % inChannels;
+    const int ky = static_cast<int>((tid % kernelSize) / kernelWidth);
+    const int kx = static_cast<int>(tid % kernelWidth);
+    const int padY = static_cast<int>(kernelHeight / 2);
+    const int padX = static_cast<int>(kernelWidth / 2);
+    const size_t planeSize = height * width;
+
+    float sum = 0.0F;
+
+    for (size_t b = 0; b < batchSize; b++) {
+        const float* thisInput = input + (b * inChannels + i) * planeSize;
+        const float* thisOutputGrad = output_grad + (b * outChannels + o) * planeSize;
+
+        for (int y = 0; y < static_cast<int>(height); y++) {
+            const int iy = y + ky - padY;
+
+            if (iy < 0 || iy >= static_cast<int>(height))
+                continue;
+
+            for (int x = 0; x < static_cast<int>(width); x++) {
+                const int ix = x + kx - padX;
+
+                if (ix < 0 || ix >= static_cast<int>(width))
+                    continue;
+
+                sum += thisOutputGrad[y * width + x] * thisInput[iy * width + ix];
+            }
+        }
+    }
+
+    filters_grad[tid] += sum;
+}
+
+extern "C" void conv2d(
+    const size_t batchSize,
+    const size_t inChannels,
+    const size_t outChannels,
+    const size_t height,
+    const size_t width,
+    const size_t kernelHeight,
+    const size_t kernelWidth,
+    const float* filters,
+    const float* input,
+    float* output)
+{
+    const size_t size = batchSize * outChannels * height * width;
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    conv2dKernel<<<numBlocks, threadsPerBlock>>>(batchSize, inChannels, outChannels, height, width, kernelHeight, kernelWidth, filters, input, output);
+}
+
+extern "C" void backpropConv2dInput(
+    const size_t batchSize,
+    const size_t inChannels,
+    const size_t outChannels,
+    const size_t height,
+    const size_t width,
+    const size_t kernelHeight,
+    const size_t kernelWidth,
+    const float* filters,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t size = batchSize * inChannels * height * width;
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropConv2dInputKernel<<<numBlocks, threadsPerBlock>>>(batchSize, inChannels, outChannels, height, width, kernelHeight, kernelWidth, filters, output_grad, input_grad);
+}
+
+extern "C" void backpropConv2dFilters(
+    const size_t batchSize,
+    const size_t inChannels,
+    const size_t outChannels,
+    const size_t height,
+    const size_t width,
+    const size_t kernelHeight,
+    const size_t kernelWidth,
+    const float* input,
+    const float* output_grad,
+    float* filters_grad)
+{
+    const size_t size = outChannels * inChannels * kernelHeight * kernelWidth;
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropConv2dFiltersKernel<<<numBlocks, threadsPerBlock>>>(batchSize, inChannels, outChannels, height, width, kernelHeight, kernelWidth, input, output_grad, filters_grad);
+}
@@ -0,0 +1,62 @@
+#include "util.cu"
+#ifdef __HIP_PLATFORM_AMD__
+#include <hip/hip_runtime.h>
+#endif
+
+// cheap stateless hash, so each element gets an independent uniform value in [0, 1)
+__device__ float hashToUniform(const unsigned int seed, const size_t idx)
+{
+    unsigned int x = seed ^ static_cast<unsigned int>(idx * 0x9E3779B9u);
+    x ^= x >> 16;
+    x *= 0x7FEB352Du;
+    x ^= x >> 15;
+    x *= 0x846CA68Bu;
+    x ^= x >> 16;
+    return static_cast<float>(x >> 8) / 16777216.0F;
+}
+
+__global__ void dropoutKernel(
+    const size_t size,
+    const float rate,
+    const unsigned int seed,
+    const float* input,
+    float* mask,
+    float* output)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    const float keep = hashToUniform(seed, i) >= rate ? 1.0F / (1.0F - rate) : 0.0F;
+    mask[i] = keep;
+    output[i] = keep * input[i];
+}
+
+__global__ void backpropDropoutKernel(const size_t size, const float* mask, const float* output_grad, float* input_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    input_grad[i] += mask[i] * output_grad[i];
+}
+
+extern "C" void dropout(
+    const size_t size,
+    const float rate,
+    const unsigned int seed,
+    const float* input,
+    float* mask,
+    float* output)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    dropoutKernel<<<numBlocks, threadsPerBlock>>>(size, rate, seed, input, mask, output);
+}
+
+extern "C" void backpropDropout(const size_t size, const float* mask, const float* output_grad, float* input_grad)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropDropoutKernel<<<numBlocks, threadsPerBlock>>>(size, mask, output_grad, input_grad);
+}
@@ -0,0 +1,163 @@
+#include "util.cu"
+#ifdef __HIP_PLATFORM_AMD__
+#include <hip/hip_runtime.h>
+#endif
+
+__global__ void elementwiseMinMaxKernel(const size_t size, const bool isMax, const float* a, const float* b, float* out)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    out[i] = isMax ? fmaxf(a[i], b[i]) : fminf(a[i], b[i]);
+}
+
+// ties are attributed to `a`
+__global__ void backpropElementwiseMinMaxKernel(
+    const size_t size,
+    const bool isMax,
+    const float* a,
+    const float* b,
+    const float* output_grad,
+    float* a_grad,
+    float* b_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    const bool takesA = isMax ? a[i] >= b[i] : a[i] <= b[i];
+
+    if (takesA && a_grad != nullptr)
+        a_grad[i] += output_grad[i];
+
+    if (!takesA && b_grad != nullptr)
+        b_grad[i] += output_grad[i];
+}
+
+__global__ void elementwiseMulKernel(const size_t size, const float* a, const float* b, float* out)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    out[i] = a[i] * b[i];
+}
+
+__global__ void backpropElementwiseMulKernel(
+    const size_t size,
+    const float* a,
+    const float* b,
+    const float* output_grad,
+    float* a_grad,
+    float* b_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    if (a_grad != nullptr)
+        a_grad[i] += b[i] * output_grad[i];
+
+    if (b_grad != nullptr)
+        b_grad[i] += a[i] * output_grad[i];
+}
+
+// denominators smaller in magnitude than epsilon are replaced by +-epsilon
+__device__ float safeDenominator(const float b, const float epsilon)
+{
+    return fabsf(b) < epsilon ? copysignf(epsilon, b) : b;
+}
+
+__global__ void elementwiseDivKernel(const size_t size, const float epsilon, const float* a, const float* b, float* out)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    out[i] = a[i] / safeDenominator(b[i], epsilon);
+}
+
+__global__ void backpropElementwiseDivKernel(
+    const size_t size,
+    const float epsilon,
+    const float* a,
+    const float* b,
+    const float* output_grad,
+    float* a_grad,
+    float* b_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    const float denom = safeDenominator(b[i], epsilon);
+
+    if (a_grad != nullptr)
+        a_grad[i] += output_grad[i] / denom;
+
+    if (b_grad != nullptr && fabsf(b[i]) >= epsilon)
+        b_grad[i] -= output_grad[i] * a[i] / (denom * denom);
+}
+
+extern "C" void elementwiseMinMax(const size_t size, const bool isMax, const float* a, const float* b, float* out)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    elementwiseMinMaxKernel<<<numBlocks, threadsPerBlock>>>(size, isMax, a, b, out);
+}
+
+extern "C" void backpropElementwiseMinMax(
+    const size_t size,
+    const bool isMax,
+    const float* a,
+    const float* b,
+    const float* output_grad,
+    float* a_grad,
+    float* b_grad)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropElementwiseMinMaxKernel<<<numBlocks, threadsPerBlock>>>(size, isMax, a, b, output_grad, a_grad, b_grad);
+}
+
+extern "C" void elementwiseMul(const size_t size, const float* a, const float* b, float* out)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    elementwiseMulKernel<<<numBlocks, threadsPerBlock>>>(size, a, b, out);
+}
+
+extern "C" void backpropElementwiseMul(
+    const size_t size,
+    const float* a,
+    const float* b,
+    const float* output_grad,
+    float* a_grad,
+    float* b_grad)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropElementwiseMulKernel<<<numBlocks, threadsPerBlock>>>(size, a, b, output_grad, a_grad, b_grad);
+}
+
+extern "C" void elementwiseDiv(const size_t size, const float epsilon, const float* a, const float* b, float* out)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    elementwiseDivKernel<<<numBlocks, threadsPerBlock>>>(size, epsilon, a, b, out);
+}
+
+extern "C" void backpropElementwiseDiv(
+    const size_t size,
+    const float epsilon,
+    const float* a,
+    const float* b,
+    const float* output_grad,
+    float* a_grad,
+    float* b_grad)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropElementwiseDivKernel<<<numBlocks, threadsPerBlock>>>(size, epsilon, a, b, output_grad, a_grad, b_grad);
+}
@@ -1,13 +1,23 @@
 #include "util.cu"
 #include "activate.cu"
+#include "attention.cu"
+#include "conv.cu"
+#include "dropout.cu"
+#include "elementwise.cu"
 #include "gather.cu"
+#include "norm.cu"
 #include "optimiser.cu"
 #include "pairwise.cu"
+#include "pool.cu"
 #include "power_error.cu"
 #include "select.cu"
+#include "transpose.cu"
 #include "softmax/masked.cu"
 #include "softmax/naive.cu"
 #include "sparse/fwd.cu"
 #include "sparse/bwd.cu"
+#include "sparse/embedding.cu"
+#include "sparse/optimiser.cu"
 #include "sparse/mask.cu"
 #include "sparse/to_dense.cu"
+#include "sparse/outer.cu"
@@ -0,0 +1,321 @@
+#include "util.cu"
+#ifdef __HIP_PLATFORM_AMD__
+#include <hip/hip_runtime.h>
+#endif
+
+constexpr float normEpsilon = 0.00001F;
+
+// one thread per column, as the normalised vectors are expected to be small
+__global__ void layerNormKernel(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* scale,
+    const float* shift,
+    float* stats,
+    float* output)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= cols)
+        return;
+
+    const float* thisColumn = input + rows * tid;
+    float* thisOutput = output + rows * tid;
+
+    float mean = 0.0F;
+
+    for (size_t i = 0; i < rows; i++) {
+        mean += thisColumn[i];
+    }
+
+    mean /= static_cast<float>(rows);
+
+    float var = 0.0F;
+
+    for (size_t i = 0; i < rows; i++) {
+        const float diff = thisColumn[i] - mean;
+        var += diff * diff;
+    }
+
+    var /= static_cast<float>(rows);
+
+    const float rstd = rsqrtf(var + normEpsilon);
+
+    for (size_t i = 0; i < rows; i++) {
+        thisOutput[i] = scale[i] * (thisColumn[i] - mean) * rstd + shift[i];
+    }
+
+    stats[2 * tid] = mean;
+    stats[2 * tid + 1] = rstd;
+}
+
+__global__ void backpropLayerNormInputKernel(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* scale,
+    const float* stats,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= cols)
+        return;
+
+    const float* thisColumn = input + rows * tid;
+    const float* thisOutputGrad = output_grad + rows * tid;
+    float* thisInputGrad = input_grad + rows * tid;
+
+    const float mean = stats[2 * tid];
+    const float rstd = stats[2 * tid + 1];
+
+    float sumGrad = 0.0F;
+    float sumGradNorm = 0.0F;
+
+    for (size_t i = 0; i < rows; i++) {
+        const float grad = scale[i] * thisOutputGrad[i];
+        sumGrad += grad;
+        sumGradNorm += grad * (thisColumn[i] - mean) * rstd;
+    }
+
+    sumGrad /= static_cast<float>(rows);
+    sumGradNorm /= static_cast<float>(rows);
+
+    for (size_t i = 0; i < rows; i++) {
+        const float norm = (thisColumn[i] - mean) * rstd;
+        const float grad = scale[i] * thisOutputGrad[i];
+        thisInputGrad[i] += rstd * (grad - sumGrad - norm * sumGradNorm);
+    }
+}
+
+// one thread per row, summing over the batch
+__global__ void backpropLayerNormParamsKernel(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* stats,
+    const float* output_grad,
+    float* scale_grad,
+    float* shift_grad)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= rows)
+        return;
+
+    float sumScale = 0.0F;
+    float sumShift = 0.0F;
+
+    for (size_t j = 0; j < cols; j++) {
+        const size_t idx = rows * j + tid;
+        const float norm = (input[idx] - stats[2 * j]) * stats[2 * j + 1];
+        sumScale += norm * output_grad[idx];
+        sumShift += output_grad[idx];
+    }
+
+    if (scale_grad != nullptr)
+        scale_grad[tid] += sumScale;
+
+    if (shift_grad != nullptr)
+        shift_grad[tid] += sumShift;
+}
+
+// one thread per row, normalising across the batch
+__global__ void batchNormStatsKernel(
+    const size_t rows,
+    const size_t cols,
+    const float momentum,
+    const float* input,
+    float* runningMean,
+    float* runningVar,
+    float* stats)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= rows)
+        return;
+
+    float mean = 0.0F;
+
+    for (size_t j = 0; j < cols; j++) {
+        mean += input[rows * j + tid];
+    }
+
+    mean /= static_cast<float>(cols);
+
+    float var = 0.0F;
+
+    for (size_t j = 0; j < cols; j++) {
+        const float diff = input[rows * j + tid] - mean;
+        var += diff * diff;
+    }
+
+    var /= static_cast<float>(cols);
+
+    runningMean[tid] = (1.0F - momentum) * runningMean[tid] + momentum * mean;
+    runningVar[tid] = (1.0F - momentum) * runningVar[tid] + momentum * var;
+
+    stats[2 * tid] = mean;
+    stats[2 * tid + 1] = rsqrtf(var + normEpsilon);
+}
+
+__global__ void batchNormRunningStatsKernel(
+    const size_t rows,
+    const float* runningMean,
+    const float* runningVar,
+    float* stats)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= rows)
+        return;
+
+    stats[2 * tid] = runningMean[tid];
+    stats[2 * tid + 1] = rsqrtf(runningVar[tid] + normEpsilon);
+}
+
+__global__ void batchNormApplyKernel(
+    const size_t rows,
+    const size_t size,
+    const float* input,
+    const float* scale,
+    const float* shift,
+    const float* stats,
+    float* output)
+{
+    const size_t i = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    const size_t row = i % rows;
+    output[i] = scale[row] * (input[i] - stats[2 * row]) * stats[2 * row + 1] + shift[row];
+}
+
+__global__ void backpropBatchNormKernel(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* scale,
+    const float* stats,
+    const float* output_grad,
+    float* input_grad,
+    float* scale_grad,
+    float* shift_grad)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= rows)
+        return;
+
+    const float mean = stats[2 * tid];
+    const float rstd = stats[2 * tid + 1];
+
+    float sumGrad = 0.0F;
+    float sumGradNorm = 0.0F;
+
+    for (size_t j = 0; j < cols; j++) {
+        const size_t idx = rows * j + tid;
+        sumGrad += output_grad[idx];
+        sumGradNorm += output_grad[idx] * (input[idx] - mean) * rstd;
+    }
+
+    if (scale_grad != nullptr)
+        scale_grad[tid] += sumGradNorm;
+
+    if (shift_grad != nullptr)
+        shift_grad[tid] += sumGrad;
+
+    if (input_grad == nullptr)
+        return;
+
+    sumGrad /= static_cast<float>(cols);
+    sumGradNorm /= static_cast<float>(cols);
+
+    for (size_t j = 0; j < cols; j++) {
+        const size_t idx = rows * j + tid;
+        const float norm = (input[idx] - mean) * rstd;
+        input_grad[idx] += scale[tid] * rstd * (output_grad[idx] - sumGrad - norm * sumGradNorm);
+    }
+}
+
+extern "C" void layerNorm(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* scale,
+    const float* shift,
+    float* stats,
+    float* output)
+{
+    const size_t numBlocks = (cols + threadsPerBlock - 1) / threadsPerBlock;
+    layerNormKernel<<<numBlocks, threadsPerBlock>>>(rows, cols, input, scale, shift, stats, output);
+}
+
+extern "C" void backpropLayerNormInput(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* scale,
+    const float* stats,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t numBlocks = (cols + threadsPerBlock - 1) / threadsPerBlock;
+    backpropLayerNormInputKernel<<<numBlocks, threadsPerBlock>>>(rows, cols, input, scale, stats, output_grad, input_grad);
+}
+
+extern "C" void backpropLayerNormParams(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* stats,
+    const float* output_grad,
+    float* scale_grad,
+    float* shift_grad)
+{
+    const size_t numBlocks = (rows + threadsPerBlock - 1) / threadsPerBlock;
+    backpropLayerNormParamsKernel<<<numBlocks, threadsPerBlock>>>(rows, cols, input, stats, output_grad, scale_grad, shift_grad);
+}
+
+extern "C" void batchNorm(
+    const size_t rows,
+    const size_t cols,
+    const bool training,
+    const float momentum,
+    const float* input,
+    const float* scale,
+    const float* shift,
+    float* runningMean,
+    float* runningVar,
+    float* stats,
+    float* output)
+{
+    const size_t rowBlocks = (rows + threadsPerBlock - 1) / threadsPerBlock;
+
+    if (training)
+        batchNormStatsKernel<<<rowBlocks, threadsPerBlock>>>(rows, cols, momentum, input, runningMean, runningVar, stats);
+    else
+        batchNormRunningStatsKernel<<<rowBlocks, threadsPerBlock>>>(rows, runningMean, runningVar, stats);
+
+    const size_t size = rows * cols;
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    batchNormApplyKernel<<<numBlocks, threadsPerBlock>>>(rows, size, input, scale, shift, stats, output);
+}
+
+extern "C" void backpropBatchNorm(
+    const size_t rows,
+    const size_t cols,
+    const float* input,
+    const float* scale,
+    const float* stats,
+    const float* output_grad,
+    float* input_grad,
+    float* scale_grad,
+    float* shift_grad)
+{
+    const size_t numBlocks = (rows + threadsPerBlock - 1) / threadsPerBlock;
+    backpropBatchNormKernel<<<numBlocks, threadsPerBlock>>>(rows, cols, input, scale, stats, output_grad, input_grad, scale_grad, shift_grad);
+}
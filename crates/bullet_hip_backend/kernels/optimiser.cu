@@ -32,6 +32,31 @@ __global__ void AdamKernel(
     network[i] -= rate * val;
 }
 
+__global__ void AdaBeliefKernel(
+    const size_t size,
+    const float beta1,
+    const float beta2,
+    const float adj,
+    const float rate,
+    float* network,
+    float* momentum,
+    float* belief,
+    const float* gradients)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= size)
+        return;
+
+    const float grad = adj * gradients[i];
+    momentum[i] = beta1 * momentum[i] + (1.0F - beta1) * grad;
+
+    const float diff = grad - momentum[i];
+    belief[i] = beta2 * belief[i] + (1.0F - beta2) * diff * diff + Epsilon;
+
+    network[i] -= rate * momentum[i] / (sqrt(belief[i]) + Epsilon);
+}
+
 __global__ void ClipKernel(const size_t size, float* params, const float min_weight, const float max_weight) {
     const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
 
@@ -68,6 +93,31 @@ extern "C" void Adam(
     );
 }
 
+extern "C" void AdaBelief(
+    const size_t size,
+    const float beta1,
+    const float beta2,
+    const float adj,
+    const float rate,
+    float* network,
+    float* momentum,
+    float* belief,
+    const float* gradients)
+{
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    AdaBeliefKernel<<<numBlocks, threadsPerBlock>>>(
+        size,
+        beta1,
+        beta2,
+        adj,
+        rate,
+        network,
+        momentum,
+        belief,
+        gradients
+    );
+}
+
 extern "C" void Clip(const size_t size, float* params, const float min_weight, const float max_weight) {
     const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
     ClipKernel<<<numBlocks, threadsPerBlock>>>(
@@ -0,0 +1,212 @@
+#include "util.cu"
+#ifdef __HIP_PLATFORM_AMD__
+#include <hip/hip_runtime.h>
+#endif
+
+// non-overlapping pooling over `channels` planes of `height x width`, one thread per output element
+__global__ void avgPoolKernel(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* input,
+    float* output)
+{
+    const size_t outHeight = height / poolHeight;
+    const size_t outWidth = width / poolWidth;
+    const size_t outSize = channels * outHeight * outWidth;
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * outSize)
+        return;
+
+    const size_t b = tid / outSize;
+    const size_t c = (tid % outSize) / (outHeight * outWidth);
+    const size_t y = (tid / outWidth) % outHeight;
+    const size_t x = tid % outWidth;
+
+    const float* thisPlane = input + b * channels * height * width + c * height * width;
+
+    float sum = 0.0F;
+
+    for (size_t dy = 0; dy < poolHeight; dy++) {
+        for (size_t dx = 0; dx < poolWidth; dx++) {
+            sum += thisPlane[(y * poolHeight + dy) * width + x * poolWidth + dx];
+        }
+    }
+
+    output[tid] = sum / static_cast<float>(poolHeight * poolWidth);
+}
+
+__global__ void backpropAvgPoolKernel(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t outHeight = height / poolHeight;
+    const size_t outWidth = width / poolWidth;
+    const size_t outSize = channels * outHeight * outWidth;
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * outSize)
+        return;
+
+    const size_t b = tid / outSize;
+    const size_t c = (tid % outSize) / (outHeight * outWidth);
+    const size_t y = (tid / outWidth) % outHeight;
+    const size_t x = tid % outWidth;
+
+    float* thisPlane = input_grad + b * channels * height * width + c * height * width;
+    const float grad = output_grad[tid] / static_cast<float>(poolHeight * poolWidth);
+
+    for (size_t dy = 0; dy < poolHeight; dy++) {
+        for (size_t dx = 0; dx < poolWidth; dx++) {
+            thisPlane[(y * poolHeight + dy) * width + x * poolWidth + dx] += grad;
+        }
+    }
+}
+
+// the position of the maximum within each window is saved in `argmax` for the backward pass
+__global__ void maxPoolKernel(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* input,
+    float* argmax,
+    float* output)
+{
+    const size_t outHeight = height / poolHeight;
+    const size_t outWidth = width / poolWidth;
+    const size_t outSize = channels * outHeight * outWidth;
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * outSize)
+        return;
+
+    const size_t b = tid / outSize;
+    const size_t c = (tid % outSize) / (outHeight * outWidth);
+    const size_t y = (tid / outWidth) % outHeight;
+    const size_t x = tid % outWidth;
+
+    const float* thisPlane = input + b * channels * height * width + c * height * width;
+
+    float best = thisPlane[y * poolHeight * width + x * poolWidth];
+    size_t bestIdx = 0;
+
+    for (size_t dy = 0; dy < poolHeight; dy++) {
+        for (size_t dx = 0; dx < poolWidth; dx++) {
+            const float val = thisPlane[(y * poolHeight + dy) * width + x * poolWidth + dx];
+            if (val > best) {
+                best = val;
+                bestIdx = dy * poolWidth + dx;
+            }
+        }
+    }
+
+    output[tid] = best;
+    argmax[tid] = static_cast<float>(bestIdx);
+}
+
+__global__ void backpropMaxPoolKernel(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* argmax,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t outHeight = height / poolHeight;
+    const size_t outWidth = width / poolWidth;
+    const size_t outSize = channels * outHeight * outWidth;
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (tid >= batchSize * outSize)
+        return;
+
+    const size_t b = tid / outSize;
+    const size_t c = (tid % outSize) / (outHeight * outWidth);
+    const size_t y = (tid / outWidth) % outHeight;
+    const size_t x = tid % outWidth;
+
+    const size_t idx = static_cast<size_t>(argmax[tid]);
+    const size_t dy = idx / poolWidth;
+    const size_t dx = idx % poolWidth;
+
+    float* thisPlane = input_grad + b * channels * height * width + c * height * width;
+    thisPlane[(y * poolHeight + dy) * width + x * poolWidth + dx] += output_grad[tid];
+}
+
+extern "C" void avgPool(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* input,
+    float* output)
+{
+    const size_t size = batchSize * channels * (height / poolHeight) * (width / poolWidth);
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    avgPoolKernel<<<numBlocks, threadsPerBlock>>>(batchSize, channels, height, width, poolHeight, poolWidth, input, output);
+}
+
+extern "C" void backpropAvgPool(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t size = batchSize * channels * (height / poolHeight) * (width / poolWidth);
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropAvgPoolKernel<<<numBlocks, threadsPerBlock>>>(batchSize, channels, height, width, poolHeight, poolWidth, output_grad, input_grad);
+}
+
+extern "C" void maxPool(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* input,
+    float* argmax,
+    float* output)
+{
+    const size_t size = batchSize * channels * (height / poolHeight) * (width / poolWidth);
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    maxPoolKernel<<<numBlocks, threadsPerBlock>>>(batchSize, channels, height, width, poolHeight, poolWidth, input, argmax, output);
+}
+
+extern "C" void backpropMaxPool(
+    const size_t batchSize,
+    const size_t channels,
+    const size_t height,
+    const size_t width,
+    const size_t poolHeight,
+    const size_t poolWidth,
+    const float* argmax,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t size = batchSize * channels * (height / poolHeight) * (width / poolWidth);
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    backpropMaxPoolKernel<<<numBlocks, threadsPerBlock>>>(batchSize, channels, height, width, poolHeight, poolWidth, argmax, output_grad, input_grad);
+}
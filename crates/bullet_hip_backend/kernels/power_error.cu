@@ -58,3 +58,188 @@ extern "C" void backpropPowerError(
     const size_t numBlocks = (bufferSize + threadsPerBlock - 1) / threadsPerBlock;
     backpropPowerErrorKernel<<<numBlocks, threadsPerBlock>>>(bufferSize, inputs, results, output_grad, input_grads, power);
 }
+
+__global__ void sigmoidBCEKernel(const size_t bufferSize, const float* logits, const float* targets, float* output)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= bufferSize)
+        return;
+
+    const float x = logits[i];
+    output[i] = max(x, 0.0F) - x * targets[i] + log1pf(expf(-abs(x)));
+}
+
+__global__ void backpropSigmoidBCEKernel(
+    const size_t bufferSize,
+    const float* logits,
+    const float* targets,
+    const float* output_grad,
+    float* logits_grad,
+    float* targets_grad)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= bufferSize)
+        return;
+
+    if (logits_grad != nullptr)
+        logits_grad[i] += (sigmoid(logits[i]) - targets[i]) * output_grad[i];
+
+    if (targets_grad != nullptr)
+        targets_grad[i] -= logits[i] * output_grad[i];
+}
+
+extern "C" void sigmoidBCE(const size_t bufferSize, const float* logits, const float* targets, float* output)
+{
+    const size_t numBlocks = (bufferSize + threadsPerBlock - 1) / threadsPerBlock;
+    sigmoidBCEKernel<<<numBlocks, threadsPerBlock>>>(bufferSize, logits, targets, output);
+}
+
+extern "C" void backpropSigmoidBCE(
+    const size_t bufferSize,
+    const float* logits,
+    const float* targets,
+    const float* output_grad,
+    float* logits_grad,
+    float* targets_grad)
+{
+    const size_t numBlocks = (bufferSize + threadsPerBlock - 1) / threadsPerBlock;
+    backpropSigmoidBCEKernel<<<numBlocks, threadsPerBlock>>>(bufferSize, logits, targets, output_grad, logits_grad, targets_grad);
+}
+
+__global__ void huberErrorKernel(
+    const size_t bufferSize,
+    const float* inputs,
+    const float* results,
+    float* output,
+    const float delta)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= bufferSize)
+        return;
+
+    const float diff = abs(inputs[i] - results[i]);
+    output[i] = diff <= delta ? 0.5F * diff * diff : delta * (diff - 0.5F * delta);
+}
+
+__global__ void backpropHuberErrorKernel(
+    const size_t bufferSize,
+    const float* inputs,
+    const float* results,
+    const float* output_grad,
+    float* input_grads,
+    const float delta)
+{
+    const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (i >= bufferSize)
+        return;
+
+    const float diff = inputs[i] - results[i];
+    input_grads[i] += max(-delta, min(diff, delta)) * output_grad[i];
+}
+
+extern "C" void huberError(
+    const size_t bufferSize,
+    const float* inputs,
+    const float* results,
+    float* output,
+    const float delta)
+{
+    const size_t numBlocks = (bufferSize + threadsPerBlock - 1) / threadsPerBlock;
+    huberErrorKernel<<<numBlocks, threadsPerBlock>>>(bufferSize, inputs, results, output, delta);
+}
+
+extern "C" void backpropHuberError(
+    const size_t bufferSize,
+    const float* inputs,
+    const float* results,
+    const float* output_grad,
+    float* input_grads,
+    const float delta)
+{
+    const size_t numBlocks = (bufferSize + threadsPerBlock - 1) / threadsPerBlock;
+    backpropHuberErrorKernel<<<numBlocks, threadsPerBlock>>>(bufferSize, inputs, results, output_grad, input_grads, delta);
+}
+
+__device__ float rankingOrder(const float a, const float b)
+{
+    return a > b ? 1.0F : (a < b ? -1.0F : 0.0F);
+}
+
+__global__ void pairwiseRankingKernel(
+    const size_t batchSize,
+    const float margin,
+    const float* preds,
+    const float* targets,
+    float* output)
+{
+    const size_t a = 2 * (blockIdx.x * blockDim.x + threadIdx.x);
+    const size_t b = a + 1;
+
+    if (a >= batchSize)
+        return;
+
+    if (b >= batchSize)
+    {
+        output[a] = 0.0F;
+        return;
+    }
+
+    const float order = rankingOrder(targets[a], targets[b]);
+    const float loss = order == 0.0F ? 0.0F : max(0.0F, margin - order * (preds[a] - preds[b]));
+
+    output[a] = 0.5F * loss;
+    output[b] = 0.5F * loss;
+}
+
+__global__ void backpropPairwiseRankingKernel(
+    const size_t batchSize,
+    const float margin,
+    const float* preds,
+    const float* targets,
+    const float* output_grad,
+    float* preds_grad)
+{
+    const size_t a = 2 * (blockIdx.x * blockDim.x + threadIdx.x);
+    const size_t b = a + 1;
+
+    if (b >= batchSize)
+        return;
+
+    const float order = rankingOrder(targets[a], targets[b]);
+
+    if (order == 0.0F || margin - order * (preds[a] - preds[b]) <= 0.0F)
+        return;
+
+    const float grad = 0.5F * order * (output_grad[a] + output_grad[b]);
+    preds_grad[a] -= grad;
+    preds_grad[b] += grad;
+}
+
+extern "C" void pairwiseRanking(
+    const size_t batchSize,
+    const float margin,
+    const float* preds,
+    const float* targets,
+    float* output)
+{
+    const size_t pairs = (batchSize + 1) / 2;
+    const size_t numBlocks = (pairs + threadsPerBlock - 1) / threadsPerBlock;
+    pairwiseRankingKernel<<<numBlocks, threadsPerBlock>>>(batchSize, margin, preds, targets, output);
+}
+
+extern "C" void backpropPairwiseRanking(
+    const size_t batchSize,
+    const float margin,
+    const float* preds,
+    const float* targets,
+    const float* output_grad,
+    float* preds_grad)
+{
+    const size_t pairs = (batchSize + 1) / 2;
+    const size_t numBlocks = (pairs + threadsPerBlock - 1) / threadsPerBlock;
+    backpropPairwiseRankingKernel<<<numBlocks, threadsPerBlock>>>(batchSize, margin, preds, targets, output_grad, preds_grad);
+}
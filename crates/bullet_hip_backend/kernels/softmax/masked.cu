@@ -109,6 +109,34 @@ __global__ void backprop_softmax_cross_entropy_masked_kernel(
     }
 }
 
+__global__ void smooth_targets_masked_kernel(
+    const size_t max_active,
+    const size_t cols,
+    const int32_t* mask,
+    const float smoothing,
+    const float* target,
+    float* out)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= cols)
+        return;
+
+    const int32_t* this_mask = mask + max_active * tid;
+    const float* this_target = target + max_active * tid;
+    float* this_out = out + max_active * tid;
+
+    size_t active = 0;
+
+    while (active < max_active && this_mask[active] != -1)
+        active++;
+
+    const float uniform = active > 0 ? smoothing / static_cast<float>(active) : 0.0F;
+
+    for (size_t i = 0; i < active; i++)
+        this_out[i] = (1.0F - smoothing) * this_target[i] + uniform;
+}
+
 extern "C" void softmax_across_columns_masked(
     const size_t max_active,
     const size_t rows,
@@ -146,4 +174,16 @@ extern "C" void backprop_softmax_crossentropy_masked(
 {
     const size_t grid_x = (cols + threadsPerBlock - 1) / threadsPerBlock;
     backprop_softmax_cross_entropy_masked_kernel<<<grid_x, threadsPerBlock>>>(max_active, rows, cols, mask, softmaxed, target, out_grad, input_grad);
-}
\ No newline at end of file
+}
+
+extern "C" void smooth_targets_masked(
+    const size_t max_active,
+    const size_t cols,
+    const int32_t* mask,
+    const float smoothing,
+    const float* target,
+    float* out)
+{
+    const size_t grid_x = (cols + threadsPerBlock - 1) / threadsPerBlock;
+    smooth_targets_masked_kernel<<<grid_x, threadsPerBlock>>>(max_active, cols, mask, smoothing, target, out);
+}
@@ -31,6 +31,33 @@ __global__ void softmax_across_columns_naive_kernel(const size_t rows, const siz
     }
 }
 
+__global__ void backprop_softmax_across_columns_kernel(
+    const size_t rows,
+    const size_t cols,
+    const float* softmaxed,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t tid = blockDim.x * blockIdx.x + threadIdx.x;
+
+    if (tid >= cols)
+        return;
+
+    const float* thisColumn = softmaxed + rows * tid;
+    const float* thisOutputGrad = output_grad + rows * tid;
+    float* thisInputGrad = input_grad + rows * tid;
+
+    float dot = 0.0F;
+
+    for (size_t i = 0; i < rows; i++) {
+        dot += thisColumn[i] * thisOutputGrad[i];
+    }
+
+    for (size_t i = 0; i < rows; i++) {
+        thisInputGrad[i] += thisColumn[i] * (thisOutputGrad[i] - dot);
+    }
+}
+
 __global__ void cross_entropy_kernel(const size_t size, const float* pred, const float* target, float* out)
 {
     const size_t i = blockIdx.x * blockDim.x + threadIdx.x;
@@ -62,6 +89,17 @@ extern "C" void softmax_across_columns(const size_t rows, const size_t cols, con
     softmax_across_columns_naive_kernel<<<grid_x, threadsPerBlock>>>(rows, cols, input, output);
 }
 
+extern "C" void backprop_softmax_across_columns(
+    const size_t rows,
+    const size_t cols,
+    const float* softmaxed,
+    const float* output_grad,
+    float* input_grad)
+{
+    const size_t grid_x = (cols + threadsPerBlock - 1) / threadsPerBlock;
+    backprop_softmax_across_columns_kernel<<<grid_x, threadsPerBlock>>>(rows, cols, softmaxed, output_grad, input_grad);
+}
+
 extern "C" void crossentropy(const size_t size, const float* pred, const float* target, float* out)
 {
     const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
@@ -0,0 +1,44 @@
+__global__ void embeddingKernel(const size_t dim, const float* table, const int32_t* indices, float* outputs)
+{
+    const size_t elem = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (elem >= dim)
+        return;
+
+    const size_t slot = blockIdx.y;
+    const int32_t idx = indices[slot];
+    outputs[dim * slot + elem] = (idx == -1) ? 0.0F : table[dim * idx + elem];
+}
+
+// only the columns of the table that were looked up are touched
+__global__ void embeddingBackpropKernel(const size_t dim, const float* output_grads, const int32_t* indices, float* table_grads)
+{
+    const size_t elem = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (elem >= dim)
+        return;
+
+    const size_t slot = blockIdx.y;
+    const int32_t idx = indices[slot];
+
+    if (idx != -1)
+        atomicAdd(&table_grads[dim * idx + elem], output_grads[dim * slot + elem]);
+}
+
+extern "C" void embedding(const size_t dim, const size_t slots, const float* table, const int32_t* indices, float* outputs)
+{
+    const size_t threads = min(dim, threadsPerBlock);
+    const size_t chunks = (dim + threads - 1) / threads;
+    dim3 grid(chunks, slots);
+
+    embeddingKernel<<<grid, threads>>>(dim, table, indices, outputs);
+}
+
+extern "C" void embedding_backprop(const size_t dim, const size_t slots, const float* output_grads, const int32_t* indices, float* table_grads)
+{
+    const size_t threads = min(dim, threadsPerBlock);
+    const size_t chunks = (dim + threads - 1) / threads;
+    dim3 grid(chunks, slots);
+
+    embeddingBackpropKernel<<<grid, threads>>>(dim, output_grads, indices, table_grads);
+}
@@ -0,0 +1,97 @@
+// only the listed columns of the weights are updated
+__global__ void SparseAdamKernel(
+    const size_t dim,
+    const float beta1,
+    const float beta2,
+    const float adj,
+    const float rate,
+    const bool denom,
+    const int32_t* columns,
+    float* network,
+    float* momentum,
+    float* velocity,
+    const float* gradients)
+{
+    const size_t elem = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (elem >= dim)
+        return;
+
+    const size_t slot = blockIdx.y;
+    const size_t i = dim * columns[slot] + elem;
+
+    const float grad = adj * gradients[dim * slot + elem];
+    momentum[i] = beta1 * momentum[i] + (1.0F - beta1) * grad;
+    velocity[i] = beta2 * velocity[i] + (1.0F - beta2) * grad * grad;
+
+    float val = momentum[i];
+    if (denom)
+        val /= sqrt(velocity[i]) + Epsilon;
+    network[i] -= rate * val;
+}
+
+__global__ void SparseScaleClipKernel(
+    const size_t dim,
+    const int32_t* columns,
+    float* params,
+    const float scale,
+    const float min_weight,
+    const float max_weight)
+{
+    const size_t elem = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (elem >= dim)
+        return;
+
+    const size_t i = dim * columns[blockIdx.y] + elem;
+    params[i] = min(max(scale * params[i], min_weight), max_weight);
+}
+
+extern "C" void SparseAdam(
+    const size_t dim,
+    const size_t num_columns,
+    const float beta1,
+    const float beta2,
+    const float adj,
+    const float rate,
+    const bool denom,
+    const int32_t* columns,
+    float* network,
+    float* momentum,
+    float* velocity,
+    const float* gradients)
+{
+    const size_t threads = min(dim, threadsPerBlock);
+    const size_t chunks = (dim + threads - 1) / threads;
+    dim3 grid(chunks, num_columns);
+
+    SparseAdamKernel<<<grid, threads>>>(
+        dim,
+        beta1,
+        beta2,
+        adj,
+        rate,
+        denom,
+        columns,
+        network,
+        momentum,
+        velocity,
+        gradients
+    );
+}
+
+extern "C" void SparseScaleClip(
+    const size_t dim,
+    const size_t num_columns,
+    const int32_t* columns,
+    float* params,
+    const float scale,
+    const float min_weight,
+    const float max_weight)
+{
+    const size_t threads = min(dim, threadsPerBlock);
+    const size_t chunks = (dim + threads - 1) / threads;
+    dim3 grid(chunks, num_columns);
+
+    SparseScaleClipKernel<<<grid, threads>>>(dim, columns, params, scale, min_weight, max_weight);
+}
@@ -0,0 +1,42 @@
+__global__ void sparseOuterKernel(const size_t batch_size, const size_t nnz_a, const int32_t* inputs_a, const size_t size_b, const size_t nnz_b, const int32_t* inputs_b, int32_t* outputs)
+{
+    const size_t elem = blockIdx.x * blockDim.x + threadIdx.x;
+
+    if (elem >= batch_size)
+        return;
+
+    const int32_t* thisA = inputs_a + nnz_a * elem;
+    const int32_t* thisB = inputs_b + nnz_b * elem;
+    int32_t* thisOutput = outputs + nnz_a * nnz_b * elem;
+
+    size_t written = 0;
+
+    for (size_t i = 0; i < nnz_a; i++) {
+        const int32_t a = thisA[i];
+
+        if (a == -1)
+            break;
+
+        for (size_t j = 0; j < nnz_b; j++) {
+            const int32_t b = thisB[j];
+
+            if (b == -1)
+                break;
+
+            thisOutput[written] = a * static_cast<int32_t>(size_b) + b;
+            written++;
+        }
+    }
+
+    for (size_t i = written; i < nnz_a * nnz_b; i++)
+        thisOutput[i] = -1;
+}
+
+extern "C" void sparse_outer(const size_t batch_size, const size_t nnz_a, const int32_t* inputs_a, const size_t size_b, const size_t nnz_b, const int32_t* inputs_b, int32_t* outputs)
+{
+    const size_t max_threads = 1024;
+    const size_t threads = min(batch_size, max_threads);
+    const size_t blocks = (batch_size + threads - 1) / threads;
+
+    sparseOuterKernel<<<blocks, threads>>>(batch_size, nnz_a, inputs_a, size_b, nnz_b, inputs_b, outputs);
+}
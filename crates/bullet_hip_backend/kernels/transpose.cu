@@ -0,0 +1,45 @@
+#include "util.cu"
+#ifdef __HIP_PLATFORM_AMD__
+#include <hip/hip_runtime.h>
+#endif
+
+// Transposes each `rows x cols` column-major matrix in the batch.
+__global__ void transposeKernel(
+    const size_t rows,
+    const size_t cols,
+    const size_t batchSize,
+    const float* input,
+    float* output,
+    const bool add)
+{
+    const size_t tid = blockIdx.x * blockDim.x + threadIdx.x;
+    const size_t size = rows * cols;
+
+    if (tid >= size * batchSize)
+        return;
+
+    const size_t b = tid / size;
+    const size_t r = tid % rows;
+    const size_t c = (tid % size) / rows;
+
+    const size_t outIdx = b * size + r * cols + c;
+    const float val = input[tid];
+
+    if (add)
+        output[outIdx] += val;
+    else
+        output[outIdx] = val;
+}
+
+extern "C" void transpose(
+    const size_t rows,
+    const size_t cols,
+    const size_t batchSize,
+    const float* input,
+    float* output,
+    const bool add)
+{
+    const size_t size = rows * cols * batchSize;
+    const size_t numBlocks = (size + threadsPerBlock - 1) / threadsPerBlock;
+    transposeKernel<<<numBlocks, threadsPerBlock>>>(rows, cols, batchSize, input, output, add);
+}
@@ -12,6 +12,9 @@ __device__ float SCReLU(float in) { return in < 0.0F ? 0.0F : (in > 1.0F ? 1.0F
 __device__ float SqrReLU(float in) { return in < 0.0F ? 0.0F : (in * in); }
 __device__ float sigmoid(float in) { return 1.0F / (1.0F + expf(-in)); }
 __device__ float square(float in) { return in * in; }
+__device__ float absolute(float in) { return fabsf(in); }
+__device__ float GELU(float in) { return 0.5F * in * (1.0F + erff(in * 0.70710678F)); }
+__device__ float SiLU(float in) { return in * sigmoid(in); }
 
 __device__ float primeIdentity([[maybe_unused]] float in) { return 1.0F; }
 __device__ float primeReLU(float in) { return in > 0.0F ? 1.0F : 0.0F; }
@@ -23,6 +26,14 @@ __device__ float primeSigmoid(float in) {
     return act * (1.0F - act);
 }
 __device__ float primeSquare(float in) { return 2.0F * in; }
+__device__ float primeAbsolute(float in) { return in > 0.0F ? 1.0F : (in < 0.0F ? -1.0F : 0.0F); }
+__device__ float primeGELU(float in) {
+    return 0.5F * (1.0F + erff(in * 0.70710678F)) + in * 0.39894228F * expf(-0.5F * in * in);
+}
+__device__ float primeSiLU(float in) {
+    const float act = sigmoid(in);
+    return act * (1.0F + in * (1.0F - act));
+}
 
 __device__ float primeInvIdentity([[maybe_unused]] float in) { return 1.0F; }
 __device__ float primeInvReLU(float in) { return in > 0.0F ? 1.0F : 0.0F; }
@@ -9,15 +9,50 @@ extern "C" {
     pub fn activateSqrReLU(size: usize, inp: *const f32, out: *mut f32);
     pub fn activateSigmoid(size: usize, inp: *const f32, out: *mut f32);
     pub fn activateSquare(size: usize, inp: *const f32, out: *mut f32);
+    pub fn activateAbs(size: usize, inp: *const f32, out: *mut f32);
+    pub fn activateGELU(size: usize, inp: *const f32, out: *mut f32);
+    pub fn activateSiLU(size: usize, inp: *const f32, out: *mut f32);
     pub fn backpropReLU(size: usize, input: *const f32, output_grad: *const f32, input_grad: *mut f32);
     pub fn backpropCReLU(size: usize, input: *const f32, output_grad: *const f32, input_grad: *mut f32);
     pub fn backpropSCReLU(size: usize, input: *const f32, output_grad: *const f32, input_grad: *mut f32);
     pub fn backpropSqrReLU(size: usize, input: *const f32, output_grad: *const f32, input_grad: *mut f32);
     pub fn backpropSigmoid(size: usize, output: *const f32, output_grad: *const f32, input_grad: *mut f32);
     pub fn backpropSquare(size: usize, output: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn backpropAbs(size: usize, input: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn backpropGELU(size: usize, input: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn backpropSiLU(size: usize, input: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn activateClamp(size: usize, min: f32, max: f32, inp: *const f32, out: *mut f32);
+    pub fn backpropClamp(size: usize, min: f32, max: f32, inp: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn biasActivate(size: usize, biasSize: usize, bias: *const f32, out: *mut f32, activation: i32);
+    pub fn backpropActivateFromOutput(size: usize, output: *const f32, output_grad: *const f32, input_grad: *mut f32, activation: i32);
+    pub fn activatePReLU(size: usize, batchSize: usize, inp: *const f32, slope: *const f32, out: *mut f32);
+    pub fn backpropPReLU(size: usize, batchSize: usize, inp: *const f32, slope: *const f32, output_grad: *const f32, input_grad: *mut f32, slope_grad: *mut f32);
+    pub fn elementwiseMinMax(size: usize, isMax: bool, a: *const f32, b: *const f32, out: *mut f32);
+    pub fn backpropElementwiseMinMax(size: usize, isMax: bool, a: *const f32, b: *const f32, output_grad: *const f32, a_grad: *mut f32, b_grad: *mut f32);
+    pub fn elementwiseMul(size: usize, a: *const f32, b: *const f32, out: *mut f32);
+    pub fn backpropElementwiseMul(size: usize, a: *const f32, b: *const f32, output_grad: *const f32, a_grad: *mut f32, b_grad: *mut f32);
+    pub fn elementwiseDiv(size: usize, epsilon: f32, a: *const f32, b: *const f32, out: *mut f32);
+    pub fn backpropElementwiseDiv(size: usize, epsilon: f32, a: *const f32, b: *const f32, output_grad: *const f32, a_grad: *mut f32, b_grad: *mut f32);
+    pub fn avgPool(batchSize: usize, channels: usize, height: usize, width: usize, poolHeight: usize, poolWidth: usize, input: *const f32, output: *mut f32);
+    pub fn backpropAvgPool(batchSize: usize, channels: usize, height: usize, width: usize, poolHeight: usize, poolWidth: usize, output_grad: *const f32, input_grad: *mut f32);
+    pub fn maxPool(batchSize: usize, channels: usize, height: usize, width: usize, poolHeight: usize, poolWidth: usize, input: *const f32, argmax: *mut f32, output: *mut f32);
+    pub fn backpropMaxPool(batchSize: usize, channels: usize, height: usize, width: usize, poolHeight: usize, poolWidth: usize, argmax: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn attention(batchSize: usize, seqLen: usize, dim: usize, q: *const f32, k: *const f32, v: *const f32, mask: *const f32, maskStride: usize, probs: *mut f32, output: *mut f32);
+    pub fn backpropAttention(batchSize: usize, seqLen: usize, dim: usize, q: *const f32, k: *const f32, v: *const f32, probs: *const f32, output_grad: *const f32, scores_grad: *mut f32, q_grad: *mut f32, k_grad: *mut f32, v_grad: *mut f32);
+    pub fn conv2d(batchSize: usize, inChannels: usize, outChannels: usize, height: usize, width: usize, kernelHeight: usize, kernelWidth: usize, filters: *const f32, input: *const f32, output: *mut f32);
+    pub fn backpropConv2dInput(batchSize: usize, inChannels: usize, outChannels: usize, height: usize, width: usize, kernelHeight: usize, kernelWidth: usize, filters: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn backpropConv2dFilters(batchSize: usize, inChannels: usize, outChannels: usize, height: usize, width: usize, kernelHeight: usize, kernelWidth: usize, input: *const f32, output_grad: *const f32, filters_grad: *mut f32);
+    pub fn transpose(rows: usize, cols: usize, batchSize: usize, input: *const f32, output: *mut f32, add: bool);
     pub fn powerError(bufferSize: usize, inputs: *const f32, results: *const f32, output: *mut f32, power: f32);
     pub fn backpropPowerError(bufferSize: usize, inputs: *const f32, results: *const f32, output_grad: *const f32, input_grads: *mut f32, power: f32);
+    pub fn huberError(bufferSize: usize, inputs: *const f32, results: *const f32, output: *mut f32, delta: f32);
+    pub fn backpropHuberError(bufferSize: usize, inputs: *const f32, results: *const f32, output_grad: *const f32, input_grads: *mut f32, delta: f32);
+    pub fn pairwiseRanking(batchSize: usize, margin: f32, preds: *const f32, targets: *const f32, output: *mut f32);
+    pub fn backpropPairwiseRanking(batchSize: usize, margin: f32, preds: *const f32, targets: *const f32, output_grad: *const f32, preds_grad: *mut f32);
+    pub fn sigmoidBCE(bufferSize: usize, logits: *const f32, targets: *const f32, output: *mut f32);
+    pub fn backpropSigmoidBCE(bufferSize: usize, logits: *const f32, targets: *const f32, output_grad: *const f32, logits_grad: *mut f32, targets_grad: *mut f32);
     pub fn Adam(size: usize, beta1: f32, beta2: f32, adj: f32, rate: f32, denom: bool, network: *mut f32, momentum: *mut f32, velocity: *mut f32, gradients: *const f32);
+    pub fn AdaBelief(size: usize, beta1: f32, beta2: f32, adj: f32, rate: f32, network: *mut f32, momentum: *mut f32, belief: *mut f32, gradients: *const f32);
     pub fn sparseAffineForward(batchSize: usize, maxInputSize: usize, outputSize: usize, weights: *const f32, biases: *const f32, inputs: *const i32, outputs: *mut f32);
     pub fn sparseAffineBackward(batchSize: usize, maxInputSize: usize, outputSize: usize, weightsGrad: *mut f32, biasesGrad: *mut f32, inputs: *const i32, outputs: *const f32, errors: *const f32);
     pub fn sparseAffineDualForward(batchSize: usize, maxInputSize: usize, outputSize: usize, weights: *const f32, biases: *const f32, stm: *const i32, ntm: *const i32, outputs: *mut f32, activation: i32);
@@ -27,15 +62,29 @@ extern "C" {
     pub fn selectForward(batchSize: usize, inputSize: usize, outputSize: usize, buckets: *const i32, inp: *const f32, out: *mut f32);
     pub fn selectBackprop(batch_size: usize, input_size: usize, output_size: usize, buckets: *const i32, output_grad: *const f32, input_grad: *mut f32);
     pub fn sparse_to_dense(rows: usize, cols: usize, max_active: usize, inputs: *const i32, outputs: *mut f32);
+    pub fn sparse_outer(batch_size: usize, nnz_a: usize, inputs_a: *const i32, size_b: usize, nnz_b: usize, inputs_b: *const i32, outputs: *mut i32);
     pub fn softmax_across_columns(rows: usize, cols: usize, inp: *const f32, out: *mut f32);
+    pub fn backprop_softmax_across_columns(rows: usize, cols: usize, softmaxed: *const f32, output_grad: *const f32, input_grad: *mut f32);
     pub fn crossentropy(size: usize, pred: *const f32, target: *const f32, out: *mut f32);
     pub fn backprop_softmax_cross_entropy(size: usize, softmaxed: *const f32, target: *const f32, out_grad: *const f32, input_grad: *mut f32);
     pub fn softmax_across_columns_masked(max_active: usize, rows: usize, cols: usize, mask: *const i32, inp: *const f32, out: *mut f32);
     pub fn crossentropy_masked(max_active: usize, cols: usize, mask: *const i32, pred: *const f32, target: *const f32, out: *mut f32, err: *mut f32);
     pub fn backprop_softmax_crossentropy_masked(max_active: usize, rows: usize, cols: usize, mask: *const i32, softmaxed: *const f32, target: *const f32, out_grad: *const f32, input_grad: *mut f32);
+    pub fn smooth_targets_masked(max_active: usize, cols: usize, mask: *const i32, smoothing: f32, target: *const f32, out: *mut f32);
     pub fn sparse_mask(rows: usize, cols: usize, max_active: usize, inputs: *const f32, masks: *const i32, outputs: *mut f32);
     pub fn sparse_mask_backprop(rows: usize, cols: usize, max_active: usize, output_grads: *const f32, masks: *const i32, input_grads: *mut f32);
     pub fn gather(input_rows: usize, output_rows: usize, cols: usize, inputs: *const f32, indices: *const i32, outputs: *mut f32);
+    pub fn embedding(dim: usize, slots: usize, table: *const f32, indices: *const i32, outputs: *mut f32);
+    pub fn embedding_backprop(dim: usize, slots: usize, output_grads: *const f32, indices: *const i32, table_grads: *mut f32);
     pub fn gather_backprop(input_rows: usize, output_rows: usize, cols: usize, output_grads: *const f32, indices: *const i32, input_grads: *mut f32);
+    pub fn layerNorm(rows: usize, cols: usize, input: *const f32, scale: *const f32, shift: *const f32, stats: *mut f32, output: *mut f32);
+    pub fn backpropLayerNormInput(rows: usize, cols: usize, input: *const f32, scale: *const f32, stats: *const f32, output_grad: *const f32, input_grad: *mut f32);
+    pub fn backpropLayerNormParams(rows: usize, cols: usize, input: *const f32, stats: *const f32, output_grad: *const f32, scale_grad: *mut f32, shift_grad: *mut f32);
+    pub fn batchNorm(rows: usize, cols: usize, training: bool, momentum: f32, input: *const f32, scale: *const f32, shift: *const f32, running_mean: *mut f32, running_var: *mut f32, stats: *mut f32, output: *mut f32);
+    pub fn backpropBatchNorm(rows: usize, cols: usize, input: *const f32, scale: *const f32, stats: *const f32, output_grad: *const f32, input_grad: *mut f32, scale_grad: *mut f32, shift_grad: *mut f32);
+    pub fn dropout(size: usize, rate: f32, seed: u32, input: *const f32, mask: *mut f32, output: *mut f32);
+    pub fn backpropDropout(size: usize, mask: *const f32, output_grad: *const f32, input_grad: *mut f32);
     pub fn Clip(size: usize, params: *mut f32, min_weight: f32, max_weight: f32);
+    pub fn SparseAdam(dim: usize, num_columns: usize, beta1: f32, beta2: f32, adj: f32, rate: f32, denom: bool, columns: *const i32, network: *mut f32, momentum: *mut f32, velocity: *mut f32, gradients: *const f32);
+    pub fn SparseScaleClip(dim: usize, num_columns: usize, columns: *const i32, params: *mut f32, scale: f32, min_weight: f32, max_weight: f32);
 }
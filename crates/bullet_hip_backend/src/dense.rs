@@ -1,15 +1,29 @@
 mod activate;
+mod attention;
+mod conv;
+mod dropout;
+mod elementwise;
 mod linear_comb;
+mod norm;
 mod optimiser;
 mod pairwise;
+mod pool;
 mod power_error;
 mod slice;
 mod softmax;
+mod transpose;
 
 pub use activate::*;
+pub use attention::*;
+pub use conv::*;
+pub use dropout::*;
+pub use elementwise::*;
 pub use linear_comb::*;
+pub use norm::*;
 pub use optimiser::*;
 pub use pairwise::*;
+pub use pool::*;
 pub use power_error::*;
 pub use slice::*;
 pub use softmax::*;
+pub use transpose::*;
@@ -1,4 +1,7 @@
-use bullet_core::device::{DeviceBuffer, OperationError};
+use bullet_core::{
+    device::{DeviceBuffer, OperationError},
+    graph::operation::Activation,
+};
 
 use crate::{backend::ops, Buffer, OperationResult};
 
@@ -46,3 +49,133 @@ define_activation!(screlu, screlu_backward, activateSCReLU, backpropSCReLU);
 define_activation!(sqrrelu, sqrrelu_backward, activateSqrReLU, backpropSqrReLU);
 define_activation!(sigmoid, sigmoid_backward, activateSigmoid, backpropSigmoid);
 define_activation!(square, square_backward, activateSquare, backpropSquare);
+define_activation!(abs, abs_backward, activateAbs, backpropAbs);
+define_activation!(gelu, gelu_backward, activateGELU, backpropGELU);
+define_activation!(silu, silu_backward, activateSiLU, backpropSiLU);
+
+pub fn clamp(size: usize, min: f32, max: f32, input: &Buffer<f32>, output: &mut Buffer<f32>) -> OperationResult {
+    if size > input.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::activateClamp(size, min, max, input.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_clamp(
+    size: usize,
+    min: f32,
+    max: f32,
+    input: &Buffer<f32>,
+    input_grad: &mut Buffer<f32>,
+    output_grad: &Buffer<f32>,
+) -> OperationResult {
+    if size > input.size() || size > input_grad.size() || size > output_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::backpropClamp(size, min, max, input.ptr(), output_grad.ptr(), input_grad.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn prelu(
+    size: usize,
+    batch_size: usize,
+    input: &Buffer<f32>,
+    slope: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if size * batch_size > input.size() || size > slope.size() || size * batch_size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::activatePReLU(size, batch_size, input.ptr(), slope.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_prelu(
+    size: usize,
+    batch_size: usize,
+    input: &Buffer<f32>,
+    slope: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_grad: Option<&mut Buffer<f32>>,
+    slope_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    let total = size * batch_size;
+
+    if total > input.size() || size > slope.size() || total > output_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let input_ptr = match input_grad {
+        Some(grad) if total <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    let slope_ptr = match slope_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    unsafe {
+        ops::backpropPReLU(size, batch_size, input.ptr(), slope.ptr(), output_grad.ptr(), input_ptr, slope_ptr);
+    }
+
+    Ok(())
+}
+
+pub fn bias_activate(
+    size: usize,
+    bias_size: usize,
+    bias: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+    activation: Activation,
+) -> OperationResult {
+    if matches!(activation, Activation::Square | Activation::GELU | Activation::SiLU) {
+        return Err(OperationError::UnsupportedOperation(format!("fused {activation:?}")));
+    }
+
+    if bias_size == 0 || size % bias_size != 0 || bias_size > bias.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::biasActivate(size, bias_size, bias.ptr(), output.mut_ptr(), activation as i32);
+    }
+
+    Ok(())
+}
+
+pub fn backprop_activate_from_output(
+    size: usize,
+    output: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_grad: &mut Buffer<f32>,
+    activation: Activation,
+) -> OperationResult {
+    if matches!(activation, Activation::Square | Activation::GELU | Activation::SiLU) {
+        return Err(OperationError::UnsupportedOperation(format!("fused {activation:?}")));
+    }
+
+    if size > output.size() || size > output_grad.size() || size > input_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::backpropActivateFromOutput(size, output.ptr(), output_grad.ptr(), input_grad.mut_ptr(), activation as i32);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use bullet_core::device::{DeviceBuffer, OperationError};
+
+use crate::{backend::ops, Buffer, OperationResult};
+
+#[allow(clippy::too_many_arguments)]
+pub fn attention(
+    batch_size: usize,
+    seq_len: usize,
+    dim: usize,
+    q: &Buffer<f32>,
+    k: &Buffer<f32>,
+    v: &Buffer<f32>,
+    mask: Option<&Buffer<f32>>,
+    mask_batched: bool,
+    probs: &mut Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    let size = batch_size * seq_len * dim;
+    let probs_size = batch_size * seq_len * seq_len;
+
+    if size > q.size() || size > k.size() || size > v.size() || size > output.size() || probs_size > probs.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let mask_stride = if mask_batched { seq_len * seq_len } else { 0 };
+
+    let mask_ptr = match mask {
+        Some(mask) if seq_len * seq_len + (batch_size - 1) * mask_stride <= mask.size() => mask.ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null(),
+    };
+
+    unsafe {
+        ops::attention(
+            batch_size,
+            seq_len,
+            dim,
+            q.ptr(),
+            k.ptr(),
+            v.ptr(),
+            mask_ptr,
+            mask_stride,
+            probs.mut_ptr(),
+            output.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn backprop_attention(
+    batch_size: usize,
+    seq_len: usize,
+    dim: usize,
+    q: &Buffer<f32>,
+    k: &Buffer<f32>,
+    v: &Buffer<f32>,
+    probs: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    scores_grad: &mut Buffer<f32>,
+    q_grad: Option<&mut Buffer<f32>>,
+    k_grad: Option<&mut Buffer<f32>>,
+    v_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    let size = batch_size * seq_len * dim;
+    let probs_size = batch_size * seq_len * seq_len;
+
+    if size > q.size()
+        || size > k.size()
+        || size > v.size()
+        || size > output_grad.size()
+        || probs_size > probs.size()
+        || probs_size > scores_grad.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let q_ptr = match q_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    let k_ptr = match k_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    let v_ptr = match v_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    unsafe {
+        ops::backpropAttention(
+            batch_size,
+            seq_len,
+            dim,
+            q.ptr(),
+            k.ptr(),
+            v.ptr(),
+            probs.ptr(),
+            output_grad.ptr(),
+            scores_grad.mut_ptr(),
+            q_ptr,
+            k_ptr,
+            v_ptr,
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use bullet_core::{
+    device::{DeviceBuffer, OperationError},
+    graph::operation::ConvSettings,
+};
+
+use crate::{backend::ops, Buffer, OperationResult};
+
+pub fn conv2d(
+    batch_size: usize,
+    settings: ConvSettings,
+    filters: &Buffer<f32>,
+    input: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if settings.filters_size() > filters.size()
+        || batch_size * settings.input_size() > input.size()
+        || batch_size * settings.output_size() > output.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let ConvSettings { in_channels, out_channels, height, width, kernel_height, kernel_width } = settings;
+
+    unsafe {
+        ops::conv2d(
+            batch_size,
+            in_channels,
+            out_channels,
+            height,
+            width,
+            kernel_height,
+            kernel_width,
+            filters.ptr(),
+            input.ptr(),
+            output.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
+
+pub fn backprop_conv2d(
+    batch_size: usize,
+    settings: ConvSettings,
+    filters: &Buffer<f32>,
+    input: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    filters_grad: Option<&mut Buffer<f32>>,
+    input_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    let input_size = batch_size * settings.input_size();
+
+    if settings.filters_size() > filters.size()
+        || input_size > input.size()
+        || batch_size * settings.output_size() > output_grad.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let ConvSettings { in_channels, out_channels, height, width, kernel_height, kernel_width } = settings;
+
+    if let Some(grad) = filters_grad {
+        if settings.filters_size() > grad.size() {
+            return Err(OperationError::IndexOutOfBounds);
+        }
+
+        unsafe {
+            ops::backpropConv2dFilters(
+                batch_size,
+                in_channels,
+                out_channels,
+                height,
+                width,
+                kernel_height,
+                kernel_width,
+                input.ptr(),
+                output_grad.ptr(),
+                grad.mut_ptr(),
+            );
+        }
+    }
+
+    if let Some(grad) = input_grad {
+        if input_size > grad.size() {
+            return Err(OperationError::IndexOutOfBounds);
+        }
+
+        unsafe {
+            ops::backpropConv2dInput(
+                batch_size,
+                in_channels,
+                out_channels,
+                height,
+                width,
+                kernel_height,
+                kernel_width,
+                filters.ptr(),
+                output_grad.ptr(),
+                grad.mut_ptr(),
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use bullet_core::device::{DeviceBuffer, OperationError};
+
+use crate::{
+    backend::{ops, Buffer},
+    OperationResult,
+};
+
+pub fn dropout(
+    size: usize,
+    rate: f32,
+    seed: u32,
+    input: &Buffer<f32>,
+    mask: &mut Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > input.size() || size > mask.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::dropout(size, rate, seed, input.ptr(), mask.mut_ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_dropout(
+    size: usize,
+    mask: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_grad: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > mask.size() || size > output_grad.size() || size > input_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::backpropDropout(size, mask.ptr(), output_grad.ptr(), input_grad.mut_ptr());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use bullet_core::device::{DeviceBuffer, OperationError};
+
+use crate::{backend::ops, Buffer, OperationResult};
+
+pub fn elementwise_min_max(
+    size: usize,
+    max: bool,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::elementwiseMinMax(size, max, input_a.ptr(), input_b.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_elementwise_min_max(
+    size: usize,
+    max: bool,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_a_grad: Option<&mut Buffer<f32>>,
+    input_b_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let a_ptr = match input_a_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    let b_ptr = match input_b_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    unsafe {
+        ops::backpropElementwiseMinMax(size, max, input_a.ptr(), input_b.ptr(), output_grad.ptr(), a_ptr, b_ptr);
+    }
+
+    Ok(())
+}
+
+pub fn elementwise_mul(
+    size: usize,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::elementwiseMul(size, input_a.ptr(), input_b.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_elementwise_mul(
+    size: usize,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_a_grad: Option<&mut Buffer<f32>>,
+    input_b_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let a_ptr = match input_a_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    let b_ptr = match input_b_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    unsafe {
+        ops::backpropElementwiseMul(size, input_a.ptr(), input_b.ptr(), output_grad.ptr(), a_ptr, b_ptr);
+    }
+
+    Ok(())
+}
+
+pub fn elementwise_div(
+    size: usize,
+    epsilon: f32,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::elementwiseDiv(size, epsilon, input_a.ptr(), input_b.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_elementwise_div(
+    size: usize,
+    epsilon: f32,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_a_grad: Option<&mut Buffer<f32>>,
+    input_b_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let a_ptr = match input_a_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    let b_ptr = match input_b_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    unsafe {
+        ops::backpropElementwiseDiv(size, epsilon, input_a.ptr(), input_b.ptr(), output_grad.ptr(), a_ptr, b_ptr);
+    }
+
+    Ok(())
+}
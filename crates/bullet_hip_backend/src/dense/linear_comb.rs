@@ -63,10 +63,12 @@ pub fn reduce_add(
     ones: &Buffer<f32>,
     size: usize,
     batch_size: usize,
+    alpha: f32,
     input: &Buffer<f32>,
     output: &mut Buffer<f32>,
+    increment: bool,
 ) -> OperationResult {
-    if size * batch_size > input.size() || size > output.size() {
+    if size * batch_size > input.size() || size > output.size() || batch_size > ones.size() {
         return Err(OperationError::IndexOutOfBounds);
     }
 
@@ -78,8 +80,8 @@ pub fn reduce_add(
             ones.ptr(),
             input.ptr(),
             output.mut_ptr(),
-            1.0,
-            false,
+            alpha,
+            increment,
         );
 
         Ok(catch_cublas(err)?)
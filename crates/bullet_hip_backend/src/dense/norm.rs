@@ -0,0 +1,198 @@
+use bullet_core::device::{DeviceBuffer, OperationError};
+
+use crate::{
+    backend::{ops, Buffer},
+    DeviceError, OperationResult,
+};
+
+fn grad_ptr(grad: Option<&mut Buffer<f32>>, size: usize) -> Result<*mut f32, OperationError<DeviceError>> {
+    if let Some(grad) = grad {
+        if size > grad.size() {
+            return Err(OperationError::IndexOutOfBounds);
+        }
+
+        Ok(grad.mut_ptr())
+    } else {
+        Ok(std::ptr::null_mut())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn layer_norm(
+    batch_size: usize,
+    single_size: usize,
+    input: &Buffer<f32>,
+    scale: &Buffer<f32>,
+    shift: &Buffer<f32>,
+    stats: &mut Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    let size = batch_size * single_size;
+
+    if size > input.size()
+        || size > output.size()
+        || single_size > scale.size()
+        || single_size > shift.size()
+        || 2 * batch_size > stats.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::layerNorm(
+            single_size,
+            batch_size,
+            input.ptr(),
+            scale.ptr(),
+            shift.ptr(),
+            stats.mut_ptr(),
+            output.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn backprop_layer_norm(
+    batch_size: usize,
+    single_size: usize,
+    input: &Buffer<f32>,
+    scale: &Buffer<f32>,
+    stats: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_grad: Option<&mut Buffer<f32>>,
+    scale_grad: Option<&mut Buffer<f32>>,
+    shift_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    let size = batch_size * single_size;
+
+    if size > input.size() || size > output_grad.size() || single_size > scale.size() || 2 * batch_size > stats.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    if let Some(grad) = input_grad {
+        if size > grad.size() {
+            return Err(OperationError::IndexOutOfBounds);
+        }
+
+        unsafe {
+            ops::backpropLayerNormInput(
+                single_size,
+                batch_size,
+                input.ptr(),
+                scale.ptr(),
+                stats.ptr(),
+                output_grad.ptr(),
+                grad.mut_ptr(),
+            );
+        }
+    }
+
+    if scale_grad.is_none() && shift_grad.is_none() {
+        return Ok(());
+    }
+
+    let scale_ptr = grad_ptr(scale_grad, single_size)?;
+    let shift_ptr = grad_ptr(shift_grad, single_size)?;
+
+    unsafe {
+        ops::backpropLayerNormParams(
+            single_size,
+            batch_size,
+            input.ptr(),
+            stats.ptr(),
+            output_grad.ptr(),
+            scale_ptr,
+            shift_ptr,
+        );
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn batch_norm(
+    batch_size: usize,
+    single_size: usize,
+    training: bool,
+    momentum: f32,
+    input: &Buffer<f32>,
+    scale: &Buffer<f32>,
+    shift: &Buffer<f32>,
+    running_mean: &mut Buffer<f32>,
+    running_var: &mut Buffer<f32>,
+    stats: &mut Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    let size = batch_size * single_size;
+
+    if size > input.size()
+        || size > output.size()
+        || single_size > scale.size()
+        || single_size > shift.size()
+        || single_size > running_mean.size()
+        || single_size > running_var.size()
+        || 2 * single_size > stats.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::batchNorm(
+            single_size,
+            batch_size,
+            training,
+            momentum,
+            input.ptr(),
+            scale.ptr(),
+            shift.ptr(),
+            running_mean.mut_ptr(),
+            running_var.mut_ptr(),
+            stats.mut_ptr(),
+            output.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn backprop_batch_norm(
+    batch_size: usize,
+    single_size: usize,
+    input: &Buffer<f32>,
+    scale: &Buffer<f32>,
+    stats: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_grad: Option<&mut Buffer<f32>>,
+    scale_grad: Option<&mut Buffer<f32>>,
+    shift_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    let size = batch_size * single_size;
+
+    if size > input.size() || size > output_grad.size() || single_size > scale.size() || 2 * single_size > stats.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let input_ptr = grad_ptr(input_grad, size)?;
+    let scale_ptr = grad_ptr(scale_grad, single_size)?;
+    let shift_ptr = grad_ptr(shift_grad, single_size)?;
+
+    unsafe {
+        ops::backpropBatchNorm(
+            single_size,
+            batch_size,
+            input.ptr(),
+            scale.ptr(),
+            stats.ptr(),
+            output_grad.ptr(),
+            input_ptr,
+            scale_ptr,
+            shift_ptr,
+        );
+    }
+
+    Ok(())
+}
@@ -40,6 +40,39 @@ pub fn adam(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn adabelief(
+    size: usize,
+    params: &mut Buffer<f32>,
+    gradient: &Buffer<f32>,
+    momentum: &mut Buffer<f32>,
+    belief: &mut Buffer<f32>,
+    beta1: f32,
+    beta2: f32,
+    gradient_factor: f32,
+    learning_rate: f32,
+) -> OperationResult {
+    if size > params.size() || size > gradient.size() || size > momentum.size() || size > belief.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::AdaBelief(
+            size,
+            beta1,
+            beta2,
+            gradient_factor,
+            learning_rate,
+            params.mut_ptr(),
+            momentum.mut_ptr(),
+            belief.mut_ptr(),
+            gradient.ptr(),
+        );
+    }
+
+    Ok(())
+}
+
 pub fn clip(size: usize, params: &mut Buffer<f32>, min: f32, max: f32) -> OperationResult {
     if size > params.size() {
         return Err(OperationError::IndexOutOfBounds);
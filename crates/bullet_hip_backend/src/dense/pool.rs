@@ -0,0 +1,122 @@
+use bullet_core::{
+    device::{DeviceBuffer, OperationError},
+    graph::operation::PoolSettings,
+};
+
+use crate::{backend::ops, Buffer, OperationResult};
+
+pub fn avg_pool(
+    batch_size: usize,
+    settings: PoolSettings,
+    input: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if batch_size * settings.input_size() > input.size() || batch_size * settings.output_size() > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let PoolSettings { channels, height, width, pool_height, pool_width } = settings;
+
+    unsafe {
+        ops::avgPool(batch_size, channels, height, width, pool_height, pool_width, input.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_avg_pool(
+    batch_size: usize,
+    settings: PoolSettings,
+    output_grad: &Buffer<f32>,
+    input_grad: &mut Buffer<f32>,
+) -> OperationResult {
+    if batch_size * settings.input_size() > input_grad.size()
+        || batch_size * settings.output_size() > output_grad.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let PoolSettings { channels, height, width, pool_height, pool_width } = settings;
+
+    unsafe {
+        ops::backpropAvgPool(
+            batch_size,
+            channels,
+            height,
+            width,
+            pool_height,
+            pool_width,
+            output_grad.ptr(),
+            input_grad.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
+
+pub fn max_pool(
+    batch_size: usize,
+    settings: PoolSettings,
+    input: &Buffer<f32>,
+    argmax: &mut Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    let output_size = batch_size * settings.output_size();
+
+    if batch_size * settings.input_size() > input.size() || output_size > output.size() || output_size > argmax.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let PoolSettings { channels, height, width, pool_height, pool_width } = settings;
+
+    unsafe {
+        ops::maxPool(
+            batch_size,
+            channels,
+            height,
+            width,
+            pool_height,
+            pool_width,
+            input.ptr(),
+            argmax.mut_ptr(),
+            output.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
+
+pub fn backprop_max_pool(
+    batch_size: usize,
+    settings: PoolSettings,
+    argmax: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_grad: &mut Buffer<f32>,
+) -> OperationResult {
+    let output_size = batch_size * settings.output_size();
+
+    if batch_size * settings.input_size() > input_grad.size()
+        || output_size > output_grad.size()
+        || output_size > argmax.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let PoolSettings { channels, height, width, pool_height, pool_width } = settings;
+
+    unsafe {
+        ops::backpropMaxPool(
+            batch_size,
+            channels,
+            height,
+            width,
+            pool_height,
+            pool_width,
+            argmax.ptr(),
+            output_grad.ptr(),
+            input_grad.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
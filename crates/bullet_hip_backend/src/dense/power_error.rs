@@ -41,3 +41,136 @@ pub fn backprop_abs_power_error_single(
 
     Ok(())
 }
+
+pub fn huber_error(
+    delta: f32,
+    size: usize,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::huberError(size, input_a.ptr(), input_b.ptr(), output.mut_ptr(), delta);
+    }
+
+    Ok(())
+}
+
+pub fn backprop_huber_error_single(
+    delta: f32,
+    size: usize,
+    input_a: &Buffer<f32>,
+    input_b: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_a_grad: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > input_a.size() || size > input_b.size() || size > output_grad.size() || size > input_a_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::backpropHuberError(size, input_a.ptr(), input_b.ptr(), output_grad.ptr(), input_a_grad.mut_ptr(), delta);
+    }
+
+    Ok(())
+}
+
+pub fn sigmoid_bce(
+    size: usize,
+    logits: &Buffer<f32>,
+    targets: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if size > logits.size() || size > targets.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::sigmoidBCE(size, logits.ptr(), targets.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_sigmoid_bce(
+    size: usize,
+    logits: &Buffer<f32>,
+    targets: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    logits_grad: Option<&mut Buffer<f32>>,
+    targets_grad: Option<&mut Buffer<f32>>,
+) -> OperationResult {
+    if size > logits.size() || size > targets.size() || size > output_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    let logits_ptr = match logits_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    let targets_ptr = match targets_grad {
+        Some(grad) if size <= grad.size() => grad.mut_ptr(),
+        Some(_) => return Err(OperationError::IndexOutOfBounds),
+        None => std::ptr::null_mut(),
+    };
+
+    unsafe {
+        ops::backpropSigmoidBCE(size, logits.ptr(), targets.ptr(), output_grad.ptr(), logits_ptr, targets_ptr);
+    }
+
+    Ok(())
+}
+
+pub fn pairwise_ranking(
+    batch_size: usize,
+    margin: f32,
+    preds: &Buffer<f32>,
+    targets: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    if batch_size > preds.size() || batch_size > targets.size() || batch_size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::pairwiseRanking(batch_size, margin, preds.ptr(), targets.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_pairwise_ranking(
+    batch_size: usize,
+    margin: f32,
+    preds: &Buffer<f32>,
+    targets: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    preds_grad: &mut Buffer<f32>,
+) -> OperationResult {
+    if batch_size > preds.size()
+        || batch_size > targets.size()
+        || batch_size > output_grad.size()
+        || batch_size > preds_grad.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::backpropPairwiseRanking(
+            batch_size,
+            margin,
+            preds.ptr(),
+            targets.ptr(),
+            output_grad.ptr(),
+            preds_grad.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
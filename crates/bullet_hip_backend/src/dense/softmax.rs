@@ -18,6 +18,32 @@ pub fn softmax_across_batch(
     Ok(())
 }
 
+pub fn backprop_softmax_across_batch(
+    batch_size: usize,
+    single_size: usize,
+    softmaxed: &Buffer<f32>,
+    output_grad: &Buffer<f32>,
+    input_grad: &mut Buffer<f32>,
+) -> OperationResult {
+    let size = batch_size * single_size;
+
+    if size > softmaxed.size() || size > output_grad.size() || size > input_grad.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::backprop_softmax_across_columns(
+            single_size,
+            batch_size,
+            softmaxed.ptr(),
+            output_grad.ptr(),
+            input_grad.mut_ptr(),
+        );
+    }
+
+    Ok(())
+}
+
 pub fn crossentropy(
     size: usize,
     pred: &Buffer<f32>,
@@ -0,0 +1,24 @@
+use bullet_core::device::{DeviceBuffer, OperationError};
+
+use crate::{backend::ops, Buffer, OperationResult};
+
+pub fn transpose(
+    rows: usize,
+    cols: usize,
+    batch_size: usize,
+    input: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+    add: bool,
+) -> OperationResult {
+    let size = rows * cols * batch_size;
+
+    if size > input.size() || size > output.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::transpose(rows, cols, batch_size, input.ptr(), output.mut_ptr(), add);
+    }
+
+    Ok(())
+}
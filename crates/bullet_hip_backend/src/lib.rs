@@ -11,7 +11,7 @@ use backend::{bindings, util, Buffer};
 
 use bullet_core::{
     device::{Device, OperationError},
-    graph::operation::Activation,
+    graph::operation::{Activation, ConvSettings, PoolSettings},
     shape::Shape,
     tensor,
 };
@@ -81,6 +81,8 @@ impl Device for ExecutionContext {
             Activation::SqrReLU => dense::sqrrelu(size, input, output),
             Activation::Sigmoid => dense::sigmoid(size, input, output),
             Activation::Square => dense::square(size, input, output),
+            Activation::GELU => dense::gelu(size, input, output),
+            Activation::SiLU => dense::silu(size, input, output),
         }
     }
 
@@ -99,9 +101,256 @@ impl Device for ExecutionContext {
             Activation::SqrReLU => dense::sqrrelu_backward(size, input, input_grad, output_grad),
             Activation::Sigmoid => dense::sigmoid_backward(size, input, input_grad, output_grad),
             Activation::Square => dense::square_backward(size, input, input_grad, output_grad),
+            Activation::GELU => dense::gelu_backward(size, input, input_grad, output_grad),
+            Activation::SiLU => dense::silu_backward(size, input, input_grad, output_grad),
         }
     }
 
+    fn bias_activate(
+        size: usize,
+        bias_size: usize,
+        bias: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+        activation: Activation,
+    ) -> OperationResult {
+        dense::bias_activate(size, bias_size, bias, output, activation)
+    }
+
+    fn backprop_activate_from_output(
+        size: usize,
+        output: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+        activation: Activation,
+    ) -> OperationResult {
+        dense::backprop_activate_from_output(size, output, output_grad, input_grad, activation)
+    }
+
+    fn abs(size: usize, input: &Self::BufferF32, output: &mut Self::BufferF32) -> OperationResult {
+        dense::abs(size, input, output)
+    }
+
+    fn backprop_abs(
+        size: usize,
+        input: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+        output_grad: &Self::BufferF32,
+    ) -> OperationResult {
+        dense::abs_backward(size, input, input_grad, output_grad)
+    }
+
+    fn elementwise_min_max(
+        size: usize,
+        max: bool,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::elementwise_min_max(size, max, input_a, input_b, output)
+    }
+
+    fn backprop_elementwise_min_max(
+        size: usize,
+        max: bool,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: Option<&mut Self::BufferF32>,
+        input_b_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_elementwise_min_max(size, max, input_a, input_b, output_grad, input_a_grad, input_b_grad)
+    }
+
+    fn elementwise_mul(
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::elementwise_mul(size, input_a, input_b, output)
+    }
+
+    fn backprop_elementwise_mul(
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: Option<&mut Self::BufferF32>,
+        input_b_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_elementwise_mul(size, input_a, input_b, output_grad, input_a_grad, input_b_grad)
+    }
+
+    fn elementwise_div(
+        size: usize,
+        epsilon: f32,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::elementwise_div(size, epsilon, input_a, input_b, output)
+    }
+
+    fn backprop_elementwise_div(
+        size: usize,
+        epsilon: f32,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: Option<&mut Self::BufferF32>,
+        input_b_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_elementwise_div(size, epsilon, input_a, input_b, output_grad, input_a_grad, input_b_grad)
+    }
+
+    fn avg_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::avg_pool(batch_size, settings, input, output)
+    }
+
+    fn backprop_avg_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::backprop_avg_pool(batch_size, settings, output_grad, input_grad)
+    }
+
+    fn max_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        input: &Self::BufferF32,
+        argmax: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::max_pool(batch_size, settings, input, argmax, output)
+    }
+
+    fn backprop_max_pool(
+        batch_size: usize,
+        settings: PoolSettings,
+        argmax: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::backprop_max_pool(batch_size, settings, argmax, output_grad, input_grad)
+    }
+
+    fn attention(
+        batch_size: usize,
+        seq_len: usize,
+        dim: usize,
+        q: &Self::BufferF32,
+        k: &Self::BufferF32,
+        v: &Self::BufferF32,
+        mask: Option<&Self::BufferF32>,
+        mask_batched: bool,
+        probs: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::attention(batch_size, seq_len, dim, q, k, v, mask, mask_batched, probs, output)
+    }
+
+    fn backprop_attention(
+        batch_size: usize,
+        seq_len: usize,
+        dim: usize,
+        q: &Self::BufferF32,
+        k: &Self::BufferF32,
+        v: &Self::BufferF32,
+        probs: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        scores_grad: &mut Self::BufferF32,
+        q_grad: Option<&mut Self::BufferF32>,
+        k_grad: Option<&mut Self::BufferF32>,
+        v_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_attention(
+            batch_size,
+            seq_len,
+            dim,
+            q,
+            k,
+            v,
+            probs,
+            output_grad,
+            scores_grad,
+            q_grad,
+            k_grad,
+            v_grad,
+        )
+    }
+
+    fn conv2d(
+        batch_size: usize,
+        settings: ConvSettings,
+        filters: &Self::BufferF32,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::conv2d(batch_size, settings, filters, input, output)
+    }
+
+    fn backprop_conv2d(
+        batch_size: usize,
+        settings: ConvSettings,
+        filters: &Self::BufferF32,
+        input: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        filters_grad: Option<&mut Self::BufferF32>,
+        input_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_conv2d(batch_size, settings, filters, input, output_grad, filters_grad, input_grad)
+    }
+
+    fn clamp(
+        size: usize,
+        min: f32,
+        max: f32,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::clamp(size, min, max, input, output)
+    }
+
+    fn backprop_clamp(
+        size: usize,
+        min: f32,
+        max: f32,
+        input: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+        output_grad: &Self::BufferF32,
+    ) -> OperationResult {
+        dense::backprop_clamp(size, min, max, input, input_grad, output_grad)
+    }
+
+    fn prelu(
+        size: usize,
+        batch_size: usize,
+        input: &Self::BufferF32,
+        slope: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::prelu(size, batch_size, input, slope, output)
+    }
+
+    fn backprop_prelu(
+        size: usize,
+        batch_size: usize,
+        input: &Self::BufferF32,
+        slope: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: Option<&mut Self::BufferF32>,
+        slope_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_prelu(size, batch_size, input, slope, output_grad, input_grad, slope_grad)
+    }
+
     fn add_assign_single_to_batched_scaled(
         single_size: usize,
         batch_size: usize,
@@ -246,6 +495,17 @@ impl Device for ExecutionContext {
         )
     }
 
+    fn transpose(
+        rows: usize,
+        cols: usize,
+        batch_size: usize,
+        input: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+        add: bool,
+    ) -> OperationResult {
+        dense::transpose(rows, cols, batch_size, input, output, add)
+    }
+
     fn pairwise(
         single_size: usize,
         batch_size: usize,
@@ -311,6 +571,20 @@ impl Device for ExecutionContext {
         dense::adam(size, params, gradient, momentum, velocity, beta1, beta2, gradient_factor, learning_rate, denom)
     }
 
+    fn adabelief(
+        size: usize,
+        params: &mut Self::BufferF32,
+        gradient: &Self::BufferF32,
+        momentum: &mut Self::BufferF32,
+        belief: &mut Self::BufferF32,
+        beta1: f32,
+        beta2: f32,
+        gradient_factor: f32,
+        learning_rate: f32,
+    ) -> OperationResult {
+        dense::adabelief(size, params, gradient, momentum, belief, beta1, beta2, gradient_factor, learning_rate)
+    }
+
     fn linear_comb_single(
         size: usize,
         alpha: f32,
@@ -326,10 +600,12 @@ impl Device for ExecutionContext {
         ones: &Self::BufferF32,
         size: usize,
         batch_size: usize,
+        alpha: f32,
         input: &Self::BufferF32,
         output: &mut Self::BufferF32,
+        increment: bool,
     ) -> OperationResult {
-        dense::reduce_add(ones, size, batch_size, input, output)
+        dense::reduce_add(ones, size, batch_size, alpha, input, output, increment)
     }
 
     fn select(
@@ -364,6 +640,18 @@ impl Device for ExecutionContext {
         sparse::sparse_to_dense(batch_size, size, nnz, sparse, dense)
     }
 
+    fn sparse_outer(
+        batch_size: usize,
+        nnz_a: usize,
+        input_a: &Self::BufferI32,
+        size_b: usize,
+        nnz_b: usize,
+        input_b: &Self::BufferI32,
+        output: &mut Self::BufferI32,
+    ) -> OperationResult {
+        sparse::sparse_outer(batch_size, nnz_a, input_a, size_b, nnz_b, input_b, output)
+    }
+
     fn softmax_across_batch(
         batch_size: usize,
         single_size: usize,
@@ -373,6 +661,16 @@ impl Device for ExecutionContext {
         dense::softmax_across_batch(batch_size, single_size, input, output)
     }
 
+    fn backprop_softmax_across_batch(
+        batch_size: usize,
+        single_size: usize,
+        softmaxed: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::backprop_softmax_across_batch(batch_size, single_size, softmaxed, output_grad, input_grad)
+    }
+
     fn crossentropy(
         size: usize,
         pred: &Self::BufferF32,
@@ -436,6 +734,30 @@ impl Device for ExecutionContext {
         sparse::backprop_gather(batch_size, input_size, output_size, output_grads, indices, input_grads)
     }
 
+    fn embedding(
+        batch_size: usize,
+        dim: usize,
+        vocab: usize,
+        nnz: usize,
+        table: &Self::BufferF32,
+        indices: &Self::BufferI32,
+        outputs: &mut Self::BufferF32,
+    ) -> OperationResult {
+        sparse::embedding(batch_size, dim, vocab, nnz, table, indices, outputs)
+    }
+
+    fn backprop_embedding(
+        batch_size: usize,
+        dim: usize,
+        vocab: usize,
+        nnz: usize,
+        output_grads: &Self::BufferF32,
+        indices: &Self::BufferI32,
+        table_grads: &mut Self::BufferF32,
+    ) -> OperationResult {
+        sparse::backprop_embedding(batch_size, dim, vocab, nnz, output_grads, indices, table_grads)
+    }
+
     fn softmax_across_batch_masked(
         batch_size: usize,
         single_size: usize,
@@ -482,7 +804,230 @@ impl Device for ExecutionContext {
         )
     }
 
+    fn smooth_targets_masked(
+        batch_size: usize,
+        nnz: usize,
+        masks: &Self::BufferI32,
+        smoothing: f32,
+        target: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        sparse::smooth_targets_masked(batch_size, nnz, masks, smoothing, target, output)
+    }
+
+    fn layer_norm(
+        batch_size: usize,
+        single_size: usize,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        shift: &Self::BufferF32,
+        stats: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::layer_norm(batch_size, single_size, input, scale, shift, stats, output)
+    }
+
+    fn backprop_layer_norm(
+        batch_size: usize,
+        single_size: usize,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        stats: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: Option<&mut Self::BufferF32>,
+        scale_grad: Option<&mut Self::BufferF32>,
+        shift_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_layer_norm(
+            batch_size,
+            single_size,
+            input,
+            scale,
+            stats,
+            output_grad,
+            input_grad,
+            scale_grad,
+            shift_grad,
+        )
+    }
+
+    fn batch_norm(
+        batch_size: usize,
+        single_size: usize,
+        training: bool,
+        momentum: f32,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        shift: &Self::BufferF32,
+        running_mean: &mut Self::BufferF32,
+        running_var: &mut Self::BufferF32,
+        stats: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::batch_norm(
+            batch_size,
+            single_size,
+            training,
+            momentum,
+            input,
+            scale,
+            shift,
+            running_mean,
+            running_var,
+            stats,
+            output,
+        )
+    }
+
+    fn backprop_batch_norm(
+        batch_size: usize,
+        single_size: usize,
+        input: &Self::BufferF32,
+        scale: &Self::BufferF32,
+        stats: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: Option<&mut Self::BufferF32>,
+        scale_grad: Option<&mut Self::BufferF32>,
+        shift_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_batch_norm(
+            batch_size,
+            single_size,
+            input,
+            scale,
+            stats,
+            output_grad,
+            input_grad,
+            scale_grad,
+            shift_grad,
+        )
+    }
+
+    fn dropout(
+        size: usize,
+        rate: f32,
+        seed: u32,
+        input: &Self::BufferF32,
+        mask: &mut Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::dropout(size, rate, seed, input, mask, output)
+    }
+
+    fn backprop_dropout(
+        size: usize,
+        mask: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_grad: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::backprop_dropout(size, mask, output_grad, input_grad)
+    }
+
+    fn huber_error(
+        delta: f32,
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::huber_error(delta, size, input_a, input_b, output)
+    }
+
+    fn backprop_huber_error_single(
+        delta: f32,
+        size: usize,
+        input_a: &Self::BufferF32,
+        input_b: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        input_a_grad: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::backprop_huber_error_single(delta, size, input_a, input_b, output_grad, input_a_grad)
+    }
+
+    fn pairwise_ranking(
+        batch_size: usize,
+        margin: f32,
+        preds: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::pairwise_ranking(batch_size, margin, preds, targets, output)
+    }
+
+    fn backprop_pairwise_ranking(
+        batch_size: usize,
+        margin: f32,
+        preds: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        preds_grad: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::backprop_pairwise_ranking(batch_size, margin, preds, targets, output_grad, preds_grad)
+    }
+
+    fn sigmoid_bce(
+        size: usize,
+        logits: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output: &mut Self::BufferF32,
+    ) -> OperationResult {
+        dense::sigmoid_bce(size, logits, targets, output)
+    }
+
+    fn backprop_sigmoid_bce(
+        size: usize,
+        logits: &Self::BufferF32,
+        targets: &Self::BufferF32,
+        output_grad: &Self::BufferF32,
+        logits_grad: Option<&mut Self::BufferF32>,
+        targets_grad: Option<&mut Self::BufferF32>,
+    ) -> OperationResult {
+        dense::backprop_sigmoid_bce(size, logits, targets, output_grad, logits_grad, targets_grad)
+    }
+
     fn clip(size: usize, params: &mut Self::BufferF32, min: f32, max: f32) -> OperationResult {
         dense::clip(size, params, min, max)
     }
+
+    fn sparse_adam(
+        dim: usize,
+        num_columns: usize,
+        columns: &Self::BufferI32,
+        params: &mut Self::BufferF32,
+        gradient: &Self::BufferF32,
+        momentum: &mut Self::BufferF32,
+        velocity: &mut Self::BufferF32,
+        beta1: f32,
+        beta2: f32,
+        gradient_factor: f32,
+        learning_rate: f32,
+        denom: bool,
+    ) -> OperationResult {
+        sparse::sparse_adam(
+            dim,
+            num_columns,
+            columns,
+            params,
+            gradient,
+            momentum,
+            velocity,
+            beta1,
+            beta2,
+            gradient_factor,
+            learning_rate,
+            denom,
+        )
+    }
+
+    fn sparse_scale_clip(
+        dim: usize,
+        num_columns: usize,
+        columns: &Self::BufferI32,
+        params: &mut Self::BufferF32,
+        scale: f32,
+        min: f32,
+        max: f32,
+    ) -> OperationResult {
+        sparse::sparse_scale_clip(dim, num_columns, columns, params, scale, min, max)
+    }
 }
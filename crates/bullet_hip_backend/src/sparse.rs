@@ -1,15 +1,19 @@
 mod affine;
 mod affine_dual;
+mod embedding;
 mod gather;
 mod mask;
+mod optimiser;
 mod select;
 mod softmax;
 
 pub use affine::*;
 pub use affine_dual::*;
 use bullet_core::device::{DeviceBuffer, OperationError};
+pub use embedding::*;
 pub use gather::*;
 pub use mask::*;
+pub use optimiser::*;
 pub use select::*;
 pub use softmax::*;
 
@@ -37,3 +41,26 @@ pub fn sparse_to_dense(
 
     Ok(())
 }
+
+pub fn sparse_outer(
+    batch_size: usize,
+    nnz_a: usize,
+    input_a: &Buffer<i32>,
+    size_b: usize,
+    nnz_b: usize,
+    input_b: &Buffer<i32>,
+    output: &mut Buffer<i32>,
+) -> OperationResult {
+    if batch_size * nnz_a > input_a.size()
+        || batch_size * nnz_b > input_b.size()
+        || batch_size * nnz_a * nnz_b > output.size()
+    {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::sparse_outer(batch_size, nnz_a, input_a.ptr(), size_b, nnz_b, input_b.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
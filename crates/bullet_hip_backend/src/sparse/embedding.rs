@@ -0,0 +1,50 @@
+use bullet_core::device::{DeviceBuffer, OperationError};
+
+use crate::{
+    backend::{ops, Buffer},
+    OperationResult,
+};
+
+pub fn embedding(
+    batch_size: usize,
+    dim: usize,
+    vocab: usize,
+    nnz: usize,
+    table: &Buffer<f32>,
+    indices: &Buffer<i32>,
+    outputs: &mut Buffer<f32>,
+) -> OperationResult {
+    let slots = batch_size * nnz;
+
+    if dim * vocab > table.size() || slots > indices.size() || slots * dim > outputs.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::embedding(dim, slots, table.ptr(), indices.ptr(), outputs.mut_ptr());
+    }
+
+    Ok(())
+}
+
+pub fn backprop_embedding(
+    batch_size: usize,
+    dim: usize,
+    vocab: usize,
+    nnz: usize,
+    output_grads: &Buffer<f32>,
+    indices: &Buffer<i32>,
+    table_grads: &mut Buffer<f32>,
+) -> OperationResult {
+    let slots = batch_size * nnz;
+
+    if dim * vocab > table_grads.size() || slots > indices.size() || slots * dim > output_grads.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    unsafe {
+        ops::embedding_backprop(dim, slots, output_grads.ptr(), indices.ptr(), table_grads.mut_ptr());
+    }
+
+    Ok(())
+}
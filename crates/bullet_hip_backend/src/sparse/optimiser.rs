@@ -0,0 +1,77 @@
+use bullet_core::device::{DeviceBuffer, OperationError};
+
+use crate::{
+    backend::{ops, Buffer},
+    OperationResult,
+};
+
+#[allow(clippy::too_many_arguments)]
+pub fn sparse_adam(
+    dim: usize,
+    num_columns: usize,
+    columns: &Buffer<i32>,
+    params: &mut Buffer<f32>,
+    gradient: &Buffer<f32>,
+    momentum: &mut Buffer<f32>,
+    velocity: &mut Buffer<f32>,
+    beta1: f32,
+    beta2: f32,
+    gradient_factor: f32,
+    learning_rate: f32,
+    denom: bool,
+) -> OperationResult {
+    if num_columns > columns.size() || dim * num_columns > gradient.size() {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    if params.size() != momentum.size() || params.size() != velocity.size() || params.size() % dim != 0 {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    if num_columns == 0 {
+        return Ok(());
+    }
+
+    unsafe {
+        ops::SparseAdam(
+            dim,
+            num_columns,
+            beta1,
+            beta2,
+            gradient_factor,
+            learning_rate,
+            denom,
+            columns.ptr(),
+            params.mut_ptr(),
+            momentum.mut_ptr(),
+            velocity.mut_ptr(),
+            gradient.ptr(),
+        );
+    }
+
+    Ok(())
+}
+
+pub fn sparse_scale_clip(
+    dim: usize,
+    num_columns: usize,
+    columns: &Buffer<i32>,
+    params: &mut Buffer<f32>,
+    scale: f32,
+    min: f32,
+    max: f32,
+) -> OperationResult {
+    if num_columns > columns.size() || params.size() % dim != 0 {
+        return Err(OperationError::IndexOutOfBounds);
+    }
+
+    if num_columns == 0 {
+        return Ok(());
+    }
+
+    unsafe {
+        ops::SparseScaleClip(dim, num_columns, columns.ptr(), params.mut_ptr(), scale, min, max);
+    }
+
+    Ok(())
+}
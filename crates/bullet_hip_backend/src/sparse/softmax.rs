@@ -88,3 +88,22 @@ pub fn backprop_softmax_crossentropy_masked(
 
     Ok(())
 }
+
+pub fn smooth_targets_masked(
+    batch_size: usize,
+    nnz: usize,
+    masks: &Buffer<i32>,
+    smoothing: f32,
+    target: &Buffer<f32>,
+    output: &mut Buffer<f32>,
+) -> OperationResult {
+    assert!(batch_size * nnz <= masks.size());
+    assert!(batch_size * nnz <= target.size());
+    assert!(batch_size * nnz <= output.size());
+
+    unsafe {
+        ops::smooth_targets_masked(nnz, batch_size, masks.ptr(), smoothing, target.ptr(), output.mut_ptr());
+    }
+
+    Ok(())
+}
@@ -6,12 +6,62 @@ tests::make_tests! {
     ExecutionContext::default(),
     matmul,
     matmul2,
+    matmul_batched_single,
+    fuse_affine_activate,
+    fuse_affine_activate_skipped,
+    common_subexpressions,
+    common_subexpressions_labelled,
+    shared_gradient_buffers,
+    description_round_trip,
     sparse_affine,
     sparse_affine_dual,
+    sparse_affine_outer,
     check_not_batched,
     relu,
     crelu,
     screlu,
     sqrrelu,
+    gelu,
+    silu,
+    prelu,
+    clamp,
+    abs,
+    elementwise_min,
+    elementwise_max,
+    elementwise_mul,
+    elementwise_div,
+    linear_comb_broadcast,
+    embedding,
+    embedding_sparse_gradients,
     concat,
+    concat_many,
+    reduce_sum,
+    reduce_mean,
+    repeat,
+    repeat_across_batch,
+    slice_columns,
+    transpose,
+    avg_pool,
+    max_pool,
+    attention,
+    conv2d,
+    softmax,
+    softmax_crossentropy_temperature,
+    softmax_crossentropy_smoothing,
+    kl_divergence,
+    sigmoid_bce,
+    huber,
+    pairwise_ranking,
+    loss_components,
+    multiple_outputs,
+    dot_export,
+    named_nodes,
+    activation_stats,
+    gradcheck_ops,
+    dropout,
+    batch_norm,
+    layer_norm,
+    checkpoint_portable,
+    bucketed_lr,
+    gradient_accumulation,
 }
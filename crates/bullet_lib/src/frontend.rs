@@ -1,12 +1,12 @@
 use std::{
     collections::HashMap,
-    ops::{Add, Sub},
+    ops::{Add, Mul, Sub},
     sync::{Mutex, MutexGuard},
 };
 
 use bullet_core::graph::{
     builder::{GraphBuilder, Node},
-    operation::Operation,
+    operation::{ConvSettings, GraphBuilderError, NodeList, Operation, PoolSettings},
     Graph,
 };
 
@@ -50,6 +50,18 @@ impl NetworkBuilder {
         NetworkBuilderNode { node, builder: self }
     }
 
+    /// Weights that are not updated by the optimiser, but are still saved in checkpoints.
+    pub fn new_non_trainable_weights<'a>(
+        &'a self,
+        id: &str,
+        shape: Shape,
+        init: InitSettings,
+    ) -> NetworkBuilderNode<'a> {
+        let node = self.builder().create_non_trainable_weights(id, shape).unwrap();
+        self.init().insert(id.to_string(), init);
+        NetworkBuilderNode { node, builder: self }
+    }
+
     pub fn new_affine(&self, id: &str, input_size: usize, output_size: usize) -> Affine {
         self.new_affine_custom(id, input_size, output_size, 1)
     }
@@ -63,19 +75,101 @@ impl NetworkBuilder {
         Affine { weights: weights.node, bias: bias.node }
     }
 
-    pub fn apply(&self, operation: Operation) -> NetworkBuilderNode {
-        match self.builder().create_result_of_operation(operation, true) {
-            Ok(node) => NetworkBuilderNode { node, builder: self },
-            Err(e) => {
-                println!("{e:#?}");
-                panic!();
-            }
+    /// Creates the learnable negative slopes of a PReLU over `size` neurons, named `{id}s`
+    /// and initialised to `0.25`.
+    pub fn new_prelu(&self, id: &str, size: usize) -> PReLU {
+        let init = InitSettings::Normal { mean: 0.25, stdev: 0.0 };
+        let slope = self.new_weights(&format!("{}s", id), Shape::new(size, 1), init);
+
+        PReLU { slope: slope.node }
+    }
+
+    /// Creates a `dim x vocab` embedding table, named `{id}w`, whose gradients only hold the
+    /// embeddings looked up in each batch, see `GraphBuilder::use_sparse_gradients`.
+    pub fn new_embedding(&self, id: &str, vocab: usize, dim: usize) -> Embedding {
+        let init = InitSettings::Normal { mean: 0.0, stdev: 1.0 / (dim as f32).sqrt() };
+        let table = self.new_weights(&format!("{}w", id), Shape::new(dim, vocab), init);
+        self.builder().use_sparse_gradients(table.node);
+
+        Embedding { table: table.node }
+    }
+
+    /// Creates the filters for a 2D convolution, named `{id}w`, see `ConvSettings`.
+    pub fn new_conv2d(&self, id: &str, settings: ConvSettings) -> Conv2d {
+        let fan_in = settings.in_channels * settings.kernel_height * settings.kernel_width;
+        let init = InitSettings::Normal { mean: 0.0, stdev: 1.0 / (fan_in as f32).sqrt() };
+        let filters = self.new_weights(&format!("{}w", id), settings.filters_shape(), init);
+
+        Conv2d { filters: filters.node, settings }
+    }
+
+    /// Weighted sum of several losses, each of which is labelled with its `id` and has
+    /// its value recorded separately so that it can be reported during training.
+    pub fn weighted_loss<'a>(&'a self, components: &[(&str, NetworkBuilderNode<'a>, f32)]) -> NetworkBuilderNode<'a> {
+        self.unwrap(self.try_weighted_loss(components))
+    }
+
+    pub fn try_weighted_loss<'a>(
+        &'a self,
+        components: &[(&str, NetworkBuilderNode<'a>, f32)],
+    ) -> Result<NetworkBuilderNode<'a>, GraphBuilderError> {
+        let (&(_, first, weight), rest) = components.split_first().expect("No losses to combine!");
+
+        for &(id, loss, _) in components {
+            let mut builder = self.builder();
+            builder.set_label(loss.node, id);
+            builder.add_loss_component(id, loss.node);
+        }
+
+        let Some((&(_, second, second_weight), rest)) = rest.split_first() else {
+            return first.try_linear_comb(weight, first, 0.0);
+        };
+
+        let mut total = first.try_linear_comb(weight, second, second_weight)?;
+
+        for &(_, loss, weight) in rest {
+            total = total.try_linear_comb(1.0, loss, weight)?;
         }
+
+        Ok(total)
+    }
+
+    /// Designates the loss of the network, which is required if it has other outputs.
+    pub fn set_loss(&self, loss: NetworkBuilderNode) {
+        self.builder().set_loss(loss.node);
+    }
+
+    /// Fuses each affine transformation whose only use is an activation into a single operation,
+    /// returning the number fused. Nodes whose values are read after building, other than
+    /// outputs, should be `named` so that they are kept.
+    pub fn fuse_affine_activate(&self) -> usize {
+        self.builder().fuse_affine_activate()
+    }
+
+    pub fn apply(&self, operation: Operation) -> NetworkBuilderNode {
+        self.unwrap(self.try_apply(operation))
+    }
+
+    /// As `apply`, returning an error if the inputs of `operation` are invalid, e.g. if
+    /// their shapes don't match, so that graphs can be built programmatically.
+    pub fn try_apply(&self, operation: Operation) -> Result<NetworkBuilderNode, GraphBuilderError> {
+        let node = self.builder().create_result_of_operation(operation, true)?;
+        Ok(NetworkBuilderNode { node, builder: self })
+    }
+
+    /// Describes the error in terms of the labels and shapes of the inputs of the operation.
+    pub fn describe_error(&self, error: &GraphBuilderError) -> String {
+        self.builder().describe_error(error)
+    }
+
+    fn unwrap<'a>(&'a self, result: Result<NetworkBuilderNode<'a>, GraphBuilderError>) -> NetworkBuilderNode<'a> {
+        result.unwrap_or_else(|e| panic!("{}", self.describe_error(&e)))
     }
 
     pub fn build(self, execution_context: ExecutionContext) -> Graph<ExecutionContext> {
         let mut builder = self.graph_builder.into_inner().unwrap();
-        builder.create_result_of_operation(Operation::ReduceAcrossBatch(builder.root()), true).unwrap();
+        let loss = builder.create_result_of_operation(Operation::ReduceAcrossBatch(builder.root()), true).unwrap();
+        builder.set_loss(loss);
         let mut graph = builder.build(execution_context).unwrap();
 
         for (id, init_data) in self.init_data.lock().unwrap().iter() {
@@ -116,83 +210,564 @@ impl Sub<Self> for NetworkBuilderNode<'_> {
     }
 }
 
+impl Mul<Self> for NetworkBuilderNode<'_> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.builder.apply(Operation::ElementwiseMul(self.node, rhs.node))
+    }
+}
+
 impl NetworkBuilderNode<'_> {
     pub fn node(self) -> Node {
         self.node
     }
 
-    pub fn reshape(mut self, shape: Shape) -> Self {
-        self.node = self.node.reshape(shape).unwrap();
+    /// Attaches a human-readable label to this node, which is used to
+    /// identify it in graph construction and runtime error messages, and
+    /// by which its values can be fetched with `Graph::get_named`.
+    pub fn named(self, label: &str) -> Self {
+        self.builder.builder().set_label(self.node, label);
         self
     }
 
+    pub fn reshape(self, shape: Shape) -> Self {
+        self.builder.unwrap(self.try_reshape(shape))
+    }
+
     pub fn activate(self, activation: Activation) -> Self {
-        self.builder.apply(Operation::Activate(self.node, activation))
+        self.builder.unwrap(self.try_activate(activation))
+    }
+
+    /// Elementwise division by `rhs`, where elements of `rhs` smaller in magnitude than
+    /// `epsilon` are replaced by `epsilon` with the same sign to avoid dividing by zero.
+    pub fn div(self, rhs: Self, epsilon: f32) -> Self {
+        self.builder.unwrap(self.try_div(rhs, epsilon))
+    }
+
+    pub fn abs(self) -> Self {
+        self.builder.unwrap(self.try_abs())
+    }
+
+    /// Elementwise minimum of this node and `rhs`.
+    pub fn min(self, rhs: Self) -> Self {
+        self.builder.unwrap(self.try_min(rhs))
+    }
+
+    /// Elementwise maximum of this node and `rhs`.
+    pub fn max(self, rhs: Self) -> Self {
+        self.builder.unwrap(self.try_max(rhs))
+    }
+
+    /// Clamps each element to `[min, max]`, with no gradient flowing through clamped
+    /// elements, e.g. to emulate the range of a quantised network during training.
+    pub fn clamp(self, min: f32, max: f32) -> Self {
+        self.builder.unwrap(self.try_clamp(min, max))
     }
 
     pub fn select(self, buckets: Self) -> Self {
-        self.builder.apply(Operation::Select(self.node, buckets.node))
+        self.builder.unwrap(self.try_select(buckets))
     }
 
     pub fn concat(self, rhs: Self) -> Self {
-        self.builder.apply(Operation::Concat(self.node, rhs.node))
+        self.builder.unwrap(self.try_concat(rhs))
+    }
+
+    /// Concatenates this vector with each of `others` in order, using one operation
+    /// per `NodeList::CAPACITY` inputs.
+    pub fn concat_many(self, others: &[Self]) -> Self {
+        self.builder.unwrap(self.try_concat_many(others))
+    }
+
+    /// Repeats this node `times` times along its columns, summing the gradients of the copies.
+    pub fn repeat(self, times: usize) -> Self {
+        self.builder.unwrap(self.try_repeat(times))
+    }
+
+    /// Broadcasts this unbatched node, e.g. a learned global bias, across the batch of `batched`,
+    /// summing the gradients across the batch.
+    pub fn repeat_across_batch(self, batched: Self) -> Self {
+        self.builder.unwrap(self.try_repeat_across_batch(batched))
     }
 
     pub fn linear_comb(self, alpha: f32, rhs: Self, beta: f32) -> Self {
-        self.builder.apply(Operation::LinearCombination(alpha, self.node, beta, rhs.node))
+        self.builder.unwrap(self.try_linear_comb(alpha, rhs, beta))
     }
 
     pub fn matmul(self, rhs: Self) -> Self {
-        if rhs.node.is_sparse() {
-            self.builder.apply(Operation::SparseAffine(self.node, rhs.node, None))
-        } else {
-            self.builder.apply(Operation::Matmul(self.node, false, rhs.node, false))
-        }
+        self.builder.unwrap(self.try_matmul(rhs))
     }
 
+    /// Multiplies this matrix by `rhs`, transposing either first if requested. If either
+    /// is batched, each batch element is multiplied separately, e.g. by weights computed
+    /// per position.
     pub fn gemm(self, transa: bool, rhs: Self, transb: bool) -> Self {
-        self.builder.apply(Operation::Matmul(self.node, transa, rhs.node, transb))
+        self.builder.unwrap(self.try_gemm(transa, rhs, transb))
     }
 
     pub fn mpe(self, targets: Self, power: f32) -> Self {
-        self.builder.apply(Operation::PowerError(self.node, targets.node, power))
+        self.builder.unwrap(self.try_mpe(targets, power))
     }
 
     pub fn mse(self, targets: Self) -> Self {
-        self.mpe(targets, 2.0)
+        self.builder.unwrap(self.try_mse(targets))
+    }
+
+    /// Huber loss between this node and `targets`, which is quadratic for errors up
+    /// to `delta` in magnitude and linear beyond, so is less sensitive to outliers.
+    pub fn huber(self, targets: Self, delta: f32) -> Self {
+        self.builder.unwrap(self.try_huber(targets, delta))
+    }
+
+    /// Binary cross-entropy between the sigmoid of this node and `targets`, computed
+    /// directly from the logits so that it is numerically stable.
+    pub fn sigmoid_bce(self, targets: Self) -> Self {
+        self.builder.unwrap(self.try_sigmoid_bce(targets))
+    }
+
+    /// Margin ranking loss on pairs of consecutive positions in the batch, `(0, 1)`, `(2, 3)`
+    /// and so on, which penalises predictions that order a pair differently to `targets`
+    /// without regard to their absolute values. Data must be loaded with pairs adjacent.
+    pub fn ranking_loss(self, targets: Self, margin: f32) -> Self {
+        self.builder.unwrap(self.try_ranking_loss(targets, margin))
     }
 
     pub fn pairwise_mul(self) -> Self {
-        self.builder.apply(Operation::PairwiseMul(self.node, false))
+        self.builder.unwrap(self.try_pairwise_mul())
     }
 
     pub fn pairwise_mul_post_affine_dual(self) -> Self {
-        self.builder.apply(Operation::PairwiseMul(self.node, true))
+        self.builder.unwrap(self.try_pairwise_mul_post_affine_dual())
     }
 
     pub fn mask(self, mask: Self) -> Self {
-        self.builder.apply(Operation::Mask(self.node, mask.node))
+        self.builder.unwrap(self.try_mask(mask))
     }
 
     pub fn gather(self, indices: Self) -> Self {
-        self.builder.apply(Operation::Gather(self.node, indices.node))
+        self.builder.unwrap(self.try_gather(indices))
+    }
+
+    /// Softmax of this vector, e.g. to output a probability distribution.
+    pub fn softmax(self) -> Self {
+        self.builder.unwrap(self.try_softmax())
     }
 
     pub fn softmax_crossentropy_loss(self, targets: Self) -> Self {
-        self.builder.apply(Operation::SoftmaxCrossEntropyLoss(self.node, targets.node))
+        self.builder.unwrap(self.try_softmax_crossentropy_loss(targets))
     }
 
     pub fn masked_softmax_crossentropy_loss(self, targets: Self, mask: Self) -> Self {
-        self.builder.apply(Operation::MaskedSoftmaxCrossEntropyLoss(mask.node, self.node, targets.node))
+        self.builder.unwrap(self.try_masked_softmax_crossentropy_loss(targets, mask))
+    }
+
+    /// KL-divergence from the softmax of this vector to the `targets` distribution, e.g. the
+    /// WDL or policy outputs of a teacher network. No gradient flows into the targets.
+    pub fn kl_divergence_loss(self, targets: Self) -> Self {
+        self.builder.unwrap(self.try_kl_divergence_loss(targets))
+    }
+
+    /// Softmax cross-entropy loss with the softmax taken of this vector divided by `temperature`,
+    /// so that temperatures above 1 soften the predicted distribution, e.g. for distillation.
+    /// The temperature can be changed during training with `Graph::set_softmax_temperature`.
+    pub fn softmax_crossentropy_loss_with_temperature(self, targets: Self, temperature: f32) -> Self {
+        self.builder.unwrap(self.try_softmax_crossentropy_loss_with_temperature(targets, temperature))
+    }
+
+    /// Masked softmax cross-entropy loss with a temperature, as in
+    /// `softmax_crossentropy_loss_with_temperature`.
+    pub fn masked_softmax_crossentropy_loss_with_temperature(
+        self,
+        targets: Self,
+        mask: Self,
+        temperature: f32,
+    ) -> Self {
+        self.builder.unwrap(self.try_masked_softmax_crossentropy_loss_with_temperature(targets, mask, temperature))
+    }
+
+    /// Softmax cross-entropy loss against the targets mixed with the uniform distribution,
+    /// weighted by `smoothing`, to regularise against overconfident predictions.
+    pub fn softmax_crossentropy_loss_with_smoothing(self, targets: Self, smoothing: f32) -> Self {
+        self.builder.unwrap(self.try_softmax_crossentropy_loss_with_smoothing(targets, smoothing))
+    }
+
+    /// Masked softmax cross-entropy loss with label smoothing, spreading `smoothing` of each
+    /// target uniformly over the entries of the mask, e.g. the legal moves of a position.
+    pub fn masked_softmax_crossentropy_loss_with_smoothing(self, targets: Self, mask: Self, smoothing: f32) -> Self {
+        self.builder.unwrap(self.try_masked_softmax_crossentropy_loss_with_smoothing(targets, mask, smoothing))
+    }
+
+    /// Randomly zeroes each element with probability `rate` during training, scaling the
+    /// remaining elements by `1 / (1 - rate)`. Does nothing outside of training.
+    pub fn dropout(self, rate: f32) -> Self {
+        self.builder.unwrap(self.try_dropout(rate))
+    }
+
+    /// Sums over the rows of each column, e.g. to aggregate a set of embeddings.
+    pub fn reduce_sum(self) -> Self {
+        self.builder.unwrap(self.try_reduce_sum())
+    }
+
+    /// Averages over the rows of each column.
+    pub fn reduce_mean(self) -> Self {
+        self.builder.unwrap(self.try_reduce_mean())
+    }
+
+    /// Scaled dot-product attention with this node as the queries, where the queries,
+    /// keys and values are each `seq_len` equally sized tokens stored one after another.
+    pub fn attention(self, keys: Self, values: Self, seq_len: usize) -> Self {
+        self.builder.unwrap(self.try_attention(keys, values, seq_len))
+    }
+
+    /// As `attention`, with `mask` (a `seq_len x seq_len` matrix indexed by query then key)
+    /// added to the attention scores, so that a large negative entry blocks attention.
+    pub fn masked_attention(self, keys: Self, values: Self, mask: Self, seq_len: usize) -> Self {
+        self.builder.unwrap(self.try_masked_attention(keys, values, mask, seq_len))
+    }
+
+    /// Average pooling over non-overlapping windows of this vector, see `PoolSettings`
+    /// for the expected layout.
+    pub fn avg_pool(self, settings: PoolSettings) -> Self {
+        self.builder.unwrap(self.try_avg_pool(settings))
+    }
+
+    /// Max pooling over non-overlapping windows of this vector, see `PoolSettings`
+    /// for the expected layout.
+    pub fn max_pool(self, settings: PoolSettings) -> Self {
+        self.builder.unwrap(self.try_max_pool(settings))
     }
 
     pub fn slice_rows(self, start: usize, end: usize) -> Self {
-        self.builder.apply(Operation::Slice(self.node, start, end))
+        self.builder.unwrap(self.try_slice_rows(start, end))
+    }
+
+    pub fn transpose(self) -> Self {
+        self.builder.unwrap(self.try_transpose())
+    }
+
+    /// Selects columns `start..end` of this matrix.
+    pub fn slice_cols(self, start: usize, end: usize) -> Self {
+        self.builder.unwrap(self.try_slice_cols(start, end))
+    }
+
+    /// Normalises this vector to zero mean and unit variance, followed by a learnable
+    /// elementwise scale and shift, stored as weights `{id}w` and `{id}b` respectively.
+    pub fn layer_norm(self, id: &str) -> Self {
+        self.builder.unwrap(self.try_layer_norm(id))
+    }
+
+    /// Normalises each element of this vector across the batch, followed by a learnable
+    /// elementwise scale and shift, stored as weights `{id}w` and `{id}b` respectively.
+    ///
+    /// The running mean and variance are tracked with the given `momentum` in the
+    /// non-trainable weights `{id}m` and `{id}v`, and used in place of the batch
+    /// statistics when the graph is not in training mode.
+    pub fn batch_norm(self, id: &str, momentum: f32) -> Self {
+        self.builder.unwrap(self.try_batch_norm(id, momentum))
     }
 
     pub fn to_dense(self) -> Self {
-        let node = self.builder.builder().create_result_of_operation(Operation::ToDense(self.node), false).unwrap();
-        Self { node, builder: self.builder }
+        self.builder.unwrap(self.try_to_dense())
+    }
+
+    /// Sparse input whose active features are the pairs of active features of `self` and `rhs`,
+    /// with feature `a` of `self` and `b` of `rhs` at index `a * rhs_size + b`. Neither input is
+    /// densified, so the result can be fed straight into an affine layer as interaction features.
+    pub fn sparse_outer(self, rhs: Self) -> Self {
+        self.builder.unwrap(self.try_sparse_outer(rhs))
+    }
+}
+
+/// Versions of the helpers above that return an error describing an invalid operation,
+/// such as mismatched input shapes, rather than panicking.
+impl NetworkBuilderNode<'_> {
+    pub fn try_reshape(mut self, shape: Shape) -> Result<Self, GraphBuilderError> {
+        self.node = self.node.reshape(shape)?;
+        Ok(self)
+    }
+
+    pub fn try_activate(self, activation: Activation) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Activate(self.node, activation))
+    }
+
+    pub fn try_div(self, rhs: Self, epsilon: f32) -> Result<Self, GraphBuilderError> {
+        assert!(epsilon > 0.0, "Division epsilon must be positive!");
+        self.builder.try_apply(Operation::ElementwiseDiv(self.node, rhs.node, epsilon))
+    }
+
+    pub fn try_abs(self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Abs(self.node))
+    }
+
+    pub fn try_min(self, rhs: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Min(self.node, rhs.node))
+    }
+
+    pub fn try_max(self, rhs: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Max(self.node, rhs.node))
+    }
+
+    pub fn try_clamp(self, min: f32, max: f32) -> Result<Self, GraphBuilderError> {
+        assert!(min < max, "Invalid clamp range [{min}, {max}]!");
+        self.builder.try_apply(Operation::Clamp(self.node, min, max))
+    }
+
+    pub fn try_select(self, buckets: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Select(self.node, buckets.node))
+    }
+
+    pub fn try_concat(self, rhs: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Concat(self.node, rhs.node))
+    }
+
+    pub fn try_concat_many(self, others: &[Self]) -> Result<Self, GraphBuilderError> {
+        let mut out = self;
+
+        for chunk in others.chunks(NodeList::CAPACITY - 1) {
+            let nodes = std::iter::once(out.node).chain(chunk.iter().map(|other| other.node)).collect::<Vec<_>>();
+            out = self.builder.try_apply(Operation::ConcatMany(NodeList::new(&nodes).unwrap()))?;
+        }
+
+        Ok(out)
+    }
+
+    pub fn try_repeat(self, times: usize) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Repeat(self.node, times))
+    }
+
+    pub fn try_repeat_across_batch(self, batched: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::RepeatAcrossBatch(self.node, batched.node))
+    }
+
+    pub fn try_linear_comb(self, alpha: f32, rhs: Self, beta: f32) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::LinearCombination(alpha, self.node, beta, rhs.node))
+    }
+
+    pub fn try_matmul(self, rhs: Self) -> Result<Self, GraphBuilderError> {
+        if rhs.node.is_sparse() {
+            self.builder.try_apply(Operation::SparseAffine(self.node, rhs.node, None))
+        } else {
+            self.builder.try_apply(Operation::Matmul(self.node, false, rhs.node, false))
+        }
+    }
+
+    pub fn try_gemm(self, transa: bool, rhs: Self, transb: bool) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Matmul(self.node, transa, rhs.node, transb))
+    }
+
+    pub fn try_mpe(self, targets: Self, power: f32) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::PowerError(self.node, targets.node, power))
+    }
+
+    pub fn try_mse(self, targets: Self) -> Result<Self, GraphBuilderError> {
+        self.try_mpe(targets, 2.0)
+    }
+
+    pub fn try_huber(self, targets: Self, delta: f32) -> Result<Self, GraphBuilderError> {
+        assert!(delta > 0.0, "Huber delta must be positive!");
+        self.builder.try_apply(Operation::HuberError(self.node, targets.node, delta))
+    }
+
+    pub fn try_sigmoid_bce(self, targets: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::SigmoidCrossEntropyLoss(self.node, targets.node))
+    }
+
+    pub fn try_ranking_loss(self, targets: Self, margin: f32) -> Result<Self, GraphBuilderError> {
+        assert!(margin >= 0.0, "Ranking margin must be non-negative!");
+        self.builder.try_apply(Operation::PairwiseRankingLoss(self.node, targets.node, margin))
+    }
+
+    pub fn try_pairwise_mul(self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::PairwiseMul(self.node, false))
+    }
+
+    pub fn try_pairwise_mul_post_affine_dual(self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::PairwiseMul(self.node, true))
+    }
+
+    pub fn try_mask(self, mask: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Mask(self.node, mask.node))
+    }
+
+    pub fn try_gather(self, indices: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Gather(self.node, indices.node))
+    }
+
+    pub fn try_softmax(self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Softmax(self.node))
+    }
+
+    pub fn try_softmax_crossentropy_loss(self, targets: Self) -> Result<Self, GraphBuilderError> {
+        self.try_softmax_crossentropy_loss_with_temperature(targets, 1.0)
+    }
+
+    pub fn try_masked_softmax_crossentropy_loss(self, targets: Self, mask: Self) -> Result<Self, GraphBuilderError> {
+        self.try_masked_softmax_crossentropy_loss_with_temperature(targets, mask, 1.0)
+    }
+
+    pub fn try_kl_divergence_loss(self, targets: Self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::KLDivergenceLoss(self.node, targets.node))
+    }
+
+    pub fn try_softmax_crossentropy_loss_with_temperature(
+        self,
+        targets: Self,
+        temperature: f32,
+    ) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::SoftmaxCrossEntropyLoss(self.node, targets.node, temperature, 0.0))
+    }
+
+    pub fn try_masked_softmax_crossentropy_loss_with_temperature(
+        self,
+        targets: Self,
+        mask: Self,
+        temperature: f32,
+    ) -> Result<Self, GraphBuilderError> {
+        let op = Operation::MaskedSoftmaxCrossEntropyLoss(mask.node, self.node, targets.node, temperature, 0.0);
+        self.builder.try_apply(op)
+    }
+
+    pub fn try_softmax_crossentropy_loss_with_smoothing(
+        self,
+        targets: Self,
+        smoothing: f32,
+    ) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::SoftmaxCrossEntropyLoss(self.node, targets.node, 1.0, smoothing))
+    }
+
+    pub fn try_masked_softmax_crossentropy_loss_with_smoothing(
+        self,
+        targets: Self,
+        mask: Self,
+        smoothing: f32,
+    ) -> Result<Self, GraphBuilderError> {
+        let op = Operation::MaskedSoftmaxCrossEntropyLoss(mask.node, self.node, targets.node, 1.0, smoothing);
+        self.builder.try_apply(op)
+    }
+
+    pub fn try_dropout(self, rate: f32) -> Result<Self, GraphBuilderError> {
+        assert!((0.0..1.0).contains(&rate), "Dropout rate must be in [0, 1)!");
+        self.builder.try_apply(Operation::Dropout(self.node, rate))
+    }
+
+    pub fn try_reduce_sum(self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::ReduceSum(self.node))
+    }
+
+    pub fn try_reduce_mean(self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::ReduceMean(self.node))
+    }
+
+    pub fn try_attention(self, keys: Self, values: Self, seq_len: usize) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Attention(self.node, keys.node, values.node, None, seq_len))
+    }
+
+    pub fn try_masked_attention(
+        self,
+        keys: Self,
+        values: Self,
+        mask: Self,
+        seq_len: usize,
+    ) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Attention(self.node, keys.node, values.node, Some(mask.node), seq_len))
+    }
+
+    pub fn try_avg_pool(self, settings: PoolSettings) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::AvgPool(self.node, settings))
+    }
+
+    pub fn try_max_pool(self, settings: PoolSettings) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::MaxPool(self.node, settings))
+    }
+
+    pub fn try_slice_rows(self, start: usize, end: usize) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Slice(self.node, start, end))
+    }
+
+    pub fn try_transpose(self) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::Transpose(self.node))
+    }
+
+    pub fn try_slice_cols(self, start: usize, end: usize) -> Result<Self, GraphBuilderError> {
+        self.builder.try_apply(Operation::SliceColumns(self.node, start, end))
+    }
+
+    pub fn try_layer_norm(self, id: &str) -> Result<Self, GraphBuilderError> {
+        let shape = self.node.shape();
+        let init = InitSettings::Normal { mean: 1.0, stdev: 0.0 };
+        let scale = self.builder.new_weights(&format!("{id}w"), shape, init);
+        let shift = self.builder.new_weights(&format!("{id}b"), shape, InitSettings::Zeroed);
+        self.builder.try_apply(Operation::LayerNorm(self.node, scale.node, shift.node))
+    }
+
+    pub fn try_batch_norm(self, id: &str, momentum: f32) -> Result<Self, GraphBuilderError> {
+        let shape = self.node.shape();
+        let ones = InitSettings::Normal { mean: 1.0, stdev: 0.0 };
+        let scale = self.builder.new_weights(&format!("{id}w"), shape, ones);
+        let shift = self.builder.new_weights(&format!("{id}b"), shape, InitSettings::Zeroed);
+        let mean = self.builder.new_non_trainable_weights(&format!("{id}m"), shape, InitSettings::Zeroed);
+        let var = self.builder.new_non_trainable_weights(&format!("{id}v"), shape, ones);
+        self.builder.try_apply(Operation::BatchNorm(self.node, scale.node, shift.node, mean.node, var.node, momentum))
+    }
+
+    pub fn try_to_dense(self) -> Result<Self, GraphBuilderError> {
+        let mut builder = self.builder.builder();
+        let node = builder.create_result_of_operation(Operation::ToDense(self.node), false)?;
+        Ok(Self { node, builder: self.builder })
+    }
+
+    pub fn try_sparse_outer(self, rhs: Self) -> Result<Self, GraphBuilderError> {
+        let mut builder = self.builder.builder();
+        let node = builder.create_result_of_operation(Operation::SparseOuter(self.node, rhs.node), false)?;
+        Ok(Self { node, builder: self.builder })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct PReLU {
+    pub slope: Node,
+}
+
+impl PReLU {
+    pub fn forward(self, input: NetworkBuilderNode<'_>) -> NetworkBuilderNode<'_> {
+        input.builder.unwrap(self.try_forward(input))
+    }
+
+    pub fn try_forward(self, input: NetworkBuilderNode<'_>) -> Result<NetworkBuilderNode<'_>, GraphBuilderError> {
+        input.builder.try_apply(Operation::PReLU(input.node, self.slope))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Embedding {
+    pub table: Node,
+}
+
+impl Embedding {
+    /// Looks up the embedding of each active index of the sparse `input`, concatenating
+    /// them in order. Padding indices produce zeroes.
+    pub fn forward(self, input: NetworkBuilderNode<'_>) -> NetworkBuilderNode<'_> {
+        input.builder.unwrap(self.try_forward(input))
+    }
+
+    pub fn try_forward(self, input: NetworkBuilderNode<'_>) -> Result<NetworkBuilderNode<'_>, GraphBuilderError> {
+        input.builder.try_apply(Operation::Embedding(self.table, input.node))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Conv2d {
+    pub filters: Node,
+    pub settings: ConvSettings,
+}
+
+impl Conv2d {
+    pub fn forward(self, input: NetworkBuilderNode<'_>) -> NetworkBuilderNode<'_> {
+        input.builder.unwrap(self.try_forward(input))
+    }
+
+    pub fn try_forward(self, input: NetworkBuilderNode<'_>) -> Result<NetworkBuilderNode<'_>, GraphBuilderError> {
+        input.builder.try_apply(Operation::Conv2d(self.filters, input.node, self.settings))
     }
 }
 
@@ -204,10 +779,14 @@ pub struct Affine {
 
 impl Affine {
     pub fn forward(self, input: NetworkBuilderNode<'_>) -> NetworkBuilderNode<'_> {
+        input.builder.unwrap(self.try_forward(input))
+    }
+
+    pub fn try_forward(self, input: NetworkBuilderNode<'_>) -> Result<NetworkBuilderNode<'_>, GraphBuilderError> {
         if input.node.is_sparse() {
-            input.builder.apply(Operation::SparseAffine(self.weights, input.node, Some(self.bias)))
+            input.builder.try_apply(Operation::SparseAffine(self.weights, input.node, Some(self.bias)))
         } else {
-            input.builder.apply(Operation::Affine(self.weights, input.node, self.bias))
+            input.builder.try_apply(Operation::Affine(self.weights, input.node, self.bias))
         }
     }
 
@@ -217,6 +796,16 @@ impl Affine {
         ntm: NetworkBuilderNode<'a>,
         activation: Activation,
     ) -> NetworkBuilderNode<'a> {
-        stm.builder.apply(Operation::SparseAffineDualActivate(self.weights, stm.node, ntm.node, self.bias, activation))
+        stm.builder.unwrap(self.try_forward_sparse_dual_with_activation(stm, ntm, activation))
+    }
+
+    pub fn try_forward_sparse_dual_with_activation<'a>(
+        self,
+        stm: NetworkBuilderNode<'a>,
+        ntm: NetworkBuilderNode<'a>,
+        activation: Activation,
+    ) -> Result<NetworkBuilderNode<'a>, GraphBuilderError> {
+        let op = Operation::SparseAffineDualActivate(self.weights, stm.node, ntm.node, self.bias, activation);
+        stm.builder.try_apply(op)
     }
 }
@@ -12,43 +12,71 @@ pub use bullet_hip_backend::ExecutionContext;
 pub use trainer::{
     default, logger, save,
     schedule::{lr, wdl, TrainingSchedule, TrainingSteps},
-    settings::LocalSettings,
+    settings::{CheckpointCompression, LocalSettings, PrefetchSettings, SwaSampling, WallclockSaves},
     DataPreparer, NetworkTrainer,
 };
 
 /// Contains the Graph API, by which neural networks are created with
 /// `NetworkBuilder`, and then compiled into an executable `Graph`
 pub mod nn {
-    pub use super::frontend::{Affine, InitSettings, NetworkBuilder, NetworkBuilderNode};
+    pub use super::frontend::{Affine, Conv2d, Embedding, InitSettings, NetworkBuilder, NetworkBuilderNode, PReLU};
 
     pub use bullet_core::{
-        graph::{builder::Node, operation::Activation},
+        graph::{
+            builder::Node,
+            operation::{Activation, ConvSettings, GraphBuilderError, GraphBuilderErrorType, PoolSettings},
+        },
         shape::Shape,
     };
     pub use bullet_hip_backend::{DeviceError, ExecutionContext};
     pub type Graph = bullet_core::graph::Graph<ExecutionContext>;
 
     pub mod optimiser {
-        use bullet_core::optimiser::{self, clip, decay, radam, utils::Placement, OptimiserState};
+        use std::marker::PhantomData;
+
+        use bullet_core::optimiser::{
+            self, bucketed, clip, decay, lamb, radam, ranger, sgd, utils::Placement, OptimiserState,
+        };
         use bullet_hip_backend::ExecutionContext;
 
         type ClipAndDecay<T> = clip::WeightClipping<decay::WeightDecay<T>>;
 
+        pub type AdaBeliefWOptimiser = optimiser::adabelief::AdaBeliefW<ExecutionContext>;
         pub type AdamWOptimiser = optimiser::adam::AdamW<ExecutionContext>;
-        pub type RAdamOptimiser = ClipAndDecay<radam::RAdam<ExecutionContext>>;
+        pub type LambOptimiser =
+            optimiser::WrapOptimiser<clip::WeightClipping<lamb::Lamb<ExecutionContext>>, LambParams>;
+        pub type RAdamOptimiser = optimiser::WrapOptimiser<ClipAndDecay<radam::RAdam<ExecutionContext>>, RAdamParams>;
         pub type RangerOptimiser = optimiser::ranger::Ranger<ExecutionContext>;
-        pub use optimiser::{adam::AdamWParams, ranger::RangerParams, Optimiser};
+        pub type SgdOptimiser = optimiser::WrapOptimiser<ClipAndDecay<sgd::Sgd<ExecutionContext>>, SgdParams>;
+        pub use optimiser::{
+            adabelief::AdaBeliefWParams,
+            adam::{AdamWOverrides, AdamWParams},
+            ranger::{RangerLookaheadParams, RangerParams},
+            Optimiser,
+        };
 
         pub trait OptimiserType: Default {
             type Optimiser: OptimiserState<ExecutionContext>;
         }
 
+        #[derive(Default)]
+        pub struct AdaBeliefW;
+        impl OptimiserType for AdaBeliefW {
+            type Optimiser = AdaBeliefWOptimiser;
+        }
+
         #[derive(Default)]
         pub struct AdamW;
         impl OptimiserType for AdamW {
             type Optimiser = AdamWOptimiser;
         }
 
+        #[derive(Default)]
+        pub struct Lamb;
+        impl OptimiserType for Lamb {
+            type Optimiser = LambOptimiser;
+        }
+
         #[derive(Default)]
         pub struct RAdam;
         impl OptimiserType for RAdam {
@@ -61,22 +89,63 @@ pub mod nn {
             type Optimiser = RangerOptimiser;
         }
 
+        #[derive(Default)]
+        pub struct Sgd;
+        impl OptimiserType for Sgd {
+            type Optimiser = SgdOptimiser;
+        }
+
+        /// Wraps another optimiser type to allow per-bucket learning rate
+        /// multipliers, see `Trainer::set_lr_bucket_multipliers`.
+        #[derive(Default)]
+        pub struct BucketedLr<O>(PhantomData<O>);
+        impl<O: OptimiserType> OptimiserType for BucketedLr<O> {
+            type Optimiser = bucketed::BucketedLr<ExecutionContext, O::Optimiser>;
+        }
+
+        /// Wraps another optimiser type with Lookahead, see `RangerLookaheadParams`.
+        #[derive(Default)]
+        pub struct Lookahead<O>(PhantomData<O>);
+        impl<O: OptimiserType> OptimiserType for Lookahead<O> {
+            type Optimiser = ranger::RangerLookahead<ExecutionContext, O::Optimiser>;
+        }
+
+        /// Rectified Adam, which falls back to SGD with momentum while the variance estimate is
+        /// unreliable, i.e. while its length is below `n_sma_threshold`, so needs no manual warmup.
         #[derive(Clone, Copy, Debug)]
         pub struct RAdamParams {
             pub decay: f32,
             pub beta1: f32,
             pub beta2: f32,
+            pub n_sma_threshold: f32,
             pub min_weight: f32,
             pub max_weight: f32,
         }
 
+        impl Default for RAdamParams {
+            fn default() -> Self {
+                Self {
+                    decay: 0.01,
+                    beta1: 0.9,
+                    beta2: 0.999,
+                    n_sma_threshold: 5.0,
+                    min_weight: -1.98,
+                    max_weight: 1.98,
+                }
+            }
+        }
+
         type ClipAndDecayParams<T> = clip::WeightClippingParams<decay::WeightDecayParams<T>>;
 
         impl From<RAdamParams> for ClipAndDecayParams<radam::RAdamParams> {
             fn from(value: RAdamParams) -> Self {
                 clip::WeightClippingParams {
                     inner: decay::WeightDecayParams {
-                        inner: radam::RAdamParams { beta1: value.beta1, beta2: value.beta2, n_sma_threshold: 5.0 },
+                        inner: radam::RAdamParams {
+                            beta1: value.beta1,
+                            beta2: value.beta2,
+                            n_sma_threshold: value.n_sma_threshold,
+                        },
                         placement: Placement::Before,
                         decay: value.decay,
                     },
@@ -86,5 +155,76 @@ pub mod nn {
                 }
             }
         }
+
+        #[derive(Clone, Copy, Debug)]
+        pub struct SgdParams {
+            pub decay: f32,
+            pub momentum: f32,
+            pub nesterov: bool,
+            pub min_weight: f32,
+            pub max_weight: f32,
+        }
+
+        impl Default for SgdParams {
+            fn default() -> Self {
+                Self { decay: 0.0, momentum: 0.9, nesterov: false, min_weight: -1.98, max_weight: 1.98 }
+            }
+        }
+
+        impl From<SgdParams> for ClipAndDecayParams<sgd::SgdParams> {
+            fn from(value: SgdParams) -> Self {
+                clip::WeightClippingParams {
+                    inner: decay::WeightDecayParams {
+                        inner: sgd::SgdParams { momentum: value.momentum, nesterov: value.nesterov },
+                        placement: Placement::Before,
+                        decay: value.decay,
+                    },
+                    placement: Placement::After,
+                    min: value.min_weight,
+                    max: value.max_weight,
+                }
+            }
+        }
+
+        /// LAMB, for stable training with very large batch sizes. Weight decay is applied
+        /// as part of the update, so is also rescaled by the layerwise trust ratio.
+        #[derive(Clone, Copy, Debug)]
+        pub struct LambParams {
+            pub decay: f32,
+            pub beta1: f32,
+            pub beta2: f32,
+            pub max_trust_ratio: f32,
+            pub min_weight: f32,
+            pub max_weight: f32,
+        }
+
+        impl Default for LambParams {
+            fn default() -> Self {
+                Self {
+                    decay: 0.01,
+                    beta1: 0.9,
+                    beta2: 0.999,
+                    max_trust_ratio: 10.0,
+                    min_weight: -1.98,
+                    max_weight: 1.98,
+                }
+            }
+        }
+
+        impl From<LambParams> for clip::WeightClippingParams<lamb::LambParams> {
+            fn from(value: LambParams) -> Self {
+                clip::WeightClippingParams {
+                    inner: lamb::LambParams {
+                        beta1: value.beta1,
+                        beta2: value.beta2,
+                        decay: value.decay,
+                        max_trust_ratio: value.max_trust_ratio,
+                    },
+                    placement: Placement::After,
+                    min: value.min_weight,
+                    max: value.max_weight,
+                }
+            }
+        }
     }
 }
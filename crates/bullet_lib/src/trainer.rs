@@ -1,21 +1,26 @@
 pub mod default;
+pub mod gradient_noise;
 pub mod logger;
 mod preparer;
+mod queue;
 pub mod save;
 pub mod schedule;
 pub mod settings;
 
 use bullet_core::optimiser::{Optimiser, OptimiserState};
 use bullet_hip_backend::ExecutionContext;
+use gradient_noise::{GradientNoise, GradientNoiseRecord, GradientNoiseTracking, ShardGradients};
 pub use preparer::DataPreparer;
+use queue::BatchReceiver;
 use save::SavedFormat;
-use schedule::{lr::LrScheduler, wdl::WdlScheduler, TrainingSchedule};
-use settings::LocalSettings;
+use schedule::{lr::LrScheduler, wdl::WdlScheduler, TrainingSchedule, TrainingSteps};
+use settings::{LocalSettings, SwaSampling};
 
 use std::{
+    any::Any,
+    collections::BTreeMap,
     fs::File,
     io::{self, Write},
-    sync::mpsc::{self, Receiver},
     time::Instant,
 };
 
@@ -26,11 +31,83 @@ pub trait NetworkTrainer {
     /// Load prepared data onto the GPU, return batch size
     fn load_batch(&mut self, prepared: &Self::PreparedData) -> usize;
 
+    /// Load shard `shard` of `shards` equal pieces of the prepared data onto
+    /// the GPU, return shard size, or `None` if this is not supported.
+    fn load_batch_shard(&mut self, _prepared: &Self::PreparedData, _shard: usize, _shards: usize) -> Option<usize> {
+        None
+    }
+
+    /// If set, gradient noise will be estimated and reported during training.
+    fn gradient_noise_tracking(&self) -> Option<GradientNoiseTracking> {
+        None
+    }
+
+    /// A data preparer queued to replace the current one from the start of the next
+    /// superbatch, it must be of the same type as the preparer passed to `train_custom`.
+    fn take_pending_data_preparer(&self) -> Option<Box<dyn Any + Send>> {
+        None
+    }
+
+    /// If set, the weights are added to a stochastic weight average at the end of the
+    /// sampled superbatches, and the average is saved at the end of training.
+    fn swa_sampling(&self) -> Option<SwaSampling> {
+        None
+    }
+
+    /// The temperature of the softmax losses of the graph for the given batch and superbatch,
+    /// if it is scheduled rather than fixed when the graph is built.
+    fn softmax_temperature(&self, _batch: usize, _superbatch: usize) -> Option<f32> {
+        None
+    }
+
+    /// Loss on each output bucket of a batch that has just been evaluated, as
+    /// `(total loss, positions)` per bucket, reported at the end of every superbatch.
+    fn bucket_losses(&self, _prepared: &Self::PreparedData) -> Option<Vec<(f32, usize)>> {
+        None
+    }
+
+    /// Computes gradients separately on each shard of the prepared data to
+    /// estimate the noise in the full batch gradient of each set of weights.
+    ///
+    /// Overwrites the data loaded onto the GPU, so `load_batch` must be
+    /// called again before training.
+    fn estimate_gradient_noise(
+        &mut self,
+        prepared: &Self::PreparedData,
+        shards: usize,
+    ) -> Option<BTreeMap<String, GradientNoise>> {
+        let mut grads = ShardGradients::default();
+
+        for shard in 0..shards {
+            let shard_size = self.load_batch_shard(prepared, shard, shards)?;
+            grads.push(&mut self.optimiser_mut().graph, shard_size);
+        }
+
+        Some(grads.finish())
+    }
+
+    /// Number of batches whose gradients are accumulated before each optimiser step.
+    fn gradient_accumulation_steps(&self) -> usize {
+        1
+    }
+
     /// Trains for a single step on a batch that has been previously
     /// loaded using `load_batch`.
     fn train_on_batch(&mut self, gf: f32, lr: f32) -> f32 {
+        self.train_on_micro_batch(gf, lr, true, true)
+    }
+
+    /// Trains on a batch that has been previously loaded using `load_batch`, as one of
+    /// a sequence of micro-batches whose gradients are accumulated. Gradients are reset
+    /// before the `first` micro-batch and the optimiser only steps after the `last`.
+    fn train_on_micro_batch(&mut self, gf: f32, lr: f32, first: bool, last: bool) -> f32 {
         self.optimiser().graph.synchronise().unwrap();
-        self.optimiser_mut().graph.zero_grads().unwrap();
+
+        if first {
+            self.optimiser_mut().graph.zero_grads().unwrap();
+        } else {
+            self.optimiser_mut().graph.zero_grads_except_weights().unwrap();
+        }
 
         let error = match self.optimiser_mut().graph.forward() {
             Ok(error) => error,
@@ -44,7 +121,9 @@ pub trait NetworkTrainer {
 
         self.optimiser_mut().graph.backward().unwrap();
 
-        self.optimiser_mut().update(gf, lr).unwrap();
+        if last {
+            self.optimiser_mut().update(gf, lr).unwrap();
+        }
 
         self.optimiser().graph.synchronise().unwrap();
 
@@ -96,18 +175,44 @@ pub trait NetworkTrainer {
 
         let mut error_record = Vec::new();
         let mut validation_record = Vec::new();
+        let mut gradient_noise_log = Vec::new();
+        let mut gradient_noise_record = GradientNoiseRecord::default();
+        let gradient_noise = self.gradient_noise_tracking();
 
         std::fs::create_dir(out_dir).unwrap_or(());
 
+        self.optimiser_mut().set_checkpoint_compression(settings.checkpoint_compression);
+
         self.optimiser().graph.synchronise().unwrap();
 
         let steps = schedule.steps;
         let pos_per_sb = steps.batch_size * steps.batches_per_superbatch;
 
-        let (sender, receiver) = mpsc::sync_channel::<D1::PreparedData>(settings.batch_queue_size);
+        let accumulation = self.gradient_accumulation_steps();
+        assert!(
+            steps.batches_per_superbatch % accumulation == 0,
+            "Batches per superbatch must be a multiple of the gradient accumulation steps!"
+        );
 
-        let dataloader =
-            preparer::create_dataloader(preparer.clone(), sender, steps, schedule.wdl_scheduler.clone(), threads);
+        let batch_queue_size = settings.batch_queue_size.unwrap_or(queue::INITIAL_QUEUE_SIZE);
+        let batch_bytes = preparer.prepared_batch_bytes(steps.batch_size);
+        let (sender, mut receiver) = queue::batch_queue::<D1::PreparedData>(batch_queue_size);
+
+        let prefetch = settings.prefetch;
+        let prep_threads = prefetch.prep_threads(threads);
+        let raw_queue_size = prefetch.raw_queue_size();
+        let loader_threads = prefetch.loader_threads();
+
+        let mut dataloader = preparer::create_dataloader(
+            preparer.clone(),
+            sender,
+            steps,
+            schedule.wdl_scheduler.clone(),
+            prep_threads,
+            raw_queue_size,
+            loader_threads,
+            steps.batches_per_superbatch * (steps.start_superbatch - 1),
+        );
 
         let mut validation_freq = settings.test_set.map_or(32, |test| test.freq);
 
@@ -119,14 +224,17 @@ pub trait NetworkTrainer {
         let (test_dataloader, test_receiver) = settings
             .test_set
             .map(|_| {
-                let (sender, receiver) = mpsc::sync_channel::<D1::PreparedData>(2);
+                let (sender, receiver) = queue::batch_queue::<D1::PreparedData>(prefetch.test_queue_size());
                 let steps = schedule.steps_for_validation(validation_freq);
                 let dataloader = preparer::create_dataloader(
                     test_preparer.clone().unwrap(),
                     sender,
                     steps,
                     schedule.wdl_scheduler.clone(),
-                    threads,
+                    prep_threads,
+                    raw_queue_size,
+                    loader_threads,
+                    steps.batches_per_superbatch * (steps.start_superbatch - 1),
                 );
                 (dataloader, receiver)
             })
@@ -135,12 +243,17 @@ pub trait NetworkTrainer {
         let mut prev_lr = schedule.lr(0, 1);
         let mut superbatch = steps.start_superbatch;
         let mut curr_batch = 0;
+        let mut micro_batch = 0;
         let mut superbatch_timer = Instant::now();
+        let mut last_save = Instant::now();
         let mut running_loss = 0.0;
 
         let mut prev32_loss = 0.0;
+        let mut component_losses = Vec::new();
+        let mut bucket_losses = Vec::new();
+        let mut validation_bucket_losses = Vec::new();
 
-        while let Ok(prepared_data) = receiver.recv() {
+        while let Some(prepared_data) = receiver.recv() {
             let lrate = schedule.lr(curr_batch, superbatch);
 
             if curr_batch == 0 {
@@ -153,19 +266,50 @@ pub trait NetworkTrainer {
 
             prev_lr = lrate;
 
+            if let Some(tracking) = gradient_noise {
+                if curr_batch % tracking.freq == 0 && micro_batch == 0 {
+                    if let Some(noise) = self.estimate_gradient_noise(&prepared_data, tracking.shards) {
+                        for (id, stats) in &noise {
+                            gradient_noise_log.push((superbatch, curr_batch, id.clone(), *stats));
+                        }
+
+                        gradient_noise_record.push(&noise);
+                    }
+                }
+            }
+
+            if let Some(temperature) = self.softmax_temperature(curr_batch, superbatch) {
+                self.optimiser_mut().graph.set_softmax_temperature(temperature);
+            }
+
             let this_batch_size = self.load_batch(&prepared_data);
-            let gf = 1.0 / this_batch_size as f32;
+            let gf = 1.0 / (this_batch_size * accumulation) as f32;
+
+            let first = micro_batch == 0;
+            micro_batch = (micro_batch + 1) % accumulation;
+            let error = self.train_on_micro_batch(gf, lrate, first, micro_batch == 0);
+            let error = error / this_batch_size as f32;
 
-            let error = self.train_on_batch(gf, lrate) / this_batch_size as f32;
+            if let Some(losses) = self.bucket_losses(&prepared_data) {
+                accumulate_bucket_losses(&mut bucket_losses, &losses);
+            }
 
             running_loss += error;
             prev32_loss += error;
 
+            let components = self.optimiser().graph.loss_components().unwrap();
+            component_losses.resize(components.len(), (String::new(), 0.0));
+            for ((id, total), (component_id, loss)) in component_losses.iter_mut().zip(components) {
+                *id = component_id;
+                *total += loss / this_batch_size as f32;
+            }
+
             // Track test loss every freq batches.
             if curr_batch % validation_freq == 0 {
-                if let Some(Ok(test_batch)) = test_receiver.as_ref().map(Receiver::recv) {
+                if let Some(test_batch) = test_receiver.as_ref().and_then(BatchReceiver::recv) {
                     let this_batch_size = self.load_batch(&test_batch);
                     self.optimiser().graph.synchronise().unwrap();
+                    self.optimiser_mut().graph.set_training(false);
 
                     let error = match self.optimiser_mut().graph.forward() {
                         Ok(error) => error / this_batch_size as f32,
@@ -177,7 +321,13 @@ pub trait NetworkTrainer {
                         }
                     };
 
+                    self.optimiser_mut().graph.set_training(true);
+
                     validation_record.push((superbatch, curr_batch, error));
+
+                    if let Some(losses) = self.bucket_losses(&test_batch) {
+                        accumulate_bucket_losses(&mut validation_bucket_losses, &losses);
+                    }
                 }
             }
 
@@ -211,17 +361,68 @@ pub trait NetworkTrainer {
                 logger::report_superbatch_finished(superbatch, error, sb_time, total_time, pos_per_sb);
                 logger::report_time_left(steps, superbatch, total_time);
 
+                if !component_losses.is_empty() {
+                    logger::report_loss_components(&component_losses, steps.batches_per_superbatch);
+                    component_losses.clear();
+                }
+
+                if !bucket_losses.is_empty() {
+                    logger::report_bucket_losses(&bucket_losses, &validation_bucket_losses);
+                    bucket_losses.clear();
+                    validation_bucket_losses.clear();
+                }
+
+                let activation_stats = self.optimiser_mut().graph.take_activation_stats();
+                if !activation_stats.is_empty() {
+                    logger::report_activation_stats(&activation_stats);
+                }
+
+                if let Some(tracking) = gradient_noise {
+                    gradient_noise::report(tracking.shards, &gradient_noise_record.take(tracking.shards));
+                }
+
+                if settings.batch_queue_size.is_none() && superbatch == steps.start_superbatch {
+                    let throughput = receiver.take_throughput();
+                    let size = throughput.queue_size(batch_bytes, prefetch.batch_queue_memory_mb());
+                    receiver.resize(size);
+
+                    println!(
+                        "Data loading {:.0} batches/s, training {:.0} batches/s, batch queue resized to {}",
+                        throughput.producer_rate,
+                        throughput.consumer_rate,
+                        logger::ansi(size, logger::num_cs()),
+                    );
+
+                    if throughput.producer_rate < throughput.consumer_rate {
+                        println!("Warning: Data loading is slower than training, consider using more threads!");
+                    }
+                }
+
+                if let Some(sampling) = self.swa_sampling() {
+                    let sample = match sampling {
+                        SwaSampling::LastSuperbatches(n) => superbatch + n > steps.end_superbatch,
+                        SwaSampling::LrMinima => {
+                            let end_lr = schedule.lr(steps.batches_per_superbatch - 1, superbatch);
+                            superbatch == steps.end_superbatch || schedule.lr(0, superbatch + 1) > end_lr
+                        }
+                    };
+
+                    if sample {
+                        self.optimiser_mut().add_to_swa().unwrap();
+                    }
+                }
+
                 if schedule.should_save(superbatch) {
                     let name = format!("{}-{superbatch}", schedule.net_id());
-                    let out_dir = settings.output_directory;
                     let path = format!("{out_dir}/{name}");
                     self.save_to_checkpoint(path.as_str());
 
-                    write_losses(&format!("{path}/log.txt"), &error_record);
-
-                    if settings.test_set.is_some() {
-                        write_losses(&format!("{path}/validation-log.txt"), &validation_record);
-                    }
+                    write_logs(
+                        &path,
+                        &error_record,
+                        settings.test_set.map(|_| validation_record.as_slice()),
+                        gradient_noise.map(|_| gradient_noise_log.as_slice()),
+                    );
 
                     println!("Saved [{}]", logger::ansi(name, 31));
                 }
@@ -232,7 +433,90 @@ pub trait NetworkTrainer {
                 curr_batch = 0;
                 prev32_loss = 0.0;
                 superbatch_timer = Instant::now();
+
+                if let Some(new_preparer) = self.take_pending_data_preparer() {
+                    match new_preparer.downcast::<D1>() {
+                        Ok(new_preparer) if superbatch <= steps.end_superbatch => {
+                            let (new_sender, new_receiver) =
+                                queue::batch_queue::<D1::PreparedData>(receiver.capacity());
+
+                            // the old data loader finishes once nothing is listening for data
+                            drop(std::mem::replace(&mut receiver, new_receiver));
+
+                            let new_dataloader = preparer::create_dataloader(
+                                *new_preparer,
+                                new_sender,
+                                TrainingSteps { start_superbatch: superbatch, ..steps },
+                                schedule.wdl_scheduler.clone(),
+                                prep_threads,
+                                raw_queue_size,
+                                loader_threads,
+                                0,
+                            );
+
+                            std::mem::replace(&mut dataloader, new_dataloader).join().unwrap();
+
+                            println!(
+                                "Switched data loader from superbatch {}",
+                                logger::ansi(superbatch, logger::num_cs())
+                            );
+                        }
+                        Ok(_) => {}
+                        Err(_) => println!("Warning: New data loader does not match the type of the current one!"),
+                    }
+                }
             }
+
+            // Only save between optimiser steps, so no accumulated gradients are lost.
+            let wallclock = settings.wallclock_saves;
+            let step_finished = micro_batch == 0;
+            let past_deadline = step_finished && wallclock.deadline.is_some_and(|deadline| timer.elapsed() >= deadline);
+            let interval_elapsed =
+                step_finished && wallclock.interval.is_some_and(|interval| last_save.elapsed() >= interval);
+
+            if past_deadline || interval_elapsed {
+                let suffix = if past_deadline { "deadline" } else { "wallclock" };
+                let name = format!("{}-{suffix}", schedule.net_id());
+                let path = format!("{out_dir}/{name}");
+                self.save_to_checkpoint(path.as_str());
+
+                write_logs(
+                    &path,
+                    &error_record,
+                    settings.test_set.map(|_| validation_record.as_slice()),
+                    gradient_noise.map(|_| gradient_noise_log.as_slice()),
+                );
+
+                println!();
+                println!(
+                    "Saved [{}] at superbatch {} batch {}",
+                    logger::ansi(name, 31),
+                    logger::ansi(superbatch, logger::num_cs()),
+                    logger::ansi(curr_batch, logger::num_cs()),
+                );
+
+                last_save = Instant::now();
+            }
+
+            if past_deadline {
+                println!("Reached wallclock deadline, stopping training.");
+                break;
+            }
+        }
+
+        // the data loaders finish once nothing is listening for data
+        drop(receiver);
+        drop(test_receiver);
+
+        if let Some(swa) = self.optimiser().swa() {
+            let count = swa.count();
+            let name = format!("{}-swa", schedule.net_id());
+            self.save_to_checkpoint(&format!("{out_dir}/{name}"));
+            println!(
+                "Saved [{}] averaged over {} snapshots",
+                logger::ansi(name, 31),
+                logger::ansi(count, logger::num_cs())
+            );
         }
 
         let total_time = timer.elapsed().as_secs();
@@ -270,6 +554,17 @@ pub trait NetworkTrainer {
     }
 }
 
+fn accumulate_bucket_losses(record: &mut Vec<(f64, u64)>, losses: &[(f32, usize)]) {
+    if record.len() < losses.len() {
+        record.resize(losses.len(), (0.0, 0));
+    }
+
+    for ((total, count), &(loss, positions)) in record.iter_mut().zip(losses) {
+        *total += f64::from(loss);
+        *count += positions as u64;
+    }
+}
+
 fn write_losses(path: &str, error_record: &[(usize, usize, f32)]) {
     use std::io::Write;
 
@@ -278,3 +573,30 @@ fn write_losses(path: &str, error_record: &[(usize, usize, f32)]) {
         writeln!(writer, "{superbatch},{batch},{loss}",).expect("Writing to log file failed!");
     }
 }
+
+fn write_logs(
+    path: &str,
+    error_record: &[(usize, usize, f32)],
+    validation_record: Option<&[(usize, usize, f32)]>,
+    gradient_noise_log: Option<&[(usize, usize, String, GradientNoise)]>,
+) {
+    write_losses(&format!("{path}/log.txt"), error_record);
+
+    if let Some(record) = validation_record {
+        write_losses(&format!("{path}/validation-log.txt"), record);
+    }
+
+    if let Some(record) = gradient_noise_log {
+        write_gradient_noise(&format!("{path}/gradient-noise-log.txt"), record);
+    }
+}
+
+fn write_gradient_noise(path: &str, record: &[(usize, usize, String, GradientNoise)]) {
+    use std::io::Write;
+
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path).expect("Opening log file failed!"));
+    for (superbatch, batch, id, stats) in record {
+        writeln!(writer, "{superbatch},{batch},{id},{},{},{}", stats.signal, stats.variance, stats.snr)
+            .expect("Writing to log file failed!");
+    }
+}
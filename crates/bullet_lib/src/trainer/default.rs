@@ -1,4 +1,9 @@
 mod builder;
+pub mod cached;
+pub mod disagreements;
+pub mod draw_rates;
+pub mod eval_scaling;
+pub mod frequencies;
 pub mod gamerunner;
 /// Contains the `InputType` trait for implementing custom input types,
 /// as well as several premade input formats that are commonly used.
@@ -16,25 +21,36 @@ pub mod formats {
     pub use sfbinpack;
 }
 
-pub use super::save::{Layout, QuantTarget, SavedFormat};
+pub use super::save::{Layout, QuantTarget, QuantisationScheme, SavedFormat};
 pub use builder::{Loss, TrainerBuilder};
 
+use cached::CachedDataLoader;
+use disagreements::DisagreementMiner;
+use draw_rates::DrawRateAnalysis;
+use eval_scaling::{EngineEvalReport, EngineEvalScaling};
+use frequencies::FeatureFrequencies;
 use inputs::SparseInputType;
 use loader::{
     CanBeDirectlySequentiallyLoaded, DataLoader, DefaultDataLoader, DefaultDataPreparer, DirectSequentialDataLoader,
+    GameResult, LoadableDataType, SparseInput, TrainingTargets,
 };
 use outputs::OutputBuckets;
 use testing::{EngineType, TestSettings};
 
 use std::{
+    any::Any,
     collections::HashSet,
     fs::File,
     io::{self, Write},
+    ops::Range,
+    sync::Mutex,
 };
 
 use super::{
+    gradient_noise::GradientNoiseTracking,
     logger,
     schedule::{lr::LrScheduler, wdl::WdlScheduler, TrainingSteps},
+    settings::SwaSampling,
     LocalSettings, NetworkTrainer, TrainingSchedule,
 };
 
@@ -43,18 +59,46 @@ use crate::save;
 use bullet_core::{
     device::OperationError,
     graph::{builder::Node, Graph},
-    optimiser::{Optimiser, OptimiserState},
+    optimiser::{bucketed::BucketedLr, Optimiser, OptimiserState},
+    tensor::DenseMatrix,
 };
 use bullet_hip_backend::{DeviceError, ExecutionContext};
 
-unsafe impl CanBeDirectlySequentiallyLoaded for bulletformat::ChessBoard {}
-unsafe impl CanBeDirectlySequentiallyLoaded for bulletformat::AtaxxBoard {}
+unsafe impl CanBeDirectlySequentiallyLoaded for bulletformat::ChessBoard {
+    fn is_well_formed(&self) -> bool {
+        let mut kings = [0; 2];
+
+        for (piece, _) in self.into_iter() {
+            if piece & 7 == 5 {
+                kings[usize::from(piece & 8 > 0)] += 1;
+            }
+        }
+
+        self.occ().count_ones() <= 32 && kings == [1, 1]
+    }
+}
+unsafe impl CanBeDirectlySequentiallyLoaded for bulletformat::AtaxxBoard {
+    fn is_well_formed(&self) -> bool {
+        let [boys, opps, gaps] = self.bbs();
+        let board = (1 << 49) - 1;
+
+        boys & opps == 0 && (boys | opps) & gaps == 0 && (boys | opps | gaps) & !board == 0
+    }
+}
 unsafe impl CanBeDirectlySequentiallyLoaded for bulletformat::chess::CudADFormat {}
 unsafe impl CanBeDirectlySequentiallyLoaded for bulletformat::chess::MarlinFormat {}
 
+/// Which copy of the weights to save.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WeightSource {
+    Current,
+    Ema,
+    Swa,
+}
+
 #[derive(Clone, Copy)]
 pub struct AdditionalTrainerInputs {
-    wdl: bool,
+    targets: TrainingTargets,
 }
 
 pub struct Trainer<Opt: OptimiserState<ExecutionContext>, Inp, Out = outputs::Single> {
@@ -65,6 +109,14 @@ pub struct Trainer<Opt: OptimiserState<ExecutionContext>, Inp, Out = outputs::Si
     additional_inputs: AdditionalTrainerInputs,
     saved_format: Vec<SavedFormat>,
     factorised_weights: Option<Vec<String>>,
+    activation_quantisations: Vec<i16>,
+    quantisation_schemes: Vec<QuantisationScheme>,
+    bucket_loss: Option<Loss>,
+    gradient_noise: Option<GradientNoiseTracking>,
+    swa_sampling: Option<SwaSampling>,
+    gradient_accumulation: usize,
+    softmax_temperature: Option<Box<dyn Fn(usize, usize) -> f32 + Send + Sync>>,
+    pending_data_loader: Mutex<Option<Box<dyn Any + Send>>>,
 }
 
 impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuckets<Inp::RequiredDataType>>
@@ -77,6 +129,59 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
         unsafe { load_into_graph(&mut self.optimiser.graph, prepared).unwrap() }
     }
 
+    fn load_batch_shard(&mut self, prepared: &Self::PreparedData, shard: usize, shards: usize) -> Option<usize> {
+        let shard_size = prepared.batch_size.div_ceil(shards);
+        let start = (shard * shard_size).min(prepared.batch_size);
+        let end = (start + shard_size).min(prepared.batch_size);
+
+        if start == end {
+            return Some(0);
+        }
+
+        Some(unsafe { load_range_into_graph(&mut self.optimiser.graph, prepared, start..end).unwrap() })
+    }
+
+    fn gradient_noise_tracking(&self) -> Option<GradientNoiseTracking> {
+        self.gradient_noise
+    }
+
+    fn swa_sampling(&self) -> Option<SwaSampling> {
+        self.swa_sampling
+    }
+
+    fn gradient_accumulation_steps(&self) -> usize {
+        self.gradient_accumulation
+    }
+
+    fn take_pending_data_preparer(&self) -> Option<Box<dyn Any + Send>> {
+        self.pending_data_loader.lock().unwrap().take()
+    }
+
+    fn softmax_temperature(&self, batch: usize, superbatch: usize) -> Option<f32> {
+        self.softmax_temperature.as_ref().map(|schedule| schedule(batch, superbatch))
+    }
+
+    fn bucket_losses(&self, prepared: &Self::PreparedData) -> Option<Vec<(f32, usize)>> {
+        let loss = self.bucket_loss?;
+        let outputs = self.optimiser.graph.get_node(self.output_node).get_dense_vals().ok()?;
+        let outputs_per_pos = outputs.len() / prepared.batch_size.max(1);
+        let targets_per_pos = prepared.targets.value.len() / prepared.batch_size.max(1);
+
+        let mut losses = vec![(0.0, 0); Out::BUCKETS];
+
+        for ((out, targets), &bucket) in outputs
+            .chunks_exact(outputs_per_pos)
+            .zip(prepared.targets.value.chunks_exact(targets_per_pos))
+            .zip(prepared.buckets.value.iter())
+        {
+            let (total, count) = &mut losses[bucket as usize];
+            *total += loss.position_loss(out, targets);
+            *count += 1;
+        }
+
+        Some(losses)
+    }
+
     fn optimiser(&self) -> &Optimiser<ExecutionContext, Self::OptimiserState> {
         &self.optimiser
     }
@@ -101,6 +206,53 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
             println!("Failed to write quantised network weights:");
             println!("{e}");
         }
+
+        if self.optimiser.ema().is_some() {
+            if let Err(e) = self.save_ema_unquantised(&format!("{path}/ema-raw.bin")) {
+                println!("Failed to write raw EMA network weights:");
+                println!("{e}");
+            }
+
+            if let Err(e) = self.save_ema_quantised(&format!("{path}/ema-quantised.bin")) {
+                println!("Failed to write quantised EMA network weights:");
+                println!("{e}");
+            }
+        }
+
+        if self.optimiser.swa().is_some() {
+            if let Err(e) = self.save_swa_unquantised(&format!("{path}/swa-raw.bin")) {
+                println!("Failed to write raw SWA network weights:");
+                println!("{e}");
+            }
+
+            if let Err(e) = self.save_swa_quantised(&format!("{path}/swa-quantised.bin")) {
+                println!("Failed to write quantised SWA network weights:");
+                println!("{e}");
+            }
+        }
+
+        for scheme in &self.quantisation_schemes {
+            let name = &scheme.name;
+            let quantised = self.save_quantised_with(
+                &format!("{path}/quantised-{name}.bin"),
+                &scheme.formats,
+                &scheme.activation_quantisations,
+                WeightSource::Current,
+            );
+
+            match quantised {
+                Ok(errors) => {
+                    println!("Quantisation scheme [{name}]:");
+                    for (id, max, rms) in errors {
+                        println!("    {id:>10} | max error {max:.6} | rms error {rms:.6}");
+                    }
+                }
+                Err(e) => {
+                    println!("Failed to write [{name}] quantised network weights:");
+                    println!("{e}");
+                }
+            }
+        }
     }
 }
 
@@ -126,14 +278,16 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
 
         let nstm = inputs.contains("nstm");
         let output_buckets = inputs.contains("buckets");
-        let expected = 2 + usize::from(nstm) + usize::from(output_buckets);
+        let dense = inputs.contains("dense");
+        let groups = inputs.iter().filter(|id| id.starts_with("stm_") || id.starts_with("nstm_")).count();
+        let policy = 2 * usize::from(inputs.contains("policy_mask"));
+        let expected = 2 + usize::from(nstm) + usize::from(output_buckets) + usize::from(dense) + groups + policy;
 
         let output_shape = output_node.shape();
 
         assert_eq!(output_shape.cols(), 1, "Output cannot have >1 column!");
-        assert!(output_shape.rows() == 1 || output_shape.rows() == 3, "Only supports 1 or 3 outputs!");
 
-        let wdl = output_shape.rows() == 3;
+        let targets = TrainingTargets::from_num_outputs(output_shape.rows()).expect("Only supports 1, 3 or 4 outputs!");
 
         if inputs.len() != expected {
             println!("WARNING: The network graph contains an unexpected number of inputs!")
@@ -144,9 +298,17 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
             input_getter,
             output_getter,
             output_node,
-            additional_inputs: AdditionalTrainerInputs { wdl },
+            additional_inputs: AdditionalTrainerInputs { targets },
             saved_format,
             factorised_weights: None,
+            activation_quantisations: Vec::new(),
+            quantisation_schemes: Vec::new(),
+            bucket_loss: None,
+            gradient_noise: None,
+            swa_sampling: None,
+            gradient_accumulation: 1,
+            softmax_temperature: None,
+            pending_data_loader: Mutex::new(None),
         }
     }
 
@@ -159,6 +321,25 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
     }
 
     pub fn eval_raw_output(&mut self, fen: &str) -> Vec<f32>
+    where
+        Inp::RequiredDataType: std::str::FromStr<Err = String>,
+    {
+        self.forward_fen(fen);
+        self.optimiser.graph.get_node(self.output_node).get_dense_vals().unwrap()
+    }
+
+    /// The values of the node named `label` (with `NetworkBuilderNode::named`) for the
+    /// position `fen`, e.g. to inspect the output of a hidden layer.
+    pub fn eval_named(&mut self, fen: &str, label: &str) -> Vec<f32>
+    where
+        Inp::RequiredDataType: std::str::FromStr<Err = String>,
+    {
+        self.forward_fen(fen);
+        let node = self.optimiser.graph.get_named(label).unwrap_or_else(|| panic!("No node named {label}!"));
+        node.get_dense_vals().unwrap()
+    }
+
+    fn forward_fen(&mut self, fen: &str)
     where
         Inp::RequiredDataType: std::str::FromStr<Err = String>,
     {
@@ -167,7 +348,7 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
         let prepared = DefaultDataPreparer::prepare(
             self.input_getter.clone(),
             self.output_getter,
-            self.additional_inputs.wdl,
+            self.additional_inputs.targets,
             &[pos],
             1,
             1.0,
@@ -175,14 +356,9 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
         );
 
         self.load_batch(&prepared);
+        self.optimiser.graph.set_training(false);
         self.optimiser.graph.forward().unwrap();
-
-        let eval = self.optimiser.graph.get_node(self.output_node);
-
-        let dense_vals = eval.values.dense().unwrap();
-        let mut vals = vec![0.0; dense_vals.size()];
-        dense_vals.write_to_slice(&mut vals).unwrap();
-        vals
+        self.optimiser.graph.set_training(true);
     }
 
     pub fn eval(&mut self, fen: &str) -> f32
@@ -200,15 +376,321 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
 
                 (win + draw / 2.0) / (win + draw + loss)
             }
-            [score] => *score,
+            [score] | [_, _, _, score] => *score,
             _ => panic!("Invalid output size!"),
         }
     }
 
+    /// Compares the draw rates predicted by the WDL head against the results of up to
+    /// `max_positions` positions from `data_loader` (typically validation data), grouped
+    /// by `key` (e.g. `draw_rates::piece_count`) and by output bucket, to help calibrate
+    /// contempt and draw adjudication in an engine. Each position is seen at most once.
+    pub fn analyse_draw_rates<D, K>(&mut self, data_loader: &D, max_positions: usize, key: K) -> DrawRateAnalysis
+    where
+        D: DataLoader<Inp::RequiredDataType>,
+        K: Fn(&Inp::RequiredDataType) -> usize,
+    {
+        let targets = self.additional_inputs.targets;
+        assert!(targets.has_wdl(), "Draw rate analysis requires a network with a WDL output!");
+
+        let max_positions = data_loader.count_positions().map_or(max_positions, |num| max_positions.min(num as usize));
+        let mut analysis = DrawRateAnalysis::default();
+        let mut positions = 0;
+
+        if max_positions == 0 {
+            return analysis;
+        }
+
+        data_loader.map_batches(0, 16384, |batch| {
+            let batch = &batch[..batch.len().min(max_positions - positions)];
+
+            let prepared = DefaultDataPreparer::prepare(
+                self.input_getter.clone(),
+                self.output_getter,
+                self.additional_inputs.targets,
+                batch,
+                4,
+                1.0,
+                1.0,
+            );
+
+            self.load_batch(&prepared);
+            self.optimiser.graph.set_training(false);
+            self.optimiser.graph.forward().unwrap();
+            self.optimiser.graph.set_training(true);
+
+            let output = self.optimiser.graph.get_node(self.output_node);
+            let output = output.values.dense().unwrap();
+            let mut vals = vec![0.0; output.size()];
+            output.write_to_slice(&mut vals).unwrap();
+
+            for (pos, out) in batch.iter().zip(vals.chunks_exact(targets.num_outputs())) {
+                let bucket = usize::from(self.output_getter.bucket(pos));
+                let drawn = pos.result() == GameResult::Draw;
+                analysis.push(key(pos), bucket, &out[..3], drawn);
+            }
+
+            positions += batch.len();
+            positions == max_positions
+        });
+
+        analysis
+    }
+
+    /// Evaluates up to `max_positions` positions from a dataset, recording the positions
+    /// where the network, the recorded score (scaled by `eval_scale`) and the game result
+    /// all disagree, for data-quality review or relabelling.
+    pub fn mine_disagreements<D: DataLoader<Inp::RequiredDataType>>(
+        &mut self,
+        data_loader: &D,
+        max_positions: usize,
+        eval_scale: f32,
+        miner: &mut DisagreementMiner<Inp::RequiredDataType>,
+    ) {
+        let mut positions = 0;
+
+        data_loader.map_batches(0, 16384, |batch| {
+            let batch = &batch[..batch.len().min(max_positions - positions)];
+
+            let prepared = DefaultDataPreparer::prepare(
+                self.input_getter.clone(),
+                self.output_getter,
+                self.additional_inputs.targets,
+                batch,
+                4,
+                1.0,
+                eval_scale,
+            );
+
+            self.load_batch(&prepared);
+            self.optimiser.graph.set_training(false);
+            self.optimiser.graph.forward().unwrap();
+            self.optimiser.graph.set_training(true);
+
+            let output = self.optimiser.graph.get_node(self.output_node);
+            let output = output.values.dense().unwrap();
+            let mut vals = vec![0.0; output.size()];
+            output.write_to_slice(&mut vals).unwrap();
+
+            let targets = self.additional_inputs.targets;
+
+            for (pos, out) in batch.iter().zip(vals.chunks_exact(targets.num_outputs())) {
+                let net = if targets == TrainingTargets::Wdl {
+                    let max = out.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+                    let exps = out.iter().map(|x| (x - max).exp()).collect::<Vec<_>>();
+                    (0.5 * exps[1] + exps[2]) / exps.iter().sum::<f32>()
+                } else {
+                    1.0 / (1.0 + (-out[out.len() - 1]).exp())
+                };
+
+                miner.push(pos.clone(), net, eval_scale);
+            }
+
+            positions += batch.len();
+            positions == max_positions
+        });
+    }
+
+    /// Passes the network outputs on up to `max_positions` positions through the same
+    /// scaling an engine would use, reporting the distribution of centipawn scores it would
+    /// output against the scores recorded in the data, to verify the scaling before shipping.
+    ///
+    /// WDL outputs are first converted to the log-odds of the expected score, and networks
+    /// with both a WDL head and an eval head use the eval head.
+    pub fn simulate_engine_eval<D: DataLoader<Inp::RequiredDataType>>(
+        &mut self,
+        data_loader: &D,
+        max_positions: usize,
+        scaling: EngineEvalScaling,
+    ) -> EngineEvalReport {
+        let mut report = EngineEvalReport::new(100);
+        let mut positions = 0;
+
+        data_loader.map_batches(0, 16384, |batch| {
+            let batch = &batch[..batch.len().min(max_positions - positions)];
+
+            let prepared = DefaultDataPreparer::prepare(
+                self.input_getter.clone(),
+                self.output_getter,
+                self.additional_inputs.targets,
+                batch,
+                4,
+                1.0,
+                1.0,
+            );
+
+            self.load_batch(&prepared);
+            self.optimiser.graph.set_training(false);
+            self.optimiser.graph.forward().unwrap();
+            self.optimiser.graph.set_training(true);
+
+            let output = self.optimiser.graph.get_node(self.output_node);
+            let output = output.values.dense().unwrap();
+            let mut vals = vec![0.0; output.size()];
+            output.write_to_slice(&mut vals).unwrap();
+
+            let targets = self.additional_inputs.targets;
+
+            for (pos, out) in batch.iter().zip(vals.chunks_exact(targets.num_outputs())) {
+                let raw = if targets == TrainingTargets::Wdl {
+                    let max = out.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+                    let exps = out.iter().map(|x| (x - max).exp()).collect::<Vec<_>>();
+                    let expected = (0.5 * exps[1] + exps[2]) / exps.iter().sum::<f32>();
+                    let expected = expected.clamp(1e-6, 1.0 - 1e-6);
+                    (expected / (1.0 - expected)).ln()
+                } else {
+                    out[out.len() - 1]
+                };
+
+                report.push(scaling.uci_cp(raw), LoadableDataType::score(pos));
+            }
+
+            positions += batch.len();
+            positions == max_positions
+        });
+
+        report
+    }
+
+    /// Sets the optimiser params for all weights, replacing any per-weight params.
     pub fn set_optimiser_params(&mut self, params: Opt::Params) {
         self.optimiser.set_params(params);
     }
 
+    pub fn set_optimiser_params_for_weight(&mut self, id: &str, params: Opt::Params) {
+        self.optimiser.set_params_for_weight(id, params);
+    }
+
+    /// Sets `params` for all weights, except those with an id in `overrides`, which are
+    /// given the paired params instead, e.g. `params.with_overrides(..)` for AdamW.
+    pub fn set_optimiser_params_with_overrides(&mut self, params: Opt::Params, overrides: &[(&str, Opt::Params)]) {
+        self.optimiser.set_params(params);
+
+        for (id, params) in overrides {
+            assert!(self.optimiser.state.contains_key(*id), "Weights with id '{id}' do not exist!");
+            self.optimiser.set_params_for_weight(id, params.clone());
+        }
+    }
+
+    /// Multiplies the scheduled learning rate for the weights with the given id, e.g.
+    /// `0.1` to slow down training of the feature transformer when fine-tuning.
+    pub fn set_lr_multiplier(&mut self, id: &str, multiplier: f32) {
+        self.optimiser.set_lr_multiplier(id, multiplier);
+    }
+
+    /// Clips the global L2 norm of the gradients of all weights to `max_norm` before
+    /// each optimiser update, or disables clipping if `None`.
+    pub fn set_gradient_clipping(&mut self, max_norm: Option<f32>) {
+        self.optimiser.set_gradient_clipping(max_norm);
+    }
+
+    /// Replaces the training data loader from the start of the next superbatch, keeping
+    /// the optimiser state. Intended to be called from a training callback, e.g. to finish
+    /// training on a curated dataset after pretraining on a larger one.
+    ///
+    /// The new loader must be the same type as the loader training was started with.
+    pub fn set_data_loader<D, LR, WDL>(&self, schedule: &TrainingSchedule<LR, WDL>, data_loader: D)
+    where
+        D: DataLoader<Inp::RequiredDataType>,
+        LR: LrScheduler,
+        WDL: WdlScheduler,
+    {
+        let preparer = DefaultDataLoader::new(
+            self.input_getter.clone(),
+            self.output_getter,
+            self.additional_inputs.targets,
+            schedule.eval_scale,
+            data_loader,
+        );
+
+        *self.pending_data_loader.lock().unwrap() = Some(Box::new(preparer));
+    }
+
+    /// Reports the loss of each output bucket at the end of every superbatch, computing the
+    /// loss of each position on the CPU with `loss`. This is enabled automatically by
+    /// `TrainerBuilder` for networks with output buckets.
+    pub fn track_bucket_losses(&mut self, loss: Loss) {
+        self.bucket_loss = Some(loss);
+    }
+
+    /// Every `freq` batches, split the batch into `shards` pieces and compute the gradients
+    /// on each separately, reporting the gradient variance and signal-to-noise ratio of each
+    /// set of weights at the end of every superbatch.
+    pub fn track_gradient_noise(&mut self, shards: usize, freq: usize) {
+        self.gradient_noise = Some(GradientNoiseTracking::new(shards, freq));
+    }
+
+    /// Accumulates gradients over `steps` batches before each optimiser step, to train with
+    /// an effective batch size larger than fits in GPU memory. The batches per superbatch
+    /// must be a multiple of `steps`, and the learning rate is scheduled per batch as usual.
+    pub fn set_gradient_accumulation(&mut self, steps: usize) {
+        assert!(steps > 0, "Must accumulate gradients over at least one batch!");
+        self.gradient_accumulation = steps;
+    }
+
+    /// Reports the minimum, maximum, mean and standard deviation of the values of every
+    /// node at the end of every superbatch, e.g. to choose quantisation ranges or spot
+    /// saturated activations. Only every `interval`-th batch is sampled, as each one
+    /// sampled slows down training considerably.
+    pub fn track_activation_stats(&mut self, interval: usize) {
+        self.optimiser.graph.record_activation_stats(Some(interval));
+    }
+
+    /// Sets the temperature of every softmax cross-entropy loss before each batch to
+    /// `schedule(batch, superbatch)`, e.g. to anneal the softness of distillation targets.
+    pub fn schedule_softmax_temperature(&mut self, schedule: impl Fn(usize, usize) -> f32 + Send + Sync + 'static) {
+        self.softmax_temperature = Some(Box::new(schedule));
+    }
+
+    /// Scans up to `max_positions` positions of `data_loader` and reports how often each
+    /// input feature occurs, warning about features that never occur.
+    pub fn scan_feature_frequencies<D>(&self, data_loader: &D, max_positions: usize) -> FeatureFrequencies
+    where
+        D: DataLoader<Inp::RequiredDataType>,
+    {
+        let frequencies = FeatureFrequencies::scan(&self.input_getter, data_loader, max_positions);
+        frequencies.report();
+        frequencies
+    }
+
+    /// Zeroes the weights of the given input features in the input weights `id`, which
+    /// must have one column per input feature. To keep them at zero during training, also
+    /// give them a learning rate multiplier of zero (see `FeatureFrequencies::lr_multipliers`).
+    pub fn prune_input_features(&mut self, id: &str, features: &[usize]) {
+        let weights = self.optimiser.graph.get_weights_mut(id);
+        let num_inputs = self.input_getter.num_inputs();
+
+        let mut buf = vec![0.0; weights.values.size()];
+        weights.values.dense().unwrap().write_to_slice(&mut buf).unwrap();
+
+        assert_eq!(buf.len() % num_inputs, 0, "Weights do not have one column per input feature!");
+        let layer_size = buf.len() / num_inputs;
+
+        for &feat in features {
+            buf[layer_size * feat..layer_size * (feat + 1)].fill(0.0);
+        }
+
+        weights.load_from_slice(None, &buf).unwrap();
+    }
+
+    /// Writes the given activation quantisation scales (as `i16`s) after the weights
+    /// in the quantised network, see `TrainerBuilder::activation_quantisations`.
+    pub fn set_activation_quantisations(&mut self, quants: &[i16]) {
+        self.activation_quantisations = quants.to_vec();
+    }
+
+    /// Additionally exports the network with a different quantisation scheme at each
+    /// checkpoint, reporting the quantisation error of each weight.
+    pub fn add_quantisation_scheme(&mut self, scheme: QuantisationScheme) {
+        assert!(
+            self.quantisation_schemes.iter().all(|existing| existing.name != scheme.name),
+            "Quantisation scheme [{}] already exists!",
+            scheme.name
+        );
+
+        self.quantisation_schemes.push(scheme);
+    }
+
     pub fn mark_weights_as_input_factorised(&mut self, weights: &[&str]) {
         if self.factorised_weights.is_none() {
             self.factorised_weights = Some(Vec::new())
@@ -220,17 +702,78 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
     }
 
     pub fn save_quantised(&self, path: &str) -> io::Result<()> {
-        let mut file = File::create(path).unwrap();
+        self.save_quantised_with(path, &self.saved_format, &self.activation_quantisations, WeightSource::Current)
+            .map(|_| ())
+    }
 
-        let mut buf = Vec::new();
+    /// Maintains an exponential moving average of the weights, see `Optimiser::enable_ema`,
+    /// which is saved in checkpoints alongside the raw weights.
+    pub fn enable_weight_ema(&mut self, decay: f32) {
+        self.optimiser.enable_ema(decay).unwrap();
+    }
+
+    /// As `save_quantised`, but with the exponential moving average of the weights.
+    pub fn save_ema_quantised(&self, path: &str) -> io::Result<()> {
+        self.save_quantised_with(path, &self.saved_format, &self.activation_quantisations, WeightSource::Ema)
+            .map(|_| ())
+    }
 
-        for SavedFormat { id, quant, layout } in &self.saved_format {
-            let weights = self.optimiser.graph.get_weights(id);
-            let weights = weights.values.dense().unwrap();
+    /// As `save_unquantised`, but with the exponential moving average of the weights.
+    pub fn save_ema_unquantised(&self, path: &str) -> io::Result<()> {
+        self.save_unquantised_with(path, WeightSource::Ema)
+    }
+
+    /// Adds the weights to a stochastic weight average at the end of the superbatches
+    /// given by `sampling`, which is saved in checkpoints and at the end of training.
+    pub fn set_swa_sampling(&mut self, sampling: SwaSampling) {
+        self.swa_sampling = Some(sampling);
+    }
+
+    /// As `save_quantised`, but with the stochastic weight average.
+    pub fn save_swa_quantised(&self, path: &str) -> io::Result<()> {
+        self.save_quantised_with(path, &self.saved_format, &self.activation_quantisations, WeightSource::Swa)
+            .map(|_| ())
+    }
+
+    /// As `save_unquantised`, but with the stochastic weight average.
+    pub fn save_swa_unquantised(&self, path: &str) -> io::Result<()> {
+        self.save_unquantised_with(path, WeightSource::Swa)
+    }
 
+    fn weight_values(&self, id: &str, source: WeightSource) -> Vec<f32> {
+        let read = |weights: &DenseMatrix<ExecutionContext>| {
             let mut weight_buf = vec![0.0; weights.size()];
             let written = weights.write_to_slice(&mut weight_buf).unwrap();
             assert_eq!(written, weights.size());
+            weight_buf
+        };
+
+        match source {
+            WeightSource::Current => read(self.optimiser.graph.get_weights(id).values.dense().unwrap()),
+            WeightSource::Ema => {
+                read(self.optimiser.ema().and_then(|ema| ema.get(id)).expect("EMA of weights is not enabled!"))
+            }
+            WeightSource::Swa => {
+                read(self.optimiser.swa().and_then(|swa| swa.get(id)).expect("No weights have been averaged!"))
+            }
+        }
+    }
+
+    /// Returns the maximum and root mean square quantisation error of each weight.
+    fn save_quantised_with(
+        &self,
+        path: &str,
+        formats: &[SavedFormat],
+        activation_quantisations: &[i16],
+        source: WeightSource,
+    ) -> io::Result<Vec<(String, f32, f32)>> {
+        let mut file = File::create(path).unwrap();
+
+        let mut buf = Vec::new();
+        let mut errors = Vec::new();
+
+        for SavedFormat { id, quant, layout } in formats {
+            let mut weight_buf = self.weight_values(id, source);
 
             if let Some(factorised) = &self.factorised_weights {
                 if factorised.contains(id) {
@@ -246,14 +789,21 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
             }
 
             if let Layout::Transposed(shape) = layout {
-                assert_eq!(shape.size(), weights.size());
+                assert_eq!(shape.size(), weight_buf.len());
                 weight_buf = save::transpose(*shape, &weight_buf);
             }
 
+            let (max, rms) = quant.quantisation_error(&weight_buf);
+            errors.push((id.clone(), max, rms));
+
             let quantised = quant.quantise(&weight_buf)?;
             buf.extend_from_slice(&quantised);
         }
 
+        for scale in activation_quantisations {
+            buf.extend_from_slice(&scale.to_le_bytes());
+        }
+
         let bytes = buf.len() % 64;
         if bytes > 0 {
             let chs = [b'b', b'u', b'l', b'l', b'e', b't'];
@@ -265,21 +815,20 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
 
         file.write_all(&buf)?;
 
-        Ok(())
+        Ok(errors)
     }
 
     pub fn save_unquantised(&self, path: &str) -> io::Result<()> {
+        self.save_unquantised_with(path, WeightSource::Current)
+    }
+
+    fn save_unquantised_with(&self, path: &str, source: WeightSource) -> io::Result<()> {
         let mut file = File::create(path).unwrap();
 
         let mut buf = Vec::new();
 
         for SavedFormat { id, .. } in &self.saved_format {
-            let weights = self.optimiser.graph.get_weights(id);
-            let weights = weights.values.dense().unwrap();
-
-            let mut weight_buf = vec![0.0; weights.size()];
-            let written = weights.write_to_slice(&mut weight_buf).unwrap();
-            assert_eq!(written, weights.size());
+            let weight_buf = self.weight_values(id, source);
 
             let quantised = QuantTarget::Float.quantise(&weight_buf)?;
             buf.extend_from_slice(&quantised);
@@ -310,7 +859,7 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
         let preparer = DefaultDataLoader::new(
             self.input_getter.clone(),
             self.output_getter,
-            self.additional_inputs.wdl,
+            self.additional_inputs.targets,
             schedule.eval_scale,
             data_loader.clone(),
         );
@@ -319,7 +868,7 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
             DefaultDataLoader::new(
                 self.input_getter.clone(),
                 self.output_getter,
-                self.additional_inputs.wdl,
+                self.additional_inputs.targets,
                 schedule.eval_scale,
                 loader.clone(),
             )
@@ -333,16 +882,22 @@ impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuc
 
 fn display_total_positions<T, D: DataLoader<T>>(data_loader: &D, steps: TrainingSteps) {
     if let Some(num) = data_loader.count_positions() {
-        let pos_per_sb = steps.batch_size * steps.batches_per_superbatch;
-        let sbs = steps.end_superbatch - steps.start_superbatch + 1;
-        let total_pos = pos_per_sb * sbs;
-        let iters = total_pos as f64 / num as f64;
+        let iters = steps.epochs(num);
 
         println!("Positions              : {}", logger::ansi(num, 31));
         println!("Total Epochs           : {}", logger::ansi(format!("{iters:.2}"), 31));
     }
 }
 
+impl<S: OptimiserState<ExecutionContext>, Inp, Out> Trainer<BucketedLr<ExecutionContext, S>, Inp, Out> {
+    /// Splits the weights with the given id into `multipliers.len()` equally sized contiguous
+    /// buckets, and scales the learning rate of each by its multiplier. Other weights are unaffected.
+    pub fn set_lr_bucket_multipliers(&mut self, id: &str, multipliers: Vec<f32>) {
+        let state = self.optimiser.state.get_mut(id).unwrap_or_else(|| panic!("Weights with id '{id}' do not exist!"));
+        state.set_multipliers(multipliers);
+    }
+}
+
 impl<Opt: OptimiserState<ExecutionContext>, Inp: SparseInputType, Out: OutputBuckets<Inp::RequiredDataType>>
     Trainer<Opt, Inp, Out>
 where
@@ -360,6 +915,49 @@ where
         self.train_custom(&preparer, &test_preparer, schedule, settings, |_, _, _, _| {});
     }
 
+    /// Same as `run`, but reads the whole of `data_loader` into memory first, caching the
+    /// prepared inputs of every position so that later epochs skip feature extraction.
+    /// Only suitable for datasets small enough to fit in RAM.
+    pub fn run_cached<D: DataLoader<Inp::RequiredDataType>, LR: LrScheduler, WDL: WdlScheduler>(
+        &mut self,
+        schedule: &TrainingSchedule<LR, WDL>,
+        settings: &LocalSettings,
+        data_loader: &D,
+    ) {
+        let test_loader = settings.test_set.map(|test| DirectSequentialDataLoader::new(&[test.path]));
+        let (_, test_preparer) = self.training_preamble(schedule, settings, data_loader, &test_loader);
+
+        let preparer = CachedDataLoader::new(
+            self.input_getter.clone(),
+            self.output_getter,
+            self.additional_inputs.targets,
+            schedule.eval_scale,
+            data_loader,
+            settings.threads,
+        );
+
+        self.train_custom(&preparer, &test_preparer, schedule, settings, |_, _, _, _| {});
+    }
+
+    /// Same as `run`, but calls `callback` at the end of every superbatch.
+    pub fn run_with_callback<D, LR, WDL, F>(
+        &mut self,
+        schedule: &TrainingSchedule<LR, WDL>,
+        settings: &LocalSettings,
+        data_loader: &D,
+        callback: F,
+    ) where
+        D: DataLoader<Inp::RequiredDataType>,
+        LR: LrScheduler,
+        WDL: WdlScheduler,
+        F: FnMut(usize, &Self, &TrainingSchedule<LR, WDL>, &LocalSettings),
+    {
+        let test_loader = settings.test_set.map(|test| DirectSequentialDataLoader::new(&[test.path]));
+        let (preparer, test_preparer) = self.training_preamble(schedule, settings, data_loader, &test_loader);
+
+        self.train_custom(&preparer, &test_preparer, schedule, settings, callback);
+    }
+
     pub fn run_and_test<D: DataLoader<Inp::RequiredDataType>, LR: LrScheduler, WDL: WdlScheduler, T: EngineType>(
         &mut self,
         schedule: &TrainingSchedule<LR, WDL>,
@@ -405,8 +1003,25 @@ where
     Inp: SparseInputType,
     Out: OutputBuckets<Inp::RequiredDataType>,
 {
-    let batch_size = prepared.batch_size;
+    load_range_into_graph(graph, prepared, 0..prepared.batch_size)
+}
+
+/// # Safety
+///
+/// Same requirements as `load_into_graph`, loads only the positions in `range`.
+pub unsafe fn load_range_into_graph<Inp, Out>(
+    graph: &mut Graph<ExecutionContext>,
+    prepared: &DefaultDataPreparer<Inp, Out>,
+    range: Range<usize>,
+) -> Result<usize, OperationError<DeviceError>>
+where
+    Inp: SparseInputType,
+    Out: OutputBuckets<Inp::RequiredDataType>,
+{
+    let batch_size = range.len();
     let expected_inputs = prepared.input_getter.num_inputs();
+    let targets_per_pos = prepared.targets.value.len() / prepared.batch_size.max(1);
+    let sparse_range = |input: &SparseInput| input.max_active * range.start..input.max_active * range.end;
 
     unsafe {
         let input = &prepared.stm;
@@ -416,7 +1031,7 @@ where
             return Err(OperationError::InvalidTensorFormat);
         }
 
-        stm.load_sparse_from_slice(input.max_active, Some(batch_size), &input.value)?;
+        stm.load_sparse_from_slice(input.max_active, Some(batch_size), &input.value[sparse_range(input)])?;
 
         if graph.input_ids().contains(&"nstm".to_string()) {
             let input = &prepared.nstm;
@@ -426,7 +1041,7 @@ where
                 return Err(OperationError::InvalidTensorFormat);
             }
 
-            ntm.load_sparse_from_slice(input.max_active, Some(batch_size), &input.value)?;
+            ntm.load_sparse_from_slice(input.max_active, Some(batch_size), &input.value[sparse_range(input)])?;
         }
     }
 
@@ -438,10 +1053,61 @@ where
             return Err(OperationError::InvalidTensorFormat);
         }
 
-        buckets.load_sparse_from_slice(input.max_active, Some(batch_size), &input.value)?;
+        buckets.load_sparse_from_slice(input.max_active, Some(batch_size), &input.value[sparse_range(input)])?;
+    }
+
+    for (idx, (group_size, _)) in prepared.input_getter.extra_groups().into_iter().enumerate() {
+        let (stm, nstm) = &prepared.groups[idx];
+
+        for (id, input) in [(format!("stm_{}", idx + 1), stm), (format!("nstm_{}", idx + 1), nstm)] {
+            if graph.input_ids().contains(&id) {
+                let tensor = graph.get_input_mut(&id);
+
+                if tensor.values.single_size() != group_size {
+                    return Err(OperationError::InvalidTensorFormat);
+                }
+
+                tensor.load_sparse_from_slice(input.max_active, Some(batch_size), &input.value[sparse_range(input)])?;
+            }
+        }
+    }
+
+    if graph.input_ids().contains(&"dense".to_string()) {
+        let dense_size = prepared.input_getter.num_dense_inputs();
+        let dense = graph.get_input_mut("dense");
+
+        if dense.values.single_size() != dense_size {
+            return Err(OperationError::InvalidTensorFormat);
+        }
+
+        dense.load_dense_from_slice(
+            Some(batch_size),
+            &prepared.dense.value[dense_size * range.start..dense_size * range.end],
+        )?;
+    }
+
+    if let Some((mask, policy_targets)) = &prepared.policy {
+        let num_outputs = prepared.input_getter.num_policy_outputs();
+        let max_moves = mask.max_active;
+
+        if graph.input_ids().contains(&"policy_mask".to_string()) {
+            let tensor = graph.get_input_mut("policy_mask");
+
+            if tensor.values.single_size() != num_outputs {
+                return Err(OperationError::InvalidTensorFormat);
+            }
+
+            tensor.load_sparse_from_slice(max_moves, Some(batch_size), &mask.value[sparse_range(mask)])?;
+
+            graph.get_input_mut("policy_targets").load_dense_from_slice(
+                Some(batch_size),
+                &policy_targets.value[max_moves * range.start..max_moves * range.end],
+            )?;
+        }
     }
 
-    graph.get_input_mut("targets").load_dense_from_slice(Some(batch_size), &prepared.targets.value)?;
+    let targets = &prepared.targets.value[targets_per_pos * range.start..targets_per_pos * range.end];
+    graph.get_input_mut("targets").load_dense_from_slice(Some(batch_size), targets)?;
 
     Ok(batch_size)
 }
@@ -12,25 +12,82 @@ use crate::{
 
 use super::{
     inputs::SparseInputType,
+    loader::TrainingTargets,
     outputs::{self, OutputBuckets},
     AdditionalTrainerInputs, Trainer,
 };
 
 use bullet_core::optimiser::Optimiser;
+use std::sync::Mutex;
 
 #[derive(Clone, Copy, Debug)]
 pub enum Loss {
     None,
     SigmoidMSE,
     SigmoidMPE(f32),
+    /// Huber loss between the sigmoid of the outputs and the targets, with the given delta.
+    SigmoidHuber(f32),
+    /// Binary cross-entropy between the sigmoid of the outputs and the targets.
+    SigmoidBCE,
     SoftmaxCrossEntropy,
+    /// For networks with 4 outputs, the first 3 being a WDL head trained with softmax
+    /// cross-entropy and the last an eval head trained with sigmoid MSE, with the two
+    /// losses weighted by `wdl_weight` and `eval_weight`.
+    WdlAndEval {
+        wdl_weight: f32,
+        eval_weight: f32,
+    },
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+impl Loss {
+    /// Loss of a single position, given its network outputs and targets.
+    pub fn position_loss(&self, outputs: &[f32], targets: &[f32]) -> f32 {
+        let sigmoid = |x: f32| 1.0 / (1.0 + (-x).exp());
+        let power_error = |power: f32| {
+            outputs.iter().zip(targets).map(|(&out, &target)| (sigmoid(out) - target).abs().powf(power)).sum()
+        };
+        let cross_entropy = |outputs: &[f32], targets: &[f32]| {
+            let max = outputs.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+            let log_total = outputs.iter().map(|x| (x - max).exp()).sum::<f32>().ln();
+            outputs.iter().zip(targets).map(|(&out, &target)| -target * (out - max - log_total)).sum::<f32>()
+        };
+
+        match *self {
+            Loss::None => 0.0,
+            Loss::SigmoidMSE => power_error(2.0),
+            Loss::SigmoidMPE(power) => power_error(power),
+            Loss::SigmoidHuber(delta) => outputs
+                .iter()
+                .zip(targets)
+                .map(|(&out, &target)| {
+                    let diff = (sigmoid(out) - target).abs();
+                    if diff <= delta {
+                        0.5 * diff * diff
+                    } else {
+                        delta * (diff - 0.5 * delta)
+                    }
+                })
+                .sum(),
+            Loss::SigmoidBCE => outputs
+                .iter()
+                .zip(targets)
+                .map(|(&out, &target)| out.max(0.0) - out * target + (-out.abs()).exp().ln_1p())
+                .sum(),
+            Loss::SoftmaxCrossEntropy => cross_entropy(outputs, targets),
+            Loss::WdlAndEval { wdl_weight, eval_weight } => {
+                let eval = (sigmoid(outputs[3]) - targets[3]).powi(2);
+                wdl_weight * cross_entropy(&outputs[..3], &targets[..3]) + eval_weight * eval
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 enum OpType {
     Activate(Activation),
     ActivateDual,
     Affine,
+    Dropout(f32),
     PairwiseMul,
 }
 
@@ -45,12 +102,15 @@ pub struct TrainerBuilder<T, U = outputs::Single, O = optimiser::AdamW> {
     ft_out_size: usize,
     nodes: Vec<NodeType>,
     quantisations: Option<Vec<QuantTarget>>,
+    activation_quantisations: Option<Vec<i16>>,
     perspective: bool,
     loss: Loss,
     optimiser: O,
     psqt_subnet: bool,
     allow_transpose: bool,
     ft_init_input_size: Option<usize>,
+    policy_weight: Option<f32>,
+    label_smoothing: f32,
 }
 
 impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType> Default for TrainerBuilder<T, U, O> {
@@ -61,12 +121,15 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
             ft_out_size: 0,
             nodes: Vec::new(),
             quantisations: None,
+            activation_quantisations: None,
             perspective: true,
             loss: Loss::None,
             optimiser: O::default(),
             psqt_subnet: false,
             allow_transpose: true,
             ft_init_input_size: None,
+            policy_weight: None,
+            label_smoothing: 0.0,
         }
     }
 }
@@ -122,6 +185,21 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         self
     }
 
+    /// Quantise the inputs to each layer after the feature transformer to `u8`, as in
+    /// two-stage nets, where an input of `1.0` to layer `i + 1` is quantised to `quants[i]`.
+    /// The bias of each such layer is then quantised by `quants[i]` multiplied by its weight
+    /// quantisation, rather than by the product of all preceding quantisations.
+    ///
+    /// The scales are written to the end of the quantised network as `i16`s, and the inputs to
+    /// these layers must come from a clipped activation (`CReLU`, `SCReLU` or dual activation),
+    /// so that training sees the same clipping as the `u8` activations at inference.
+    pub fn activation_quantisations(mut self, quants: &[i16]) -> Self {
+        assert!(self.activation_quantisations.is_none(), "Activation quantisations already set!");
+        assert!(quants.iter().all(|&q| (1..=255).contains(&q)), "Activation quantisations must fit in a u8!");
+        self.activation_quantisations = Some(quants.to_vec());
+        self
+    }
+
     /// Sets the size of the feature-transformer.
     /// Must be done before all other layers.
     pub fn feature_transformer(mut self, size: usize) -> Self {
@@ -187,6 +265,13 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         self.add(size, OpType::Activate(activation))
     }
 
+    /// Applies dropout with the given rate, which only has an effect during training.
+    pub fn add_dropout(self, rate: f32) -> Self {
+        assert!((0.0..1.0).contains(&rate), "Dropout rate must be in [0, 1)!");
+        let size = self.get_last_layer_size();
+        self.add(size, OpType::Dropout(rate))
+    }
+
     /// Adds SF-style dual activation
     pub fn add_dual_activation(self) -> Self {
         let size = self.get_last_layer_size() * 2;
@@ -206,6 +291,24 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         self
     }
 
+    /// Adds a policy head, an affine layer from the output of the feature transformer to
+    /// the policy outputs of the input type (e.g. `inputs::WithPolicy`), trained with a masked
+    /// softmax cross-entropy loss weighted by `weight` relative to the value loss.
+    ///
+    /// The policy weights are saved unquantised, after the value network.
+    pub fn policy_head(mut self, weight: f32) -> Self {
+        self.policy_weight = Some(weight);
+        self
+    }
+
+    /// Mixes the targets of the softmax cross-entropy losses, i.e. of WDL and policy heads,
+    /// with the uniform distribution, weighted by `smoothing`.
+    pub fn label_smoothing(mut self, smoothing: f32) -> Self {
+        assert!((0.0..1.0).contains(&smoothing), "Label smoothing must be in [0, 1)!");
+        self.label_smoothing = smoothing;
+        self
+    }
+
     fn push_saved_format(&self, layer: usize, shape: Shape, saved_format: &mut Vec<SavedFormat>, net_quant: &mut i16) {
         let w = format!("l{layer}w");
         let b = format!("l{layer}b");
@@ -217,6 +320,10 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         };
 
         let (wquant, bquant) = if let Some(quants) = &self.quantisations {
+            if let Some(act_quants) = self.activation_quantisations.as_ref().filter(|_| layer > 0) {
+                *net_quant = act_quants[layer - 1];
+            }
+
             let bquant = match quants[layer] {
                 QuantTarget::Float => {
                     *net_quant = 1;
@@ -248,6 +355,10 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         let output_buckets = U::BUCKETS > 1;
 
         let input_getter = self.input_getter.clone().expect("Need to set the input features!");
+        assert!(
+            input_getter.extra_groups().is_empty(),
+            "Additional input groups are not supported by `TrainerBuilder`, use a custom network!"
+        );
         let input_size = input_getter.num_inputs();
         let input_shape = Shape::new(input_size, 1);
 
@@ -255,7 +366,11 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         let buckets = output_buckets.then(|| builder.new_sparse_input("buckets", Shape::new(U::BUCKETS, 1), 1));
         let l0 = builder.new_affine("l0", input_size, self.ft_out_size);
 
+        let dense_size = input_getter.num_dense_inputs();
+        let dense = (dense_size > 0).then(|| builder.new_dense_input("dense", Shape::new(dense_size, 1)));
+
         let mut still_in_ft = true;
+        let mut policy_input = None;
         let mut saved_format = Vec::new();
 
         if self.ft_out_size % 8 != 0 {
@@ -268,12 +383,17 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         }
 
         let mut net_quant = 1i16;
+        let mut clipped = false;
         let mut ft_desc = format!("{} -> {}", input_getter.shorthand(), self.ft_out_size);
 
         if self.perspective {
             ft_desc = format!("({ft_desc})x2");
         }
 
+        if dense_size > 0 {
+            ft_desc = format!("{ft_desc} + {dense_size}");
+        }
+
         let pst = self.psqt_subnet.then(|| {
             let pst = builder.new_weights("pst", Shape::new(1, input_size), InitSettings::Zeroed);
             saved_format.push(SavedFormat { id: "pst".to_string(), quant: QuantTarget::Float, layout: Layout::Normal });
@@ -298,6 +418,7 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
 
             let ntm = builder.new_sparse_input("nstm", input_shape, input_getter.max_active());
             out = l0.forward_sparse_dual_with_activation(out, ntm, activation);
+            clipped = matches!(activation, Activation::CReLU | Activation::SCReLU);
             skip
         } else {
             out = l0.forward(out);
@@ -310,13 +431,33 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
 
         for &NodeType { size, op } in self.nodes.iter().skip(skip) {
             match op {
-                OpType::Activate(activation) => out = out.activate(activation),
+                OpType::Activate(activation) => {
+                    out = out.activate(activation);
+                    clipped = matches!(activation, Activation::CReLU | Activation::SCReLU);
+                }
                 OpType::ActivateDual => {
                     out = out.concat(out.activate(Activation::Square)).activate(Activation::CReLU);
                     prev_size = size;
+                    clipped = true;
                 }
                 OpType::Affine => {
+                    if let Some(dense) = dense.filter(|_| still_in_ft) {
+                        out = out.concat(dense);
+                        prev_size += dense_size;
+                    }
+
+                    if still_in_ft {
+                        policy_input = Some((out, prev_size));
+                    }
+
                     still_in_ft = false;
+
+                    assert!(
+                        clipped || self.activation_quantisations.is_none(),
+                        "Inputs to layer {layer} must be clipped to [0, 1] to be quantised to u8!"
+                    );
+
+                    clipped = false;
                     let raw_size = size * U::BUCKETS;
 
                     let l = builder.new_affine(&format!("l{layer}"), prev_size, raw_size);
@@ -334,6 +475,7 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
                         out = out.select(buckets);
                     }
                 }
+                OpType::Dropout(rate) => out = out.dropout(rate),
                 OpType::PairwiseMul => {
                     if still_in_ft && self.perspective {
                         out = out.pairwise_mul_post_affine_dual();
@@ -352,16 +494,63 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
             out = out + pst;
         }
 
-        let output_node = out.node();
+        let output_node = out.named("output").node();
         let output_size = prev_size;
         let targets = builder.new_dense_input("targets", Shape::new(output_size, 1));
-        match self.loss {
+        let smoothing = self.label_smoothing;
+        let loss = match self.loss {
             Loss::None => panic!("No loss function specified!"),
             Loss::SigmoidMSE => out.activate(Activation::Sigmoid).mse(targets),
             Loss::SigmoidMPE(power) => out.activate(Activation::Sigmoid).mpe(targets, power),
-            Loss::SoftmaxCrossEntropy => out.softmax_crossentropy_loss(targets),
+            Loss::SigmoidHuber(delta) => out.activate(Activation::Sigmoid).huber(targets, delta),
+            Loss::SigmoidBCE => out.sigmoid_bce(targets),
+            Loss::SoftmaxCrossEntropy => out.softmax_crossentropy_loss_with_smoothing(targets, smoothing),
+            Loss::WdlAndEval { wdl_weight, eval_weight } => {
+                assert_eq!(output_size, 4, "WDL and eval heads require 4 outputs!");
+                let wdl = out.slice_rows(0, 3);
+                let wdl = wdl.softmax_crossentropy_loss_with_smoothing(targets.slice_rows(0, 3), smoothing);
+                let eval = out.slice_rows(3, 4).activate(Activation::Sigmoid).mse(targets.slice_rows(3, 4));
+                builder.weighted_loss(&[("wdl", wdl, wdl_weight), ("eval", eval, eval_weight)])
+            }
+        };
+
+        if let Some(weight) = self.policy_weight {
+            let num_moves = input_getter.num_policy_outputs();
+            let max_moves = input_getter.max_policy_moves();
+            assert!(num_moves > 0, "Policy head requires an input type with policy targets!");
+
+            let (hidden, hidden_size) = policy_input.unwrap();
+            let policy = builder.new_affine("policy", hidden_size, num_moves);
+
+            for id in ["policyw", "policyb"] {
+                saved_format.push(SavedFormat {
+                    id: id.to_string(),
+                    quant: QuantTarget::Float,
+                    layout: Layout::Normal,
+                });
+            }
+
+            let mask = builder.new_sparse_input("policy_mask", Shape::new(num_moves, 1), max_moves);
+            let targets = builder.new_dense_input("policy_targets", Shape::new(max_moves, 1));
+            let policy = policy.forward(hidden);
+            let policy_loss = policy.masked_softmax_crossentropy_loss_with_smoothing(targets, mask, smoothing);
+
+            loss.linear_comb(1.0, policy_loss, weight);
+        }
+
+        let training_targets = match self.loss {
+            Loss::WdlAndEval { .. } => TrainingTargets::WdlAndEval,
+            _ if output_size == 3 => TrainingTargets::Wdl,
+            _ => TrainingTargets::Eval,
         };
 
+        if let Some(act_quants) = &self.activation_quantisations {
+            assert!(self.quantisations.is_some(), "Activation quantisations require weight quantisations!");
+            assert_eq!(act_quants.len(), layer - 1, "Need one activation quantisation per layer after the FT!");
+        }
+
+        builder.fuse_affine_activate();
+
         let ctx = ExecutionContext::default();
         let mut graph = builder.build(ctx);
 
@@ -397,9 +586,17 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
             input_getter: input_getter.clone(),
             output_getter: self.bucket_getter,
             output_node,
-            additional_inputs: AdditionalTrainerInputs { wdl: output_size == 3 },
+            additional_inputs: AdditionalTrainerInputs { targets: training_targets },
             saved_format: saved_format.clone(),
             factorised_weights,
+            gradient_noise: None,
+            swa_sampling: None,
+            gradient_accumulation: 1,
+            softmax_temperature: None,
+            pending_data_loader: Mutex::new(None),
+            activation_quantisations: self.activation_quantisations.clone().unwrap_or_default(),
+            quantisation_schemes: Vec::new(),
+            bucket_loss: output_buckets.then_some(self.loss),
         };
 
         logger::clear_colours();
@@ -407,6 +604,11 @@ impl<T: SparseInputType, U: OutputBuckets<T::RequiredDataType>, O: OptimiserType
         println!("Architecture           : {}", logger::ansi(format!("{ft_desc} -> {output_desc}"), "32;1"));
         println!("Inputs                 : {}", input_getter.description());
 
+        if let Some(weight) = self.policy_weight {
+            let desc = format!("{} moves, weight {weight}", input_getter.num_policy_outputs());
+            println!("Policy Head            : {}", logger::ansi(desc, "32;1"));
+        }
+
         let num_params = trainer.optimiser.graph.get_num_params();
         let fmt = if num_params >= 1_000_000 {
             format!("{:.2}m", num_params as f64 / 1_000_000.0)
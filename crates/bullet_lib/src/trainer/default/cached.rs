@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use super::{
+    inputs::SparseInputType,
+    loader::{
+        prepared_batch_bytes, DataLoader, DefaultDataPreparer, DenseInput, LoadableDataType, SparseInput,
+        TrainingTargets,
+    },
+    outputs::OutputBuckets,
+};
+
+use crate::trainer::{
+    logger::{ansi, num_cs},
+    DataPreparer,
+};
+
+/// Prepared inputs of every position of a dataset, extracted once so that training
+/// for multiple epochs skips `map_features` after the first pass.
+///
+/// Everything is held in memory, using roughly `8 * max_active` bytes per position
+/// for the main sparse inputs, so this is only suitable for small datasets.
+struct FeatureCache {
+    positions: usize,
+    stm: Vec<i32>,
+    nstm: Vec<i32>,
+    buckets: Vec<i32>,
+    dense: Vec<f32>,
+    groups: Vec<(Vec<i32>, Vec<i32>)>,
+    policy_mask: Vec<i32>,
+    policy_targets: Vec<f32>,
+    scores: Vec<i16>,
+    results: Vec<u8>,
+}
+
+impl FeatureCache {
+    fn bytes(&self) -> usize {
+        let groups = self.groups.iter().map(|(stm, nstm)| stm.len() + nstm.len()).sum::<usize>();
+        let policy = self.policy_mask.len() + self.policy_targets.len();
+        4 * (self.stm.len() + self.nstm.len() + self.buckets.len() + self.dense.len() + groups + policy)
+            + 3 * self.positions
+    }
+}
+
+/// A `DataPreparer` that reads the whole of a dataset once, caching the prepared inputs of each
+/// position, and then serves batches from the cache, see `Trainer::run_cached`.
+///
+/// Batches wrap around the end of the dataset, and training targets are still computed
+/// per batch, so WDL schedules behave the same as with uncached data.
+#[derive(Clone)]
+pub struct CachedDataLoader<I, O> {
+    input_getter: I,
+    output_getter: O,
+    targets: TrainingTargets,
+    scale: f32,
+    paths: Vec<String>,
+    cache: Arc<FeatureCache>,
+}
+
+impl<I: SparseInputType, O: OutputBuckets<I::RequiredDataType>> CachedDataLoader<I, O> {
+    /// Reads one epoch of `loader`, which must be able to count its positions.
+    pub fn new<D: DataLoader<I::RequiredDataType>>(
+        input_getter: I,
+        output_getter: O,
+        targets: TrainingTargets,
+        scale: f32,
+        loader: &D,
+        threads: usize,
+    ) -> Self {
+        let total = loader.count_positions().expect("Cached loading requires the number of positions to be known!");
+        let total = usize::try_from(total).unwrap();
+        assert!(total > 0, "Cannot cache an empty dataset!");
+
+        let mut cache = FeatureCache {
+            positions: 0,
+            stm: Vec::new(),
+            nstm: Vec::new(),
+            buckets: Vec::new(),
+            dense: Vec::new(),
+            groups: vec![(Vec::new(), Vec::new()); input_getter.extra_groups().len()],
+            policy_mask: Vec::new(),
+            policy_targets: Vec::new(),
+            scores: Vec::with_capacity(total),
+            results: Vec::with_capacity(total),
+        };
+
+        loader.map_batches(0, 16384, |batch| {
+            let batch = &batch[..batch.len().min(total - cache.positions)];
+            let prepared =
+                DefaultDataPreparer::prepare(input_getter.clone(), output_getter, targets, batch, threads, 0.0, scale);
+
+            cache.stm.extend_from_slice(&prepared.stm.value);
+            cache.nstm.extend_from_slice(&prepared.nstm.value);
+            cache.buckets.extend_from_slice(&prepared.buckets.value);
+            cache.dense.extend_from_slice(&prepared.dense.value);
+
+            for ((stm, nstm), (group_stm, group_nstm)) in cache.groups.iter_mut().zip(prepared.groups.iter()) {
+                stm.extend_from_slice(&group_stm.value);
+                nstm.extend_from_slice(&group_nstm.value);
+            }
+
+            if let Some((mask, targets)) = &prepared.policy {
+                cache.policy_mask.extend_from_slice(&mask.value);
+                cache.policy_targets.extend_from_slice(&targets.value);
+            }
+
+            for pos in batch {
+                cache.scores.push(pos.score());
+                cache.results.push(pos.result() as u8);
+            }
+
+            cache.positions += batch.len();
+            cache.positions == total
+        });
+
+        let num_cs = num_cs();
+        println!(
+            "Cached Features        : {} positions ({} MB)",
+            ansi(cache.positions, num_cs),
+            ansi(cache.bytes() / (1024 * 1024), num_cs),
+        );
+
+        Self {
+            input_getter,
+            output_getter,
+            targets,
+            scale,
+            paths: loader.data_file_paths().to_vec(),
+            cache: Arc::new(cache),
+        }
+    }
+}
+
+impl<I: SparseInputType, O: OutputBuckets<I::RequiredDataType>> DataPreparer for CachedDataLoader<I, O> {
+    /// Index of a position in the cache.
+    type DataType = usize;
+    type PreparedData = DefaultDataPreparer<I, O>;
+
+    fn get_data_file_paths(&self) -> &[String] {
+        &self.paths
+    }
+
+    fn try_count_positions(&self) -> Option<u64> {
+        Some(self.cache.positions as u64)
+    }
+
+    fn load_and_map_batches<F: FnMut(&[usize]) -> bool>(&self, start_batch: usize, batch_size: usize, mut f: F) {
+        let positions = self.cache.positions;
+        let mut start = (start_batch * batch_size) % positions;
+        let mut batch = Vec::with_capacity(batch_size);
+
+        loop {
+            batch.clear();
+            batch.extend((start..start + batch_size).map(|idx| idx % positions));
+            start = (start + batch_size) % positions;
+
+            if f(&batch) {
+                break;
+            }
+        }
+    }
+
+    fn prepare(&self, data: &[usize], _: usize, blend: f32) -> Self::PreparedData {
+        let cache = &self.cache;
+        let batch_size = data.len();
+        let max_active = self.input_getter.max_active();
+        let dense_size = self.input_getter.num_dense_inputs();
+        let output_size = self.targets.num_outputs();
+        let rscale = 1.0 / self.scale;
+
+        let gather = |src: &[i32], width: usize| {
+            let mut value = Vec::with_capacity(width * batch_size);
+            for &idx in data {
+                value.extend_from_slice(&src[width * idx..width * (idx + 1)]);
+            }
+            SparseInput { max_active: width, value }
+        };
+
+        let max_moves = self.input_getter.max_policy_moves();
+
+        let mut dense = Vec::with_capacity(dense_size * batch_size);
+        let mut policy_targets = Vec::with_capacity(max_moves * batch_size);
+        let mut targets = vec![0.0; output_size * batch_size];
+
+        for (i, &idx) in data.iter().enumerate() {
+            dense.extend_from_slice(&cache.dense[dense_size * idx..dense_size * (idx + 1)]);
+            policy_targets.extend_from_slice(&cache.policy_targets[max_moves * idx..max_moves * (idx + 1)]);
+
+            self.targets.write(
+                &mut targets[output_size * i..output_size * (i + 1)],
+                cache.results[idx],
+                cache.scores[idx],
+                rscale,
+                blend,
+            );
+        }
+
+        let groups = self
+            .input_getter
+            .extra_groups()
+            .iter()
+            .zip(cache.groups.iter())
+            .map(|(&(_, group_active), (stm, nstm))| (gather(stm, group_active), gather(nstm, group_active)))
+            .collect();
+
+        DefaultDataPreparer {
+            input_getter: self.input_getter.clone(),
+            output_getter: self.output_getter,
+            batch_size,
+            stm: gather(&cache.stm, max_active),
+            nstm: gather(&cache.nstm, max_active),
+            buckets: gather(&cache.buckets, 1),
+            targets: DenseInput { value: targets },
+            dense: DenseInput { value: dense },
+            groups,
+            policy: (self.input_getter.num_policy_outputs() > 0)
+                .then(|| (gather(&cache.policy_mask, max_moves), DenseInput { value: policy_targets })),
+        }
+    }
+
+    fn prepared_batch_bytes(&self, batch_size: usize) -> Option<usize> {
+        Some(prepared_batch_bytes(&self.input_getter, self.targets, batch_size))
+    }
+}
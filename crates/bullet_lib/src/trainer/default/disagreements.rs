@@ -0,0 +1,112 @@
+use std::{fs::File, io::Write};
+
+use super::loader::{GameResult, LoadableDataType};
+
+/// A position for which the network, the recorded score and the game result disagree,
+/// with each expressed as an expected score in `[0, 1]` from the side to move's perspective.
+#[derive(Clone, Copy, Debug)]
+pub struct Disagreement<T> {
+    pub pos: T,
+    pub net: f32,
+    pub score: f32,
+    pub result: f32,
+}
+
+impl<T> Disagreement<T> {
+    /// The smallest difference between any two of the network, score and result,
+    /// so that a position is only ranked highly if all three disagree.
+    pub fn severity(&self) -> f32 {
+        let net_score = (self.net - self.score).abs();
+        let net_result = (self.net - self.result).abs();
+        let score_result = (self.score - self.result).abs();
+
+        net_score.min(net_result).min(score_result)
+    }
+}
+
+/// Collects the positions with the most severe disagreements seen by `Trainer::mine_disagreements`.
+pub struct DisagreementMiner<T> {
+    max_entries: usize,
+    min_severity: f32,
+    scanned: u64,
+    entries: Vec<Disagreement<T>>,
+}
+
+impl<T: LoadableDataType> DisagreementMiner<T> {
+    /// Keeps the `max_entries` most severe disagreements.
+    pub fn new(max_entries: usize) -> Self {
+        Self { max_entries, min_severity: 0.0, scanned: 0, entries: Vec::new() }
+    }
+
+    /// Ignores disagreements with a severity below `min_severity`, defaults to `0.0`.
+    pub fn with_min_severity(mut self, min_severity: f32) -> Self {
+        self.min_severity = min_severity;
+        self
+    }
+
+    /// Records a position, given its expected score according to the network
+    /// and the sigmoid scale used to convert its recorded score to an expected score.
+    pub fn push(&mut self, pos: T, net: f32, eval_scale: f32) {
+        self.scanned += 1;
+
+        let score = 1.0 / (1.0 + (-f32::from(LoadableDataType::score(&pos)) / eval_scale).exp());
+        let result = match LoadableDataType::result(&pos) {
+            GameResult::Loss => 0.0,
+            GameResult::Draw => 0.5,
+            GameResult::Win => 1.0,
+        };
+
+        let disagreement = Disagreement { pos, net, score, result };
+
+        if disagreement.severity() >= self.min_severity {
+            self.entries.push(disagreement);
+
+            if self.entries.len() >= 2 * self.max_entries.max(1) {
+                self.truncate();
+            }
+        }
+    }
+
+    /// The most severe disagreements, in descending order of severity.
+    pub fn entries(&mut self) -> &[Disagreement<T>] {
+        self.truncate();
+        &self.entries
+    }
+
+    pub fn report(&mut self) {
+        self.truncate();
+
+        let mean = self.entries.iter().map(Disagreement::severity).sum::<f32>() / self.entries.len().max(1) as f32;
+        let max = self.entries.first().map(Disagreement::severity).unwrap_or(0.0);
+
+        println!("Scanned {} positions, kept {} disagreements", self.scanned, self.entries.len());
+        println!("    Severity: max {max:.3}, mean {mean:.3}");
+    }
+
+    /// Writes `description | score | result | net | severity` lines in descending order of severity,
+    /// where `describe` formats each position, e.g. `stm_relative_fen` for `ChessBoard`s.
+    pub fn write<F: Fn(&T) -> String>(&mut self, path: &str, describe: F) -> std::io::Result<()> {
+        self.truncate();
+
+        let mut file = File::create(path)?;
+
+        for entry in &self.entries {
+            writeln!(
+                file,
+                "{} | {} | {:.1} | {:.3} | {:.3}",
+                describe(&entry.pos),
+                LoadableDataType::score(&entry.pos),
+                entry.result,
+                entry.net,
+                entry.severity(),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn truncate(&mut self) {
+        self.entries.sort_by(|a, b| b.severity().total_cmp(&a.severity()));
+        self.entries.truncate(self.max_entries);
+    }
+}
@@ -0,0 +1,104 @@
+use std::{collections::BTreeMap, fs::File, io::Write};
+
+use bulletformat::ChessBoard;
+
+use crate::trainer::logger::{ansi, num_cs};
+
+/// Predicted and empirical draw rates for a group of positions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DrawRate {
+    pub positions: u64,
+    /// Sum of the draw probabilities predicted by the WDL head.
+    pub predicted_draws: f64,
+    /// Number of positions from drawn games.
+    pub empirical_draws: u64,
+}
+
+impl DrawRate {
+    pub fn predicted(&self) -> f64 {
+        self.predicted_draws / self.positions.max(1) as f64
+    }
+
+    pub fn empirical(&self) -> f64 {
+        self.empirical_draws as f64 / self.positions.max(1) as f64
+    }
+
+    fn push(&mut self, predicted_draw: f32, drawn: bool) {
+        self.positions += 1;
+        self.predicted_draws += f64::from(predicted_draw);
+        self.empirical_draws += u64::from(drawn);
+    }
+}
+
+/// Draw rates predicted by a WDL network against those observed in a dataset,
+/// grouped both by a user-defined key (e.g. material) and by output bucket.
+#[derive(Clone, Debug, Default)]
+pub struct DrawRateAnalysis {
+    pub by_key: BTreeMap<usize, DrawRate>,
+    pub by_bucket: BTreeMap<usize, DrawRate>,
+    pub total: DrawRate,
+}
+
+impl DrawRateAnalysis {
+    /// Records a position, given the raw `[loss, draw, win]` outputs of the network.
+    pub fn push(&mut self, key: usize, bucket: usize, output: &[f32], drawn: bool) {
+        let max = output.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+        let exps = output.iter().map(|x| (x - max).exp()).collect::<Vec<_>>();
+        let draw = exps[1] / exps.iter().sum::<f32>();
+
+        self.by_key.entry(key).or_default().push(draw, drawn);
+        self.by_bucket.entry(bucket).or_default().push(draw, drawn);
+        self.total.push(draw, drawn);
+    }
+
+    pub fn report(&self, key_name: &str) {
+        let num_cs = num_cs();
+
+        println!("Draw rates over {} positions:", ansi(self.total.positions, num_cs));
+
+        let print_table = |name: &str, rates: &BTreeMap<usize, DrawRate>| {
+            println!("    {name:>10} | {:>10} | predicted | empirical | difference", "positions");
+
+            for (key, rate) in rates {
+                println!(
+                    "    {key:>10} | {:>10} | {:>8.2}% | {:>8.2}% | {}",
+                    rate.positions,
+                    100.0 * rate.predicted(),
+                    100.0 * rate.empirical(),
+                    ansi(format!("{:>+9.2}%", 100.0 * (rate.predicted() - rate.empirical())), num_cs),
+                );
+            }
+        };
+
+        print_table(key_name, &self.by_key);
+
+        if self.by_bucket.len() > 1 {
+            print_table("bucket", &self.by_bucket);
+        }
+
+        println!(
+            "    Overall: predicted {:.2}%, empirical {:.2}%",
+            100.0 * self.total.predicted(),
+            100.0 * self.total.empirical()
+        );
+    }
+
+    /// Writes `group,key,positions,predicted,empirical` rows in CSV format.
+    pub fn write_csv(&self, path: &str) -> std::io::Result<()> {
+        let mut file = File::create(path)?;
+        writeln!(file, "group,key,positions,predicted,empirical")?;
+
+        for (group, rates) in [("key", &self.by_key), ("bucket", &self.by_bucket)] {
+            for (key, rate) in rates {
+                writeln!(file, "{group},{key},{},{},{}", rate.positions, rate.predicted(), rate.empirical())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Groups positions by the number of pieces on the board, for use with `Trainer::analyse_draw_rates`.
+pub fn piece_count(pos: &ChessBoard) -> usize {
+    pos.occ().count_ones() as usize
+}
@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::trainer::logger::{ansi, num_cs};
+
+/// The path an engine takes from the raw network output to the centipawn score it
+/// reports over UCI, used by `Trainer::simulate_engine_eval`.
+#[derive(Clone, Copy, Debug)]
+pub struct EngineEvalScaling {
+    /// Multiplier applied to the network output, normally the `eval_scale` used in training.
+    pub scale: i32,
+    /// Quantisation of the network output, if set the output is rounded to a multiple
+    /// of `1 / quantisation` and scaled using integer arithmetic, as a quantised network would be.
+    pub quantisation: Option<i32>,
+    /// If set, the internal eval is normalised so that `normalise_to_pawn` is reported as 100cp.
+    pub normalise_to_pawn: Option<i32>,
+    /// Largest absolute centipawn score reported.
+    pub clamp: i32,
+}
+
+impl Default for EngineEvalScaling {
+    fn default() -> Self {
+        Self { scale: 400, quantisation: None, normalise_to_pawn: None, clamp: 30000 }
+    }
+}
+
+impl EngineEvalScaling {
+    /// Internal engine eval of a raw network output.
+    pub fn internal_eval(&self, output: f32) -> i32 {
+        match self.quantisation {
+            Some(q) => {
+                let quantised = (output * q as f32).round() as i64;
+                (quantised * i64::from(self.scale) / i64::from(q)) as i32
+            }
+            None => (output * self.scale as f32) as i32,
+        }
+    }
+
+    /// Centipawn score reported over UCI for a raw network output.
+    pub fn uci_cp(&self, output: f32) -> i32 {
+        let eval = self.internal_eval(output);
+        let cp = self.normalise_to_pawn.map_or(eval, |pawn| eval * 100 / pawn);
+        cp.clamp(-self.clamp, self.clamp)
+    }
+}
+
+/// Distribution of the centipawn scores an engine would report, against the scores recorded in the data.
+#[derive(Clone, Debug, Default)]
+pub struct EngineEvalReport {
+    pub positions: u64,
+    /// Counts of reported scores, in bins of `bin_width` centipawns.
+    pub net_histogram: BTreeMap<i32, u64>,
+    /// Counts of recorded scores, in bins of `bin_width` centipawns.
+    pub data_histogram: BTreeMap<i32, u64>,
+    pub bin_width: i32,
+    pub sum_abs_cp: f64,
+    pub sum_abs_diff: f64,
+}
+
+impl EngineEvalReport {
+    pub fn new(bin_width: i32) -> Self {
+        Self { bin_width: bin_width.max(1), ..Default::default() }
+    }
+
+    pub fn push(&mut self, cp: i32, recorded: i16) {
+        let recorded = i32::from(recorded);
+
+        self.positions += 1;
+        *self.net_histogram.entry(cp.div_euclid(self.bin_width)).or_default() += 1;
+        *self.data_histogram.entry(recorded.div_euclid(self.bin_width)).or_default() += 1;
+        self.sum_abs_cp += f64::from(cp.abs());
+        self.sum_abs_diff += f64::from((cp - recorded).abs());
+    }
+
+    pub fn mean_abs_cp(&self) -> f64 {
+        self.sum_abs_cp / self.positions.max(1) as f64
+    }
+
+    /// Mean absolute difference between the reported and recorded scores.
+    pub fn mean_abs_diff(&self) -> f64 {
+        self.sum_abs_diff / self.positions.max(1) as f64
+    }
+
+    pub fn report(&self) {
+        let num_cs = num_cs();
+        let total = self.positions.max(1) as f64;
+
+        println!("Simulated engine eval over {} positions:", ansi(self.positions, num_cs));
+        println!("    {:>15} | {:>9} | {:>9}", "cp", "net", "data");
+
+        let bins = self.net_histogram.keys().chain(self.data_histogram.keys()).collect::<BTreeSet<_>>();
+
+        for &bin in bins {
+            let net = self.net_histogram.get(&bin).copied().unwrap_or(0);
+            let data = self.data_histogram.get(&bin).copied().unwrap_or(0);
+            let range = format!("[{}, {})", bin * self.bin_width, (bin + 1) * self.bin_width);
+
+            println!("    {range:>15} | {:>8.2}% | {:>8.2}%", 100.0 * net as f64 / total, 100.0 * data as f64 / total);
+        }
+
+        println!("    Mean |cp|: {:.1}", self.mean_abs_cp());
+        println!("    Mean |cp - recorded score|: {}", ansi(format!("{:.1}", self.mean_abs_diff()), num_cs));
+    }
+}
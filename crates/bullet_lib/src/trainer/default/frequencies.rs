@@ -0,0 +1,139 @@
+use super::{inputs::SparseInputType, loader::DataLoader};
+
+use crate::trainer::logger::{ansi, num_cs};
+
+/// Number of times each input feature was active over a sample of the training data,
+/// counting both perspectives.
+#[derive(Clone, Debug)]
+pub struct FeatureFrequencies {
+    counts: Vec<u64>,
+    positions: u64,
+}
+
+impl FeatureFrequencies {
+    /// Scans up to `max_positions` positions from the start of `data_loader`, stopping
+    /// after a single pass if it reports holding fewer positions than that.
+    pub fn scan<I, D>(input_getter: &I, data_loader: &D, max_positions: usize) -> Self
+    where
+        I: SparseInputType,
+        D: DataLoader<I::RequiredDataType>,
+    {
+        let max_positions = data_loader.count_positions().map_or(max_positions, |num| max_positions.min(num as usize));
+        let mut counts = vec![0; input_getter.num_inputs()];
+        let mut positions = 0;
+
+        if max_positions == 0 {
+            return Self { counts, positions: 0 };
+        }
+
+        data_loader.map_batches(0, 16384, |batch| {
+            for pos in batch.iter().take(max_positions - positions) {
+                input_getter.map_features(pos, |stm, ntm| {
+                    counts[stm] += 1;
+                    counts[ntm] += 1;
+                });
+            }
+
+            positions = max_positions.min(positions + batch.len());
+            positions == max_positions
+        });
+
+        Self { counts, positions: positions as u64 }
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn positions(&self) -> u64 {
+        self.positions
+    }
+
+    /// Average number of times the feature is active per position.
+    pub fn frequency(&self, feature: usize) -> f32 {
+        self.counts[feature] as f32 / self.positions.max(1) as f32
+    }
+
+    /// Features that were never active, which is often a sign of an input encoding bug
+    /// (or of features that cannot occur, such as pawns on the back ranks).
+    pub fn unseen(&self) -> Vec<usize> {
+        self.rare(1)
+    }
+
+    /// Features that were active fewer than `min_count` times.
+    pub fn rare(&self, min_count: u64) -> Vec<usize> {
+        self.counts.iter().enumerate().filter(|(_, &count)| count < min_count).map(|(feat, _)| feat).collect()
+    }
+
+    /// Features that were never active, grouped into buckets of `inputs_per_bucket` consecutive
+    /// features (e.g. 768 for king bucketed chess inputs), indexed relative to the start of each bucket.
+    pub fn unseen_by_bucket(&self, inputs_per_bucket: usize) -> Vec<Vec<usize>> {
+        assert!(inputs_per_bucket > 0 && self.counts.len() % inputs_per_bucket == 0, "Invalid bucket size!");
+
+        self.counts
+            .chunks(inputs_per_bucket)
+            .map(|bucket| bucket.iter().enumerate().filter(|(_, &count)| count == 0).map(|(feat, _)| feat).collect())
+            .collect()
+    }
+
+    /// Per-feature learning rate multipliers, for use with `Trainer::set_lr_bucket_multipliers`
+    /// on the input weights (with one bucket per input feature).
+    ///
+    /// Features are scaled by `(mean / count)^power`, where `mean` is the mean count of
+    /// all features that were seen. Features active fewer than `min_count` times are
+    /// pruned by giving them a multiplier of zero, so that they are never updated.
+    pub fn lr_multipliers(&self, power: f32, min_count: u64) -> Vec<f32> {
+        let seen = self.counts.iter().filter(|&&count| count > 0);
+        let num_seen = seen.clone().count().max(1);
+        let mean = seen.sum::<u64>() as f32 / num_seen as f32;
+
+        self.counts
+            .iter()
+            .map(|&count| match count {
+                _ if count < min_count => 0.0,
+                0 => 1.0,
+                _ => (mean / count as f32).powf(power),
+            })
+            .collect()
+    }
+
+    pub fn report(&self) {
+        let num_cs = num_cs();
+        let unseen = self.unseen();
+
+        println!("Feature frequencies over {} positions:", ansi(self.positions, num_cs));
+        println!("    Features active  : {}/{}", ansi(self.counts.len() - unseen.len(), num_cs), self.counts.len());
+
+        if let Some((feat, count)) = self.counts.iter().enumerate().filter(|(_, &c)| c > 0).min_by_key(|(_, &c)| c) {
+            println!("    Rarest feature   : {} ({} times)", ansi(feat, num_cs), ansi(count, num_cs));
+        }
+
+        if let Some((feat, count)) = self.counts.iter().enumerate().max_by_key(|(_, &c)| c) {
+            println!("    Commonest feature: {} ({} times)", ansi(feat, num_cs), ansi(count, num_cs));
+        }
+
+        if !unseen.is_empty() {
+            let shown = unseen.iter().take(16).map(usize::to_string).collect::<Vec<_>>().join(", ");
+            let more = if unseen.len() > 16 { ", ..." } else { "" };
+            println!("{}", ansi(format!("WARNING: {} features never occurred: {shown}{more}", unseen.len()), 31));
+        }
+    }
+
+    /// Reports the number of never-active features in each bucket of `inputs_per_bucket`
+    /// consecutive features, to show how much feature transformer capacity is wasted.
+    pub fn report_buckets(&self, inputs_per_bucket: usize) {
+        let num_cs = num_cs();
+        let unseen = self.unseen_by_bucket(inputs_per_bucket);
+        let total = unseen.iter().map(Vec::len).sum::<usize>();
+
+        println!("Dead features per bucket of {} inputs:", ansi(inputs_per_bucket, num_cs));
+
+        for (bucket, dead) in unseen.iter().enumerate() {
+            let pct = 100.0 * dead.len() as f32 / inputs_per_bucket as f32;
+            println!("    Bucket {bucket:>3}: {:>6} dead ({pct:>5.1}%)", dead.len());
+        }
+
+        let pct = 100.0 * total as f32 / self.counts.len().max(1) as f32;
+        println!("    Total     : {} dead ({pct:.1}%)", ansi(total, num_cs));
+    }
+}
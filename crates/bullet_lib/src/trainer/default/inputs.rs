@@ -1,8 +1,21 @@
 mod ataxx147;
+mod ataxx_tuples;
+mod bucket_layout;
+mod castling;
 mod chess768;
 mod chess_buckets;
 mod chess_buckets_mk;
+mod chess_buckets_relative;
 mod factorised;
+mod groups;
+mod halfka;
+mod king_relative;
+mod phase;
+mod piece_square;
+mod planes;
+mod policy;
+mod shogi;
+mod threats;
 
 #[allow(deprecated)]
 mod legacy;
@@ -10,10 +23,26 @@ mod legacy;
 use super::loader::LoadableDataType;
 
 pub use ataxx147::{Ataxx147, Ataxx98};
+pub use ataxx_tuples::Ataxx2Tuples;
+pub use bucket_layout::KingBucketLayout;
+pub use castling::WithCastling;
 pub use chess768::Chess768;
 pub use chess_buckets::{ChessBuckets, ChessBucketsMirrored};
 pub use chess_buckets_mk::{ChessBucketsMergedKings, ChessBucketsMergedKingsMirrored};
+pub use chess_buckets_relative::{
+    ChessBucketsRelative, Horizontal, HorizontalAndVertical, KingSymmetry, NoMirror, PerspectiveTransform, RankFlip,
+    Rotate, Vertical,
+};
 pub use factorised::{Factorised, Factorises};
+pub use groups::WithGroup;
+pub use halfka::{HalfAv2Hm, HalfKAv2Hm};
+pub use king_relative::KingRelative;
+pub use phase::WithPhase;
+pub use piece_square::{PieceSquareIndex, PieceSquareInputs};
+pub use planes::ChessPlanes;
+pub use policy::WithPolicy;
+pub use shogi::ShogiHalfKP;
+pub use threats::Chess768Threats;
 
 #[allow(deprecated)]
 pub use legacy::InputType;
@@ -46,8 +75,15 @@ impl ChessBucketsMergedKingsMirroredFactorised {
     }
 }
 
+pub type HalfKAv2HmFactorised = Factorised<HalfKAv2Hm, HalfAv2Hm>;
+impl HalfKAv2HmFactorised {
+    pub fn new() -> Self {
+        Self::from_parts(HalfKAv2Hm, HalfAv2Hm)
+    }
+}
+
 pub trait SparseInputType: Clone + Send + Sync + 'static {
-    type RequiredDataType: LoadableDataType + Send + Sync;
+    type RequiredDataType: LoadableDataType + Clone + Send + Sync;
 
     /// The total number of inputs
     fn num_inputs(&self) -> usize;
@@ -63,6 +99,40 @@ pub trait SparseInputType: Clone + Send + Sync + 'static {
     /// Description of the input type
     fn description(&self) -> String;
 
+    /// Sizes `(num_inputs, max_active)` of any additional groups of sparse inputs, which are
+    /// loaded into graph inputs `stm_{group}` and `nstm_{group}` for groups `1, 2, ...`,
+    /// for use in custom networks.
+    fn extra_groups(&self) -> Vec<(usize, usize)> {
+        Vec::new()
+    }
+
+    /// Maps the features of additional group `group`, counting from 1.
+    fn map_group_features<F: FnMut(usize, usize)>(&self, _group: usize, _pos: &Self::RequiredDataType, _f: F) {}
+
+    /// Number of dense auxiliary inputs per position (e.g. material counts), which
+    /// `TrainerBuilder` concatenates onto the output of the feature transformer.
+    fn num_dense_inputs(&self) -> usize {
+        0
+    }
+
+    /// Writes the `num_dense_inputs` dense auxiliary inputs of a position into `buf`.
+    fn map_dense_features(&self, _pos: &Self::RequiredDataType, _buf: &mut [f32]) {}
+
+    /// Number of outputs of a policy head trained on the policy targets of this input type,
+    /// or 0 if there are no policy targets.
+    fn num_policy_outputs(&self) -> usize {
+        0
+    }
+
+    /// Maximum number of moves with policy targets in a position.
+    fn max_policy_moves(&self) -> usize {
+        0
+    }
+
+    /// Maps the policy targets of a position as `(policy output, target probability)`,
+    /// there must be at least one move per position.
+    fn map_policy<F: FnMut(usize, f32)>(&self, _pos: &Self::RequiredDataType, _f: F) {}
+
     fn is_factorised(&self) -> bool {
         false
     }
@@ -0,0 +1,75 @@
+use bulletformat::AtaxxBoard;
+
+use super::SparseInputType;
+
+const PER_TUPLE: usize = 3usize.pow(4);
+const NUM_TUPLES: usize = 36;
+
+/// Ataxx inputs made up of every overlapping 2x2 tuple of squares on the board, with
+/// one feature per tuple for each of the `3^4` ways the tuple can be occupied.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ataxx2Tuples;
+impl SparseInputType for Ataxx2Tuples {
+    type RequiredDataType = AtaxxBoard;
+
+    fn num_inputs(&self) -> usize {
+        NUM_TUPLES * PER_TUPLE
+    }
+
+    fn max_active(&self) -> usize {
+        NUM_TUPLES
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        let [boys, opps, _] = pos.bbs();
+
+        for i in 0..6 {
+            for j in 0..6 {
+                const POWERS: [usize; 4] = [1, 3, 9, 27];
+                const MASK: u64 = 0b0001_1000_0011;
+
+                let tuple = 6 * i + j;
+                let mut stm = PER_TUPLE * tuple;
+                let mut ntm = stm;
+
+                let offset = 7 * i + j;
+                let mut b = (boys >> offset) & MASK;
+                let mut o = (opps >> offset) & MASK;
+
+                while b > 0 {
+                    let mut sq = b.trailing_zeros() as usize;
+                    if sq > 6 {
+                        sq -= 5;
+                    }
+
+                    stm += POWERS[sq];
+                    ntm += 2 * POWERS[sq];
+
+                    b &= b - 1;
+                }
+
+                while o > 0 {
+                    let mut sq = o.trailing_zeros() as usize;
+                    if sq > 6 {
+                        sq -= 5;
+                    }
+
+                    stm += 2 * POWERS[sq];
+                    ntm += POWERS[sq];
+
+                    o &= o - 1;
+                }
+
+                f(stm, ntm);
+            }
+        }
+    }
+
+    fn shorthand(&self) -> String {
+        "2-tuples".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Ataxx 2x2 tuple inputs".to_string()
+    }
+}
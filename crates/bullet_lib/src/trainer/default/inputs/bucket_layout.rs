@@ -0,0 +1,153 @@
+use super::{ChessBuckets, ChessBucketsMirrored};
+
+/// Builder for king bucket maps, indexed by the square of the king from
+/// its own perspective (so `a1 = 0`, `h8 = 63` for both sides).
+///
+/// ```ignore
+/// // 2 buckets by rank, split into queenside and kingside, giving 4 buckets in total
+/// let ranks = KingBucketLayout::by_rank([0, 0, 1, 1, 1, 1, 1, 1]);
+/// let files = KingBucketLayout::by_file([0, 0, 0, 0, 1, 1, 1, 1]);
+/// let inputs = ranks.product(files).build();
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KingBucketLayout {
+    buckets: [usize; 64],
+}
+
+impl Default for KingBucketLayout {
+    fn default() -> Self {
+        Self::single()
+    }
+}
+
+impl KingBucketLayout {
+    /// A single bucket for all king squares.
+    pub fn single() -> Self {
+        Self { buckets: [0; 64] }
+    }
+
+    pub fn from_array(buckets: [usize; 64]) -> Self {
+        Self { buckets }
+    }
+
+    /// Takes buckets for files a-d in the same format as `ChessBucketsMirrored::new`,
+    /// and mirrors them onto files e-h.
+    pub fn from_mirrored_array(buckets: [usize; 32]) -> Self {
+        Self::from_fn(|sq| buckets[(sq / 8) * 4 + [0, 1, 2, 3, 3, 2, 1, 0][sq % 8]])
+    }
+
+    /// Assigns each king square the bucket `f(square)`.
+    pub fn from_fn<F: Fn(usize) -> usize>(f: F) -> Self {
+        let mut buckets = [0; 64];
+
+        for (sq, bucket) in buckets.iter_mut().enumerate() {
+            *bucket = f(sq);
+        }
+
+        Self { buckets }
+    }
+
+    /// Buckets by rank, from the first rank to the eighth.
+    pub fn by_rank(ranks: [usize; 8]) -> Self {
+        Self::from_fn(|sq| ranks[sq / 8])
+    }
+
+    /// Buckets by file, from the a-file to the h-file.
+    pub fn by_file(files: [usize; 8]) -> Self {
+        Self::from_fn(|sq| files[sq % 8])
+    }
+
+    /// Buckets by quadrant of the board, in the order
+    /// `[queenside, kingside]` on ranks 1-4, followed by ranks 5-8.
+    pub fn by_quadrant(quadrants: [usize; 4]) -> Self {
+        Self::from_fn(|sq| quadrants[2 * usize::from(sq / 8 > 3) + usize::from(sq % 8 > 3)])
+    }
+
+    /// Overrides the bucket of a single square.
+    pub fn with_square(mut self, sq: usize, bucket: usize) -> Self {
+        self.buckets[sq] = bucket;
+        self
+    }
+
+    /// Combines two layouts, so that squares share a bucket only if they share a bucket in both.
+    pub fn product(self, other: Self) -> Self {
+        let others = other.num_buckets();
+        Self::from_fn(|sq| self.buckets[sq] * others + other.buckets[sq]).compact()
+    }
+
+    /// Copies the buckets of files a-d onto files e-h, as required by `build_mirrored`.
+    pub fn mirrored(self) -> Self {
+        Self::from_fn(|sq| self.buckets[if sq % 8 > 3 { sq ^ 7 } else { sq }])
+    }
+
+    /// Renumbers buckets in order of first appearance from `a1`, removing any unused bucket indices.
+    pub fn compact(self) -> Self {
+        let mut map = Vec::new();
+        let mut buckets = self.buckets;
+
+        for bucket in &mut buckets {
+            let idx = map.iter().position(|&b| b == *bucket).unwrap_or_else(|| {
+                map.push(*bucket);
+                map.len() - 1
+            });
+
+            *bucket = idx;
+        }
+
+        Self { buckets }
+    }
+
+    pub fn num_buckets(&self) -> usize {
+        self.buckets.iter().max().unwrap() + 1
+    }
+
+    pub fn is_mirrored(&self) -> bool {
+        *self == self.mirrored()
+    }
+
+    /// Checks that every bucket index below `num_buckets` is used by at least one square.
+    pub fn validate(&self) -> Result<(), String> {
+        for bucket in 0..self.num_buckets() {
+            if !self.buckets.contains(&bucket) {
+                return Err(format!("Bucket {bucket} is not used by any square!"));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn as_array(&self) -> [usize; 64] {
+        self.buckets
+    }
+
+    /// Buckets of files a-d, in the format used by `ChessBucketsMirrored::new`.
+    pub fn as_mirrored_array(&self) -> [usize; 32] {
+        assert!(self.is_mirrored(), "Layout is not horizontally symmetric!");
+
+        let mut buckets = [0; 32];
+        for (idx, bucket) in buckets.iter_mut().enumerate() {
+            *bucket = self.buckets[8 * (idx / 4) + idx % 4];
+        }
+
+        buckets
+    }
+
+    pub fn build(&self) -> ChessBuckets {
+        self.validate().unwrap();
+        ChessBuckets::new(self.buckets)
+    }
+
+    /// Requires the layout to be horizontally symmetric, see `mirrored`.
+    pub fn build_mirrored(&self) -> ChessBucketsMirrored {
+        self.validate().unwrap();
+        ChessBucketsMirrored::new(self.as_mirrored_array())
+    }
+
+    /// Prints the layout as a board, with the eighth rank at the top.
+    pub fn display(&self) {
+        for rank in (0..8).rev() {
+            let row = (0..8).map(|file| format!("{:>3}", self.buckets[8 * rank + file])).collect::<String>();
+            println!("{row}");
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use bulletformat::ChessBoard;
+
+use super::SparseInputType;
+use crate::default::loader::CastlingBoard;
+
+/// Appends castling rights features to a chess input type, which requires loading `CastlingBoard`s.
+///
+/// By default there are 4 features, one for each of kingside and queenside castling for
+/// each side. With `chess960` there are instead 16 features, one for each file a castling
+/// rook can be on for each side, so that engines playing Chess960/DFRC can distinguish
+/// the rook placements. Rook files are not mirrored along with the inner input type.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithCastling<I> {
+    inner: I,
+    chess960: bool,
+}
+
+impl<I: SparseInputType<RequiredDataType = ChessBoard>> WithCastling<I> {
+    pub fn new(inner: I) -> Self {
+        Self { inner, chess960: false }
+    }
+
+    pub fn chess960(mut self, chess960: bool) -> Self {
+        self.chess960 = chess960;
+        self
+    }
+
+    fn features_per_side(&self) -> usize {
+        if self.chess960 {
+            8
+        } else {
+            2
+        }
+    }
+}
+
+impl<I: SparseInputType<RequiredDataType = ChessBoard>> SparseInputType for WithCastling<I> {
+    type RequiredDataType = CastlingBoard;
+
+    fn num_inputs(&self) -> usize {
+        self.inner.num_inputs() + 2 * self.features_per_side()
+    }
+
+    fn max_active(&self) -> usize {
+        self.inner.max_active() + 4
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        self.inner.map_features(&pos.board, &mut f);
+
+        let offset = self.inner.num_inputs();
+        let per_side = self.features_per_side();
+
+        for (i, sq) in pos.castling_rooks().into_iter().enumerate() {
+            if let Some(sq) = sq {
+                let ours = i < 2;
+                let feat = if self.chess960 { usize::from(sq % 8) } else { i % 2 };
+
+                let stm = offset + per_side * usize::from(!ours) + feat;
+                let ntm = offset + per_side * usize::from(ours) + feat;
+
+                f(stm, ntm);
+            }
+        }
+    }
+
+    fn shorthand(&self) -> String {
+        format!("{} + {}", self.inner.shorthand(), if self.chess960 { "frc" } else { "castling" })
+    }
+
+    fn description(&self) -> String {
+        let castling = if self.chess960 { "Chess960 castling rooks" } else { "castling rights" };
+        format!("{}, with {castling}", self.inner.description())
+    }
+
+    fn extra_groups(&self) -> Vec<(usize, usize)> {
+        self.inner.extra_groups()
+    }
+
+    fn map_group_features<F: FnMut(usize, usize)>(&self, group: usize, pos: &Self::RequiredDataType, f: F) {
+        self.inner.map_group_features(group, &pos.board, f);
+    }
+
+    fn num_dense_inputs(&self) -> usize {
+        self.inner.num_dense_inputs()
+    }
+
+    fn map_dense_features(&self, pos: &Self::RequiredDataType, buf: &mut [f32]) {
+        self.inner.map_dense_features(&pos.board, buf);
+    }
+
+    fn is_factorised(&self) -> bool {
+        self.inner.is_factorised()
+    }
+
+    fn merge_factoriser(&self, unmerged: Vec<f32>) -> Vec<f32> {
+        let layer_size = unmerged.len() / self.num_inputs();
+        let split = self.inner.num_inputs() * layer_size;
+
+        let mut merged = self.inner.merge_factoriser(unmerged[..split].to_vec());
+        merged.extend_from_slice(&unmerged[split..]);
+        merged
+    }
+}
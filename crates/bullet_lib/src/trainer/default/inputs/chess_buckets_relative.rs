@@ -0,0 +1,145 @@
+use std::marker::PhantomData;
+
+use bulletformat::ChessBoard;
+
+use super::{Chess768, Factorises, KingBucketLayout, SparseInputType};
+
+/// Which squares the board is mirrored on, based on the square of the king.
+pub trait KingSymmetry: Clone + Copy + Default + Send + Sync + 'static {
+    const SHORTHAND: &'static str;
+
+    /// Square mask to xor all squares with, given the square of the king.
+    fn mask(ksq: u8) -> u8;
+}
+
+/// No mirroring.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoMirror;
+impl KingSymmetry for NoMirror {
+    const SHORTHAND: &'static str = "";
+
+    fn mask(_: u8) -> u8 {
+        0
+    }
+}
+
+/// Mirrors the board horizontally so that the king is always on files a-d.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Horizontal;
+impl KingSymmetry for Horizontal {
+    const SHORTHAND: &'static str = "hm";
+
+    fn mask(ksq: u8) -> u8 {
+        if ksq % 8 > 3 {
+            7
+        } else {
+            0
+        }
+    }
+}
+
+/// Mirrors the board vertically so that the king is always on ranks 1-4.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Vertical;
+impl KingSymmetry for Vertical {
+    const SHORTHAND: &'static str = "vm";
+
+    fn mask(ksq: u8) -> u8 {
+        if ksq / 8 > 3 {
+            56
+        } else {
+            0
+        }
+    }
+}
+
+/// Mirrors the board so that the king is always in the a1-d4 quadrant.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HorizontalAndVertical;
+impl KingSymmetry for HorizontalAndVertical {
+    const SHORTHAND: &'static str = "hvm";
+
+    fn mask(ksq: u8) -> u8 {
+        Horizontal::mask(ksq) | Vertical::mask(ksq)
+    }
+}
+
+/// How the board is transformed to be seen from the perspective of the side not to move.
+pub trait PerspectiveTransform: Clone + Copy + Default + Send + Sync + 'static {
+    const SHORTHAND: &'static str;
+
+    /// Square mask to xor onto the ranks-flipped squares used by `ChessBoard`.
+    const MASK: u8;
+}
+
+/// Flips the ranks of the board, the convention used by `Chess768`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RankFlip;
+impl PerspectiveTransform for RankFlip {
+    const SHORTHAND: &'static str = "";
+    const MASK: u8 = 0;
+}
+
+/// Rotates the board by 180 degrees, flipping both ranks and files.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rotate;
+impl PerspectiveTransform for Rotate {
+    const SHORTHAND: &'static str = "r";
+    const MASK: u8 = 7;
+}
+
+/// King bucketed psqt chess inputs, generic over the symmetry used to mirror the
+/// board based on the king square and over the transform to the other perspective.
+///
+/// `ChessBucketsRelative<Horizontal, RankFlip>` is equivalent to `ChessBucketsMirrored`.
+#[derive(Clone, Copy, Debug)]
+pub struct ChessBucketsRelative<S: KingSymmetry = Horizontal, P: PerspectiveTransform = RankFlip> {
+    buckets: [usize; 64],
+    num_buckets: usize,
+    transform: PhantomData<(S, P)>,
+}
+
+impl<S: KingSymmetry, P: PerspectiveTransform> ChessBucketsRelative<S, P> {
+    /// Only the buckets of king squares that are not mirrored under `S` are used.
+    pub fn new(layout: KingBucketLayout) -> Self {
+        let buckets = layout.as_array();
+        let layout = KingBucketLayout::from_fn(|sq| buckets[sq ^ usize::from(S::mask(sq as u8))]);
+
+        Self { buckets: layout.as_array(), num_buckets: layout.num_buckets(), transform: PhantomData }
+    }
+}
+
+impl<S: KingSymmetry, P: PerspectiveTransform> SparseInputType for ChessBucketsRelative<S, P> {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        768 * self.num_buckets
+    }
+
+    fn max_active(&self) -> usize {
+        32
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        let get = |ksq: u8| (usize::from(S::mask(ksq)), 768 * self.buckets[usize::from(ksq)]);
+        let (stm_mask, stm_bucket) = get(pos.our_ksq());
+        let (ntm_mask, ntm_bucket) = get(pos.opp_ksq() ^ P::MASK);
+        let ntm_mask = ntm_mask ^ usize::from(P::MASK);
+
+        Chess768.map_features(pos, |stm, ntm| f(stm_bucket + (stm ^ stm_mask), ntm_bucket + (ntm ^ ntm_mask)));
+    }
+
+    fn shorthand(&self) -> String {
+        format!("768x{}{}{}", self.num_buckets, S::SHORTHAND, P::SHORTHAND)
+    }
+
+    fn description(&self) -> String {
+        "Relative king bucketed psqt chess inputs".to_string()
+    }
+}
+
+impl<S: KingSymmetry, P: PerspectiveTransform> Factorises<ChessBucketsRelative<S, P>> for Chess768 {
+    fn derive_feature(&self, _: &ChessBucketsRelative<S, P>, feat: usize) -> Option<usize> {
+        Some(feat % 768)
+    }
+}
@@ -55,6 +55,34 @@ impl<A: SparseInputType, B: Factorises<A>> SparseInputType for Factorised<A, B>
         format!("{}, factorised by {}", self.normal.description(), self.factoriser.description().to_lowercase())
     }
 
+    fn extra_groups(&self) -> Vec<(usize, usize)> {
+        self.normal.extra_groups()
+    }
+
+    fn map_group_features<F: FnMut(usize, usize)>(&self, group: usize, pos: &Self::RequiredDataType, f: F) {
+        self.normal.map_group_features(group, pos, f);
+    }
+
+    fn num_dense_inputs(&self) -> usize {
+        self.normal.num_dense_inputs()
+    }
+
+    fn map_dense_features(&self, pos: &Self::RequiredDataType, buf: &mut [f32]) {
+        self.normal.map_dense_features(pos, buf);
+    }
+
+    fn num_policy_outputs(&self) -> usize {
+        self.normal.num_policy_outputs()
+    }
+
+    fn max_policy_moves(&self) -> usize {
+        self.normal.max_policy_moves()
+    }
+
+    fn map_policy<F: FnMut(usize, f32)>(&self, pos: &Self::RequiredDataType, f: F) {
+        self.normal.map_policy(pos, f);
+    }
+
     fn is_factorised(&self) -> bool {
         true
     }
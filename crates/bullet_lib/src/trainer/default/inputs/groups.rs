@@ -0,0 +1,81 @@
+use super::SparseInputType;
+
+/// Uses `A` as the main group of sparse inputs (graph inputs `stm` and `nstm`), and
+/// appends `B` as an additional group after any extra groups that `A` already has.
+///
+/// e.g. `WithGroup<WithGroup<Chess768, Pawns>, Kings>` loads pawn features into
+/// `stm_1`/`nstm_1` and king features into `stm_2`/`nstm_2`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithGroup<A, B> {
+    main: A,
+    group: B,
+}
+
+impl<A: SparseInputType, B: SparseInputType<RequiredDataType = A::RequiredDataType>> WithGroup<A, B> {
+    pub fn new(main: A, group: B) -> Self {
+        assert!(group.extra_groups().is_empty(), "Additional input group cannot have its own extra groups!");
+        Self { main, group }
+    }
+}
+
+impl<A, B> SparseInputType for WithGroup<A, B>
+where
+    A: SparseInputType,
+    B: SparseInputType<RequiredDataType = A::RequiredDataType>,
+{
+    type RequiredDataType = A::RequiredDataType;
+
+    fn num_inputs(&self) -> usize {
+        self.main.num_inputs()
+    }
+
+    fn max_active(&self) -> usize {
+        self.main.max_active()
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, f: F) {
+        self.main.map_features(pos, f);
+    }
+
+    fn extra_groups(&self) -> Vec<(usize, usize)> {
+        let mut groups = self.main.extra_groups();
+        groups.push((self.group.num_inputs(), self.group.max_active()));
+        groups
+    }
+
+    fn map_group_features<F: FnMut(usize, usize)>(&self, group: usize, pos: &Self::RequiredDataType, f: F) {
+        if group <= self.main.extra_groups().len() {
+            self.main.map_group_features(group, pos, f);
+        } else {
+            self.group.map_features(pos, f);
+        }
+    }
+
+    fn num_dense_inputs(&self) -> usize {
+        self.main.num_dense_inputs()
+    }
+
+    fn map_dense_features(&self, pos: &Self::RequiredDataType, buf: &mut [f32]) {
+        self.main.map_dense_features(pos, buf);
+    }
+
+    fn num_policy_outputs(&self) -> usize {
+        self.main.num_policy_outputs()
+    }
+
+    fn max_policy_moves(&self) -> usize {
+        self.main.max_policy_moves()
+    }
+
+    fn map_policy<F: FnMut(usize, f32)>(&self, pos: &Self::RequiredDataType, f: F) {
+        self.main.map_policy(pos, f);
+    }
+
+    fn shorthand(&self) -> String {
+        format!("{} + {}", self.main.shorthand(), self.group.shorthand())
+    }
+
+    fn description(&self) -> String {
+        format!("{}, with additional group of {}", self.main.description(), self.group.description().to_lowercase())
+    }
+}
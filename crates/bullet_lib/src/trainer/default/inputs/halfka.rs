@@ -0,0 +1,90 @@
+use bulletformat::ChessBoard;
+
+use super::{Factorises, SparseInputType};
+
+/// Stockfish's `HalfKAv2_hm` feature set: 32 king buckets (one per king square, with the board
+/// mirrored so the king is always on files e-h), each containing 11 piece planes of 64 squares,
+/// where both kings share a single plane.
+///
+/// Feature indices match Stockfish's, so weights can be loaded directly by SF-compatible inference code.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HalfKAv2Hm;
+
+impl HalfKAv2Hm {
+    const PLANES: usize = 11 * 64;
+
+    /// `ksq` and `sq` are relative to the perspective.
+    fn feature(ksq: u8, piece: u8, sq: u8) -> usize {
+        let (rank, file) = (usize::from(ksq / 8), usize::from(ksq % 8));
+        let flip = if file < 4 { 7 } else { 0 };
+        let bucket = 4 * (7 - rank) + file.min(7 - file);
+
+        let pt = usize::from(piece & 7);
+        let plane = if pt == 5 { 10 } else { 2 * pt + usize::from(piece & 8 > 0) };
+
+        Self::PLANES * bucket + 64 * plane + usize::from(sq ^ flip)
+    }
+}
+
+impl SparseInputType for HalfKAv2Hm {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        32 * Self::PLANES
+    }
+
+    fn max_active(&self) -> usize {
+        32
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        for (piece, square) in pos.into_iter() {
+            let stm = Self::feature(pos.our_ksq(), piece, square);
+            let ntm = Self::feature(pos.opp_ksq(), piece ^ 8, square ^ 56);
+            f(stm, ntm)
+        }
+    }
+
+    fn shorthand(&self) -> String {
+        "HalfKAv2_hm".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Stockfish HalfKAv2_hm chess inputs".to_string()
+    }
+}
+
+/// `HalfKAv2Hm` without king buckets, for use as a factoriser, where each feature
+/// is shared by the corresponding feature in all of the king buckets.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HalfAv2Hm;
+
+impl SparseInputType for HalfAv2Hm {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        HalfKAv2Hm::PLANES
+    }
+
+    fn max_active(&self) -> usize {
+        32
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        HalfKAv2Hm.map_features(pos, |stm, ntm| f(stm % HalfKAv2Hm::PLANES, ntm % HalfKAv2Hm::PLANES));
+    }
+
+    fn shorthand(&self) -> String {
+        "HalfAv2_hm".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Unbucketed HalfKAv2_hm chess inputs".to_string()
+    }
+}
+
+impl Factorises<HalfKAv2Hm> for HalfAv2Hm {
+    fn derive_feature(&self, _: &HalfKAv2Hm, feat: usize) -> Option<usize> {
+        Some(feat % HalfKAv2Hm::PLANES)
+    }
+}
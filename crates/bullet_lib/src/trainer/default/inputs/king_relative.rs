@@ -0,0 +1,57 @@
+use std::marker::PhantomData;
+
+use bulletformat::ChessBoard;
+
+use super::{Chess768, KingSymmetry, NoMirror, SparseInputType};
+
+/// Number of possible `(rank, file)` offsets of a square from the king.
+const OFFSETS: usize = 15 * 15;
+
+/// Chess inputs where each piece is indexed by its rank and file offset from the perspective's
+/// king, rather than by its absolute square within a king bucket, giving `12 x 15 x 15` inputs.
+///
+/// The board is first mirrored according to `S`, based on the square of the king.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KingRelative<S: KingSymmetry = NoMirror> {
+    symmetry: PhantomData<S>,
+}
+
+impl<S: KingSymmetry> KingRelative<S> {
+    fn index(ksq: u8, feat: usize) -> usize {
+        let mask = usize::from(S::mask(ksq));
+        let ksq = usize::from(ksq) ^ mask;
+        let sq = (feat % 64) ^ mask;
+
+        let rank = 7 + sq / 8 - ksq / 8;
+        let file = 7 + sq % 8 - ksq % 8;
+
+        OFFSETS * (feat / 64) + 15 * rank + file
+    }
+}
+
+impl<S: KingSymmetry> SparseInputType for KingRelative<S> {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        12 * OFFSETS
+    }
+
+    fn max_active(&self) -> usize {
+        32
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        let stm_ksq = pos.our_ksq();
+        let ntm_ksq = pos.opp_ksq();
+
+        Chess768.map_features(pos, |stm, ntm| f(Self::index(stm_ksq, stm), Self::index(ntm_ksq, ntm)));
+    }
+
+    fn shorthand(&self) -> String {
+        format!("{}{}", 12 * OFFSETS, S::SHORTHAND)
+    }
+
+    fn description(&self) -> String {
+        "King-relative chess inputs".to_string()
+    }
+}
@@ -0,0 +1,80 @@
+use bulletformat::ChessBoard;
+
+use super::SparseInputType;
+
+/// Phase weights of pawn, knight, bishop, rook, queen and king.
+const PHASE_WEIGHTS: [u32; 6] = [0, 1, 1, 2, 4, 0];
+
+/// Total phase of the starting position.
+const MAX_PHASE: u32 = 24;
+
+/// Appends the game phase to the dense auxiliary inputs of a chess input type, as a single
+/// scalar from 0 (no non-pawn material) to 1 (full non-pawn material), so that the layers
+/// after the feature transformer can condition on phase without output buckets.
+///
+/// Phase is counted with the usual weights of 1 for minor pieces, 2 for rooks and 4 for queens.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithPhase<I> {
+    inner: I,
+}
+
+impl<I: SparseInputType<RequiredDataType = ChessBoard>> WithPhase<I> {
+    pub fn new(inner: I) -> Self {
+        Self { inner }
+    }
+
+    pub fn phase(pos: &ChessBoard) -> f32 {
+        let phase = pos.into_iter().map(|(piece, _)| PHASE_WEIGHTS[usize::from(piece & 7)]).sum::<u32>();
+        phase.min(MAX_PHASE) as f32 / MAX_PHASE as f32
+    }
+}
+
+impl<I: SparseInputType<RequiredDataType = ChessBoard>> SparseInputType for WithPhase<I> {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        self.inner.num_inputs()
+    }
+
+    fn max_active(&self) -> usize {
+        self.inner.max_active()
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, f: F) {
+        self.inner.map_features(pos, f);
+    }
+
+    fn shorthand(&self) -> String {
+        self.inner.shorthand()
+    }
+
+    fn description(&self) -> String {
+        format!("{}, with game phase", self.inner.description())
+    }
+
+    fn extra_groups(&self) -> Vec<(usize, usize)> {
+        self.inner.extra_groups()
+    }
+
+    fn map_group_features<F: FnMut(usize, usize)>(&self, group: usize, pos: &Self::RequiredDataType, f: F) {
+        self.inner.map_group_features(group, pos, f);
+    }
+
+    fn num_dense_inputs(&self) -> usize {
+        self.inner.num_dense_inputs() + 1
+    }
+
+    fn map_dense_features(&self, pos: &Self::RequiredDataType, buf: &mut [f32]) {
+        let (inner, phase) = buf.split_at_mut(self.inner.num_dense_inputs());
+        self.inner.map_dense_features(pos, inner);
+        phase[0] = Self::phase(pos);
+    }
+
+    fn is_factorised(&self) -> bool {
+        self.inner.is_factorised()
+    }
+
+    fn merge_factoriser(&self, unmerged: Vec<f32>) -> Vec<f32> {
+        self.inner.merge_factoriser(unmerged)
+    }
+}
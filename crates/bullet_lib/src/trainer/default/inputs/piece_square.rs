@@ -0,0 +1,113 @@
+use bulletformat::ChessBoard;
+
+use super::{KingBucketLayout, KingSymmetry, NoMirror, SparseInputType};
+
+/// Maps a piece and square, both relative to the perspective as in `ChessBoard`, to a feature index.
+pub type PieceSquareIndex = fn(piece: u8, square: u8) -> Option<usize>;
+
+/// Builds a chess `SparseInputType` from a per-piece index function, with optional
+/// king buckets and mirroring, for when a custom input type only differs from the
+/// premade ones in how pieces are indexed.
+///
+/// ```ignore
+/// // psqt inputs that ignore the colour of pieces, with 4 king buckets and horizontal mirroring
+/// let inputs = PieceSquareInputs::new("384", 384, |piece, sq| Some(64 * usize::from(piece & 7) + usize::from(sq)))
+///     .with_king_buckets(KingBucketLayout::by_quadrant([0, 1, 2, 3]))
+///     .with_mirroring::<Horizontal>();
+/// ```
+#[derive(Clone, Copy)]
+pub struct PieceSquareInputs {
+    name: &'static str,
+    index: PieceSquareIndex,
+    inputs_per_bucket: usize,
+    max_active: usize,
+    buckets: [usize; 64],
+    num_buckets: usize,
+    mirror: fn(u8) -> u8,
+}
+
+impl PieceSquareInputs {
+    /// `index` must return values less than `inputs_per_bucket`, or `None` if a piece has no feature.
+    pub fn new(name: &'static str, inputs_per_bucket: usize, index: PieceSquareIndex) -> Self {
+        Self {
+            name,
+            index,
+            inputs_per_bucket,
+            max_active: 32,
+            buckets: [0; 64],
+            num_buckets: 1,
+            mirror: NoMirror::mask,
+        }
+    }
+
+    /// Sets the maximum number of active features, defaults to 32.
+    pub fn with_max_active(mut self, max_active: usize) -> Self {
+        self.max_active = max_active;
+        self
+    }
+
+    pub fn with_king_buckets(mut self, layout: KingBucketLayout) -> Self {
+        layout.validate().unwrap();
+        self.buckets = layout.as_array();
+        self.num_buckets = layout.num_buckets();
+        self
+    }
+
+    /// Mirrors all squares (including the king square used for bucketing) according to `S`.
+    pub fn with_mirroring<S: KingSymmetry>(mut self) -> Self {
+        self.mirror = S::mask;
+        self
+    }
+}
+
+impl SparseInputType for PieceSquareInputs {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        self.inputs_per_bucket * self.num_buckets
+    }
+
+    fn max_active(&self) -> usize {
+        self.max_active
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        let get = |ksq: u8| {
+            let mask = (self.mirror)(ksq);
+            (mask, self.inputs_per_bucket * self.buckets[usize::from(ksq ^ mask)])
+        };
+
+        let (stm_mask, stm_bucket) = get(pos.our_ksq());
+        let (ntm_mask, ntm_bucket) = get(pos.opp_ksq());
+
+        for (piece, square) in pos.into_iter() {
+            let stm = (self.index)(piece, square ^ stm_mask);
+            let ntm = (self.index)(piece ^ 8, square ^ 56 ^ ntm_mask);
+
+            match (stm, ntm) {
+                (Some(stm), Some(ntm)) => {
+                    assert!(
+                        stm < self.inputs_per_bucket && ntm < self.inputs_per_bucket,
+                        "Piece-square index exceeded inputs per bucket!"
+                    );
+
+                    f(stm_bucket + stm, ntm_bucket + ntm);
+                }
+                (None, None) => {}
+                _ => panic!("Piece-square index must be defined from both perspectives!"),
+            }
+        }
+    }
+
+    fn shorthand(&self) -> String {
+        if self.num_buckets > 1 {
+            format!("{}x{}", self.name, self.num_buckets)
+        } else {
+            self.name.to_string()
+        }
+    }
+
+    fn description(&self) -> String {
+        format!("Custom piece-square inputs [{}]", self.name)
+    }
+}
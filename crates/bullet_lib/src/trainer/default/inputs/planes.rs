@@ -0,0 +1,88 @@
+use bulletformat::ChessBoard;
+
+use super::{threats::attacks_by, Chess768, SparseInputType};
+
+/// Dense `C x 8 x 8` board planes for convolutional networks, provided as the dense auxiliary
+/// inputs (graph input `dense`), indexed by `64 * plane + square`, from the side to move's perspective.
+///
+/// The first 12 planes are the pieces of the side to move followed by those of the opponent, in the
+/// order pawn, knight, bishop, rook, queen, king. If `with_attacks` is set there are 2 more planes,
+/// containing the squares attacked by the side to move and by the opponent respectively.
+///
+/// The sparse inputs (graph inputs `stm` and `nstm`) are the same as `Chess768`, and can be
+/// left unused by the network.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChessPlanes {
+    attacks: bool,
+}
+
+impl ChessPlanes {
+    pub fn with_attacks(mut self) -> Self {
+        self.attacks = true;
+        self
+    }
+
+    pub fn num_planes(&self) -> usize {
+        if self.attacks {
+            14
+        } else {
+            12
+        }
+    }
+}
+
+impl SparseInputType for ChessPlanes {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        768
+    }
+
+    fn max_active(&self) -> usize {
+        32
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, f: F) {
+        Chess768.map_features(pos, f);
+    }
+
+    fn num_dense_inputs(&self) -> usize {
+        64 * self.num_planes()
+    }
+
+    fn map_dense_features(&self, pos: &Self::RequiredDataType, buf: &mut [f32]) {
+        buf.fill(0.0);
+
+        let mut bbs = [[0u64; 6]; 2];
+
+        for (piece, square) in pos.into_iter() {
+            let c = usize::from(piece & 8 > 0);
+            let pc = usize::from(piece & 7);
+
+            bbs[c][pc] |= 1 << square;
+            buf[64 * (6 * c + pc) + usize::from(square)] = 1.0;
+        }
+
+        if self.attacks {
+            let occ = pos.occ();
+
+            for (side, ours) in [(0, true), (1, false)] {
+                let mut attacks = attacks_by(&bbs[side], occ, ours);
+
+                while attacks > 0 {
+                    let sq = attacks.trailing_zeros() as usize;
+                    attacks &= attacks - 1;
+                    buf[64 * (12 + side) + sq] = 1.0;
+                }
+            }
+        }
+    }
+
+    fn shorthand(&self) -> String {
+        format!("{}x8x8", self.num_planes())
+    }
+
+    fn description(&self) -> String {
+        "Dense chess board planes".to_string()
+    }
+}
@@ -0,0 +1,84 @@
+use bulletformat::ChessBoard;
+
+use super::SparseInputType;
+use crate::default::loader::PolicyBoard;
+
+/// Adds policy targets to a chess input type, which requires loading `PolicyBoard`s.
+///
+/// Moves are indexed as `64 * from + to`, from the perspective of the side to move,
+/// for a policy head of 4096 outputs (see `TrainerBuilder::policy_head`). The target
+/// for each move is its weight divided by the total weight of the moves in the position.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithPolicy<I> {
+    inner: I,
+}
+
+impl<I: SparseInputType<RequiredDataType = ChessBoard>> WithPolicy<I> {
+    pub fn new(inner: I) -> Self {
+        Self { inner }
+    }
+}
+
+impl<I: SparseInputType<RequiredDataType = ChessBoard>> SparseInputType for WithPolicy<I> {
+    type RequiredDataType = PolicyBoard;
+
+    fn num_inputs(&self) -> usize {
+        self.inner.num_inputs()
+    }
+
+    fn max_active(&self) -> usize {
+        self.inner.max_active()
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, f: F) {
+        self.inner.map_features(&pos.board, f);
+    }
+
+    fn shorthand(&self) -> String {
+        self.inner.shorthand()
+    }
+
+    fn description(&self) -> String {
+        format!("{}, with policy targets", self.inner.description())
+    }
+
+    fn extra_groups(&self) -> Vec<(usize, usize)> {
+        self.inner.extra_groups()
+    }
+
+    fn map_group_features<F: FnMut(usize, usize)>(&self, group: usize, pos: &Self::RequiredDataType, f: F) {
+        self.inner.map_group_features(group, &pos.board, f);
+    }
+
+    fn num_dense_inputs(&self) -> usize {
+        self.inner.num_dense_inputs()
+    }
+
+    fn map_dense_features(&self, pos: &Self::RequiredDataType, buf: &mut [f32]) {
+        self.inner.map_dense_features(&pos.board, buf);
+    }
+
+    fn num_policy_outputs(&self) -> usize {
+        4096
+    }
+
+    fn max_policy_moves(&self) -> usize {
+        PolicyBoard::MAX_MOVES
+    }
+
+    fn map_policy<F: FnMut(usize, f32)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        let total = pos.moves().map(|(_, _, weight)| f32::from(weight)).sum::<f32>();
+
+        for (from, to, weight) in pos.moves() {
+            f(64 * usize::from(from) + usize::from(to), f32::from(weight) / total);
+        }
+    }
+
+    fn is_factorised(&self) -> bool {
+        self.inner.is_factorised()
+    }
+
+    fn merge_factoriser(&self, unmerged: Vec<f32>) -> Vec<f32> {
+        self.inner.merge_factoriser(unmerged)
+    }
+}
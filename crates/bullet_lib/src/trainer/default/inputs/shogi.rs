@@ -0,0 +1,81 @@
+use super::SparseInputType;
+use crate::default::loader::{shogi_piece, ShogiBoard, SHOGI_MAX_HAND, SHOGI_OPPONENT};
+
+/// Non-king piece types.
+const PIECE_TYPES: usize = shogi_piece::NUM_TYPES as usize - 1;
+
+const BOARD_FEATURES: usize = 2 * PIECE_TYPES * 81;
+
+/// Maximum number of pieces in hand for one side.
+const HAND_FEATURES: usize = 38;
+
+const PER_KING: usize = BOARD_FEATURES + 2 * HAND_FEATURES;
+
+/// Offset of the features of each hand piece type.
+const HAND_OFFSETS: [usize; 7] = {
+    let mut offsets = [0; 7];
+    let mut i = 1;
+    while i < 7 {
+        offsets[i] = offsets[i - 1] + SHOGI_MAX_HAND[i - 1] as usize;
+        i += 1;
+    }
+    offsets
+};
+
+/// Shogi HalfKP inputs, each non-king piece on the board is indexed by the square of the
+/// perspective's king, and the `n`th piece of each type held in hand by each side has
+/// its own feature, so that a side holding `n` pieces of a type has `n` active features.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ShogiHalfKP;
+impl SparseInputType for ShogiHalfKP {
+    type RequiredDataType = ShogiBoard;
+
+    fn num_inputs(&self) -> usize {
+        81 * PER_KING
+    }
+
+    fn max_active(&self) -> usize {
+        38
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        let stm_king = PER_KING * usize::from(pos.our_ksq());
+        let ntm_king = PER_KING * usize::from(pos.opp_ksq());
+
+        for (piece, square) in pos.pieces() {
+            let pc = piece & !SHOGI_OPPONENT;
+
+            if pc == shogi_piece::KING {
+                continue;
+            }
+
+            let pc = usize::from(if pc > shogi_piece::KING { pc - 1 } else { pc });
+            let c = usize::from(piece & SHOGI_OPPONENT > 0);
+            let sq = usize::from(square);
+
+            let stm = stm_king + 81 * (PIECE_TYPES * c + pc) + sq;
+            let ntm = ntm_king + 81 * (PIECE_TYPES * (1 - c) + pc) + 80 - sq;
+            f(stm, ntm);
+        }
+
+        for side in 0..2 {
+            for (pc, &count) in pos.hand(side).iter().enumerate() {
+                for n in 0..usize::from(count) {
+                    let feat = BOARD_FEATURES + HAND_OFFSETS[pc] + n;
+
+                    let stm = stm_king + feat + HAND_FEATURES * side;
+                    let ntm = ntm_king + feat + HAND_FEATURES * (1 - side);
+                    f(stm, ntm);
+                }
+            }
+        }
+    }
+
+    fn shorthand(&self) -> String {
+        format!("{PER_KING}x81")
+    }
+
+    fn description(&self) -> String {
+        "Shogi HalfKP inputs with hand pieces".to_string()
+    }
+}
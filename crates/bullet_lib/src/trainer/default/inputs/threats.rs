@@ -0,0 +1,128 @@
+use bulletformat::ChessBoard;
+
+use super::{Chess768, SparseInputType};
+
+const NOT_A_FILE: u64 = 0xfefe_fefe_fefe_fefe;
+const NOT_H_FILE: u64 = 0x7f7f_7f7f_7f7f_7f7f;
+const NOT_AB_FILES: u64 = 0xfcfc_fcfc_fcfc_fcfc;
+const NOT_GH_FILES: u64 = 0x3f3f_3f3f_3f3f_3f3f;
+
+const DIAGONALS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const ORTHOGONALS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// `Chess768` with two additional blocks of 768 inputs, indexed in the same way:
+/// - the first is active for each piece that is attacked by an enemy piece
+/// - the second is active for each piece that is defended by a friendly piece
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Chess768Threats;
+
+impl SparseInputType for Chess768Threats {
+    type RequiredDataType = ChessBoard;
+
+    fn num_inputs(&self) -> usize {
+        3 * 768
+    }
+
+    fn max_active(&self) -> usize {
+        3 * 32
+    }
+
+    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
+        let mut bbs = [[0u64; 6]; 2];
+        for (piece, square) in pos.into_iter() {
+            bbs[usize::from(piece & 8 > 0)][usize::from(piece & 7)] |= 1 << square;
+        }
+
+        let occ = pos.occ();
+        let attacked = [attacks_by(&bbs[0], occ, true), attacks_by(&bbs[1], occ, false)];
+
+        Chess768.map_features(pos, &mut f);
+
+        for (piece, square) in pos.into_iter() {
+            let c = usize::from(piece & 8 > 0);
+            let pc = 64 * usize::from(piece & 7);
+            let sq = usize::from(square);
+
+            let stm = [0, 384][c] + pc + sq;
+            let ntm = [384, 0][c] + pc + (sq ^ 56);
+
+            if attacked[c ^ 1] & (1 << sq) > 0 {
+                f(768 + stm, 768 + ntm);
+            }
+
+            if attacked[c] & (1 << sq) > 0 {
+                f(1536 + stm, 1536 + ntm);
+            }
+        }
+    }
+
+    fn shorthand(&self) -> String {
+        "768x3".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Default psqt chess inputs with threatened and defended pieces".to_string()
+    }
+}
+
+/// All squares attacked by one side, where `ours` indicates the side to move,
+/// whose pawns move up the board.
+pub(super) fn attacks_by(bbs: &[u64; 6], occ: u64, ours: bool) -> u64 {
+    let pawns = bbs[0];
+    let mut attacks = if ours {
+        ((pawns & NOT_A_FILE) << 7) | ((pawns & NOT_H_FILE) << 9)
+    } else {
+        ((pawns & NOT_A_FILE) >> 9) | ((pawns & NOT_H_FILE) >> 7)
+    };
+
+    attacks |= knight_attacks(bbs[1]) | king_attacks(bbs[5]);
+
+    for (pt, dirs) in [(2, &DIAGONALS), (3, &ORTHOGONALS)] {
+        let mut sliders = bbs[pt] | bbs[4];
+
+        while sliders > 0 {
+            let sq = sliders.trailing_zeros() as i32;
+            sliders &= sliders - 1;
+            attacks |= ray_attacks(sq, occ, dirs);
+        }
+    }
+
+    attacks
+}
+
+fn knight_attacks(bb: u64) -> u64 {
+    let one = ((bb >> 1) & NOT_H_FILE) | ((bb << 1) & NOT_A_FILE);
+    let two = ((bb >> 2) & NOT_GH_FILES) | ((bb << 2) & NOT_AB_FILES);
+    (one << 16) | (one >> 16) | (two << 8) | (two >> 8)
+}
+
+fn king_attacks(bb: u64) -> u64 {
+    let row = bb | ((bb >> 1) & NOT_H_FILE) | ((bb << 1) & NOT_A_FILE);
+    (row | (row << 8) | (row >> 8)) ^ bb
+}
+
+fn ray_attacks(sq: i32, occ: u64, dirs: &[(i32, i32)]) -> u64 {
+    let mut attacks = 0;
+
+    for &(dr, df) in dirs {
+        let (mut rank, mut file) = (sq / 8, sq % 8);
+
+        loop {
+            rank += dr;
+            file += df;
+
+            if !(0..8).contains(&rank) || !(0..8).contains(&file) {
+                break;
+            }
+
+            let bit = 1 << (8 * rank + file);
+            attacks |= bit;
+
+            if occ & bit > 0 {
+                break;
+            }
+        }
+    }
+
+    attacks
+}
@@ -1,13 +1,41 @@
+mod castling;
+mod closure;
+mod corrupted;
+mod detect;
 mod direct;
+mod mixer;
 mod montybinpack;
+mod montydual;
+mod policy;
 mod rng;
+mod sampling;
 mod sfbinpack;
+mod sharded;
+mod shogi;
+mod stdin;
+mod tablebase;
+mod tail;
 mod text;
 
 use bulletformat::BulletFormat;
+pub use castling::CastlingBoard;
+pub use closure::FnDataLoader;
+pub use corrupted::{CorruptedRecords, DEFAULT_ERROR_BUDGET};
+pub use detect::{detect_format, detect_loader, DataFormat, DetectedLoader};
 pub use direct::{CanBeDirectlySequentiallyLoaded, DirectSequentialDataLoader};
+pub use mixer::DatasetMixer;
 pub use montybinpack::MontyBinpackLoader;
+pub use montydual::{MontyDualHalf, MontyDualLoader};
+pub use policy::PolicyBoard;
+pub use sampling::GameSampling;
 pub use sfbinpack::SfBinpackLoader;
+pub use sharded::{DataShard, ShardedDataLoader};
+pub use shogi::{shogi_piece, ShogiBoard, SHOGI_MAX_HAND, SHOGI_OPPONENT};
+pub use stdin::StdinDataLoader;
+#[cfg(feature = "syzygy")]
+pub use tablebase::SyzygyProber;
+pub use tablebase::{stm_relative_fen, TablebaseAction, TablebaseProber, TablebaseRescoringLoader};
+pub use tail::TailingDataLoader;
 pub use text::InMemoryTextLoader;
 
 use super::{inputs::SparseInputType, outputs::OutputBuckets};
@@ -49,20 +77,103 @@ pub trait DataLoader<T>: Clone + Send + Sync + 'static {
     }
 
     fn map_batches<F: FnMut(&[T]) -> bool>(&self, start_batch: usize, batch_size: usize, f: F);
+
+    /// Same as `count_positions`, but only counts the positions belonging to `shard`.
+    fn count_positions_sharded(&self, shard: DataShard) -> Option<u64> {
+        let world_size = shard.world_size as u64;
+        let rank = shard.rank as u64;
+        self.count_positions().map(|count| count / world_size + u64::from(count % world_size > rank))
+    }
+
+    /// Same as `map_batches`, but only produces the batches belonging to `shard`,
+    /// where `start_batch` counts batches of this shard only.
+    ///
+    /// By default batches are dealt out round-robin between shards, which is only disjoint
+    /// if the loader produces the same sequence of batches on every rank. Loaders that
+    /// shuffle data randomly, or that can cheaply skip data, should override this (along
+    /// with `count_positions_sharded`) to split the data itself, e.g. by file or record range.
+    fn map_batches_sharded<F: FnMut(&[T]) -> bool>(
+        &self,
+        shard: DataShard,
+        start_batch: usize,
+        batch_size: usize,
+        mut f: F,
+    ) {
+        let mut batch = 0;
+
+        self.map_batches(start_batch * shard.world_size, batch_size, |data| {
+            let owned = shard.owns(batch);
+            batch += 1;
+            owned && f(data)
+        });
+    }
+}
+
+/// The training targets written for each position, which must match the outputs of the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrainingTargets {
+    /// A single target, the game result blended with the sigmoid of the scaled score.
+    Eval,
+    /// The game result, one-hot encoded as `[loss, draw, win]`.
+    Wdl,
+    /// `[loss, draw, win]` one-hot encoded game result, followed by the blended eval target,
+    /// for networks with both a WDL head and an eval head.
+    WdlAndEval,
+}
+
+impl TrainingTargets {
+    /// Infers the targets from the number of network outputs.
+    pub fn from_num_outputs(outputs: usize) -> Option<Self> {
+        match outputs {
+            1 => Some(Self::Eval),
+            3 => Some(Self::Wdl),
+            4 => Some(Self::WdlAndEval),
+            _ => None,
+        }
+    }
+
+    pub fn num_outputs(self) -> usize {
+        match self {
+            Self::Eval => 1,
+            Self::Wdl => 3,
+            Self::WdlAndEval => 4,
+        }
+    }
+
+    pub fn has_wdl(self) -> bool {
+        self != Self::Eval
+    }
+
+    /// Writes the targets of a position with the given `result` (as a `GameResult`) and `score` into `out`.
+    pub(crate) fn write(self, out: &mut [f32], result: u8, score: i16, rscale: f32, blend: f32) {
+        let eval = || {
+            let score = 1. / (1. + (-rscale * f32::from(score)).exp());
+            blend * f32::from(result) / 2.0 + (1. - blend) * score
+        };
+
+        match self {
+            Self::Eval => out[0] = eval(),
+            Self::Wdl => out[usize::from(result)] = 1.0,
+            Self::WdlAndEval => {
+                out[usize::from(result)] = 1.0;
+                out[3] = eval();
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct DefaultDataLoader<I, O, D> {
     input_getter: I,
     output_getter: O,
-    wdl: bool,
+    targets: TrainingTargets,
     scale: f32,
     loader: D,
 }
 
 impl<I, O, D> DefaultDataLoader<I, O, D> {
-    pub fn new(input_getter: I, output_getter: O, wdl: bool, scale: f32, loader: D) -> Self {
-        Self { input_getter, output_getter, wdl, scale, loader }
+    pub fn new(input_getter: I, output_getter: O, targets: TrainingTargets, scale: f32, loader: D) -> Self {
+        Self { input_getter, output_getter, targets, scale, loader }
     }
 }
 
@@ -91,13 +202,29 @@ where
         DefaultDataPreparer::prepare(
             self.input_getter.clone(),
             self.output_getter,
-            self.wdl,
+            self.targets,
             data,
             threads,
             blend,
             self.scale,
         )
     }
+
+    fn prepared_batch_bytes(&self, batch_size: usize) -> Option<usize> {
+        Some(prepared_batch_bytes(&self.input_getter, self.targets, batch_size))
+    }
+}
+
+pub(crate) fn prepared_batch_bytes<I: SparseInputType>(
+    input_getter: &I,
+    targets: TrainingTargets,
+    batch_size: usize,
+) -> usize {
+    let outputs = targets.num_outputs();
+    let dense = input_getter.num_dense_inputs();
+    let groups = input_getter.extra_groups().iter().map(|&(_, max_active)| max_active).sum::<usize>();
+    let policy = 2 * input_getter.max_policy_moves();
+    4 * batch_size * (2 * (input_getter.max_active() + groups) + 1 + outputs + dense + policy)
 }
 
 pub(crate) struct DenseInput {
@@ -119,6 +246,11 @@ pub struct DefaultDataPreparer<I, O> {
     pub(crate) nstm: SparseInput,
     pub(crate) buckets: SparseInput,
     pub(crate) targets: DenseInput,
+    pub(crate) dense: DenseInput,
+    /// Additional sparse input groups, see `SparseInputType::extra_groups`.
+    pub(crate) groups: Vec<(SparseInput, SparseInput)>,
+    /// Policy move mask and targets, see `SparseInputType::map_policy`.
+    pub(crate) policy: Option<(SparseInput, DenseInput)>,
 }
 
 impl<I: SparseInputType, O: OutputBuckets<I::RequiredDataType>> DefaultDataPreparer<I, O> {
@@ -126,7 +258,7 @@ impl<I: SparseInputType, O: OutputBuckets<I::RequiredDataType>> DefaultDataPrepa
     pub fn prepare(
         input_getter: I,
         output_getter: O,
-        wdl: bool,
+        targets: TrainingTargets,
         data: &[I::RequiredDataType],
         threads: usize,
         blend: f32,
@@ -137,8 +269,9 @@ impl<I: SparseInputType, O: OutputBuckets<I::RequiredDataType>> DefaultDataPrepa
         let max_active = input_getter.max_active();
         let chunk_size = batch_size.div_ceil(threads);
         let input_size = input_getter.num_inputs();
-        let output_size = if wdl { 3 } else { 1 };
+        let output_size = targets.num_outputs();
         let sparse_size = max_active * batch_size;
+        let dense_size = input_getter.num_dense_inputs();
 
         let mut prep = Self {
             input_getter,
@@ -148,6 +281,9 @@ impl<I: SparseInputType, O: OutputBuckets<I::RequiredDataType>> DefaultDataPrepa
             nstm: SparseInput { max_active, value: vec![0; sparse_size] },
             buckets: SparseInput { max_active: 1, value: vec![0; batch_size] },
             targets: DenseInput { value: vec![0.0; output_size * batch_size] },
+            dense: DenseInput { value: vec![0.0; dense_size * batch_size] },
+            groups: Vec::new(),
+            policy: None,
         };
 
         let sparse_chunk_size = max_active * chunk_size;
@@ -190,18 +326,104 @@ impl<I: SparseInputType, O: OutputBuckets<I::RequiredDataType>> DefaultDataPrepa
 
                             buckets_chunk[i] = i32::from(out.bucket(pos));
 
-                            if wdl {
-                                results_chunk[output_size * i + usize::from(pos.result() as u8)] = 1.0;
-                            } else {
-                                let score = 1. / (1. + (-rscale * f32::from(pos.score())).exp());
-                                let result = f32::from(pos.result() as u8) / 2.0;
-                                results_chunk[i] = blend * result + (1. - blend) * score;
-                            }
+                            targets.write(
+                                &mut results_chunk[output_size * i..output_size * (i + 1)],
+                                pos.result() as u8,
+                                pos.score(),
+                                rscale,
+                                blend,
+                            );
                         }
                     });
                 });
         });
 
+        if dense_size > 0 {
+            for (pos, buf) in data.iter().zip(prep.dense.value.chunks_exact_mut(dense_size)) {
+                prep.input_getter.map_dense_features(pos, buf);
+            }
+        }
+
+        prep.groups = prep
+            .input_getter
+            .extra_groups()
+            .iter()
+            .enumerate()
+            .map(|(idx, &(size, max_active))| {
+                prepare_group(&prep.input_getter, idx + 1, size, max_active, data, chunk_size)
+            })
+            .collect();
+
+        if prep.input_getter.num_policy_outputs() > 0 {
+            prep.policy = Some(prepare_policy(&prep.input_getter, data));
+        }
+
         prep
     }
 }
+
+fn prepare_policy<I: SparseInputType>(input_getter: &I, data: &[I::RequiredDataType]) -> (SparseInput, DenseInput) {
+    let num_outputs = input_getter.num_policy_outputs();
+    let max_moves = input_getter.max_policy_moves();
+
+    let mut mask = SparseInput { max_active: max_moves, value: vec![-1; max_moves * data.len()] };
+    let mut targets = DenseInput { value: vec![0.0; max_moves * data.len()] };
+
+    for (i, pos) in data.iter().enumerate() {
+        let mut j = 0;
+
+        input_getter.map_policy(pos, |mv, target| {
+            assert!(mv < num_outputs, "Policy move index exceeded number of policy outputs!");
+            assert!(j < max_moves, "More policy moves provided than the specified maximum!");
+
+            mask.value[max_moves * i + j] = mv as i32;
+            targets.value[max_moves * i + j] = target;
+
+            j += 1;
+        });
+
+        assert!(j > 0, "Positions must have at least one policy move!");
+    }
+
+    (mask, targets)
+}
+
+fn prepare_group<I: SparseInputType>(
+    input_getter: &I,
+    group: usize,
+    input_size: usize,
+    max_active: usize,
+    data: &[I::RequiredDataType],
+    chunk_size: usize,
+) -> (SparseInput, SparseInput) {
+    assert!(max_active > 0, "Input group {group} must have at least one active input!");
+
+    let mut stm = SparseInput { max_active, value: vec![-1; max_active * data.len()] };
+    let mut nstm = SparseInput { max_active, value: vec![-1; max_active * data.len()] };
+
+    std::thread::scope(|s| {
+        data.chunks(chunk_size)
+            .zip(stm.value.chunks_mut(max_active * chunk_size))
+            .zip(nstm.value.chunks_mut(max_active * chunk_size))
+            .for_each(|((data_chunk, stm_chunk), nstm_chunk)| {
+                s.spawn(move || {
+                    for (i, pos) in data_chunk.iter().enumerate() {
+                        let mut j = 0;
+                        let sparse_offset = max_active * i;
+
+                        input_getter.map_group_features(group, pos, |our, opp| {
+                            assert!(our < input_size && opp < input_size, "Input feature index exceeded input size!");
+                            assert!(j < max_active, "More inputs provided than the specified maximum!");
+
+                            stm_chunk[sparse_offset + j] = our as i32;
+                            nstm_chunk[sparse_offset + j] = opp as i32;
+
+                            j += 1;
+                        });
+                    }
+                });
+            });
+    });
+
+    (stm, nstm)
+}
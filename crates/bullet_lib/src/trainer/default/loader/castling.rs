@@ -0,0 +1,76 @@
+use bulletformat::ChessBoard;
+
+use super::{CanBeDirectlySequentiallyLoaded, GameResult, LoadableDataType};
+
+const NO_ROOK: u8 = 64;
+
+/// A `ChessBoard` along with the castling rights of the position, stored as the squares of
+/// the rooks that can castle so that Chess960/DFRC positions are represented exactly.
+///
+/// Like `ChessBoard`, squares are relative to the side to move.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct CastlingBoard {
+    pub board: ChessBoard,
+    /// `[our queenside, our kingside, their queenside, their kingside]`, or 64 if unavailable.
+    rooks: [u8; 4],
+    _pad: [u8; 4],
+}
+
+impl CastlingBoard {
+    /// Takes the same white-relative arguments as `ChessBoard::from_raw`, along with the squares
+    /// of the castling rooks as `[white queenside, white kingside, black queenside, black kingside]`.
+    pub fn from_raw(
+        bbs: [u64; 4],
+        stm: usize,
+        score: i16,
+        result: f32,
+        rooks: [Option<u8>; 4],
+    ) -> Result<Self, String> {
+        let board = ChessBoard::from_raw(bbs, stm, score, result)?;
+
+        if rooks.iter().flatten().any(|&sq| sq >= 64) {
+            return Err("Invalid castling rook square!".to_string());
+        }
+
+        let flip = if stm == 1 { 56 } else { 0 };
+        let relative = |sq: Option<u8>| sq.map_or(NO_ROOK, |sq| sq ^ flip);
+
+        let rooks = if stm == 1 {
+            [relative(rooks[2]), relative(rooks[3]), relative(rooks[0]), relative(rooks[1])]
+        } else {
+            rooks.map(relative)
+        };
+
+        Ok(Self { board, rooks, _pad: [0; 4] })
+    }
+
+    /// A position with no castling rights.
+    pub fn from_board(board: ChessBoard) -> Self {
+        Self { board, rooks: [NO_ROOK; 4], _pad: [0; 4] }
+    }
+
+    /// Squares of the castling rooks relative to the side to move,
+    /// as `[our queenside, our kingside, their queenside, their kingside]`.
+    pub fn castling_rooks(&self) -> [Option<u8>; 4] {
+        self.rooks.map(|sq| (sq < 64).then_some(sq))
+    }
+}
+
+impl LoadableDataType for CastlingBoard {
+    fn score(&self) -> i16 {
+        LoadableDataType::score(&self.board)
+    }
+
+    fn result(&self) -> GameResult {
+        LoadableDataType::result(&self.board)
+    }
+}
+
+unsafe impl CanBeDirectlySequentiallyLoaded for CastlingBoard {
+    fn is_well_formed(&self) -> bool {
+        let occ = self.board.occ();
+
+        self.board.is_well_formed() && self.rooks.iter().all(|&sq| sq == NO_ROOK || (sq < 64 && occ & (1 << sq) > 0))
+    }
+}
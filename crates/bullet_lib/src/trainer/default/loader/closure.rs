@@ -0,0 +1,72 @@
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use super::DataLoader;
+
+/// Wraps a closure that produces data, for prototyping data sources without
+/// implementing `DataLoader` directly.
+///
+/// The closure is called with the requested batch size and may return
+/// any number of positions, which are regrouped into batches of the correct
+/// size. Returning `None` marks the end of a pass over the data, after which
+/// the closure is called again to start the next pass, in the same way that
+/// other loaders loop over their files. A pass that produces no positions at
+/// all is treated as an error, rather than looping forever.
+///
+/// The closure may be `FnMut`, and is shared between clones of the loader.
+pub struct FnDataLoader<T, F> {
+    func: Arc<Mutex<F>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, F> Clone for FnDataLoader<T, F> {
+    fn clone(&self) -> Self {
+        Self { func: self.func.clone(), _marker: PhantomData }
+    }
+}
+
+impl<T, F> FnDataLoader<T, F>
+where
+    F: FnMut(usize) -> Option<Vec<T>>,
+{
+    pub fn new(func: F) -> Self {
+        Self { func: Arc::new(Mutex::new(func)), _marker: PhantomData }
+    }
+}
+
+impl<T, F> DataLoader<T> for FnDataLoader<T, F>
+where
+    T: 'static,
+    F: FnMut(usize) -> Option<Vec<T>> + Send + 'static,
+{
+    fn data_file_paths(&self) -> &[String] {
+        &[]
+    }
+
+    fn map_batches<G: FnMut(&[T]) -> bool>(&self, _: usize, batch_size: usize, mut f: G) {
+        let mut buffer = Vec::with_capacity(batch_size);
+
+        loop {
+            let mut empty_pass = true;
+
+            while let Some(data) = (self.func.lock().unwrap())(batch_size) {
+                for pos in data {
+                    empty_pass = false;
+                    buffer.push(pos);
+
+                    if buffer.len() == batch_size {
+                        if f(&buffer) {
+                            return;
+                        }
+
+                        buffer.clear();
+                    }
+                }
+            }
+
+            assert!(!empty_pass, "FnDataLoader produced no positions in a pass over the data!");
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// Number of malformed records a loader will skip before giving up, by default.
+pub const DEFAULT_ERROR_BUDGET: u64 = 1024;
+
+/// Number of individual record locations kept for the final report.
+const LOCATIONS_TO_REPORT: usize = 16;
+
+#[derive(Default)]
+struct CorruptedRecordsState {
+    count: u64,
+    seen: HashSet<(String, u64)>,
+    locations: Vec<(String, u64)>,
+}
+
+/// Shared tally of malformed records that have been skipped by a data loader,
+/// so that a few bad bytes in a very large file do not end a training run.
+///
+/// Records are identified by file and offset, so a malformed record that is
+/// encountered again on a later pass through the data is only counted once.
+/// Once more than `budget` records have been skipped, the loader gives up.
+#[derive(Clone)]
+pub struct CorruptedRecords {
+    budget: u64,
+    state: Arc<Mutex<CorruptedRecordsState>>,
+}
+
+impl Default for CorruptedRecords {
+    fn default() -> Self {
+        Self::new(DEFAULT_ERROR_BUDGET)
+    }
+}
+
+impl CorruptedRecords {
+    pub fn new(budget: u64) -> Self {
+        Self { budget, state: Arc::new(Mutex::new(CorruptedRecordsState::default())) }
+    }
+
+    pub fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    pub fn count(&self) -> u64 {
+        self.state.lock().unwrap().count
+    }
+
+    /// Records that a malformed record was skipped. The `offset` is the byte offset
+    /// into the file for formats that support it, and the index of the record otherwise.
+    ///
+    /// Panics if the error budget has been exceeded.
+    pub fn record(&self, file: &str, offset: u64) {
+        let exceeded = {
+            let mut state = self.state.lock().unwrap();
+
+            if !state.seen.insert((file.to_string(), offset)) {
+                return;
+            }
+
+            state.count += 1;
+
+            if state.locations.len() < LOCATIONS_TO_REPORT {
+                state.locations.push((file.to_string(), offset));
+            }
+
+            state.count > self.budget
+        };
+
+        if exceeded {
+            self.report();
+            panic!("Exceeded budget of {} malformed records!", self.budget);
+        }
+    }
+
+    /// Prints a summary of all skipped records, if there were any.
+    pub fn report(&self) {
+        let state = self.state.lock().unwrap();
+
+        if state.count == 0 {
+            return;
+        }
+
+        println!();
+        println!("Skipped {} malformed record(s) (budget {}):", state.count, self.budget);
+
+        for (file, offset) in &state.locations {
+            println!("    [{file}] at offset {offset}");
+        }
+
+        if state.count as usize > state.locations.len() {
+            println!("    ... and {} more", state.count as usize - state.locations.len());
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+};
+
+use montyformat::{
+    chess::{Move, Position},
+    MontyValueFormat,
+};
+use sfbinpack::TrainingDataEntry;
+
+use crate::default::formats::bulletformat::ChessBoard;
+
+use super::{
+    CanBeDirectlySequentiallyLoaded, DataLoader, DirectSequentialDataLoader, MontyBinpackLoader, SfBinpackLoader,
+};
+
+type SfFilter = fn(&TrainingDataEntry) -> bool;
+type MontyFilter = fn(&Position, Move, i16, f32) -> bool;
+
+/// Formats of chess training data that can be identified from their contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataFormat {
+    BulletFormat,
+    SfBinpack,
+    MontyBinpack,
+}
+
+/// Number of leading records that must be valid for a file to be considered bulletformat.
+const BULLETFORMAT_RECORDS_CHECKED: usize = 64;
+
+/// Inspects the start of a file to determine its format.
+///
+/// Stockfish binpacks are identified by their `BINP` chunk header, bulletformat by all of
+/// the leading records being valid positions, and Monty binpacks by the first game
+/// deserialising successfully, in that order.
+pub fn detect_format(path: &str) -> io::Result<DataFormat> {
+    let mut header = Vec::new();
+    File::open(path)?.take((BULLETFORMAT_RECORDS_CHECKED * size_of::<ChessBoard>()) as u64).read_to_end(&mut header)?;
+
+    if header.starts_with(b"BINP") {
+        return Ok(DataFormat::SfBinpack);
+    }
+
+    let file_size = std::fs::metadata(path)?.len();
+    let record_size = size_of::<ChessBoard>();
+
+    if file_size > 0 && file_size % record_size as u64 == 0 {
+        let all_valid = header.chunks_exact(record_size).all(|record| {
+            // any bit pattern is a valid `ChessBoard`, so can be read straight from the bytes
+            let board = unsafe { std::ptr::read_unaligned(record.as_ptr().cast::<ChessBoard>()) };
+            board.is_well_formed()
+        });
+
+        if all_valid {
+            return Ok(DataFormat::BulletFormat);
+        }
+    }
+
+    let mut reader = BufReader::new(File::open(path)?);
+    if MontyValueFormat::deserialise_from(&mut reader, Vec::new()).is_ok() {
+        return Ok(DataFormat::MontyBinpack);
+    }
+
+    Err(io::Error::new(io::ErrorKind::InvalidData, format!("Could not detect the format of [{path}]!")))
+}
+
+/// A loader for any of the formats in `DataFormat`, produced by `detect_loader`.
+#[derive(Clone)]
+pub enum DetectedLoader {
+    BulletFormat(DirectSequentialDataLoader),
+    SfBinpack(SfBinpackLoader<SfFilter>),
+    MontyBinpack(MontyBinpackLoader<MontyFilter>),
+}
+
+/// Detects the format of a file with `detect_format` and constructs the matching loader,
+/// with no filtering of positions. Binpack loaders use a 1024MB shuffle buffer and
+/// 4 threads, construct them manually if different settings or filters are needed.
+pub fn detect_loader(path: &str) -> io::Result<DetectedLoader> {
+    let loader = match detect_format(path)? {
+        DataFormat::BulletFormat => DetectedLoader::BulletFormat(DirectSequentialDataLoader::new(&[path])),
+        DataFormat::SfBinpack => {
+            let filter: SfFilter = |_| true;
+            DetectedLoader::SfBinpack(SfBinpackLoader::new(path, 1024, 4, filter))
+        }
+        DataFormat::MontyBinpack => {
+            let filter: MontyFilter = |_, _, _, _| true;
+            DetectedLoader::MontyBinpack(MontyBinpackLoader::new(path, 1024, 4, filter))
+        }
+    };
+
+    Ok(loader)
+}
+
+impl DetectedLoader {
+    pub fn format(&self) -> DataFormat {
+        match self {
+            Self::BulletFormat(_) => DataFormat::BulletFormat,
+            Self::SfBinpack(_) => DataFormat::SfBinpack,
+            Self::MontyBinpack(_) => DataFormat::MontyBinpack,
+        }
+    }
+}
+
+impl DataLoader<ChessBoard> for DetectedLoader {
+    fn data_file_paths(&self) -> &[String] {
+        match self {
+            Self::BulletFormat(loader) => DataLoader::<ChessBoard>::data_file_paths(loader),
+            Self::SfBinpack(loader) => loader.data_file_paths(),
+            Self::MontyBinpack(loader) => loader.data_file_paths(),
+        }
+    }
+
+    fn count_positions(&self) -> Option<u64> {
+        match self {
+            Self::BulletFormat(loader) => DataLoader::<ChessBoard>::count_positions(loader),
+            Self::SfBinpack(loader) => loader.count_positions(),
+            Self::MontyBinpack(loader) => loader.count_positions(),
+        }
+    }
+
+    fn map_batches<F: FnMut(&[ChessBoard]) -> bool>(&self, start_batch: usize, batch_size: usize, f: F) {
+        match self {
+            Self::BulletFormat(loader) => loader.map_batches(start_batch, batch_size, f),
+            Self::SfBinpack(loader) => loader.map_batches(start_batch, batch_size, f),
+            Self::MontyBinpack(loader) => loader.map_batches(start_batch, batch_size, f),
+        }
+    }
+}
@@ -1,21 +1,32 @@
 use std::{
     fs::File,
-    io::{Read, Seek, SeekFrom},
+    io::{BufReader, Read, Seek, SeekFrom},
     mem::MaybeUninit,
     path::PathBuf,
     slice,
+    sync::mpsc,
 };
 
-use super::DataLoader;
+use super::{rng::SimpleRand, CorruptedRecords, DataLoader, DataShard};
 
 /// ### Safety
 /// This indicates that the type can be validly transmuted from
 /// *any* sequence of bytes of the same size as the struct.
-pub unsafe trait CanBeDirectlySequentiallyLoaded: Copy + 'static {}
+pub unsafe trait CanBeDirectlySequentiallyLoaded: Copy + Send + 'static {
+    /// Cheap sanity check on a loaded record, records that fail
+    /// this are skipped by the loader.
+    fn is_well_formed(&self) -> bool {
+        true
+    }
+}
 
 #[derive(Clone)]
 pub struct DirectSequentialDataLoader {
     file_paths: Vec<String>,
+    corrupted: CorruptedRecords,
+    concurrent_files: usize,
+    reshuffle: bool,
+    shard: DataShard,
 }
 
 impl DirectSequentialDataLoader {
@@ -27,7 +38,37 @@ impl DirectSequentialDataLoader {
             assert!(path_buf.exists(), "File not found: {path}");
         }
 
-        Self { file_paths }
+        Self {
+            file_paths,
+            corrupted: CorruptedRecords::default(),
+            concurrent_files: 1,
+            reshuffle: false,
+            shard: DataShard::default(),
+        }
+    }
+
+    /// Reads from up to `files` files concurrently, interleaving their records
+    /// round-robin, which improves IO parallelism and shuffles data across files.
+    pub fn with_concurrent_files(mut self, files: usize) -> Self {
+        assert!(files > 0, "Must read from at least one file at a time!");
+        self.concurrent_files = files;
+        self
+    }
+
+    /// Sets the maximum number of malformed records that will be skipped before panicking.
+    pub fn with_error_budget(mut self, budget: u64) -> Self {
+        self.corrupted = CorruptedRecords::new(budget);
+        self
+    }
+
+    /// After each full pass over the data, visit the files in a random order
+    /// and shuffle the records within each block that is read, so that the
+    /// network does not see the same sequence of batches every epoch.
+    ///
+    /// The first epoch is read in order, so data should still be shuffled beforehand.
+    pub fn with_epoch_reshuffle(mut self) -> Self {
+        self.reshuffle = true;
+        self
     }
 
     pub fn map_file_sizes<F: FnMut(&str, u64)>(&self, mut f: F) {
@@ -35,6 +76,23 @@ impl DirectSequentialDataLoader {
             f(file, std::fs::metadata(file).unwrap().len());
         }
     }
+
+    /// The range of bytes of `file` read by this loader's shard, each shard gets
+    /// a contiguous, disjoint range of whole records of size `data_size`.
+    fn shard_byte_range(&self, file: &str, data_size: u64) -> (u64, u64) {
+        let size = std::fs::metadata(file).unwrap().len();
+        let records = size / data_size;
+        let DataShard { rank, world_size } = self.shard;
+
+        let start = records * rank as u64 / world_size as u64 * data_size;
+        let end = if rank + 1 == world_size {
+            size
+        } else {
+            records * (rank + 1) as u64 / world_size as u64 * data_size
+        };
+
+        (start, end)
+    }
 }
 
 impl<T: CanBeDirectlySequentiallyLoaded> DataLoader<T> for DirectSequentialDataLoader {
@@ -45,20 +103,41 @@ impl<T: CanBeDirectlySequentiallyLoaded> DataLoader<T> for DirectSequentialDataL
     fn count_positions(&self) -> Option<u64> {
         let data_size = std::mem::size_of::<T>() as u64;
 
-        let mut file_size = 0;
+        let mut positions = 0;
 
         self.map_file_sizes(|file, this_size| {
             if this_size % data_size != 0 {
-                panic!("File [{file}] does not have a multiple of {data_size} size!");
+                println!("Warning: File [{file}] does not have a multiple of {data_size} size!");
             }
 
-            file_size += this_size;
+            let (start, end) = self.shard_byte_range(file, data_size);
+            positions += (end - start) / data_size;
         });
 
-        Some(file_size / data_size)
+        Some(positions)
+    }
+
+    fn count_positions_sharded(&self, shard: DataShard) -> Option<u64> {
+        DataLoader::<T>::count_positions(&Self { shard, ..self.clone() })
+    }
+
+    /// Each shard reads only its own range of records from each file.
+    fn map_batches_sharded<F: FnMut(&[T]) -> bool>(
+        &self,
+        shard: DataShard,
+        start_batch: usize,
+        batch_size: usize,
+        f: F,
+    ) {
+        Self { shard, ..self.clone() }.map_batches(start_batch, batch_size, f);
     }
 
     fn map_batches<F: FnMut(&[T]) -> bool>(&self, start_batch: usize, batch_size: usize, mut f: F) {
+        if self.concurrent_files > 1 && self.file_paths.len() > 1 {
+            self.map_batches_interleaved(start_batch, batch_size, f);
+            return;
+        }
+
         let buffer_size_mb = 256;
         let buffer_size = buffer_size_mb * 1024 * 1024;
         let data_size = size_of::<T>();
@@ -68,15 +147,18 @@ impl<T: CanBeDirectlySequentiallyLoaded> DataLoader<T> for DirectSequentialDataL
         let data_size = std::mem::size_of::<T>() as u64;
 
         let mut batches_per_epoch = 0;
-        self.map_file_sizes(|_, this_size| batches_per_epoch += (this_size / data_size).div_ceil(batch_size as u64));
+        self.map_file_sizes(|file, _| {
+            let (start, end) = self.shard_byte_range(file, data_size);
+            batches_per_epoch += ((end - start) / data_size).div_ceil(batch_size as u64);
+        });
 
         let start_point = start_batch % batches_per_epoch as usize;
 
         let mut start_file_idx = 0;
         let mut net_batches = 0;
         for file in self.file_paths.iter() {
-            let this_size = std::fs::metadata(file).unwrap().len();
-            let this_batches = (this_size / data_size).div_ceil(batch_size as u64);
+            let (start, end) = self.shard_byte_range(file, data_size);
+            let this_batches = ((end - start) / data_size).div_ceil(batch_size as u64);
 
             net_batches += this_batches;
 
@@ -94,36 +176,75 @@ impl<T: CanBeDirectlySequentiallyLoaded> DataLoader<T> for DirectSequentialDataL
         let mut to_skip = (start_point - net_batches as usize) * batch_size;
 
         let mut buf = unsafe { zeroed_boxed_slice::<T>(cap) };
+        let mut epoch = 0;
 
         'dataloading: loop {
+            let reshuffle = self.reshuffle && epoch > 0;
+
+            if reshuffle {
+                shuffle(&mut file_paths);
+            }
+
             let mut loader_files = vec![];
             for file in file_paths.iter() {
                 loader_files.push(File::open(file).unwrap());
             }
 
             for (mut loader_file, file_path) in loader_files.into_iter().zip(file_paths.iter()) {
+                let (start, end) = self.shard_byte_range(file_path, data_size);
+                let mut file_offset = start;
+
                 if to_skip > 0 {
                     println!("Skipping to {to_skip}th entry in file [{file_path}]");
-                    loader_file.seek(SeekFrom::Current((to_skip * data_size as usize) as i64)).unwrap();
+                    file_offset += (to_skip * data_size as usize) as u64;
                     to_skip = 0;
                 }
 
+                loader_file.seek(SeekFrom::Start(file_offset)).unwrap();
+                let mut loader_file = loader_file.take(end - file_offset);
+
                 loop {
-                    let count = loader_file
-                        .read(
-                            // we can cast the type `T` to an array of bytes
-                            unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr().cast(), cap * size_of::<T>()) },
-                        )
-                        .unwrap_or(0);
+                    // we can cast the type `T` to an array of bytes
+                    let bytes =
+                        unsafe { std::slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), cap * size_of::<T>()) };
+
+                    let mut count = loader_file.read(bytes).unwrap_or(0);
 
                     if count == 0 {
                         break;
                     }
 
-                    assert_eq!(count % size_of::<T>(), 0);
+                    let partial = count % size_of::<T>();
+                    if partial > 0 {
+                        let needed = size_of::<T>() - partial;
+
+                        if loader_file.read_exact(&mut bytes[count..count + needed]).is_ok() {
+                            count += needed;
+                        } else {
+                            count -= partial;
+                            self.corrupted.record(file_path, file_offset + count as u64);
+                        }
+                    }
+
                     let len = count / size_of::<T>();
 
-                    for batch in buf[..len].chunks(batch_size) {
+                    let mut valid = 0;
+                    for i in 0..len {
+                        if buf[i].is_well_formed() {
+                            buf[valid] = buf[i];
+                            valid += 1;
+                        } else {
+                            self.corrupted.record(file_path, file_offset + (i * size_of::<T>()) as u64);
+                        }
+                    }
+
+                    file_offset += count as u64;
+
+                    if reshuffle {
+                        shuffle(&mut buf[..valid]);
+                    }
+
+                    for batch in buf[..valid].chunks(batch_size) {
                         let should_break = f(batch);
 
                         if should_break {
@@ -132,11 +253,155 @@ impl<T: CanBeDirectlySequentiallyLoaded> DataLoader<T> for DirectSequentialDataL
                     }
                 }
             }
+
+            epoch += 1;
         }
+
+        self.corrupted.report();
+    }
+}
+
+impl DirectSequentialDataLoader {
+    fn map_batches_interleaved<T, F>(&self, start_batch: usize, batch_size: usize, mut f: F)
+    where
+        T: CanBeDirectlySequentiallyLoaded,
+        F: FnMut(&[T]) -> bool,
+    {
+        const CHUNK_SIZE: usize = 16384;
+
+        let data_size = size_of::<T>() as u64;
+        let mut positions = 0;
+        self.map_file_sizes(|file, _| {
+            let (start, end) = self.shard_byte_range(file, data_size);
+            positions += (end - start) / data_size;
+        });
+
+        let mut to_skip = (start_batch * batch_size) as u64 % positions.max(1);
+
+        if to_skip > 0 {
+            println!("Skipping {to_skip} interleaved entries");
+        }
+
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut file_paths = self.file_paths.clone();
+        let mut epoch = 0;
+
+        'dataloading: loop {
+            let reshuffle = self.reshuffle && epoch > 0;
+
+            if reshuffle {
+                shuffle(&mut file_paths);
+            }
+
+            for group in file_paths.chunks(self.concurrent_files) {
+                let mut receivers = Vec::new();
+
+                for file_path in group {
+                    let (sender, receiver) = mpsc::sync_channel::<Vec<T>>(2);
+                    let (start, end) = self.shard_byte_range(file_path, data_size);
+                    let file_path = file_path.clone();
+                    let corrupted = self.corrupted.clone();
+
+                    std::thread::spawn(move || {
+                        let mut file = File::open(&file_path).unwrap();
+                        file.seek(SeekFrom::Start(start)).unwrap();
+                        let mut file = BufReader::new(file.take(end - start));
+                        let mut buf = unsafe { zeroed_boxed_slice::<T>(1) };
+                        let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+                        let mut offset = start;
+
+                        loop {
+                            // we can cast the type `T` to an array of bytes
+                            let bytes = unsafe {
+                                std::slice::from_raw_parts_mut(buf.as_mut_ptr().cast::<u8>(), size_of::<T>())
+                            };
+
+                            let finished = file.read_exact(bytes).is_err();
+
+                            if !finished {
+                                if buf[0].is_well_formed() {
+                                    chunk.push(buf[0]);
+                                } else {
+                                    corrupted.record(&file_path, offset);
+                                }
+
+                                offset += size_of::<T>() as u64;
+                            }
+
+                            if chunk.len() == CHUNK_SIZE || (finished && !chunk.is_empty()) {
+                                if reshuffle {
+                                    shuffle(&mut chunk);
+                                }
+
+                                if sender.send(chunk).is_err() {
+                                    return;
+                                }
+
+                                chunk = Vec::with_capacity(CHUNK_SIZE);
+                            }
+
+                            if finished {
+                                return;
+                            }
+                        }
+                    });
+
+                    receivers.push((receiver, Vec::new().into_iter()));
+                }
+
+                while !receivers.is_empty() {
+                    let mut i = 0;
+
+                    while i < receivers.len() {
+                        let (receiver, current) = &mut receivers[i];
+
+                        let next = current.next().or_else(|| {
+                            *current = receiver.recv().ok()?.into_iter();
+                            current.next()
+                        });
+
+                        let Some(pos) = next else {
+                            receivers.swap_remove(i);
+                            continue;
+                        };
+
+                        i += 1;
+
+                        if to_skip > 0 {
+                            to_skip -= 1;
+                            continue;
+                        }
+
+                        batch.push(pos);
+
+                        if batch.len() == batch_size {
+                            if f(&batch) {
+                                break 'dataloading;
+                            }
+
+                            batch.clear();
+                        }
+                    }
+                }
+            }
+
+            epoch += 1;
+        }
+
+        self.corrupted.report();
+    }
+}
+
+fn shuffle<T>(data: &mut [T]) {
+    let mut rng = SimpleRand::with_seed();
+
+    for i in (0..data.len()).rev() {
+        let idx = rng.rng() as usize % (i + 1);
+        data.swap(idx, i);
     }
 }
 
-unsafe fn zeroed_boxed_slice<T: CanBeDirectlySequentiallyLoaded>(cap: usize) -> Box<[T]> {
+pub(super) unsafe fn zeroed_boxed_slice<T: CanBeDirectlySequentiallyLoaded>(cap: usize) -> Box<[T]> {
     let mut buf = Box::<[T]>::new_uninit_slice(cap);
 
     // safe as `T` can be any bit pattern, including 0s
@@ -0,0 +1,253 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, SyncSender},
+    time::Instant,
+};
+
+use super::{rng::SimpleRand, CanBeDirectlySequentiallyLoaded, DataLoader};
+
+type SourceFn<T> = Box<dyn FnOnce(u64, SyncSender<Vec<T>>) + Send>;
+
+struct Source<T> {
+    weight: f64,
+    run: SourceFn<T>,
+}
+
+/// Builds a single shuffled data file from several data loaders, for offline data preparation.
+///
+/// Each source is filtered and contributes a share of the output positions proportional to its
+/// weight. Positions from all sources are interleaved at random, and shuffled using an external
+/// shuffle with bounded memory, spilling to temporary files if the output does not fit in memory.
+///
+/// Most loaders loop over their data indefinitely, so the size of the output must be given.
+/// If a source runs out of data before providing its share, the output is correspondingly smaller.
+pub struct DatasetMixer<T> {
+    output: String,
+    positions: u64,
+    memory_mb: usize,
+    tmp_dir: String,
+    sources: Vec<Source<T>>,
+}
+
+impl<T: CanBeDirectlySequentiallyLoaded> DatasetMixer<T> {
+    pub fn new(output: &str, positions: u64) -> Self {
+        Self {
+            output: output.to_string(),
+            positions,
+            memory_mb: 1024,
+            tmp_dir: "./tmp".to_string(),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Sets the memory used for shuffling, defaults to 1024MB.
+    pub fn with_memory_mb(mut self, memory_mb: usize) -> Self {
+        assert!(memory_mb > 0, "Must use some memory for shuffling!");
+        self.memory_mb = memory_mb;
+        self
+    }
+
+    /// Sets the directory used for temporary files, defaults to `./tmp`.
+    pub fn with_tmp_dir(mut self, tmp_dir: &str) -> Self {
+        self.tmp_dir = tmp_dir.to_string();
+        self
+    }
+
+    /// Adds a source of positions, of which only those that pass `filter` are used.
+    pub fn add_source<D, F>(mut self, loader: D, weight: f64, filter: F) -> Self
+    where
+        D: DataLoader<T>,
+        F: Fn(&T) -> bool + Send + 'static,
+    {
+        assert!(weight > 0.0, "Source weights must be positive!");
+
+        let run = move |quota: u64, sender: SyncSender<Vec<T>>| {
+            let mut sent = 0;
+
+            loader.map_batches(0, 16384, |batch| {
+                let filtered =
+                    batch.iter().filter(|pos| filter(pos)).take((quota - sent) as usize).copied().collect::<Vec<_>>();
+
+                sent += filtered.len() as u64;
+
+                sender.send(filtered).is_err() || sent == quota
+            });
+        };
+
+        self.sources.push(Source { weight, run: Box::new(run) });
+        self
+    }
+
+    pub fn run(mut self) -> io::Result<()> {
+        let sources = std::mem::take(&mut self.sources);
+        assert!(!sources.is_empty(), "No sources to mix!");
+
+        let timer = Instant::now();
+        let total_weight = sources.iter().map(|source| source.weight).sum::<f64>();
+
+        let mut quotas = sources
+            .iter()
+            .map(|source| (self.positions as f64 * source.weight / total_weight) as u64)
+            .collect::<Vec<_>>();
+
+        // give any rounding error to the first source
+        quotas[0] += self.positions - quotas.iter().sum::<u64>();
+
+        let mut streams = Vec::new();
+
+        for (source, &quota) in sources.into_iter().zip(quotas.iter()) {
+            let (sender, receiver) = mpsc::sync_channel(4);
+            let run = source.run;
+            std::thread::spawn(move || run(quota, sender));
+            streams.push(Stream { remaining: quota, receiver, current: Vec::new().into_iter() });
+        }
+
+        let chunk_size = (self.memory_mb * 1024 * 1024 / size_of::<T>()).max(1);
+        let mut chunk = Vec::with_capacity(chunk_size.min(self.positions as usize));
+        let mut parts = Vec::new();
+        let mut rng = SimpleRand::with_seed();
+        let mut written = 0;
+
+        loop {
+            let remaining = streams.iter().map(|stream| stream.remaining).sum::<u64>();
+
+            if remaining == 0 {
+                break;
+            }
+
+            // picking streams in proportion to their remaining positions
+            // means that the sources are spread evenly through the output
+            let mut spot = rng.rng() % remaining;
+            let mut idx = 0;
+            while spot >= streams[idx].remaining {
+                spot -= streams[idx].remaining;
+                idx += 1;
+            }
+
+            match streams[idx].next() {
+                Some(pos) => chunk.push(pos),
+                None => {
+                    println!("Source {idx} ran out of data with {} positions remaining", streams[idx].remaining);
+                    streams[idx].remaining = 0;
+                    continue;
+                }
+            }
+
+            written += 1;
+
+            if chunk.len() == chunk_size {
+                shuffle(&mut chunk);
+                parts.push(self.write_part(parts.len(), &chunk)?);
+                chunk.clear();
+            }
+
+            if written % (1 << 20) == 0 {
+                print!("Mixed {written} / {} positions\r", self.positions);
+                let _ = io::stdout().flush();
+            }
+        }
+
+        println!();
+
+        shuffle(&mut chunk);
+
+        if parts.is_empty() {
+            write_records(&mut BufWriter::new(File::create(&self.output)?), &chunk)?;
+        } else {
+            if !chunk.is_empty() {
+                parts.push(self.write_part(parts.len(), &chunk)?);
+            }
+
+            drop(chunk);
+            interleave_parts::<T>(&parts, &self.output, &mut rng)?;
+
+            for (path, _) in &parts {
+                fs::remove_file(path)?;
+            }
+        }
+
+        println!("Wrote {written} positions to {} in {:.2} seconds", self.output, timer.elapsed().as_secs_f32());
+
+        Ok(())
+    }
+
+    fn write_part(&self, idx: usize, records: &[T]) -> io::Result<(PathBuf, u64)> {
+        fs::create_dir_all(&self.tmp_dir)?;
+        let path = PathBuf::from(format!("{}/mix_part_{idx}.bin", self.tmp_dir));
+        write_records(&mut BufWriter::new(File::create(&path)?), records)?;
+        Ok((path, records.len() as u64))
+    }
+}
+
+struct Stream<T> {
+    remaining: u64,
+    receiver: Receiver<Vec<T>>,
+    current: std::vec::IntoIter<T>,
+}
+
+impl<T> Stream<T> {
+    fn next(&mut self) -> Option<T> {
+        let pos = self.current.next().or_else(|| {
+            // sources may send empty batches if everything is filtered out
+            loop {
+                self.current = self.receiver.recv().ok()?.into_iter();
+
+                if let Some(pos) = self.current.next() {
+                    return Some(pos);
+                }
+            }
+        })?;
+
+        self.remaining -= 1;
+        Some(pos)
+    }
+}
+
+fn write_records<T: CanBeDirectlySequentiallyLoaded>(writer: &mut impl Write, records: &[T]) -> io::Result<()> {
+    // `T` can be cast to an array of bytes
+    let bytes = unsafe { std::slice::from_raw_parts(records.as_ptr().cast::<u8>(), std::mem::size_of_val(records)) };
+    writer.write_all(bytes)?;
+    writer.flush()
+}
+
+/// Randomly interleaves shuffled files, which produces a uniform shuffle of their contents.
+fn interleave_parts<T>(parts: &[(PathBuf, u64)], output: &str, rng: &mut SimpleRand) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(output)?);
+    let mut streams = Vec::new();
+
+    for (path, count) in parts {
+        streams.push((*count, BufReader::new(File::open(path)?)));
+    }
+
+    let mut remaining = streams.iter().map(|(count, _)| count).sum::<u64>();
+    let mut record = vec![0; size_of::<T>()];
+
+    while remaining > 0 {
+        let mut spot = rng.rng() % remaining;
+        let mut idx = 0;
+        while spot >= streams[idx].0 {
+            spot -= streams[idx].0;
+            idx += 1;
+        }
+
+        let (count, reader) = &mut streams[idx];
+        reader.read_exact(&mut record)?;
+        writer.write_all(&record)?;
+
+        *count -= 1;
+        remaining -= 1;
+    }
+
+    writer.flush()
+}
+
+fn shuffle<T>(data: &mut [T]) {
+    let mut rng = SimpleRand::with_seed();
+
+    for i in (0..data.len()).rev() {
+        let idx = rng.rng() as usize % (i + 1);
+        data.swap(idx, i);
+    }
+}
@@ -1,12 +1,12 @@
 use std::{
     fs::File,
-    io::{BufReader, Cursor},
+    io::{BufReader, Cursor, Seek},
     sync::mpsc::{self, SyncSender},
 };
 
 use crate::default::{formats::bulletformat::ChessBoard, loader::DataLoader};
 
-use super::rng::SimpleRand;
+use super::{rng::SimpleRand, CorruptedRecords, GameSampling};
 
 use montyformat::{
     chess::{Move, Position},
@@ -19,6 +19,8 @@ pub struct MontyBinpackLoader<T: Fn(&Position, Move, i16, f32) -> bool> {
     buffer_size: usize,
     threads: usize,
     filter: T,
+    corrupted: CorruptedRecords,
+    sampling: Option<GameSampling>,
 }
 
 impl<T: Fn(&Position, Move, i16, f32) -> bool> MontyBinpackLoader<T> {
@@ -28,8 +30,22 @@ impl<T: Fn(&Position, Move, i16, f32) -> bool> MontyBinpackLoader<T> {
             buffer_size: buffer_size_mb * 1024 * 1024 / std::mem::size_of::<ChessBoard>() / 2,
             threads,
             filter,
+            corrupted: CorruptedRecords::default(),
+            sampling: None,
         }
     }
+
+    /// Sets the maximum number of malformed records that will be skipped before panicking.
+    pub fn with_error_budget(mut self, budget: u64) -> Self {
+        self.corrupted = CorruptedRecords::new(budget);
+        self
+    }
+
+    /// Limits the number of positions taken from each game (after filtering).
+    pub fn with_game_sampling(mut self, sampling: GameSampling) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
 }
 
 impl<T> DataLoader<ChessBoard> for MontyBinpackLoader<T>
@@ -51,18 +67,23 @@ where
         let file_path = self.file_path[0].clone();
         let buffer_size = self.buffer_size;
 
-        let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(256);
+        let corrupted = self.corrupted.clone();
+
+        let (sender, receiver) = mpsc::sync_channel::<(u64, Vec<u8>)>(256);
         let (msg_sender, msg_receiver) = mpsc::sync_channel::<bool>(1);
 
+        let reader_file_path = file_path.clone();
         std::thread::spawn(move || 'dataloading: loop {
-            let mut reader = BufReader::new(File::open(file_path.as_str()).unwrap());
+            let mut reader = BufReader::new(File::open(reader_file_path.as_str()).unwrap());
 
             let mut buffer = Vec::new();
+            let mut offset = 0;
             while let Ok(()) = MontyValueFormat::deserialise_fast_into_buffer(&mut reader, &mut buffer) {
-                if msg_receiver.try_recv().unwrap_or(false) || sender.send(buffer).is_err() {
+                if msg_receiver.try_recv().unwrap_or(false) || sender.send((offset, buffer)).is_err() {
                     break 'dataloading;
                 }
 
+                offset = reader.stream_position().unwrap();
                 buffer = Vec::new();
             }
         });
@@ -72,6 +93,8 @@ where
 
         let threads = self.threads;
         let filter = self.filter.clone();
+        let converter_corrupted = corrupted.clone();
+        let sampling = self.sampling;
 
         std::thread::spawn(move || {
             let mut reusable = Vec::new();
@@ -84,7 +107,15 @@ where
                 reusable.push(game_bytes);
 
                 if reusable.len() % (8192 * threads) == 0 {
-                    convert_buffer(threads, &game_sender, &reusable, &filter);
+                    convert_buffer(
+                        threads,
+                        &game_sender,
+                        &reusable,
+                        &filter,
+                        sampling,
+                        &file_path,
+                        &converter_corrupted,
+                    );
                     reusable.clear();
                 }
             }
@@ -134,14 +165,19 @@ where
         }
 
         drop(buffer_receiver);
+
+        corrupted.report();
     }
 }
 
 fn convert_buffer<T: Fn(&Position, Move, i16, f32) -> bool + Send + Sync>(
     threads: usize,
     sender: &SyncSender<Vec<ChessBoard>>,
-    games: &[Vec<u8>],
+    games: &[(u64, Vec<u8>)],
     filter: &T,
+    sampling: Option<GameSampling>,
+    file_path: &str,
+    corrupted: &CorruptedRecords,
 ) {
     let chunk_size = games.len().div_ceil(threads);
 
@@ -150,9 +186,12 @@ fn convert_buffer<T: Fn(&Position, Move, i16, f32) -> bool + Send + Sync>(
             let this_sender = sender.clone();
             s.spawn(move || {
                 let mut buffer = Vec::new();
+                let mut rng = SimpleRand::with_seed();
 
-                for game_bytes in chunk {
-                    parse_into_buffer(game_bytes, &mut buffer, filter);
+                for (offset, game_bytes) in chunk {
+                    if parse_into_buffer(game_bytes, &mut buffer, filter, sampling, &mut rng).is_err() {
+                        corrupted.record(file_path, *offset);
+                    }
                 }
 
                 this_sender.send(buffer)
@@ -165,20 +204,38 @@ fn parse_into_buffer<T: Fn(&Position, Move, i16, f32) -> bool>(
     game_bytes: &[u8],
     buffer: &mut Vec<ChessBoard>,
     filter: &T,
-) {
+    sampling: Option<GameSampling>,
+    rng: &mut SimpleRand,
+) -> Result<(), ()> {
     let mut reader = Cursor::new(game_bytes);
-    let game = MontyValueFormat::deserialise_from(&mut reader, Vec::new()).unwrap();
+    let game = MontyValueFormat::deserialise_from(&mut reader, Vec::new()).map_err(|_| ())?;
 
     let mut pos = game.startpos;
     let castling = game.castling;
+    let mut malformed = false;
+    let mut positions = Vec::new();
 
-    for data in game.moves {
+    for (ply, data) in game.moves.into_iter().enumerate() {
         if filter(&pos, data.best_move, data.score, game.result) {
-            buffer.push(ChessBoard::from_raw(pos.bbs(), pos.stm(), data.score, game.result).unwrap());
+            match ChessBoard::from_raw(pos.bbs(), pos.stm(), data.score, game.result) {
+                Ok(board) => positions.push((ply, board)),
+                Err(_) => malformed = true,
+            }
         }
 
         pos.make(data.best_move, &castling);
     }
+
+    match sampling {
+        Some(sampling) => sampling.sample_into(positions, buffer, rng),
+        None => buffer.extend(positions.into_iter().map(|(_, board)| board)),
+    }
+
+    if malformed {
+        Err(())
+    } else {
+        Ok(())
+    }
 }
 
 fn shuffle(data: &mut [ChessBoard]) {
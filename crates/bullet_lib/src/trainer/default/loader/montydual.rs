@@ -0,0 +1,357 @@
+use std::{
+    fs::File,
+    io::{BufReader, Cursor, Seek},
+    marker::PhantomData,
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Condvar, Mutex,
+    },
+};
+
+use crate::default::{formats::bulletformat::ChessBoard, loader::DataLoader};
+
+use super::{rng::SimpleRand, CorruptedRecords};
+
+use montyformat::{
+    chess::{Move, Position},
+    FastDeserialise, MontyFormat, SearchData,
+};
+
+type Senders<P> = (SyncSender<Vec<ChessBoard>>, SyncSender<Vec<P>>);
+
+const VALUE: usize = 0;
+const POLICY: usize = 1;
+
+#[derive(Default)]
+struct Activity {
+    loading: [bool; 2],
+    dropped: [bool; 2],
+}
+
+struct Shared<P, FV, FP> {
+    file_path: String,
+    threads: usize,
+    eval_scale: f32,
+    value_filter: FV,
+    policy_map: FP,
+    corrupted: CorruptedRecords,
+    senders: Mutex<Option<Senders<P>>>,
+    activity: Mutex<Activity>,
+    changed: Condvar,
+}
+
+impl<P, FV, FP> Shared<P, FV, FP> {
+    fn update(&self, f: impl FnOnce(&mut Activity)) {
+        f(&mut self.activity.lock().unwrap());
+        self.changed.notify_all();
+    }
+
+    /// Blocks until at least one half is loading, returning false once both halves have been dropped.
+    fn wait_for_loading(&self) -> bool {
+        let mut activity = self.activity.lock().unwrap();
+
+        loop {
+            if activity.dropped == [true; 2] {
+                return false;
+            }
+
+            if activity.loading.contains(&true) {
+                return true;
+            }
+
+            activity = self.changed.wait(activity).unwrap();
+        }
+    }
+}
+
+/// Reads a Monty policy binpack once and feeds two trainers at the same time, a value
+/// net trained on `ChessBoard`s and a policy net trained on a user-defined data type,
+/// so that decompression and parsing are shared between them.
+///
+/// Use `split` to get the two halves, which are each passed to their own trainer.
+/// Both trainers should be run concurrently (e.g. on separate threads), as the shared
+/// reader only advances as fast as the slower of the two consumes data, and data for a
+/// half that is not currently being loaded from is discarded. Each half can be loaded
+/// from any number of times, e.g. for several training runs.
+pub struct MontyDualLoader<P, FV, FP> {
+    file_path: String,
+    buffer_size: usize,
+    threads: usize,
+    eval_scale: f32,
+    value_filter: FV,
+    policy_map: FP,
+    corrupted: CorruptedRecords,
+    _marker: PhantomData<fn() -> P>,
+}
+
+impl<P, FV, FP> MontyDualLoader<P, FV, FP>
+where
+    P: Send + 'static,
+    FV: Fn(&Position, Move, i16, f32) -> bool + Send + Sync + 'static,
+    FP: Fn(&Position, &SearchData, f32) -> Option<P> + Send + Sync + 'static,
+{
+    /// `value_filter` decides which positions are used for the value net, and `policy_map`
+    /// converts a position and its search data (including the visit distribution) into
+    /// policy training data, returning `None` to skip it.
+    ///
+    /// Search scores are stored as win rates, so are converted to centipawns for the value
+    /// net (and `value_filter`) with the sigmoid scale `eval_scale`, e.g. 400.
+    pub fn new(
+        path: &str,
+        buffer_size_mb: usize,
+        threads: usize,
+        eval_scale: f32,
+        value_filter: FV,
+        policy_map: FP,
+    ) -> Self {
+        assert!(eval_scale > 0.0, "Eval scale must be positive!");
+
+        Self {
+            file_path: path.to_string(),
+            buffer_size: buffer_size_mb * 1024 * 1024 / std::mem::size_of::<ChessBoard>() / 2,
+            threads,
+            eval_scale,
+            value_filter,
+            policy_map,
+            corrupted: CorruptedRecords::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the maximum number of malformed records that will be skipped before panicking.
+    pub fn with_error_budget(mut self, budget: u64) -> Self {
+        self.corrupted = CorruptedRecords::new(budget);
+        self
+    }
+
+    pub fn split(self) -> (MontyDualHalf<ChessBoard, P, FV, FP>, MontyDualHalf<P, P, FV, FP>) {
+        let (value_sender, value_receiver) = mpsc::sync_channel(4 * self.threads);
+        let (policy_sender, policy_receiver) = mpsc::sync_channel(4 * self.threads);
+
+        let shared = Arc::new(Shared {
+            file_path: self.file_path.clone(),
+            threads: self.threads,
+            eval_scale: self.eval_scale,
+            value_filter: self.value_filter,
+            policy_map: self.policy_map,
+            corrupted: self.corrupted,
+            senders: Mutex::new(Some((value_sender, policy_sender))),
+            activity: Mutex::new(Activity::default()),
+            changed: Condvar::new(),
+        });
+
+        let value = MontyDualHalf {
+            file_path: [self.file_path.clone()],
+            buffer_size: self.buffer_size,
+            output: Arc::new(Output { index: VALUE, receiver: Mutex::new(value_receiver), shared: shared.clone() }),
+        };
+
+        let policy = MontyDualHalf {
+            file_path: [self.file_path],
+            buffer_size: self.buffer_size,
+            output: Arc::new(Output { index: POLICY, receiver: Mutex::new(policy_receiver), shared }),
+        };
+
+        (value, policy)
+    }
+}
+
+struct Output<T, P, FV, FP> {
+    index: usize,
+    receiver: Mutex<Receiver<Vec<T>>>,
+    shared: Arc<Shared<P, FV, FP>>,
+}
+
+impl<T, P, FV, FP> Drop for Output<T, P, FV, FP> {
+    fn drop(&mut self) {
+        let index = self.index;
+        self.shared.update(|activity| activity.dropped[index] = true);
+    }
+}
+
+/// One half of a `MontyDualLoader`, producing data of type `T`.
+pub struct MontyDualHalf<T, P, FV, FP> {
+    file_path: [String; 1],
+    buffer_size: usize,
+    output: Arc<Output<T, P, FV, FP>>,
+}
+
+impl<T, P, FV, FP> Clone for MontyDualHalf<T, P, FV, FP> {
+    fn clone(&self) -> Self {
+        Self { file_path: self.file_path.clone(), buffer_size: self.buffer_size, output: self.output.clone() }
+    }
+}
+
+impl<T, P, FV, FP> DataLoader<T> for MontyDualHalf<T, P, FV, FP>
+where
+    T: Send + 'static,
+    P: Send + 'static,
+    FV: Fn(&Position, Move, i16, f32) -> bool + Send + Sync + 'static,
+    FP: Fn(&Position, &SearchData, f32) -> Option<P> + Send + Sync + 'static,
+{
+    fn data_file_paths(&self) -> &[String] {
+        &self.file_path
+    }
+
+    fn count_positions(&self) -> Option<u64> {
+        None
+    }
+
+    fn map_batches<F: FnMut(&[T]) -> bool>(&self, _: usize, batch_size: usize, mut f: F) {
+        let Output { index, receiver, shared } = &*self.output;
+        let receiver = receiver.lock().unwrap();
+
+        shared.update(|activity| activity.loading[*index] = true);
+
+        if let Some(senders) = shared.senders.lock().unwrap().take() {
+            let shared = shared.clone();
+            std::thread::spawn(move || read_games(shared, senders));
+        }
+
+        let mut shuffle_buffer = Vec::with_capacity(self.buffer_size);
+
+        'dataloading: while let Ok(converted) = receiver.recv() {
+            for entry in converted {
+                shuffle_buffer.push(entry);
+
+                if shuffle_buffer.len() == self.buffer_size {
+                    shuffle(&mut shuffle_buffer);
+
+                    for batch in shuffle_buffer.chunks(batch_size) {
+                        if f(batch) {
+                            break 'dataloading;
+                        }
+                    }
+
+                    shuffle_buffer.clear();
+                }
+            }
+        }
+
+        shared.update(|activity| activity.loading[*index] = false);
+
+        // the reader may be blocked on a full channel, so make room for it to notice
+        while receiver.try_recv().is_ok() {}
+
+        shared.corrupted.report();
+    }
+}
+
+fn read_games<P, FV, FP>(shared: Arc<Shared<P, FV, FP>>, senders: Senders<P>)
+where
+    P: Send + 'static,
+    FV: Fn(&Position, Move, i16, f32) -> bool + Send + Sync + 'static,
+    FP: Fn(&Position, &SearchData, f32) -> Option<P> + Send + Sync + 'static,
+{
+    let (value_sender, policy_sender) = senders;
+
+    let chunk_size = 8192 * shared.threads;
+
+    'dataloading: loop {
+        let mut reader = BufReader::new(File::open(shared.file_path.as_str()).unwrap());
+
+        let mut games = Vec::with_capacity(chunk_size);
+        let mut buffer = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let finished = MontyFormat::deserialise_fast_into_buffer(&mut reader, &mut buffer).is_err();
+
+            if !finished {
+                games.push((offset, buffer));
+                buffer = Vec::new();
+                offset = reader.stream_position().unwrap();
+            }
+
+            if games.len() == chunk_size || (finished && !games.is_empty()) {
+                let per_thread = games.len().div_ceil(shared.threads);
+
+                std::thread::scope(|s| {
+                    let mut handles = Vec::new();
+
+                    for chunk in games.chunks(per_thread) {
+                        let shared = &shared;
+                        handles.push(s.spawn(move || convert_games(shared, chunk)));
+                    }
+
+                    for handle in handles {
+                        let (values, policies) = handle.join().unwrap();
+                        let loading = shared.activity.lock().unwrap().loading;
+
+                        if loading[VALUE] {
+                            let _ = value_sender.send(values);
+                        }
+
+                        if loading[POLICY] {
+                            let _ = policy_sender.send(policies);
+                        }
+                    }
+                });
+
+                games.clear();
+
+                if !shared.wait_for_loading() {
+                    break 'dataloading;
+                }
+            }
+
+            if finished {
+                break;
+            }
+        }
+    }
+}
+
+fn convert_games<P, FV, FP>(shared: &Shared<P, FV, FP>, games: &[(u64, Vec<u8>)]) -> (Vec<ChessBoard>, Vec<P>)
+where
+    FV: Fn(&Position, Move, i16, f32) -> bool,
+    FP: Fn(&Position, &SearchData, f32) -> Option<P>,
+{
+    let mut values = Vec::new();
+    let mut policies = Vec::new();
+
+    for (offset, game_bytes) in games {
+        let mut reader = Cursor::new(game_bytes);
+
+        let Ok(game) = MontyFormat::deserialise_from(&mut reader, Vec::new()) else {
+            shared.corrupted.record(&shared.file_path, *offset);
+            continue;
+        };
+
+        let mut pos = game.startpos;
+        let castling = game.castling;
+
+        for data in game.moves {
+            let score = win_rate_to_cp(data.score, shared.eval_scale);
+
+            if (shared.value_filter)(&pos, data.best_move, score, game.result) {
+                match ChessBoard::from_raw(pos.bbs(), pos.stm(), score, game.result) {
+                    Ok(board) => values.push(board),
+                    Err(_) => shared.corrupted.record(&shared.file_path, *offset),
+                }
+            }
+
+            if let Some(policy) = (shared.policy_map)(&pos, &data, game.result) {
+                policies.push(policy);
+            }
+
+            pos.make(data.best_move, &castling);
+        }
+    }
+
+    (values, policies)
+}
+
+fn win_rate_to_cp(win_rate: f32, eval_scale: f32) -> i16 {
+    let win_rate = win_rate.clamp(1e-6, 1.0 - 1e-6);
+    (eval_scale * (win_rate / (1.0 - win_rate)).ln()).clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16
+}
+
+fn shuffle<T>(data: &mut [T]) {
+    let mut rng = SimpleRand::with_seed();
+
+    for i in (0..data.len()).rev() {
+        let idx = rng.rng() as usize % (i + 1);
+        data.swap(idx, i);
+    }
+}
@@ -0,0 +1,71 @@
+use bulletformat::ChessBoard;
+
+use super::{CanBeDirectlySequentiallyLoaded, GameResult, LoadableDataType};
+
+/// A `ChessBoard` along with policy targets, as a weighting (e.g. search visits) of up to
+/// `PolicyBoard::MAX_MOVES` moves, for training a policy head (see `inputs::WithPolicy`).
+///
+/// Like `ChessBoard`, squares are relative to the side to move, so must be flipped with
+/// `sq ^ 56` if black is to move. Data with only a best move can use a single move with weight 1.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PolicyBoard {
+    pub board: ChessBoard,
+    /// Moves encoded as `from | to << 6`.
+    moves: [u16; 32],
+    /// Weight of each move, moves with zero weight are unused.
+    weights: [u16; 32],
+}
+
+impl PolicyBoard {
+    pub const MAX_MOVES: usize = 32;
+
+    /// Takes moves as `(from, to, weight)`.
+    pub fn new(board: ChessBoard, moves: &[(u8, u8, u16)]) -> Result<Self, String> {
+        if moves.len() > Self::MAX_MOVES {
+            return Err(format!("Cannot store more than {} moves!", Self::MAX_MOVES));
+        }
+
+        let mut pos = Self { board, moves: [0; 32], weights: [0; 32] };
+
+        for (i, &(from, to, weight)) in moves.iter().filter(|(_, _, weight)| *weight > 0).enumerate() {
+            if from >= 64 || to >= 64 {
+                return Err(format!("Invalid move {from} -> {to}!"));
+            }
+
+            pos.moves[i] = u16::from(from) | u16::from(to) << 6;
+            pos.weights[i] = weight;
+        }
+
+        if !pos.is_well_formed() {
+            return Err("Malformed position!".to_string());
+        }
+
+        Ok(pos)
+    }
+
+    /// Iterates over the moves as `(from, to, weight)`.
+    pub fn moves(&self) -> impl Iterator<Item = (u8, u8, u16)> + '_ {
+        self.moves
+            .iter()
+            .zip(self.weights.iter())
+            .take_while(|&(_, &weight)| weight > 0)
+            .map(|(&mv, &weight)| ((mv & 63) as u8, ((mv >> 6) & 63) as u8, weight))
+    }
+}
+
+impl LoadableDataType for PolicyBoard {
+    fn score(&self) -> i16 {
+        LoadableDataType::score(&self.board)
+    }
+
+    fn result(&self) -> GameResult {
+        LoadableDataType::result(&self.board)
+    }
+}
+
+unsafe impl CanBeDirectlySequentiallyLoaded for PolicyBoard {
+    fn is_well_formed(&self) -> bool {
+        self.board.is_well_formed() && self.weights[0] > 0
+    }
+}
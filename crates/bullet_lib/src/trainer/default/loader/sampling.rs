@@ -0,0 +1,60 @@
+use super::rng::SimpleRand;
+
+/// Limits how many positions are taken from each game, to reduce the
+/// correlation between positions in a batch.
+#[derive(Clone, Copy, Debug)]
+pub enum GameSampling {
+    /// Sample up to `positions` positions per game, uniformly at random.
+    Uniform { positions: usize },
+    /// Sample up to `positions` positions per game, with the position at
+    /// ply `p` weighted by `(p + 1)^power`, so a positive `power` favours
+    /// later positions and a negative `power` favours earlier positions.
+    PlyWeighted { positions: usize, power: f32 },
+}
+
+impl GameSampling {
+    fn positions(&self) -> usize {
+        match *self {
+            Self::Uniform { positions } | Self::PlyWeighted { positions, .. } => positions,
+        }
+    }
+
+    /// Samples from the positions of a single game, given alongside their ply.
+    pub(super) fn sample_into<T>(&self, mut game: Vec<(usize, T)>, out: &mut Vec<T>, rng: &mut SimpleRand) {
+        let positions = self.positions();
+
+        if game.len() > positions {
+            match *self {
+                Self::Uniform { .. } => {
+                    for i in 0..positions {
+                        let idx = i + rng.rng() as usize % (game.len() - i);
+                        game.swap(i, idx);
+                    }
+                }
+                Self::PlyWeighted { power, .. } => {
+                    // weighted sampling without replacement, by taking the
+                    // positions with the largest keys of u^(1 / weight)
+                    let mut keyed = game
+                        .into_iter()
+                        .map(|(ply, pos)| {
+                            let weight = (ply as f32 + 1.0).powf(power);
+                            (uniform(rng).ln() / weight, (ply, pos))
+                        })
+                        .collect::<Vec<_>>();
+
+                    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+                    game = keyed.into_iter().map(|(_, entry)| entry).collect();
+                }
+            }
+
+            game.truncate(positions);
+        }
+
+        out.extend(game.into_iter().map(|(_, pos)| pos));
+    }
+}
+
+/// Uniform in (0, 1].
+fn uniform(rng: &mut SimpleRand) -> f32 {
+    ((rng.rng() >> 40) + 1) as f32 / (1u64 << 24) as f32
+}
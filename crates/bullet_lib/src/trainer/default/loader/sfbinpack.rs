@@ -7,9 +7,9 @@ use sfbinpack::{
 
 use crate::default::{formats::bulletformat::ChessBoard, loader::DataLoader};
 
-use super::rng::SimpleRand;
+use super::{rng::SimpleRand, CorruptedRecords, GameSampling};
 
-fn convert_to_bulletformat(entry: &TrainingDataEntry) -> ChessBoard {
+fn convert_to_bulletformat(entry: &TrainingDataEntry) -> Option<ChessBoard> {
     let mut bbs = [0; 8];
 
     let stm = usize::from(entry.pos.side_to_move().ordinal());
@@ -33,7 +33,7 @@ fn convert_to_bulletformat(entry: &TrainingDataEntry) -> ChessBoard {
         result = 1.0 - result;
     }
 
-    ChessBoard::from_raw(bbs, stm, score, result).expect("Binpack must be malformed!")
+    ChessBoard::from_raw(bbs, stm, score, result).ok()
 }
 
 #[derive(Clone)]
@@ -42,6 +42,8 @@ pub struct SfBinpackLoader<T: Fn(&TrainingDataEntry) -> bool> {
     buffer_size: usize,
     threads: usize,
     filter: T,
+    corrupted: CorruptedRecords,
+    sampling: Option<GameSampling>,
 }
 
 impl<T: Fn(&TrainingDataEntry) -> bool> SfBinpackLoader<T> {
@@ -51,8 +53,27 @@ impl<T: Fn(&TrainingDataEntry) -> bool> SfBinpackLoader<T> {
             buffer_size: buffer_size_mb * 1024 * 1024 / std::mem::size_of::<ChessBoard>() / 2,
             threads,
             filter,
+            corrupted: CorruptedRecords::default(),
+            sampling: None,
         }
     }
+
+    /// Sets the maximum number of malformed records that will be skipped before panicking.
+    /// Locations of skipped records are reported as entry indices within the binpack.
+    pub fn with_error_budget(mut self, budget: u64) -> Self {
+        self.corrupted = CorruptedRecords::new(budget);
+        self
+    }
+
+    /// Limits the number of positions taken from each game (after filtering).
+    ///
+    /// Games are delimited by discontinuities in the ply of consecutive entries. The
+    /// binpack is converted in parallel chunks, so a game spanning two chunks will be
+    /// sampled as two separate games.
+    pub fn with_game_sampling(mut self, sampling: GameSampling) -> Self {
+        self.sampling = Some(sampling);
+        self
+    }
 }
 
 impl<T> DataLoader<ChessBoard> for SfBinpackLoader<T>
@@ -72,25 +93,34 @@ where
         let buffer_size = self.buffer_size;
         let threads = self.threads;
         let filter = self.filter.clone();
+        let corrupted = self.corrupted.clone();
+        let sampling = self.sampling;
 
         let reader_buffer_size = 16384 * threads;
-        let (reader_sender, reader_receiver) = mpsc::sync_channel::<Vec<TrainingDataEntry>>(8);
+        let (reader_sender, reader_receiver) = mpsc::sync_channel::<(u64, Vec<TrainingDataEntry>)>(8);
         let (reader_msg_sender, reader_msg_receiver) = mpsc::sync_channel::<bool>(1);
 
+        let reader_file_path = file_path.clone();
         std::thread::spawn(move || {
             let mut buffer = Vec::with_capacity(reader_buffer_size);
 
             'dataloading: loop {
-                let mut reader = CompressedTrainingDataEntryReader::new(&file_path).unwrap();
+                let mut reader = CompressedTrainingDataEntryReader::new(&reader_file_path).unwrap();
+                let mut index = 0;
 
                 while reader.has_next() {
                     buffer.push(reader.next());
 
                     if buffer.len() == reader_buffer_size || !reader.has_next() {
-                        if reader_msg_receiver.try_recv().unwrap_or(false) || reader_sender.send(buffer).is_err() {
+                        let len = buffer.len() as u64;
+
+                        if reader_msg_receiver.try_recv().unwrap_or(false)
+                            || reader_sender.send((index, buffer)).is_err()
+                        {
                             break 'dataloading;
                         }
 
+                        index += len;
                         buffer = Vec::with_capacity(reader_buffer_size);
                     }
                 }
@@ -100,10 +130,13 @@ where
         let (converted_sender, converted_receiver) = mpsc::sync_channel::<Vec<ChessBoard>>(4 * threads);
         let (converted_msg_sender, converted_msg_receiver) = mpsc::sync_channel::<bool>(1);
 
+        let converter_corrupted = corrupted.clone();
         std::thread::spawn(move || {
             let filter = &filter;
+            let corrupted = &converter_corrupted;
+            let file_path = &file_path;
             let mut should_break = false;
-            'dataloading: while let Ok(unfiltered) = reader_receiver.recv() {
+            'dataloading: while let Ok((start_index, unfiltered)) = reader_receiver.recv() {
                 if should_break || converted_msg_receiver.try_recv().unwrap_or(false) {
                     reader_msg_sender.send(true).unwrap();
                     break 'dataloading;
@@ -113,17 +146,38 @@ where
                     let chunk_size = unfiltered.len().div_ceil(threads);
                     let mut handles = Vec::new();
 
-                    for chunk in unfiltered.chunks(chunk_size) {
+                    for (chunk_idx, chunk) in unfiltered.chunks(chunk_size).enumerate() {
                         let this_sender = converted_sender.clone();
                         let handle = s.spawn(move || {
                             let mut buffer = Vec::with_capacity(chunk_size);
+                            let chunk_start = start_index + (chunk_idx * chunk_size) as u64;
+
+                            let mut rng = SimpleRand::with_seed();
+                            let mut game = Vec::new();
+                            let mut prev_ply = None;
+
+                            for (i, entry) in chunk.iter().enumerate() {
+                                if let Some(sampling) = sampling {
+                                    if prev_ply.map(|ply: u16| ply.wrapping_add(1)) != Some(entry.ply) {
+                                        sampling.sample_into(std::mem::take(&mut game), &mut buffer, &mut rng);
+                                    }
+
+                                    prev_ply = Some(entry.ply);
+                                }
 
-                            for entry in chunk {
                                 if filter(entry) {
-                                    buffer.push(convert_to_bulletformat(entry));
+                                    match convert_to_bulletformat(entry) {
+                                        Some(board) if sampling.is_some() => game.push((usize::from(entry.ply), board)),
+                                        Some(board) => buffer.push(board),
+                                        None => corrupted.record(file_path, chunk_start + i as u64),
+                                    }
                                 }
                             }
 
+                            if let Some(sampling) = sampling {
+                                sampling.sample_into(game, &mut buffer, &mut rng);
+                            }
+
                             this_sender.send(buffer).is_err()
                         });
 
@@ -192,6 +246,8 @@ where
         }
 
         drop(batch_reciever);
+
+        corrupted.report();
     }
 }
 
@@ -0,0 +1,55 @@
+use super::DataLoader;
+
+/// Identifies which disjoint slice of a data stream a device should see,
+/// when training is split across `world_size` devices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataShard {
+    pub rank: usize,
+    pub world_size: usize,
+}
+
+impl DataShard {
+    pub fn new(rank: usize, world_size: usize) -> Self {
+        assert!(world_size > 0, "World size must be positive!");
+        assert!(rank < world_size, "Rank {rank} out of range for world size {world_size}!");
+        Self { rank, world_size }
+    }
+
+    pub fn owns(&self, batch: usize) -> bool {
+        batch % self.world_size == self.rank
+    }
+}
+
+impl Default for DataShard {
+    fn default() -> Self {
+        Self::new(0, 1)
+    }
+}
+
+/// Wraps a data loader so that it only produces the batches belonging
+/// to a single shard, so that each device sees a disjoint stream of data.
+#[derive(Clone)]
+pub struct ShardedDataLoader<D> {
+    loader: D,
+    shard: DataShard,
+}
+
+impl<D> ShardedDataLoader<D> {
+    pub fn new(loader: D, shard: DataShard) -> Self {
+        Self { loader, shard }
+    }
+}
+
+impl<T, D: DataLoader<T>> DataLoader<T> for ShardedDataLoader<D> {
+    fn data_file_paths(&self) -> &[String] {
+        self.loader.data_file_paths()
+    }
+
+    fn count_positions(&self) -> Option<u64> {
+        self.loader.count_positions_sharded(self.shard)
+    }
+
+    fn map_batches<F: FnMut(&[T]) -> bool>(&self, start_batch: usize, batch_size: usize, f: F) {
+        self.loader.map_batches_sharded(self.shard, start_batch, batch_size, f);
+    }
+}
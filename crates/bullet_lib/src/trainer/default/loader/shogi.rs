@@ -0,0 +1,151 @@
+use super::{CanBeDirectlySequentiallyLoaded, GameResult, LoadableDataType};
+
+/// Marks the end of the piece list of a `ShogiBoard`.
+const NO_PIECE: u8 = 0xFF;
+
+/// Bit set on pieces belonging to the opponent.
+pub const SHOGI_OPPONENT: u8 = 16;
+
+/// Piece types, a piece is `type | SHOGI_OPPONENT` if it belongs to the opponent.
+pub mod shogi_piece {
+    pub const PAWN: u8 = 0;
+    pub const LANCE: u8 = 1;
+    pub const KNIGHT: u8 = 2;
+    pub const SILVER: u8 = 3;
+    pub const GOLD: u8 = 4;
+    pub const BISHOP: u8 = 5;
+    pub const ROOK: u8 = 6;
+    pub const KING: u8 = 7;
+    pub const PROMOTED_PAWN: u8 = 8;
+    pub const PROMOTED_LANCE: u8 = 9;
+    pub const PROMOTED_KNIGHT: u8 = 10;
+    pub const PROMOTED_SILVER: u8 = 11;
+    pub const HORSE: u8 = 12;
+    pub const DRAGON: u8 = 13;
+
+    pub const NUM_TYPES: u8 = 14;
+}
+
+/// Maximum number of each piece type (pawn, lance, knight, silver, gold, bishop, rook) that can be in hand.
+pub const SHOGI_MAX_HAND: [u8; 7] = [18, 4, 4, 4, 4, 2, 2];
+
+/// A shogi position, stored relative to the side to move, with the board rotated
+/// 180 degrees when it is gote to move so that the side to move always plays "up" the board.
+///
+/// Squares are numbered `0..81` as `9 * rank + file`, with rank 0 being the furthest from the side to move.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct ShogiBoard {
+    pieces: [u8; 40],
+    squares: [u8; 40],
+    /// Pieces in hand, ours then the opponent's, indexed by piece type.
+    hands: [[u8; 7]; 2],
+    score: i16,
+    result: u8,
+    _pad: [u8; 7],
+}
+
+impl ShogiBoard {
+    /// Builds a position from the point of view of sente.
+    ///
+    /// - `pieces` are `(piece, square)` pairs, with the `SHOGI_OPPONENT` bit set on gote's pieces
+    /// - `hands` are the pieces in hand of sente and gote respectively
+    /// - `stm` is 0 if sente is to move, 1 if gote is to move
+    /// - `score` is relative to the side to move
+    /// - `result` is 1.0 if sente won, 0.5 for a draw and 0.0 if gote won
+    pub fn from_raw(
+        pieces: &[(u8, u8)],
+        hands: [[u8; 7]; 2],
+        stm: usize,
+        score: i16,
+        result: f32,
+    ) -> Result<Self, String> {
+        if pieces.len() > 40 {
+            return Err("Too many pieces!".to_string());
+        }
+
+        let mut board =
+            Self { pieces: [NO_PIECE; 40], squares: [0; 40], hands, score, result: (2.0 * result) as u8, _pad: [0; 7] };
+
+        for (i, &(piece, square)) in pieces.iter().enumerate() {
+            if piece & !SHOGI_OPPONENT >= shogi_piece::NUM_TYPES || square >= 81 {
+                return Err(format!("Invalid piece {piece} on square {square}!"));
+            }
+
+            board.pieces[i] = piece;
+            board.squares[i] = square;
+        }
+
+        if stm == 1 {
+            for i in 0..pieces.len() {
+                board.pieces[i] ^= SHOGI_OPPONENT;
+                board.squares[i] = 80 - board.squares[i];
+            }
+
+            board.hands.swap(0, 1);
+            board.result = 2 - board.result;
+        }
+
+        if !board.is_well_formed() {
+            return Err("Malformed position!".to_string());
+        }
+
+        Ok(board)
+    }
+
+    /// Iterates over the `(piece, square)` pairs of pieces on the board.
+    pub fn pieces(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        self.pieces.iter().zip(self.squares.iter()).take_while(|&(&pc, _)| pc != NO_PIECE).map(|(&pc, &sq)| (pc, sq))
+    }
+
+    /// Pieces in hand of the side to move (`side = 0`) or the opponent (`side = 1`).
+    pub fn hand(&self, side: usize) -> [u8; 7] {
+        self.hands[side]
+    }
+
+    pub fn our_ksq(&self) -> u8 {
+        self.king_square(shogi_piece::KING)
+    }
+
+    /// Square of the opponent's king, from the opponent's perspective.
+    pub fn opp_ksq(&self) -> u8 {
+        80 - self.king_square(shogi_piece::KING | SHOGI_OPPONENT)
+    }
+
+    fn king_square(&self, king: u8) -> u8 {
+        self.pieces().find(|&(pc, _)| pc == king).map(|(_, sq)| sq).unwrap_or(0)
+    }
+}
+
+impl LoadableDataType for ShogiBoard {
+    fn score(&self) -> i16 {
+        self.score
+    }
+
+    fn result(&self) -> GameResult {
+        [GameResult::Loss, GameResult::Draw, GameResult::Win][usize::from(self.result.min(2))]
+    }
+}
+
+unsafe impl CanBeDirectlySequentiallyLoaded for ShogiBoard {
+    fn is_well_formed(&self) -> bool {
+        let mut kings = [0; 2];
+        let mut occ = [false; 81];
+
+        for (piece, square) in self.pieces() {
+            if piece & !SHOGI_OPPONENT >= shogi_piece::NUM_TYPES || square >= 81 || occ[usize::from(square)] {
+                return false;
+            }
+
+            occ[usize::from(square)] = true;
+
+            if piece & !SHOGI_OPPONENT == shogi_piece::KING {
+                kings[usize::from(piece & SHOGI_OPPONENT > 0)] += 1;
+            }
+        }
+
+        let hands_valid = self.hands.iter().all(|hand| hand.iter().zip(SHOGI_MAX_HAND).all(|(&n, max)| n <= max));
+
+        kings == [1, 1] && hands_valid && self.result <= 2
+    }
+}
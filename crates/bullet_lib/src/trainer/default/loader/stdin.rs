@@ -0,0 +1,83 @@
+use std::io::{ErrorKind, Read};
+
+use super::{direct::zeroed_boxed_slice, CanBeDirectlySequentiallyLoaded, CorruptedRecords, DataLoader};
+
+/// Reads records directly from stdin, so that data generation can be piped straight
+/// into the trainer, e.g. `datagen | trainer`.
+///
+/// Training ends early if stdin is closed before the final superbatch.
+#[derive(Clone)]
+pub struct StdinDataLoader {
+    file_path: [String; 1],
+    corrupted: CorruptedRecords,
+}
+
+impl Default for StdinDataLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StdinDataLoader {
+    pub fn new() -> Self {
+        Self { file_path: ["<stdin>".to_string()], corrupted: CorruptedRecords::default() }
+    }
+
+    /// Sets the maximum number of malformed records that will be skipped before panicking.
+    pub fn with_error_budget(mut self, budget: u64) -> Self {
+        self.corrupted = CorruptedRecords::new(budget);
+        self
+    }
+}
+
+impl<T: CanBeDirectlySequentiallyLoaded> DataLoader<T> for StdinDataLoader {
+    fn data_file_paths(&self) -> &[String] {
+        &self.file_path
+    }
+
+    fn count_positions(&self) -> Option<u64> {
+        None
+    }
+
+    fn map_batches<F: FnMut(&[T]) -> bool>(&self, _: usize, batch_size: usize, mut f: F) {
+        let mut stdin = std::io::stdin().lock();
+        let mut buf = unsafe { zeroed_boxed_slice::<T>(batch_size) };
+        let mut offset = 0;
+        let mut len = 0;
+
+        loop {
+            // we can cast the type `T` to an array of bytes
+            let bytes = unsafe { std::slice::from_raw_parts_mut(buf[len..].as_mut_ptr().cast::<u8>(), size_of::<T>()) };
+
+            match stdin.read_exact(bytes) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                    if len > 0 {
+                        f(&buf[..len]);
+                    }
+
+                    break;
+                }
+                Err(e) => panic!("Failed to read from stdin: {e}"),
+            }
+
+            if buf[len].is_well_formed() {
+                len += 1;
+            } else {
+                self.corrupted.record(&self.file_path[0], offset);
+            }
+
+            offset += size_of::<T>() as u64;
+
+            if len == batch_size {
+                if f(&buf) {
+                    break;
+                }
+
+                len = 0;
+            }
+        }
+
+        self.corrupted.report();
+    }
+}
@@ -0,0 +1,265 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::default::formats::bulletformat::ChessBoard;
+
+use super::{DataLoader, GameResult, LoadableDataType};
+
+/// Probes endgame tablebases for the exact result of a position.
+pub trait TablebaseProber: Clone + Send + Sync + 'static {
+    /// Largest number of pieces (including kings) that can be probed.
+    fn max_pieces(&self) -> u32;
+
+    /// Returns the result from the side to move's perspective, assuming
+    /// best play and a halfmove clock of zero, or `None` if probing fails.
+    fn probe(&self, pos: &ChessBoard) -> Option<GameResult>;
+}
+
+/// What to do with positions that are found in the tablebases.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TablebaseAction {
+    /// Replace the game result with the tablebase result.
+    Rescore,
+    /// Remove positions found in the tablebases from the data.
+    Discard,
+    /// Remove positions whose game result disagrees with the tablebase result.
+    DiscardMismatched,
+}
+
+/// Wraps a `ChessBoard` data loader, probing every position with at most
+/// `max_pieces` pieces and either substituting the exact tablebase result
+/// or filtering the position out, depending on the `TablebaseAction`.
+#[derive(Clone)]
+pub struct TablebaseRescoringLoader<D, P> {
+    loader: D,
+    prober: P,
+    action: TablebaseAction,
+    max_pieces: u32,
+}
+
+impl<D: DataLoader<ChessBoard>, P: TablebaseProber> TablebaseRescoringLoader<D, P> {
+    pub fn new(loader: D, prober: P, action: TablebaseAction) -> Self {
+        let max_pieces = prober.max_pieces();
+        Self { loader, prober, action, max_pieces }
+    }
+
+    /// Only probe positions with at most `max_pieces` pieces, which can
+    /// be used to avoid slow probes into the largest tables.
+    pub fn with_max_pieces(mut self, max_pieces: u32) -> Self {
+        self.max_pieces = max_pieces.min(self.prober.max_pieces());
+        self
+    }
+
+    fn process(&self, pos: &ChessBoard, stats: &TablebaseStats) -> Option<ChessBoard> {
+        if pos.occ().count_ones() > self.max_pieces {
+            return Some(*pos);
+        }
+
+        let Some(wdl) = self.prober.probe(pos) else {
+            stats.failed.fetch_add(1, Ordering::Relaxed);
+            return Some(*pos);
+        };
+
+        stats.probed.fetch_add(1, Ordering::Relaxed);
+
+        let mismatched = wdl != LoadableDataType::result(pos);
+
+        if mismatched {
+            stats.mismatched.fetch_add(1, Ordering::Relaxed);
+        }
+
+        match self.action {
+            TablebaseAction::Rescore if mismatched => Some(with_result(pos, wdl)),
+            TablebaseAction::Rescore => Some(*pos),
+            TablebaseAction::Discard => None,
+            TablebaseAction::DiscardMismatched => (!mismatched).then_some(*pos),
+        }
+    }
+}
+
+impl<D: DataLoader<ChessBoard>, P: TablebaseProber> DataLoader<ChessBoard> for TablebaseRescoringLoader<D, P> {
+    fn data_file_paths(&self) -> &[String] {
+        self.loader.data_file_paths()
+    }
+
+    fn count_positions(&self) -> Option<u64> {
+        match self.action {
+            TablebaseAction::Rescore => self.loader.count_positions(),
+            TablebaseAction::Discard | TablebaseAction::DiscardMismatched => None,
+        }
+    }
+
+    fn map_batches<F: FnMut(&[ChessBoard]) -> bool>(&self, start_batch: usize, batch_size: usize, mut f: F) {
+        let stats = TablebaseStats::default();
+
+        if self.action == TablebaseAction::Rescore {
+            let mut buffer = Vec::with_capacity(batch_size);
+
+            self.loader.map_batches(start_batch, batch_size, |batch| {
+                buffer.clear();
+                buffer.extend(batch.iter().filter_map(|pos| self.process(pos, &stats)));
+                f(&buffer)
+            });
+        } else {
+            // filtering shrinks batches, so they need to be regrouped
+            let mut buffer = Vec::with_capacity(batch_size);
+            let mut finished = false;
+
+            self.loader.map_batches(start_batch, batch_size, |batch| {
+                for pos in batch {
+                    if let Some(pos) = self.process(pos, &stats) {
+                        buffer.push(pos);
+                    }
+
+                    if buffer.len() == batch_size {
+                        finished = f(&buffer);
+                        buffer.clear();
+
+                        if finished {
+                            return true;
+                        }
+                    }
+                }
+
+                false
+            });
+
+            if !finished && !buffer.is_empty() {
+                f(&buffer);
+            }
+        }
+
+        stats.report(self.action);
+    }
+}
+
+#[derive(Default)]
+struct TablebaseStats {
+    probed: AtomicU64,
+    mismatched: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl TablebaseStats {
+    fn report(&self, action: TablebaseAction) {
+        let probed = self.probed.load(Ordering::Relaxed);
+        let mismatched = self.mismatched.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+
+        let verb = match action {
+            TablebaseAction::Rescore => "rescored",
+            TablebaseAction::Discard => "discarded",
+            TablebaseAction::DiscardMismatched => "discarded as mismatched",
+        };
+
+        let affected = if action == TablebaseAction::Discard { probed } else { mismatched };
+
+        println!("Probed {probed} positions in tablebases, {affected} {verb}, {failed} failed probes");
+    }
+}
+
+fn with_result(pos: &ChessBoard, result: GameResult) -> ChessBoard {
+    let mut bbs = [0; 8];
+
+    // boards are stored relative to the side to move, so
+    // rebuilding it with white to move preserves everything
+    for (piece, square) in pos.into_iter() {
+        let bit = 1 << square;
+        bbs[usize::from(piece & 8 > 0)] |= bit;
+        bbs[2 + usize::from(piece & 7)] |= bit;
+    }
+
+    let result = f32::from(result as u8) / 2.0;
+
+    ChessBoard::from_raw(bbs, 0, LoadableDataType::score(pos), result).unwrap()
+}
+
+/// Writes the FEN of a position as seen by the side to move, i.e. always with white to move,
+/// without castling rights or en passant as they are not stored in `ChessBoard`.
+pub fn stm_relative_fen(pos: &ChessBoard) -> String {
+    let mut board = [None; 64];
+
+    for (piece, square) in pos.into_iter() {
+        let c = b"pnbrqk"[usize::from(piece & 7)];
+        board[usize::from(square)] = Some(if piece & 8 > 0 { c } else { c.to_ascii_uppercase() });
+    }
+
+    let mut fen = String::new();
+
+    for rank in (0..8).rev() {
+        let mut empty = 0;
+
+        for file in 0..8 {
+            if let Some(c) = board[8 * rank + file] {
+                if empty > 0 {
+                    fen += &empty.to_string();
+                    empty = 0;
+                }
+
+                fen.push(char::from(c));
+            } else {
+                empty += 1;
+            }
+        }
+
+        if empty > 0 {
+            fen += &empty.to_string();
+        }
+
+        if rank > 0 {
+            fen.push('/');
+        }
+    }
+
+    fen + " w - - 0 1"
+}
+
+#[cfg(feature = "syzygy")]
+pub use syzygy::SyzygyProber;
+
+#[cfg(feature = "syzygy")]
+mod syzygy {
+    use std::{io, sync::Arc};
+
+    use shakmaty::{fen::Fen, CastlingMode, Chess};
+    use shakmaty_syzygy::{Tablebase, Wdl};
+
+    use super::{stm_relative_fen, ChessBoard, GameResult, TablebaseProber};
+
+    /// Probes Syzygy tablebases, with castling rights and en passant ignored
+    /// as they are not stored in `ChessBoard`.
+    #[derive(Clone)]
+    pub struct SyzygyProber {
+        tables: Arc<Tablebase<Chess>>,
+    }
+
+    impl SyzygyProber {
+        /// Loads all tables from the given directories.
+        pub fn new(paths: &[&str]) -> io::Result<Self> {
+            let mut tables = Tablebase::new();
+
+            for path in paths {
+                tables.add_directory(path)?;
+            }
+
+            Ok(Self { tables: Arc::new(tables) })
+        }
+    }
+
+    impl TablebaseProber for SyzygyProber {
+        fn max_pieces(&self) -> u32 {
+            self.tables.max_pieces() as u32
+        }
+
+        fn probe(&self, pos: &ChessBoard) -> Option<GameResult> {
+            let fen: Fen = stm_relative_fen(pos).parse().ok()?;
+            let pos: Chess = fen.into_position(CastlingMode::Standard).ok()?;
+
+            // cursed wins and blessed losses are draws under the 50 move rule
+            match self.tables.probe_wdl_after_zeroing(&pos).ok()? {
+                Wdl::Win => Some(GameResult::Win),
+                Wdl::Loss => Some(GameResult::Loss),
+                Wdl::CursedWin | Wdl::Draw | Wdl::BlessedLoss => Some(GameResult::Draw),
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::{fs::File, io::Read, time::Duration};
+
+use super::{CanBeDirectlySequentiallyLoaded, CorruptedRecords, DataLoader};
+
+/// Reads records from files that are being appended to by a concurrent data generation
+/// process, waiting for more data at the end of a file instead of terminating, so that
+/// training can run continuously alongside data generation.
+///
+/// Files are read round-robin, and files that do not exist yet are waited for. Each record
+/// is seen exactly once, so the number of superbatches is limited by the rate of datagen.
+#[derive(Clone)]
+pub struct TailingDataLoader {
+    file_paths: Vec<String>,
+    poll_interval: Duration,
+    corrupted: CorruptedRecords,
+}
+
+impl TailingDataLoader {
+    pub fn new(file_paths: &[&str]) -> Self {
+        Self {
+            file_paths: file_paths.iter().map(|path| path.to_string()).collect(),
+            poll_interval: Duration::from_millis(500),
+            corrupted: CorruptedRecords::default(),
+        }
+    }
+
+    /// Sets how long to wait before checking for new data, when all files are exhausted.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets the maximum number of malformed records that will be skipped before panicking.
+    pub fn with_error_budget(mut self, budget: u64) -> Self {
+        self.corrupted = CorruptedRecords::new(budget);
+        self
+    }
+}
+
+struct TailedFile {
+    file: Option<File>,
+    /// Bytes of a record that has only been partially written.
+    pending: Vec<u8>,
+    offset: u64,
+}
+
+impl<T: CanBeDirectlySequentiallyLoaded> DataLoader<T> for TailingDataLoader {
+    fn data_file_paths(&self) -> &[String] {
+        &self.file_paths
+    }
+
+    fn count_positions(&self) -> Option<u64> {
+        None
+    }
+
+    fn map_batches<F: FnMut(&[T]) -> bool>(&self, start_batch: usize, batch_size: usize, mut f: F) {
+        let record_size = size_of::<T>();
+        let mut to_skip = start_batch * batch_size;
+
+        let mut files = self
+            .file_paths
+            .iter()
+            .map(|_| TailedFile { file: None, pending: Vec::new(), offset: 0 })
+            .collect::<Vec<_>>();
+
+        let mut chunk = vec![0; 64 * 1024];
+        let mut batch = Vec::with_capacity(batch_size);
+
+        'dataloading: loop {
+            let mut progressed = false;
+
+            for (path, tailed) in self.file_paths.iter().zip(files.iter_mut()) {
+                if tailed.file.is_none() {
+                    tailed.file = File::open(path).ok();
+                }
+
+                let Some(file) = tailed.file.as_mut() else { continue };
+
+                let bytes = match file.read(&mut chunk) {
+                    Ok(bytes) => bytes,
+                    Err(e) => panic!("Failed to read from {path}: {e}"),
+                };
+
+                if bytes == 0 {
+                    continue;
+                }
+
+                progressed = true;
+                tailed.pending.extend_from_slice(&chunk[..bytes]);
+
+                let complete = tailed.pending.len() / record_size;
+
+                for record in tailed.pending.chunks_exact(record_size) {
+                    // records are plain old data, so can be read straight from the bytes
+                    let pos = unsafe { std::ptr::read_unaligned(record.as_ptr().cast::<T>()) };
+
+                    if !pos.is_well_formed() {
+                        self.corrupted.record(path, tailed.offset);
+                    } else if to_skip > 0 {
+                        to_skip -= 1;
+                    } else {
+                        batch.push(pos);
+                    }
+
+                    tailed.offset += record_size as u64;
+
+                    if batch.len() == batch_size {
+                        if f(&batch) {
+                            break 'dataloading;
+                        }
+
+                        batch.clear();
+                    }
+                }
+
+                tailed.pending.drain(..complete * record_size);
+            }
+
+            if !progressed {
+                std::thread::sleep(self.poll_interval);
+            }
+        }
+
+        self.corrupted.report();
+    }
+}
@@ -5,16 +5,23 @@ use std::{
     str::FromStr,
 };
 
-use super::DataLoader;
+use super::{CorruptedRecords, DataLoader};
 
 #[derive(Clone)]
 pub struct InMemoryTextLoader {
     file_path: [String; 1],
+    corrupted: CorruptedRecords,
 }
 
 impl InMemoryTextLoader {
     pub fn new(file_path: &str) -> Self {
-        Self { file_path: [file_path.to_string()] }
+        Self { file_path: [file_path.to_string()], corrupted: CorruptedRecords::default() }
+    }
+
+    /// Sets the maximum number of malformed lines that will be skipped before panicking.
+    pub fn with_error_budget(mut self, budget: u64) -> Self {
+        self.corrupted = CorruptedRecords::new(budget);
+        self
     }
 }
 
@@ -33,7 +40,24 @@ where
     fn map_batches<F: FnMut(&[T]) -> bool>(&self, _: usize, batch_size: usize, mut f: F) {
         let file = File::open(&self.file_path[0]).unwrap();
         let reader = BufReader::new(file);
-        let data = reader.lines().map(|ln| ln.unwrap().parse::<T>().unwrap()).collect::<Vec<_>>();
+
+        let mut data = Vec::new();
+        let mut offset = 0;
+
+        for line in reader.split(b'\n') {
+            let line = line.unwrap();
+            let line_offset = offset;
+            offset += line.len() as u64 + 1;
+
+            let parsed = std::str::from_utf8(&line).ok().and_then(|ln| ln.trim_end_matches('\r').parse::<T>().ok());
+
+            match parsed {
+                Some(pos) => data.push(pos),
+                None => self.corrupted.record(&self.file_path[0], line_offset),
+            }
+        }
+
+        self.corrupted.report();
 
         'dataloading: loop {
             for batch in data.chunks(batch_size) {
@@ -1,5 +1,7 @@
 use bulletformat::ChessBoard;
 
+use super::inputs::KingBucketLayout;
+
 pub trait OutputBuckets<T>: Send + Sync + Copy + Default + 'static {
     const BUCKETS: usize;
 
@@ -26,3 +28,74 @@ impl<const N: usize> OutputBuckets<ChessBoard> for MaterialCount<N> {
         (pos.occ().count_ones() as u8 - 2) / divisor as u8
     }
 }
+
+/// Buckets positions by the square of the side to move's king, from its own perspective.
+///
+/// The default layout splits the ranks evenly between the `N` buckets, starting from the back rank.
+#[derive(Clone, Copy)]
+pub struct KingBuckets<const N: usize> {
+    buckets: [u8; 64],
+}
+
+impl<const N: usize> KingBuckets<N> {
+    pub fn new(layout: KingBucketLayout) -> Self {
+        layout.validate().unwrap();
+        assert_eq!(layout.num_buckets(), N, "Layout does not have {N} buckets!");
+
+        Self { buckets: layout.as_array().map(|bucket| bucket as u8) }
+    }
+}
+
+impl<const N: usize> Default for KingBuckets<N> {
+    fn default() -> Self {
+        assert!((1..=8).contains(&N), "Default layout requires between 1 and 8 buckets!");
+        Self { buckets: std::array::from_fn(|sq| (N * (sq / 8) / 8) as u8) }
+    }
+}
+
+impl<const N: usize> OutputBuckets<ChessBoard> for KingBuckets<N> {
+    const BUCKETS: usize = N;
+
+    fn bucket(&self, pos: &ChessBoard) -> u8 {
+        self.buckets[usize::from(pos.our_ksq())]
+    }
+}
+
+/// Output buckets from a function, for custom bucketing schemes, e.g.
+/// ```ignore
+/// let buckets = outputs::FromFn::<4, _>::new(|pos: &ChessBoard| (pos.occ().count_ones() > 16) as u8);
+/// ```
+/// The function must return buckets less than `N`, and can be a closure as long as it does not capture anything.
+pub struct FromFn<const N: usize, T> {
+    f: fn(&T) -> u8,
+}
+
+impl<const N: usize, T> FromFn<N, T> {
+    pub fn new(f: fn(&T) -> u8) -> Self {
+        Self { f }
+    }
+}
+
+impl<const N: usize, T> Clone for FromFn<N, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const N: usize, T> Copy for FromFn<N, T> {}
+
+impl<const N: usize, T> Default for FromFn<N, T> {
+    fn default() -> Self {
+        Self { f: |_| 0 }
+    }
+}
+
+impl<const N: usize, T: 'static> OutputBuckets<T> for FromFn<N, T> {
+    const BUCKETS: usize = N;
+
+    fn bucket(&self, pos: &T) -> u8 {
+        let bucket = (self.f)(pos);
+        assert!(usize::from(bucket) < N, "Output bucket {bucket} out of range!");
+        bucket
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+
+use bullet_core::graph::Graph;
+use bullet_hip_backend::ExecutionContext;
+
+use super::logger::{ansi, num_cs};
+
+/// Periodically splits a batch into `shards` pieces and computes the gradient
+/// of each separately, to estimate the noise in the full batch gradient.
+#[derive(Clone, Copy, Debug)]
+pub struct GradientNoiseTracking {
+    /// Number of shards to split the batch into, must be at least 2.
+    pub shards: usize,
+    /// Measure gradient noise every `freq` batches.
+    pub freq: usize,
+}
+
+impl GradientNoiseTracking {
+    pub fn new(shards: usize, freq: usize) -> Self {
+        assert!(shards >= 2, "Need at least 2 shards to estimate gradient variance!");
+        assert!(freq > 0, "Gradient noise frequency must be positive!");
+        Self { shards, freq }
+    }
+}
+
+/// Gradient statistics for a single set of weights.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GradientNoise {
+    /// Squared norm of the mean per-position gradient over all shards.
+    pub signal: f32,
+    /// Sum over all weights of the variance of the per-position gradient between shards.
+    pub variance: f32,
+    /// Estimated signal-to-noise ratio of the full batch gradient.
+    pub snr: f32,
+}
+
+impl GradientNoise {
+    fn from_shards(grads: &[Vec<f32>]) -> Self {
+        let shards = grads.len();
+        let size = grads[0].len();
+
+        let mut signal = 0.0;
+        let mut variance = 0.0;
+
+        for i in 0..size {
+            let mean = grads.iter().map(|g| g[i]).sum::<f32>() / shards as f32;
+            signal += mean * mean;
+            variance += grads.iter().map(|g| (g[i] - mean).powi(2)).sum::<f32>() / (shards - 1) as f32;
+        }
+
+        // each shard is 1/shards of the batch, so the full batch gradient
+        // has 1/shards of the variance of a single shard gradient
+        let snr = if variance > 0.0 { signal * shards as f32 / variance } else { f32::INFINITY };
+
+        Self { signal, variance, snr }
+    }
+}
+
+/// Per-position gradients of every set of weights, for each shard of a batch.
+#[derive(Default)]
+pub struct ShardGradients {
+    grads: BTreeMap<String, Vec<Vec<f32>>>,
+}
+
+impl ShardGradients {
+    /// Computes the gradients on a shard that has already been loaded into the graph.
+    ///
+    /// This overwrites the gradients in the graph, so the full batch
+    /// must be reloaded before training on it.
+    pub fn push(&mut self, graph: &mut Graph<ExecutionContext>, shard_size: usize) {
+        if shard_size == 0 {
+            return;
+        }
+
+        graph.synchronise().unwrap();
+        graph.zero_grads().unwrap();
+        graph.forward().unwrap();
+        graph.backward().unwrap();
+
+        for id in graph.weight_ids() {
+            let weights = graph.get_weights(&id);
+
+            if let Some(gradients) = weights.gradients.as_ref() {
+                let mut buf = vec![0.0; gradients.size()];
+                gradients.write_to_slice(&mut buf).unwrap();
+
+                for x in &mut buf {
+                    *x /= shard_size as f32;
+                }
+
+                self.grads.entry(id).or_default().push(buf);
+            }
+        }
+    }
+
+    pub fn finish(self) -> BTreeMap<String, GradientNoise> {
+        self.grads
+            .into_iter()
+            .filter(|(_, shard_grads)| shard_grads.len() >= 2)
+            .map(|(id, shard_grads)| (id, GradientNoise::from_shards(&shard_grads)))
+            .collect()
+    }
+}
+
+/// Running average of gradient noise measurements over a superbatch.
+#[derive(Default)]
+pub struct GradientNoiseRecord {
+    sums: BTreeMap<String, GradientNoise>,
+    count: usize,
+}
+
+impl GradientNoiseRecord {
+    pub fn push(&mut self, noise: &BTreeMap<String, GradientNoise>) {
+        for (id, stats) in noise {
+            let entry = self.sums.entry(id.clone()).or_default();
+            entry.signal += stats.signal;
+            entry.variance += stats.variance;
+        }
+
+        self.count += 1;
+    }
+
+    /// Returns the averaged statistics and resets the record.
+    pub fn take(&mut self, shards: usize) -> BTreeMap<String, GradientNoise> {
+        let count = self.count.max(1) as f32;
+        self.count = 0;
+
+        std::mem::take(&mut self.sums)
+            .into_iter()
+            .map(|(id, sum)| {
+                let signal = sum.signal / count;
+                let variance = sum.variance / count;
+                let snr = if variance > 0.0 { signal * shards as f32 / variance } else { f32::INFINITY };
+                (id, GradientNoise { signal, variance, snr })
+            })
+            .collect()
+    }
+}
+
+pub fn report(shards: usize, noise: &BTreeMap<String, GradientNoise>) {
+    if noise.is_empty() {
+        return;
+    }
+
+    let num_cs = num_cs();
+
+    println!("Gradient noise ({} shards):", ansi(shards, num_cs));
+
+    for (id, stats) in noise {
+        println!(
+            "    {id:<20} | grad norm {} | variance {} | snr {}",
+            ansi(format!("{:.6}", stats.signal.sqrt()), num_cs),
+            ansi(format!("{:.6}", stats.variance), num_cs),
+            ansi(format!("{:.3}", stats.snr), num_cs),
+        );
+    }
+}
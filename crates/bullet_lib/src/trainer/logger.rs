@@ -5,6 +5,8 @@ use std::{
     time::Instant,
 };
 
+use bullet_core::graph::stats::ActivationStats;
+
 use super::schedule::TrainingSteps;
 
 static CBCS: AtomicBool = AtomicBool::new(false);
@@ -89,6 +91,52 @@ pub fn report_superbatch_finished(
     );
 }
 
+/// Reports the mean of each separately recorded component of the loss over the last superbatch.
+pub fn report_loss_components(components: &[(String, f32)], batches: usize) {
+    let num_cs = num_cs();
+
+    for (id, total) in components {
+        println!("    {id} loss {}", ansi(format!("{:.6}", total / batches as f32), num_cs));
+    }
+}
+
+/// Reports the range and distribution of the values of each node over the last superbatch.
+pub fn report_activation_stats(stats: &[(String, ActivationStats)]) {
+    let num_cs = num_cs();
+
+    println!("    {:>16} | {:>10} | {:>10} | {:>10} | {:>10}", "node", "min", "max", "mean", "std");
+
+    for (label, stats) in stats {
+        let fmt = |x: f32| ansi(format!("{x:>10.4}"), num_cs);
+        println!(
+            "    {label:>16} | {} | {} | {} | {}",
+            fmt(stats.min),
+            fmt(stats.max),
+            fmt(stats.mean()),
+            fmt(stats.std())
+        );
+    }
+}
+
+/// Reports the mean loss of each output bucket over the last superbatch.
+pub fn report_bucket_losses(train: &[(f64, u64)], validation: &[(f64, u64)]) {
+    let num_cs = num_cs();
+    let mean = |losses: &[(f64, u64)], bucket: usize| match losses.get(bucket) {
+        Some(&(total, count)) if count > 0 => format!("{:.6}", total / count as f64),
+        _ => "-".to_string(),
+    };
+
+    println!("    {:>6} | {:>10} | {:>10} | {:>10}", "bucket", "positions", "train", "validation");
+
+    for (bucket, &(_, count)) in train.iter().enumerate() {
+        println!(
+            "    {bucket:>6} | {count:>10} | {} | {}",
+            ansi(format!("{:>10}", mean(train, bucket)), num_cs),
+            ansi(format!("{:>10}", mean(validation, bucket)), num_cs),
+        );
+    }
+}
+
 pub fn report_time_left(steps: TrainingSteps, superbatch: usize, total_time: f32) {
     let num_cs = num_cs();
     let finished_superbatches = superbatch - steps.start_superbatch + 1;
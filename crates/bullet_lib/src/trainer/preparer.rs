@@ -1,9 +1,12 @@
-use std::sync::mpsc::SyncSender;
+use std::sync::mpsc;
 
-use super::schedule::{wdl::WdlScheduler, TrainingSteps};
+use super::{
+    queue::BatchSender,
+    schedule::{wdl::WdlScheduler, TrainingSteps},
+};
 
 pub trait DataPreparer: Clone + Send + Sync {
-    type DataType: Send + Sync;
+    type DataType: Clone + Send + Sync;
     type PreparedData: Send + Sync;
 
     fn get_data_file_paths(&self) -> &[String];
@@ -15,32 +18,62 @@ pub trait DataPreparer: Clone + Send + Sync {
     fn load_and_map_batches<F: FnMut(&[Self::DataType]) -> bool>(&self, start_batch: usize, batch_size: usize, f: F);
 
     fn prepare(&self, data: &[Self::DataType], threads: usize, blend: f32) -> Self::PreparedData;
+
+    /// Approximate memory used by a prepared batch, used to limit the size of the batch queue.
+    fn prepared_batch_bytes(&self, _batch_size: usize) -> Option<usize> {
+        None
+    }
 }
 
+/// Spawns a thread that reads raw batches from the data loader, starting at `start_batch`,
+/// which are queued up to `raw_queue_size` deep for each of `loader_threads` threads, that
+/// prepare them using `threads` threads each. Prepared batches are sent in the order they were read.
+#[allow(clippy::too_many_arguments)]
 pub fn create_dataloader<D: DataPreparer + 'static, WDL: WdlScheduler>(
     preparer: D,
-    sender: SyncSender<D::PreparedData>,
+    sender: BatchSender<D::PreparedData>,
     steps: TrainingSteps,
     wdl: WDL,
     threads: usize,
+    raw_queue_size: usize,
+    loader_threads: usize,
+    start_batch: usize,
 ) -> std::thread::JoinHandle<()> {
-    std::thread::spawn(move || {
+    let mut raw_senders = Vec::new();
+    let mut prepared_receivers = Vec::new();
+    let mut handles = Vec::new();
+
+    for _ in 0..loader_threads {
+        let (raw_sender, raw_receiver) = mpsc::sync_channel::<(Vec<D::DataType>, f32)>(raw_queue_size);
+        let (prepared_sender, prepared_receiver) = mpsc::sync_channel(1);
+        let preparer = preparer.clone();
+
+        handles.push(std::thread::spawn(move || {
+            while let Ok((batch, blend)) = raw_receiver.recv() {
+                if prepared_sender.send(preparer.prepare(&batch, threads, blend)).is_err() {
+                    break;
+                }
+            }
+        }));
+
+        raw_senders.push(raw_sender);
+        prepared_receivers.push(prepared_receiver);
+    }
+
+    handles.push(std::thread::spawn(move || {
         let mut curr_superbatch = steps.start_superbatch;
         let mut curr_batch = 0;
-
-        let start_batch = steps.batches_per_superbatch * (steps.start_superbatch - 1);
+        let mut next_thread = 0;
 
         preparer.load_and_map_batches(start_batch, steps.batch_size, |batch| {
             let blend = wdl.blend(curr_batch, curr_superbatch, steps.end_superbatch);
 
-            let prepared_data = preparer.prepare(batch, threads, blend);
-
-            sender.send(prepared_data).unwrap();
+            // batches are handed out in turn, so they can be collected in the same order
+            let mut should_break = raw_senders[next_thread].send((batch.to_vec(), blend)).is_err();
+            next_thread = (next_thread + 1) % raw_senders.len();
 
             curr_batch += 1;
 
-            let mut should_break = false;
-
             if curr_batch % steps.batches_per_superbatch == 0 {
                 if curr_superbatch == steps.end_superbatch {
                     should_break = true;
@@ -52,5 +85,23 @@ pub fn create_dataloader<D: DataPreparer + 'static, WDL: WdlScheduler>(
 
             should_break
         });
+    }));
+
+    std::thread::spawn(move || {
+        'collect: loop {
+            for receiver in &prepared_receivers {
+                let Ok(prepared_data) = receiver.recv() else { break 'collect };
+
+                if sender.send(prepared_data).is_err() {
+                    break 'collect;
+                }
+            }
+        }
+
+        drop(prepared_receivers);
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
     })
 }
@@ -0,0 +1,167 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Number of seconds of training that an automatically sized batch queue should be able to cover.
+const TARGET_QUEUE_SECONDS: f64 = 1.0;
+
+/// Capacity of an automatically sized batch queue before the first measurement.
+pub const INITIAL_QUEUE_SIZE: usize = 32;
+
+/// Used if the size of a prepared batch is unknown.
+const DEFAULT_MAX_QUEUE_SIZE: usize = 512;
+
+struct State<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    sender_alive: bool,
+    receiver_alive: bool,
+    produced: usize,
+    consumed: usize,
+    producer_blocked: Duration,
+    consumer_blocked: Duration,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+/// Bounded single-producer single-consumer queue, like `mpsc::sync_channel`,
+/// but whose capacity can be changed after creation, and which measures how
+/// long each side spends waiting on the other.
+pub fn batch_queue<T>(capacity: usize) -> (BatchSender<T>, BatchReceiver<T>) {
+    let state = State {
+        items: VecDeque::new(),
+        capacity: capacity.max(1),
+        sender_alive: true,
+        receiver_alive: true,
+        produced: 0,
+        consumed: 0,
+        producer_blocked: Duration::ZERO,
+        consumer_blocked: Duration::ZERO,
+    };
+
+    let shared = Arc::new(Shared { state: Mutex::new(state), not_empty: Condvar::new(), not_full: Condvar::new() });
+
+    (BatchSender { shared: shared.clone() }, BatchReceiver { shared, measuring_since: Instant::now() })
+}
+
+pub struct BatchSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> BatchSender<T> {
+    /// Blocks until there is space in the queue, returns the item if the receiver has been dropped.
+    pub fn send(&self, item: T) -> Result<(), T> {
+        let mut state = self.shared.state.lock().unwrap();
+        let start = Instant::now();
+
+        while state.receiver_alive && state.items.len() >= state.capacity {
+            state = self.shared.not_full.wait(state).unwrap();
+        }
+
+        if !state.receiver_alive {
+            return Err(item);
+        }
+
+        state.producer_blocked += start.elapsed();
+        state.produced += 1;
+        state.items.push_back(item);
+        self.shared.not_empty.notify_one();
+
+        Ok(())
+    }
+}
+
+impl<T> Drop for BatchSender<T> {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().sender_alive = false;
+        self.shared.not_empty.notify_all();
+    }
+}
+
+pub struct BatchReceiver<T> {
+    shared: Arc<Shared<T>>,
+    measuring_since: Instant,
+}
+
+impl<T> BatchReceiver<T> {
+    /// Blocks until an item is available, returns `None` once the queue
+    /// is empty and the sender has been dropped.
+    pub fn recv(&self) -> Option<T> {
+        let mut state = self.shared.state.lock().unwrap();
+        let start = Instant::now();
+
+        while state.sender_alive && state.items.is_empty() {
+            state = self.shared.not_empty.wait(state).unwrap();
+        }
+
+        let item = state.items.pop_front()?;
+
+        state.consumer_blocked += start.elapsed();
+        state.consumed += 1;
+        self.shared.not_full.notify_one();
+
+        Some(item)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.state.lock().unwrap().capacity
+    }
+
+    pub fn resize(&self, capacity: usize) {
+        self.shared.state.lock().unwrap().capacity = capacity.max(1);
+        self.shared.not_full.notify_all();
+    }
+
+    /// Returns the throughput of each side since the last call, and resets the measurements.
+    pub fn take_throughput(&mut self) -> QueueThroughput {
+        let mut state = self.shared.state.lock().unwrap();
+        let elapsed = self.measuring_since.elapsed().as_secs_f64();
+
+        let rate = |count: usize, blocked: Duration| count as f64 / (elapsed - blocked.as_secs_f64()).max(1e-6);
+
+        let throughput = QueueThroughput {
+            producer_rate: rate(state.produced, state.producer_blocked),
+            consumer_rate: rate(state.consumed, state.consumer_blocked),
+        };
+
+        state.produced = 0;
+        state.consumed = 0;
+        state.producer_blocked = Duration::ZERO;
+        state.consumer_blocked = Duration::ZERO;
+        self.measuring_since = Instant::now();
+
+        throughput
+    }
+}
+
+impl<T> Drop for BatchReceiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.receiver_alive = false;
+        state.items.clear();
+        self.shared.not_full.notify_all();
+    }
+}
+
+/// Batches per second that each side of a queue could process, excluding time spent waiting.
+#[derive(Clone, Copy, Debug)]
+pub struct QueueThroughput {
+    pub producer_rate: f64,
+    pub consumer_rate: f64,
+}
+
+impl QueueThroughput {
+    /// Size of queue that covers `TARGET_QUEUE_SECONDS` of training, limited
+    /// by `memory_mb` if the size of each batch is known.
+    pub fn queue_size(&self, batch_bytes: Option<usize>, memory_mb: usize) -> usize {
+        let max = batch_bytes.map_or(DEFAULT_MAX_QUEUE_SIZE, |bytes| memory_mb * 1024 * 1024 / bytes.max(1));
+        let target = (self.consumer_rate * TARGET_QUEUE_SECONDS).ceil() as usize;
+        target.clamp(2, max.max(2))
+    }
+}
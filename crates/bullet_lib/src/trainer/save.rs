@@ -29,6 +29,27 @@ impl SavedFormat {
     }
 }
 
+/// An additional quantised export of the network, written alongside the
+/// main quantised network at each checkpoint.
+#[derive(Clone)]
+pub struct QuantisationScheme {
+    pub(super) name: String,
+    pub(super) formats: Vec<SavedFormat>,
+    pub(super) activation_quantisations: Vec<i16>,
+}
+
+impl QuantisationScheme {
+    /// Saved as `quantised-{name}.bin` in each checkpoint.
+    pub fn new(name: &str, formats: Vec<SavedFormat>) -> Self {
+        Self { name: name.to_string(), formats, activation_quantisations: Vec::new() }
+    }
+
+    pub fn with_activation_quantisations(mut self, quants: &[i16]) -> Self {
+        self.activation_quantisations = quants.to_vec();
+        self
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Layout {
     /// Column-major
@@ -90,6 +111,27 @@ impl QuantTarget {
 
         Ok(quantised)
     }
+
+    /// Maximum and root mean square of the absolute error introduced by quantising `buf`.
+    pub fn quantisation_error(self, buf: &[f32]) -> (f32, f32) {
+        let q = match self {
+            Self::Float => return (0.0, 0.0),
+            Self::I8(q) | Self::I16(q) => f64::from(q),
+            Self::I32(q) => f64::from(q),
+        };
+
+        let mut max = 0f64;
+        let mut sum_sq = 0f64;
+
+        for &float in buf {
+            let float = f64::from(float);
+            let err = (float - (q * float).trunc() / q).abs();
+            max = max.max(err);
+            sum_sq += err * err;
+        }
+
+        (max as f32, (sum_sq / buf.len().max(1) as f64).sqrt() as f32)
+    }
 }
 
 pub(super) fn transpose(shape: Shape, weights: &[f32]) -> Vec<f32> {
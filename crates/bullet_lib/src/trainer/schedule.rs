@@ -17,6 +17,23 @@ pub struct TrainingSteps {
 }
 
 impl TrainingSteps {
+    /// Trains for (at least) `epochs` full passes over a dataset of `positions` positions,
+    /// e.g. from `DataLoader::count_positions`, rounded up to a whole number of superbatches.
+    pub fn from_epochs(batch_size: usize, batches_per_superbatch: usize, epochs: f64, positions: u64) -> Self {
+        assert!(epochs > 0.0, "Must train for a positive number of epochs!");
+
+        let positions_per_superbatch = (batch_size * batches_per_superbatch) as f64;
+        let superbatches = (epochs * positions as f64 / positions_per_superbatch).ceil() as usize;
+
+        Self { batch_size, batches_per_superbatch, start_superbatch: 1, end_superbatch: superbatches.max(1) }
+    }
+
+    /// Number of full passes over a dataset of `positions` positions.
+    pub fn epochs(&self, positions: u64) -> f64 {
+        let superbatches = self.end_superbatch - self.start_superbatch + 1;
+        (superbatches * self.batches_per_superbatch * self.batch_size) as f64 / positions.max(1) as f64
+    }
+
     fn display(&self) {
         println!("Batch Size             : {}", ansi(self.batch_size, 31));
         println!("Batches / Superbatch   : {}", ansi(self.batches_per_superbatch, 31));
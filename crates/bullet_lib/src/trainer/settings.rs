@@ -1,3 +1,7 @@
+use std::time::Duration;
+
+pub use bullet_core::optimiser::utils::CheckpointCompression;
+
 use super::logger::ansi;
 
 #[derive(Clone, Copy)]
@@ -14,6 +18,67 @@ impl<'a> TestDataset<'a> {
     }
 }
 
+/// Fine-grained control over the data loading pipeline, each `None`
+/// field falls back to its default.
+#[derive(Clone, Copy, Default)]
+pub struct PrefetchSettings {
+    /// Number of threads used to prepare each batch, defaults to `LocalSettings::threads`.
+    pub prep_threads: Option<usize>,
+    /// Number of raw batches that can be read from the data loader ahead of
+    /// being prepared, defaults to 1.
+    pub raw_queue_size: Option<usize>,
+    /// Number of threads preparing raw batches concurrently, each with `prep_threads`
+    /// threads of its own, defaults to 1.
+    pub loader_threads: Option<usize>,
+    /// Number of prepared validation batches that can be queued, defaults to 2.
+    pub test_queue_size: Option<usize>,
+    /// Memory available to an automatically sized batch queue, defaults to 2048MB.
+    pub batch_queue_memory_mb: Option<usize>,
+}
+
+impl PrefetchSettings {
+    pub fn prep_threads(&self, threads: usize) -> usize {
+        self.prep_threads.unwrap_or(threads)
+    }
+
+    pub fn raw_queue_size(&self) -> usize {
+        self.raw_queue_size.unwrap_or(1)
+    }
+
+    pub fn loader_threads(&self) -> usize {
+        self.loader_threads.unwrap_or(1).max(1)
+    }
+
+    pub fn test_queue_size(&self) -> usize {
+        self.test_queue_size.unwrap_or(2)
+    }
+
+    pub fn batch_queue_memory_mb(&self) -> usize {
+        self.batch_queue_memory_mb.unwrap_or(2048)
+    }
+}
+
+/// Checkpoints saved based on wallclock time, in addition to
+/// those saved every `TrainingSchedule::save_rate` superbatches.
+#[derive(Clone, Copy, Default)]
+pub struct WallclockSaves {
+    /// Save a checkpoint every `interval` of training, overwriting the previous one.
+    pub interval: Option<Duration>,
+    /// Save a checkpoint and stop training once `deadline` has elapsed
+    /// since training started, e.g. to fit within a cluster job limit.
+    pub deadline: Option<Duration>,
+}
+
+/// When to add the current weights to the stochastic weight average.
+#[derive(Clone, Copy, Debug)]
+pub enum SwaSampling {
+    /// At the end of each of the last `n` superbatches of training.
+    LastSuperbatches(usize),
+    /// At the end of each superbatch after which the learning rate increases, i.e. at
+    /// the minima of a cyclic learning rate schedule, and at the end of training.
+    LrMinima,
+}
+
 pub struct LocalSettings<'a> {
     /// Number of threads to make available for training, in addition
     /// to the main trainer thread (used only for loading data if training
@@ -24,13 +89,35 @@ pub struct LocalSettings<'a> {
     /// Directory to write checkpoints to.
     pub output_directory: &'a str,
     /// Number of batches that the dataloader can prepare and put in a queue before
-    /// they are processed in training.
-    pub batch_queue_size: usize,
+    /// they are processed in training. If `None`, the queue is sized automatically
+    /// after the first superbatch, based on the measured throughput of data loading
+    /// and training, within `PrefetchSettings::batch_queue_memory_mb`.
+    pub batch_queue_size: Option<usize>,
+    /// Sizes of the other queues and thread pools in the data loading pipeline.
+    pub prefetch: PrefetchSettings,
+    /// Compression applied to checkpoint weight and optimiser state files.
+    pub checkpoint_compression: CheckpointCompression,
+    /// Additional checkpoints saved based on wallclock time.
+    pub wallclock_saves: WallclockSaves,
 }
 
 impl LocalSettings<'_> {
     pub fn display(&self) {
         println!("Threads                : {}", ansi(self.threads, 31));
+        println!("Prep Threads           : {}", ansi(self.prefetch.prep_threads(self.threads), 31));
+        println!("Loader Threads         : {}", ansi(self.prefetch.loader_threads(), 31));
+        match self.batch_queue_size {
+            Some(size) => println!("Batch Queue Size       : {}", ansi(size, 31)),
+            None => println!("Batch Queue Size       : {}", ansi("Automatic", 31)),
+        }
         println!("Output Path            : {}", ansi(self.output_directory, "32;1"));
+
+        if let Some(interval) = self.wallclock_saves.interval {
+            println!("Save Interval          : {}", ansi(format!("{}s", interval.as_secs()), 31));
+        }
+
+        if let Some(deadline) = self.wallclock_saves.deadline {
+            println!("Save Deadline          : {}", ansi(format!("{}s", deadline.as_secs()), 31));
+        }
     }
 }
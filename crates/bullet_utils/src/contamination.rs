@@ -0,0 +1,116 @@
+use anyhow::Context;
+use bulletformat::{ChessBoard, DataLoader};
+use structopt::StructOpt;
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::Instant,
+};
+
+use crate::Rand;
+
+/// Checks how many validation positions also appear in the training data.
+#[derive(StructOpt)]
+pub struct ContaminationOptions {
+    #[structopt(required = true, min_values = 1)]
+    inputs: Vec<PathBuf>,
+    #[structopt(required = true, short, long)]
+    validation: PathBuf,
+}
+
+impl ContaminationOptions {
+    pub fn run(&self) -> anyhow::Result<()> {
+        let timer = Instant::now();
+        let zobrist = Zobrist::new();
+
+        let loader =
+            DataLoader::<ChessBoard>::new(&self.validation, 256).with_context(|| "Failed to create dataloader.")?;
+
+        let mut validation = HashMap::new();
+        let mut validation_total = 0u64;
+
+        loader.map_positions(|pos| {
+            validation.insert(zobrist.hash(pos), false);
+            validation_total += 1;
+        });
+
+        println!("Hashed {validation_total} validation positions ({} unique)", validation.len());
+
+        let mut total = 0u64;
+        let mut contaminated = 0u64;
+
+        for path in &self.inputs {
+            let loader = DataLoader::<ChessBoard>::new(path, 256).with_context(|| "Failed to create dataloader.")?;
+
+            let mut file_total = 0u64;
+            let mut file_contaminated = 0u64;
+
+            loader.map_positions(|pos| {
+                if let Some(seen) = validation.get_mut(&zobrist.hash(pos)) {
+                    *seen = true;
+                    file_contaminated += 1;
+                }
+
+                file_total += 1;
+                if (total + file_total) % 10_000_000 == 0 {
+                    println!("Checked {} Positions", total + file_total);
+                }
+            });
+
+            println!("File {}: {file_contaminated}/{file_total} positions in validation set", path.display());
+
+            total += file_total;
+            contaminated += file_contaminated;
+        }
+
+        let leaked = validation.values().filter(|&&seen| seen).count();
+
+        println!();
+        println!("SUMMARY:");
+        println!("Checked {total} Positions in {:.2} seconds", timer.elapsed().as_secs_f32());
+        println!("Training positions in validation set : {contaminated} ({:.4}%)", percent(contaminated, total));
+        println!(
+            "Validation positions in training set : {leaked}/{} ({:.4}%)",
+            validation.len(),
+            percent(leaked as u64, validation.len() as u64)
+        );
+
+        if leaked > 0 {
+            println!("Validation losses will be optimistic, consider removing these positions.");
+        }
+
+        Ok(())
+    }
+}
+
+fn percent(x: u64, total: u64) -> f64 {
+    100.0 * x as f64 / total.max(1) as f64
+}
+
+/// Hashes positions from the side to move's perspective, so a position
+/// and its colour-flipped counterpart (identical network inputs) collide.
+struct Zobrist {
+    keys: [[u64; 64]; 16],
+}
+
+impl Zobrist {
+    fn new() -> Self {
+        let mut rng = Rand(0x5EED_B011_E7C0_FFEE);
+        let mut keys = [[0; 64]; 16];
+        let mut seen = HashSet::new();
+
+        for key in keys.iter_mut().flatten() {
+            // keys must be distinct and nonzero
+            while *key == 0 || !seen.insert(*key) {
+                *key = rng.rand();
+            }
+        }
+
+        Self { keys }
+    }
+
+    fn hash(&self, pos: &ChessBoard) -> u64 {
+        pos.into_iter().fold(0, |hash, (piece, square)| hash ^ self.keys[usize::from(piece)][usize::from(square)])
+    }
+}
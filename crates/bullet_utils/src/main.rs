@@ -1,3 +1,4 @@
+mod contamination;
 mod convert;
 mod count_buckets;
 mod interleave;
@@ -15,6 +16,7 @@ pub enum Options {
     Validate(validate::ValidateOptions),
     BucketCount(count_buckets::ValidateOptions),
     Montybinpack(montybinpack::MontyBinpackOptions),
+    Contamination(contamination::ContaminationOptions),
 }
 
 fn main() -> anyhow::Result<()> {
@@ -25,6 +27,7 @@ fn main() -> anyhow::Result<()> {
         Options::Validate(options) => options.run(),
         Options::BucketCount(options) => options.run(),
         Options::Montybinpack(options) => options.run(),
+        Options::Contamination(options) => options.run(),
     }
 }
 
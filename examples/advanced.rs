@@ -7,7 +7,7 @@ use bullet_lib::{
         default::{inputs, loader, outputs, Trainer},
         save::{Layout, QuantTarget, SavedFormat},
         schedule::{lr, wdl, TrainingSchedule, TrainingSteps},
-        settings::LocalSettings,
+        settings::{CheckpointCompression, LocalSettings, PrefetchSettings, WallclockSaves},
     },
 };
 
@@ -59,7 +59,15 @@ fn main() {
         save_rate: 150,
     };
 
-    let settings = LocalSettings { threads: 4, test_set: None, output_directory: "checkpoints", batch_queue_size: 512 };
+    let settings = LocalSettings {
+        threads: 4,
+        test_set: None,
+        output_directory: "checkpoints",
+        batch_queue_size: None,
+        prefetch: PrefetchSettings::default(),
+        checkpoint_compression: CheckpointCompression::None,
+        wallclock_saves: WallclockSaves::default(),
+    };
 
     let data_loader = loader::DirectSequentialDataLoader::new(&["data/baseline.data"]);
 
@@ -7,7 +7,7 @@ use bullet_lib::{
             Loss, TrainerBuilder,
         },
         schedule::{lr, wdl, TrainingSchedule, TrainingSteps},
-        settings::LocalSettings,
+        settings::{CheckpointCompression, LocalSettings, PrefetchSettings, WallclockSaves},
     },
 };
 
@@ -73,7 +73,7 @@ fn main() {
     let settings = LocalSettings { threads: 8,
        //test_set: Option::Some(TestDataset.new("/data2/bullet/sep2024/validationdata/val1.bullet",20)),
        test_set: None,
-       output_directory: "checkpoints", batch_queue_size: 512 };
+       output_directory: "checkpoints", batch_queue_size: None, prefetch: PrefetchSettings::default(), checkpoint_compression: CheckpointCompression::None, wallclock_saves: WallclockSaves::default() };
 
     let data_loader = loader::DirectSequentialDataLoader::new(&[
 //        "/data2/bullet/oct2024/new/trainingdata/pos1.bullet",
@@ -1,82 +1,13 @@
 use bullet_lib::{
     nn::{optimiser, Activation},
     trainer::{
-        default::{formats::bulletformat::AtaxxBoard, inputs::SparseInputType, loader, outputs, Loss, TrainerBuilder},
+        default::{inputs, loader, outputs, Loss, TrainerBuilder},
         schedule::{lr, wdl, TrainingSchedule, TrainingSteps},
-        settings::LocalSettings,
+        settings::{CheckpointCompression, LocalSettings, PrefetchSettings, WallclockSaves},
     },
 };
 
 const HIDDEN_SIZE: usize = 128;
-const PER_TUPLE: usize = 3usize.pow(4);
-const NUM_TUPLES: usize = 36;
-
-#[derive(Clone, Copy, Default)]
-pub struct Ataxx2Tuples;
-impl SparseInputType for Ataxx2Tuples {
-    type RequiredDataType = AtaxxBoard;
-
-    fn num_inputs(&self) -> usize {
-        NUM_TUPLES * PER_TUPLE
-    }
-
-    fn max_active(&self) -> usize {
-        NUM_TUPLES
-    }
-
-    fn map_features<F: FnMut(usize, usize)>(&self, pos: &Self::RequiredDataType, mut f: F) {
-        let [boys, opps, _] = pos.bbs();
-
-        for i in 0..6 {
-            for j in 0..6 {
-                const POWERS: [usize; 4] = [1, 3, 9, 27];
-                const MASK: u64 = 0b0001_1000_0011;
-
-                let tuple = 6 * i + j;
-                let mut stm = PER_TUPLE * tuple;
-                let mut ntm = stm;
-
-                let offset = 7 * i + j;
-                let mut b = (boys >> offset) & MASK;
-                let mut o = (opps >> offset) & MASK;
-
-                while b > 0 {
-                    let mut sq = b.trailing_zeros() as usize;
-                    if sq > 6 {
-                        sq -= 5;
-                    }
-
-                    stm += POWERS[sq];
-                    ntm += 2 * POWERS[sq];
-
-                    b &= b - 1;
-                }
-
-                while o > 0 {
-                    let mut sq = o.trailing_zeros() as usize;
-                    if sq > 6 {
-                        sq -= 5;
-                    }
-
-                    stm += 2 * POWERS[sq];
-                    ntm += POWERS[sq];
-
-                    o &= o - 1;
-                }
-
-                f(stm, ntm);
-            }
-        }
-    }
-
-    fn shorthand(&self) -> String {
-        "2-tuples".to_string()
-    }
-
-    fn description(&self) -> String {
-        "Ataxx 2x2-typles".to_string()
-    }
-}
 
 fn main() {
     let mut trainer = TrainerBuilder::default()
@@ -84,7 +15,7 @@ fn main() {
         .quantisations(&[255, 64])
         .optimiser(optimiser::AdamW)
         .loss_fn(Loss::SigmoidMSE)
-        .input(Ataxx2Tuples)
+        .input(inputs::Ataxx2Tuples)
         .output_buckets(outputs::Single)
         .feature_transformer(HIDDEN_SIZE)
         .activate(Activation::SCReLU)
@@ -107,7 +38,15 @@ fn main() {
 
     trainer.set_optimiser_params(optimiser::AdamWParams::default());
 
-    let settings = LocalSettings { threads: 4, test_set: None, output_directory: "checkpoints", batch_queue_size: 512 };
+    let settings = LocalSettings {
+        threads: 4,
+        test_set: None,
+        output_directory: "checkpoints",
+        batch_queue_size: None,
+        prefetch: PrefetchSettings::default(),
+        checkpoint_compression: CheckpointCompression::None,
+        wallclock_saves: WallclockSaves::default(),
+    };
 
     let data_loader = loader::DirectSequentialDataLoader::new(&["../../data/ataxx/005.data"]);
 
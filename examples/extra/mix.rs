@@ -0,0 +1,27 @@
+/*
+Mixes several datasets of different formats into a single shuffled bulletformat file
+*/
+
+use bullet_lib::trainer::default::{
+    formats::bulletformat::ChessBoard,
+    loader::{DatasetMixer, DirectSequentialDataLoader, MontyBinpackLoader, SfBinpackLoader},
+};
+
+const OUTPUT_PATH: &str = "data/mixed.data";
+const OUTPUT_POSITIONS: u64 = 1_000_000_000;
+
+fn main() {
+    let bullet = DirectSequentialDataLoader::new(&["data/baseline.data"]);
+    let sf = SfBinpackLoader::new("data/test80-2024.binpack", 1024, 4, |entry| {
+        entry.score.unsigned_abs() <= 10000 && !entry.pos.is_checked(entry.pos.side_to_move())
+    });
+    let monty = MontyBinpackLoader::new("data/monty.binpack", 1024, 4, |_, _, score, _| score.abs() < 2000);
+
+    DatasetMixer::<ChessBoard>::new(OUTPUT_PATH, OUTPUT_POSITIONS)
+        .with_memory_mb(8192)
+        .add_source(bullet, 2.0, |_| true)
+        .add_source(sf, 1.0, |pos| pos.score.unsigned_abs() < 2000)
+        .add_source(monty, 1.0, |_| true)
+        .run()
+        .unwrap();
+}
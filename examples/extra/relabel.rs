@@ -9,7 +9,7 @@ use bullet_lib::{
         formats::bulletformat::{ChessBoard, DataLoader},
         inputs::{self, SparseInputType},
         load_into_graph,
-        loader::DefaultDataPreparer,
+        loader::{DefaultDataPreparer, TrainingTargets},
         outputs,
     },
 };
@@ -43,7 +43,8 @@ fn main() {
         let loader = DataLoader::new(DATA_PATH, 128).unwrap();
 
         loader.map_batches(batch_size, |batch: &[ChessBoard]| {
-            let prepared = DefaultDataPreparer::prepare(inputs, output_buckets, false, batch, 4, 0.0, eval_scale);
+            let prepared =
+                DefaultDataPreparer::prepare(inputs, output_buckets, TrainingTargets::Eval, batch, 4, 0.0, eval_scale);
             sender.send((batch.to_vec(), prepared)).unwrap();
         });
 
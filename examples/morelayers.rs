@@ -10,7 +10,7 @@ use bullet_lib::{
         default::{inputs, loader, outputs, Loss, TrainerBuilder},
         save::QuantTarget,
         schedule::{lr, wdl, TrainingSchedule, TrainingSteps},
-        settings::LocalSettings,
+        settings::{CheckpointCompression, LocalSettings, PrefetchSettings, WallclockSaves},
     },
 };
 
@@ -46,7 +46,15 @@ fn main() {
 
     trainer.set_optimiser_params(optimiser::AdamWParams::default());
 
-    let settings = LocalSettings { threads: 4, test_set: None, output_directory: "checkpoints", batch_queue_size: 512 };
+    let settings = LocalSettings {
+        threads: 4,
+        test_set: None,
+        output_directory: "checkpoints",
+        batch_queue_size: None,
+        prefetch: PrefetchSettings::default(),
+        checkpoint_compression: CheckpointCompression::None,
+        wallclock_saves: WallclockSaves::default(),
+    };
 
     let data_loader = loader::DirectSequentialDataLoader::new(&["data/baseline.data"]);
 